@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+-- Lowercased emails are kept
+DROP INDEX IF EXISTS users_email_lower_unique;
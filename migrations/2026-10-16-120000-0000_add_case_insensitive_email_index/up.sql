@@ -0,0 +1,27 @@
+-- Make user emails unique regardless of letter case
+-- First, check for emails that only differ in case
+DO $$
+DECLARE
+    duplicate_count INTEGER;
+BEGIN
+    -- Count emails that collide once lowercased
+    SELECT COUNT(*) INTO duplicate_count
+    FROM (
+        SELECT LOWER(email)
+        FROM users
+        WHERE email IS NOT NULL AND email != ''
+        GROUP BY LOWER(email)
+        HAVING COUNT(*) > 1
+    ) AS duplicates;
+
+    -- If duplicates exist, abort the migration with a clear error message
+    IF duplicate_count > 0 THEN
+        RAISE EXCEPTION 'Migration aborted: Found % email(s) in users table differing only in case. Please resolve them before applying this index. Run this query to find them: SELECT LOWER(email), COUNT(*) FROM users WHERE email IS NOT NULL AND email != '''' GROUP BY LOWER(email) HAVING COUNT(*) > 1;', duplicate_count;
+    END IF;
+END $$;
+
+-- Emails are stored lowercased from now on
+UPDATE users SET email = LOWER(email) WHERE email != LOWER(email);
+
+-- Enforce case-insensitive uniqueness (also serves LOWER(email) lookups)
+CREATE UNIQUE INDEX users_email_lower_unique ON users (LOWER(email));
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+-- Soft-deleted contacts become visible again
+DROP INDEX IF EXISTS people_not_deleted_idx;
+ALTER TABLE people DROP COLUMN IF EXISTS deleted_at;
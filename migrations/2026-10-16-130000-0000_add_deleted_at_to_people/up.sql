@@ -0,0 +1,5 @@
+-- Soft delete for address book contacts: deleted rows keep their data until restored
+ALTER TABLE people ADD COLUMN deleted_at TIMESTAMP NULL;
+
+-- Listings only ever read rows that are not deleted
+CREATE INDEX people_not_deleted_idx ON people (id) WHERE deleted_at IS NULL;
@@ -0,0 +1,12 @@
+-- Refresh token rotation: every token belongs to a family started at login
+ALTER TABLE refresh_tokens ADD COLUMN family_id VARCHAR;
+
+-- Tokens issued before rotation each form their own family
+UPDATE refresh_tokens SET family_id = token WHERE family_id IS NULL;
+ALTER TABLE refresh_tokens ALTER COLUMN family_id SET NOT NULL;
+
+-- Set when a token is exchanged; presenting it again means it was stolen
+ALTER TABLE refresh_tokens ADD COLUMN used_at TIMESTAMP WITH TIME ZONE NULL;
+ALTER TABLE refresh_tokens ADD COLUMN replaced_by INTEGER NULL REFERENCES refresh_tokens(id) ON DELETE SET NULL;
+
+CREATE INDEX idx_refresh_tokens_family_id ON refresh_tokens(family_id);
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_login_history_refresh_family_id;
+DROP INDEX IF EXISTS idx_login_history_login_session;
+ALTER TABLE login_history DROP COLUMN IF EXISTS revoked_at;
+ALTER TABLE login_history DROP COLUMN IF EXISTS last_seen_at;
+ALTER TABLE login_history DROP COLUMN IF EXISTS ip_address;
+ALTER TABLE login_history DROP COLUMN IF EXISTS user_agent;
+ALTER TABLE login_history DROP COLUMN IF EXISTS refresh_family_id;
+ALTER TABLE login_history DROP COLUMN IF EXISTS login_session;
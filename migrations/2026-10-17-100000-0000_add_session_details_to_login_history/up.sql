@@ -0,0 +1,22 @@
+-- Every login is a session: its own access-token session id, refresh token
+-- family and client details, so sessions can be listed and revoked one by one
+ALTER TABLE login_history ADD COLUMN login_session VARCHAR NULL;
+ALTER TABLE login_history ADD COLUMN refresh_family_id VARCHAR NULL;
+ALTER TABLE login_history ADD COLUMN user_agent VARCHAR NULL;
+ALTER TABLE login_history ADD COLUMN ip_address VARCHAR NULL;
+ALTER TABLE login_history ADD COLUMN last_seen_at TIMESTAMP WITH TIME ZONE NULL;
+ALTER TABLE login_history ADD COLUMN revoked_at TIMESTAMP WITH TIME ZONE NULL;
+
+-- Tokens issued before this migration keep working: they belong to the
+-- latest login of their user
+UPDATE login_history
+SET login_session = users.login_session
+FROM users
+WHERE login_history.user_id = users.id
+  AND users.login_session <> ''
+  AND login_history.id = (
+      SELECT MAX(latest.id) FROM login_history latest WHERE latest.user_id = users.id
+  );
+
+CREATE INDEX idx_login_history_login_session ON login_history(login_session);
+CREATE INDEX idx_login_history_refresh_family_id ON login_history(refresh_family_id);
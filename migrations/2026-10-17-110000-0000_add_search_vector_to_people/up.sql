@@ -0,0 +1,10 @@
+-- Full-text search over contacts. The 'simple' configuration skips stemming
+-- and stop words, which suit names, emails and phone numbers; weights rank
+-- name matches above email matches above phone matches
+ALTER TABLE people ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+    setweight(to_tsvector('simple', coalesce(email, '')), 'B') ||
+    setweight(to_tsvector('simple', coalesce(phone, '')), 'C')
+) STORED;
+
+CREATE INDEX idx_people_search_vector ON people USING GIN (search_vector);
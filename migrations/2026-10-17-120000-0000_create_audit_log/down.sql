@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_audit_log_tenant_created_at;
+DROP TABLE IF EXISTS audit_log;
@@ -0,0 +1,14 @@
+-- Audit trail of mutating requests, one row per request, written after the
+-- response is known
+CREATE TABLE audit_log (
+    id SERIAL PRIMARY KEY,
+    tenant_id VARCHAR NOT NULL,
+    user_id INTEGER NULL,
+    method VARCHAR NOT NULL,
+    path VARCHAR NOT NULL,
+    resource_id VARCHAR NULL,
+    status INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_tenant_created_at ON audit_log(tenant_id, created_at);
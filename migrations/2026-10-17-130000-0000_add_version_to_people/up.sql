@@ -0,0 +1,3 @@
+-- Optimistic concurrency for address book contacts: every update increments the version,
+-- and a full update only applies to the version the client read
+ALTER TABLE people ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,13 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_people_search_vector;
+ALTER TABLE people DROP COLUMN search_vector;
+
+ALTER TABLE people ALTER COLUMN phone TYPE VARCHAR(11);
+
+ALTER TABLE people ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+    setweight(to_tsvector('simple', coalesce(email, '')), 'B') ||
+    setweight(to_tsvector('simple', coalesce(phone, '')), 'C')
+) STORED;
+
+CREATE INDEX idx_people_search_vector ON people USING GIN (search_vector);
@@ -0,0 +1,14 @@
+-- Phone numbers are stored normalized to E.164: a leading + and up to 15 digits.
+-- search_vector is generated from phone, so it is dropped and rebuilt around the change
+DROP INDEX IF EXISTS idx_people_search_vector;
+ALTER TABLE people DROP COLUMN search_vector;
+
+ALTER TABLE people ALTER COLUMN phone TYPE VARCHAR(16);
+
+ALTER TABLE people ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('simple', coalesce(name, '')), 'A') ||
+    setweight(to_tsvector('simple', coalesce(email, '')), 'B') ||
+    setweight(to_tsvector('simple', coalesce(phone, '')), 'C')
+) STORED;
+
+CREATE INDEX idx_people_search_vector ON people USING GIN (search_vector);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS people_tenant_id_idx;
+ALTER TABLE people DROP COLUMN IF EXISTS tenant_id;
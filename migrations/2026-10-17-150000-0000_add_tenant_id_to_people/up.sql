@@ -0,0 +1,5 @@
+-- Owning tenant of each contact, so tenants sharing a database cannot act on each
+-- other's contacts. Contacts created before this column have no owner.
+ALTER TABLE people ADD COLUMN tenant_id VARCHAR NULL;
+
+CREATE INDEX people_tenant_id_idx ON people (tenant_id);
@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS idx_nfe_documents_recipient;
+DROP INDEX IF EXISTS idx_nfe_documents_emitter;
+
+ALTER TABLE nfe_documents DROP COLUMN IF EXISTS recipient_id;
+ALTER TABLE nfe_documents DROP COLUMN IF EXISTS emitter_id;
+
+ALTER TABLE nfe_emitters DROP CONSTRAINT IF EXISTS nfe_emitters_tenant_id_cpf_key;
+DELETE FROM nfe_emitters WHERE cnpj IS NULL;
+ALTER TABLE nfe_emitters ALTER COLUMN cnpj SET NOT NULL;
@@ -0,0 +1,10 @@
+-- Emitter and recipient of each imported document. Emitters identified by CPF
+-- (e.g. rural producers) have no CNPJ, so it can no longer be mandatory.
+ALTER TABLE nfe_emitters ALTER COLUMN cnpj DROP NOT NULL;
+ALTER TABLE nfe_emitters ADD CONSTRAINT nfe_emitters_tenant_id_cpf_key UNIQUE (tenant_id, cpf);
+
+ALTER TABLE nfe_documents ADD COLUMN emitter_id INTEGER NULL REFERENCES nfe_emitters(id);
+ALTER TABLE nfe_documents ADD COLUMN recipient_id INTEGER NULL REFERENCES nfe_recipients(id);
+
+CREATE INDEX idx_nfe_documents_emitter ON nfe_documents(emitter_id);
+CREATE INDEX idx_nfe_documents_recipient ON nfe_documents(recipient_id);
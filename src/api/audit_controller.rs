@@ -0,0 +1,190 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime};
+use log::{error, info};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::db::Pool,
+    constants,
+    error::{ErrorTag, ServiceError},
+    models::{
+        audit_log::{AuditLog, AuditPeriod},
+        response::ResponseBody,
+        user_token::UserToken,
+    },
+    pagination::Pagination,
+};
+
+/// Default and maximum page size of `GET /api/audit`
+const DEFAULT_AUDIT_PAGE_SIZE: i64 = 50;
+const MAX_AUDIT_PAGE_SIZE: i64 = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    page: Option<i64>,
+    per_page: Option<i64>,
+    from: Option<String>,
+    to: Option<String>,
+}
+
+/// A page of `GET /api/audit`
+#[derive(Serialize, JsonSchema)]
+pub struct AuditPage {
+    pub data: Vec<AuditLog>,
+    pub total: i64,
+    pub page: i64,
+    pub per_page: i64,
+    pub total_pages: i64,
+}
+
+/// Which end of a day a bare date stands for
+#[derive(Clone, Copy)]
+enum Bound {
+    Start,
+    End,
+}
+
+/// Parses an RFC 3339 timestamp, or a `YYYY-MM-DD` date covering that whole UTC day.
+fn parse_bound(value: &str, bound: Bound) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Some(timestamp.naive_utc());
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()?;
+    Some(match bound {
+        Bound::Start => date.and_time(NaiveTime::MIN),
+        Bound::End => date.and_hms_micro_opt(23, 59, 59, 999_999)?,
+    })
+}
+
+fn period(query: &AuditQuery) -> Result<AuditPeriod, ServiceError> {
+    let bound = |name: &str, value: Option<&String>, bound: Bound| {
+        value
+            .map(|value| {
+                parse_bound(value, bound).ok_or_else(|| {
+                    ServiceError::bad_request(format!(
+                        "Invalid '{}': expected an RFC 3339 timestamp or YYYY-MM-DD",
+                        name
+                    ))
+                    .with_tag(ErrorTag::Validation)
+                })
+            })
+            .transpose()
+    };
+    Ok(AuditPeriod {
+        from: bound("from", query.from.as_ref(), Bound::Start)?,
+        to: bound("to", query.to.as_ref(), Bound::End)?,
+    })
+}
+
+/// Lists the audit trail of the caller's tenant, newest first.
+///
+/// `page` is 1-based and `per_page` defaults to 50 (at most 500). `from` and
+/// `to` bound the entries by time, inclusively; each takes an RFC 3339
+/// timestamp or a `YYYY-MM-DD` date, which covers that whole UTC day. Only
+/// entries of the tenant named in the caller's token are returned.
+///
+/// # Returns
+///
+/// `Ok(HttpResponse)` with a `ResponseBody` holding `data`, `total`, `page`,
+/// `per_page` and `total_pages`; a `BadRequest` for an unparsable `from` or
+/// `to`; an `InternalServerError` when the query fails.
+pub async fn find_all(
+    req: HttpRequest,
+    query: web::Query<AuditQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    let period = period(&query)?;
+    let per_page = query
+        .per_page
+        .unwrap_or(DEFAULT_AUDIT_PAGE_SIZE)
+        .clamp(1, MAX_AUDIT_PAGE_SIZE);
+    let pagination = Pagination::new(
+        (query.page.unwrap_or(1).max(1) - 1) as usize,
+        per_page as usize,
+    );
+
+    let (pool, tenant_id) = {
+        let extensions = req.extensions();
+        let pool = extensions.get::<Pool>().cloned();
+        let tenant_id = extensions
+            .get::<UserToken>()
+            .map(|claims| claims.tenant_id.clone());
+        (pool, tenant_id)
+    };
+    let (Some(pool), Some(tenant_id)) = (pool, tenant_id) else {
+        return Err(ServiceError::internal_server_error("Pool not found")
+            .with_tag(ErrorTag::Internal)
+            .with_detail("Missing tenant pool or token claims in request extensions"));
+    };
+
+    info!(
+        "Fetching audit log of tenant {} - page: {}, per_page: {}",
+        tenant_id,
+        pagination.cursor() + 1,
+        per_page
+    );
+
+    let mut conn = pool.get().map_err(|e| {
+        ServiceError::from_pool_error(e, &pool)
+            .with_tag(ErrorTag::Db)
+            .with_metadata("operation", "audit_find_all")
+    })?;
+    let (entries, total) = AuditLog::list(
+        &tenant_id,
+        period,
+        pagination.offset() as i64,
+        per_page,
+        &mut conn,
+    )
+    .map_err(|e| {
+        error!("Failed to fetch audit log of tenant {}: {}", tenant_id, e);
+        ServiceError::internal_server_error(constants::MESSAGE_CAN_NOT_FETCH_DATA)
+            .with_tag(ErrorTag::Db)
+            .with_metadata("operation", "audit_find_all")
+    })?;
+
+    let response = AuditPage {
+        data: entries,
+        total,
+        page: pagination.cursor() as i64 + 1,
+        per_page,
+        total_pages: pagination.total_pages(total as usize) as i64,
+    };
+    Ok(HttpResponse::Ok().json(ResponseBody::new(constants::MESSAGE_OK, response)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounds_accept_timestamps_and_whole_days() {
+        let day = NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        assert_eq!(
+            parse_bound("2026-10-17", Bound::Start),
+            Some(day.and_hms_opt(0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            parse_bound("2026-10-17", Bound::End),
+            Some(day.and_hms_micro_opt(23, 59, 59, 999_999).unwrap())
+        );
+        assert_eq!(
+            parse_bound("2026-10-17T12:30:00+02:00", Bound::Start),
+            Some(day.and_hms_opt(10, 30, 0).unwrap())
+        );
+        assert_eq!(parse_bound("yesterday", Bound::Start), None);
+    }
+
+    #[test]
+    fn test_invalid_period_is_rejected() {
+        let query = AuditQuery {
+            page: None,
+            per_page: None,
+            from: Some("2026-10-01".to_string()),
+            to: Some("17/10/2026".to_string()),
+        };
+        let err = period(&query).unwrap_err();
+        assert!(matches!(err, ServiceError::BadRequest { .. }));
+    }
+}
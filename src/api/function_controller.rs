@@ -0,0 +1,102 @@
+use actix_web::{web, HttpRequest, HttpResponse, Responder, Result};
+use serde::Deserialize;
+use std::borrow::Cow;
+
+use crate::{
+    constants,
+    error::{ErrorTag, ServiceError},
+    functional::{
+        function_traits::FunctionCategory, pure_function_registry::PureFunctionRegistry,
+        response_transformers::ResponseTransformer,
+    },
+    services::functional_service_base::FunctionalErrorHandling,
+};
+
+/// Query parameters of `GET /api/admin/functions`
+#[derive(Debug, Deserialize)]
+pub struct FunctionQuery {
+    /// Only list functions of this category, e.g. `mathematical`
+    pub category: Option<FunctionCategory>,
+}
+
+// GET api/admin/functions
+/// Lists the functions of the shared pure function registry with their usage.
+///
+/// Each entry carries `name`, `signature`, `category` and `call_count`, busiest
+/// function first. `?category=` restricts the list to one category.
+///
+/// # Returns
+///
+/// `200 OK` with the function list, `400 Bad Request` for an unknown category,
+/// or a `ServiceError` when the registry cannot be read.
+pub async fn find_all(
+    query: web::Query<FunctionQuery>,
+    registry: web::Data<PureFunctionRegistry>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    registry
+        .function_stats(query.category)
+        .map_err(|e| {
+            ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR)
+                .with_tag(ErrorTag::Internal)
+                .with_detail(e.to_string())
+        })
+        .log_error("function_controller::find_all")
+        .map(|stats| {
+            ResponseTransformer::new(stats)
+                .with_message(Cow::Borrowed(constants::MESSAGE_OK))
+                .respond_to(&req)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+    use serde_json::Value;
+
+    use super::*;
+    use crate::functional::pure_function_registry::prelude::create_standard_registry;
+
+    #[actix_web::test]
+    async fn test_registered_function_is_listed_with_metrics() {
+        let registry = create_standard_registry().unwrap();
+        for input in [1, 2, 3] {
+            registry
+                .execute::<i32, i32>(FunctionCategory::Mathematical, "double", input)
+                .unwrap();
+        }
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(registry))
+                .route("/api/admin/functions", web::get().to(find_all)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/admin/functions")
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        let functions = body["data"].as_array().unwrap();
+        assert_eq!(functions.len(), 3);
+        assert_eq!(functions[0]["name"], "double");
+        assert_eq!(functions[0]["signature"], "i32 -> i32");
+        assert_eq!(functions[0]["category"], "mathematical");
+        assert_eq!(functions[0]["call_count"], 3);
+
+        let req = test::TestRequest::get()
+            .uri("/api/admin/functions?category=string_processing")
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        let functions = body["data"].as_array().unwrap();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0]["name"], "string_length");
+        assert_eq!(functions[0]["call_count"], 0);
+
+        let req = test::TestRequest::get()
+            .uri("/api/admin/functions?category=astrology")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -1,34 +1,42 @@
-use actix_web::{get, web, HttpRequest, HttpResponse};
-use serde::Serialize;
+use actix_web::{get, route, web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
 use tokio::time::{timeout, Duration};
 
 use crate::config::cache::Pool as RedisPool;
-use crate::config::db::{Pool as DatabasePool, TenantPoolManager};
+use crate::config::db::{PendingTenantPool, Pool as DatabasePool, TenantPoolManager};
+use crate::config::functional_config::{
+    Criticality, EffectiveConfig, HealthCriticality, EFFECTIVE_CONFIG,
+};
 use crate::constants;
-use crate::error::ServiceError;
+use crate::error::{ErrorTag, ServiceError};
 use crate::models::response::ResponseBody;
 use crate::models::tenant::Tenant;
+use crate::utils::log_stream::{log_stream_hub, LogStreamStats};
 
 use actix_web::web::Bytes;
 use chrono::Utc;
 use diesel::prelude::*;
-use log::{debug, error, info};
+use log::{error, info};
+use once_cell::sync::Lazy;
 use redis;
-use std::io::Error as IoError;
+use std::future::Future;
 use std::path::Path;
 
-use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
-use tokio::sync::mpsc;
-use tokio_stream::wrappers::ReceiverStream;
+use futures::future::join_all;
+use futures::stream::{self, StreamExt};
 
+use crate::functional::immutable_state::{get_state_manager, StateTransitionMetrics};
 use crate::functional::performance_monitoring::{
     get_performance_monitor, HealthSummary as PerformanceHealthSummary, OperationType,
 };
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
 enum Status {
     #[serde(rename = "healthy")]
     Healthy,
+    /// Only non-critical components are failing
+    #[serde(rename = "degraded")]
+    Degraded,
     #[serde(rename = "unhealthy")]
     Unhealthy,
 }
@@ -39,18 +47,66 @@ impl Status {
     }
 }
 
+/// Component criticality used to aggregate the overall status, see [`HealthCriticality::from_env`]
+static HEALTH_CRITICALITY: Lazy<HealthCriticality> = Lazy::new(HealthCriticality::from_env);
+
+/// Combines component statuses into the overall status.
+///
+/// A failing critical component makes the service `Unhealthy`; failing
+/// non-critical components, and partially failing ones such as some of the
+/// tenant databases, only make it `Degraded`.
+fn overall_status<'a>(
+    criticality: &HealthCriticality,
+    components: impl IntoIterator<Item = (&'a str, &'a Status)>,
+) -> Status {
+    components
+        .into_iter()
+        .filter(|(_, status)| !status.is_healthy())
+        .map(
+            |(component, status)| match (status, criticality.of(component)) {
+                (Status::Unhealthy, Criticality::Critical) => Status::Unhealthy,
+                _ => Status::Degraded,
+            },
+        )
+        .fold(Status::Healthy, |overall, status| {
+            if overall == Status::Unhealthy {
+                overall
+            } else {
+                status
+            }
+        })
+}
+
+/// Answers 503 when the service is unhealthy so load balancers take it out of rotation
+fn health_response(response: HealthResponse) -> HttpResponse {
+    let mut builder = match response.status {
+        Status::Unhealthy => HttpResponse::ServiceUnavailable(),
+        Status::Healthy | Status::Degraded => HttpResponse::Ok(),
+    };
+    builder.json(ResponseBody::new(constants::MESSAGE_OK, response))
+}
+
 #[derive(Serialize)]
 struct HealthStatus {
     database: Status,
     cache: Status,
 }
 
+#[derive(Serialize)]
+struct LivenessResponse {
+    status: Status,
+    timestamp: String,
+}
+
 #[derive(Serialize)]
 struct HealthResponse {
     status: Status,
     timestamp: String,
     components: HealthStatus,
     tenants: Option<Vec<TenantHealth>>,
+    /// Tenants whose pool failed to build at startup and is being retried
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pending_tenants: Vec<PendingTenantPool>,
     performance: Option<PerformanceHealthSummary>,
 }
 
@@ -59,6 +115,71 @@ struct TenantHealth {
     tenant_id: String,
     name: String,
     status: Status,
+    /// Why the tenant database is unhealthy (connection error, failed query or timeout)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+/// Time each tenant database gets to answer `SELECT 1`
+const TENANT_PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Time the main database gets to answer `SELECT 1`
+const DATABASE_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time the cache gets to answer `PING`
+const CACHE_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Outcome of a database or cache probe
+type ProbeResult = Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>;
+
+/// Metrics of a single operation type as reported by `/health/performance`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OperationMetricsView {
+    pub operation: String,
+    pub execution_count: u64,
+    pub average_duration_ms: f64,
+    pub min_duration_ms: f64,
+    pub max_duration_ms: f64,
+    pub memory_allocated_mb: u64,
+    pub memory_peak_mb: u64,
+    pub success_rate: f64,
+    pub error_count: u64,
+    pub last_execution: String,
+}
+
+/// Aggregate over the (optionally filtered) operation metrics
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MetricsSummary {
+    pub total_operations: u64,
+    pub average_duration_ms: f64,
+    pub total_memory_allocated_mb: u64,
+    pub operations_by_type: Vec<OperationMetricsView>,
+}
+
+/// Placeholder returned for `include_history=true`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoricalData {
+    pub note: String,
+    pub future_enhancements: Vec<String>,
+}
+
+/// Response payload of `/health/performance`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HealthReport {
+    pub performance_health: PerformanceHealthSummary,
+    pub metrics_summary: MetricsSummary,
+    #[serde(default)]
+    pub log_stream: LogStreamStats,
+    #[serde(default)]
+    pub state_transitions: StateTransitionMetrics,
+    /// Error responses per `ErrorTag` since startup
+    #[serde(default)]
+    pub errors_by_tag: std::collections::BTreeMap<String, u64>,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub historical_data: Option<HistoricalData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counters_reset: Option<bool>,
 }
 
 /// Check whether the database accepts a simple health query using the provided connection pool.
@@ -74,9 +195,7 @@ struct TenantHealth {
 /// assert!(result.is_ok());
 /// # }
 /// ```
-async fn check_database_health_async(
-    pool: web::Data<DatabasePool>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+async fn check_database_health_async(pool: web::Data<DatabasePool>) -> ProbeResult {
     tokio::task::spawn_blocking(move || check_database_health(pool)).await?
 }
 
@@ -93,18 +212,53 @@ async fn check_database_health_async(
 /// assert!(result.is_ok() || result.is_err());
 /// # }
 /// ```
-async fn check_cache_health_async(
-    redis_pool: web::Data<RedisPool>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+async fn check_cache_health_async(redis_pool: web::Data<RedisPool>) -> ProbeResult {
     tokio::task::spawn_blocking(move || check_cache_health(&redis_pool))
         .await
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync + 'static>)?
 }
 
+/// Awaits `probe` for at most `probe_timeout`, logging why `component` is unhealthy.
+async fn component_status(
+    component: &str,
+    probe_timeout: Duration,
+    probe: impl Future<Output = ProbeResult>,
+) -> Status {
+    match timeout(probe_timeout, probe).await {
+        Ok(Ok(())) => Status::Healthy,
+        Ok(Err(e)) => {
+            error!("{} health check failed: {}", component, e);
+            Status::Unhealthy
+        }
+        Err(_) => {
+            error!("{} health check timeout", component);
+            Status::Unhealthy
+        }
+    }
+}
+
+/// Runs the database, cache and tenant probes of `/health/detailed` concurrently.
+///
+/// The probes are independent, so the check takes as long as the slowest one
+/// rather than the sum of all of them.
+async fn probe_components(
+    database: impl Future<Output = ProbeResult>,
+    cache: impl Future<Output = ProbeResult>,
+    tenants: impl Future<Output = Option<Vec<TenantHealth>>>,
+) -> (Status, Status, Option<Vec<TenantHealth>>) {
+    tokio::join!(
+        component_status("Database", DATABASE_PROBE_TIMEOUT, database),
+        component_status("Cache", CACHE_PROBE_TIMEOUT, cache),
+        tenants,
+    )
+}
+
 /// Return a JSON health summary for the service.
 ///
 /// Includes the overall `Status`, an RFC3339 `timestamp`, and component statuses
-/// for `database` and `cache`. The `tenants` field is omitted.
+/// for `database` and `cache`. The `tenants` field is omitted. The overall status
+/// weighs each component by its configured criticality and answers 503 when unhealthy.
+/// `HEAD` runs the same checks and answers the same status and headers without a body.
 ///
 /// # Examples
 ///
@@ -118,45 +272,30 @@ async fn check_cache_health_async(
 /// assert!(resp.status().is_success());
 /// # }
 /// ```
-#[get("/health")]
+#[route("/health", method = "GET", method = "HEAD")]
 async fn health(
     pool: web::Data<DatabasePool>,
     redis_pool: web::Data<RedisPool>,
 ) -> Result<HttpResponse, ServiceError> {
     info!("Health check requested");
 
-    // Check database with timeout
-    let db_status = match timeout(Duration::from_secs(5), check_database_health_async(pool)).await {
-        Ok(Ok(())) => Status::Healthy,
-        Ok(Err(e)) => {
-            error!("Database health check failed: {}", e);
-            Status::Unhealthy
-        }
-        Err(_) => {
-            error!("Database health check timeout");
-            Status::Unhealthy
-        }
-    };
-
-    // Check cache with timeout
-    let cache_status =
-        match timeout(Duration::from_secs(3), check_cache_health_async(redis_pool)).await {
-            Ok(Ok(())) => Status::Healthy,
-            Ok(Err(e)) => {
-                error!("Cache health check failed: {}", e);
-                Status::Unhealthy
-            }
-            Err(_) => {
-                error!("Cache health check timeout");
-                Status::Unhealthy
-            }
-        };
-
-    let overall_status = if db_status.is_healthy() && cache_status.is_healthy() {
-        Status::Healthy
-    } else {
-        Status::Unhealthy
-    };
+    let (db_status, cache_status) = tokio::join!(
+        component_status(
+            "Database",
+            DATABASE_PROBE_TIMEOUT,
+            check_database_health_async(pool)
+        ),
+        component_status(
+            "Cache",
+            CACHE_PROBE_TIMEOUT,
+            check_cache_health_async(redis_pool)
+        ),
+    );
+
+    let overall_status = overall_status(
+        &HEALTH_CRITICALITY,
+        [("database", &db_status), ("cache", &cache_status)],
+    );
 
     let response = HealthResponse {
         status: overall_status,
@@ -166,19 +305,40 @@ async fn health(
             cache: cache_status,
         },
         tenants: None,
+        pending_tenants: Vec::new(),
         performance: None,
     };
 
-    Ok(HttpResponse::Ok().json(ResponseBody::new(constants::MESSAGE_OK, response)))
+    Ok(health_response(response))
+}
+
+/// Answers 200 whenever the process can serve requests.
+///
+/// Unlike [`health`], which reports whether the database and cache are usable,
+/// this liveness check touches neither, so an outage of a dependency does not
+/// get the service restarted.
+#[route("/health/live", method = "GET", method = "HEAD")]
+async fn health_live() -> HttpResponse {
+    HttpResponse::Ok().json(ResponseBody::new(
+        constants::MESSAGE_OK,
+        LivenessResponse {
+            status: Status::Healthy,
+            timestamp: Utc::now().to_rfc3339(),
+        },
+    ))
 }
 
 /// Produces a detailed health report that includes database, cache, and per-tenant statuses.
 ///
 /// The response body is a JSON-encoded `HealthResponse` containing:
-/// - `status`: overall system status,
+/// - `status`: overall system status (`healthy`, `degraded` or `unhealthy`; the latter answers 503),
 /// - `timestamp`: RFC3339 timestamp of the check,
 /// - `components`: individual `database` and `cache` statuses,
-/// - `tenants`: optional list of `TenantHealth` entries when tenant pools are available.
+/// - `tenants`: optional list of `TenantHealth` entries when tenant pools are available;
+///   tenants are probed concurrently, each with its own timeout, and unhealthy ones carry a `reason`.
+///
+/// The database, cache and tenant probes run at the same time, so the latency of
+/// the check is that of the slowest probe.
 ///
 /// # Examples
 ///
@@ -197,7 +357,7 @@ async fn health(
 /// });
 /// assert_eq!(resp.status(), StatusCode::OK);
 /// ```
-#[get("/health/detailed")]
+#[route("/health/detailed", method = "GET", method = "HEAD")]
 async fn health_detailed(
     req: HttpRequest,
     pool: web::Data<DatabasePool>,
@@ -207,81 +367,31 @@ async fn health_detailed(
     let manager = req.app_data::<web::Data<TenantPoolManager>>();
     info!("Detailed health check requested");
 
-    // Check database with timeout
-    let db_status = match timeout(Duration::from_secs(5), check_database_health_async(pool)).await {
-        Ok(Ok(())) => Status::Healthy,
-        Ok(Err(e)) => {
-            error!("Database health check failed: {}", e);
-            Status::Unhealthy
-        }
-        Err(_) => {
-            error!("Database health check timeout");
-            Status::Unhealthy
-        }
-    };
-
-    // Check cache with timeout
-    let cache_status =
-        match timeout(Duration::from_secs(3), check_cache_health_async(redis_pool)).await {
-            Ok(Ok(())) => Status::Healthy,
-            Ok(Err(e)) => {
-                error!("Cache health check failed: {}", e);
-                Status::Unhealthy
-            }
-            Err(_) => {
-                error!("Cache health check timeout");
-                Status::Unhealthy
-            }
-        };
+    let pending_tenants = manager.map_or_else(Vec::new, |manager| manager.pending_tenant_pools());
 
-    // Check tenant health if tenant manager is available
-    let tenants = if let Some(manager_ref) = manager {
-        let manager_data = manager_ref.clone();
-        match tokio::task::spawn_blocking(move || {
-            let mut main_conn = main_conn
-                .get()
-                .map_err(|e| format!("Failed to get db connection: {}", e))?;
-            let tenants = Tenant::list_all(&mut main_conn).unwrap_or_else(|_| Vec::new());
-            let mut tenant_healths = Vec::new();
-
-            for tenant in tenants {
-                let status = match manager_data.get_tenant_pool(&tenant.id) {
-                    Some(pool) => match pool.get() {
-                        Ok(mut conn) => match diesel::sql_query("SELECT 1").execute(&mut conn) {
-                            Ok(_) => Status::Healthy,
-                            Err(_) => Status::Unhealthy,
-                        },
-                        Err(_) => Status::Unhealthy,
-                    },
-                    None => Status::Unhealthy,
-                };
-                tenant_healths.push(TenantHealth {
-                    tenant_id: tenant.id,
-                    name: tenant.name,
-                    status,
-                });
+    let (db_status, cache_status, tenants) = probe_components(
+        check_database_health_async(pool),
+        check_cache_health_async(redis_pool),
+        async {
+            match manager {
+                Some(manager) => check_tenants_health(manager.clone(), main_conn).await,
+                None => None,
             }
-            Ok::<Vec<TenantHealth>, String>(tenant_healths)
-        })
-        .await
-        {
-            Ok(Ok(healths)) if !healths.is_empty() => Some(healths),
-            _ => None,
-        }
-    } else {
-        None
-    };
-
-    let overall_status = if db_status.is_healthy()
-        && cache_status.is_healthy()
-        && tenants
-            .as_ref()
-            .map_or(true, |t| t.iter().all(|th| th.status.is_healthy()))
-    {
-        Status::Healthy
-    } else {
-        Status::Unhealthy
-    };
+        },
+    )
+    .await;
+
+    let tenants_status = tenants
+        .as_ref()
+        .map_or(Status::Healthy, |t| tenants_status(t));
+    let overall_status = overall_status(
+        &HEALTH_CRITICALITY,
+        [
+            ("database", &db_status),
+            ("cache", &cache_status),
+            ("tenants", &tenants_status),
+        ],
+    );
 
     // Get performance monitoring health summary
     let performance_summary = get_performance_monitor().get_health_summary();
@@ -294,10 +404,104 @@ async fn health_detailed(
             cache: cache_status,
         },
         tenants,
+        pending_tenants,
         performance: Some(performance_summary),
     };
 
-    Ok(HttpResponse::Ok().json(ResponseBody::new(constants::MESSAGE_OK, response)))
+    Ok(health_response(response))
+}
+
+/// Probes every registered tenant database concurrently.
+///
+/// Each tenant gets its own [`TENANT_PROBE_TIMEOUT`], so a slow or unreachable
+/// database is reported as unhealthy with a reason instead of stalling the
+/// others. Returns `None` when the tenant list cannot be loaded or is empty.
+async fn check_tenants_health(
+    manager: web::Data<TenantPoolManager>,
+    main_conn: web::Data<DatabasePool>,
+) -> Option<Vec<TenantHealth>> {
+    let tenants = tokio::task::spawn_blocking(move || {
+        let mut conn = main_conn.get().ok()?;
+        Tenant::list_all(&mut conn).ok()
+    })
+    .await
+    .ok()
+    .flatten()
+    .filter(|tenants| !tenants.is_empty())?;
+
+    let targets = tenants
+        .iter()
+        .map(|tenant| manager.get_tenant_pool(&tenant.id))
+        .collect();
+    let results = probe_concurrently(targets, TENANT_PROBE_TIMEOUT, |pool| {
+        let pool = pool.ok_or_else(|| "No connection pool registered".to_string())?;
+        let mut conn = pool
+            .get()
+            .map_err(|e| format!("Failed to get connection: {}", e))?;
+        diesel::sql_query("SELECT 1")
+            .execute(&mut conn)
+            .map(|_| ())
+            .map_err(|e| format!("Health query failed: {}", e))
+    })
+    .await;
+
+    Some(
+        tenants
+            .into_iter()
+            .zip(results)
+            .map(|(tenant, result)| TenantHealth {
+                tenant_id: tenant.id,
+                name: tenant.name,
+                status: if result.is_ok() {
+                    Status::Healthy
+                } else {
+                    Status::Unhealthy
+                },
+                reason: result.err(),
+            })
+            .collect(),
+    )
+}
+
+/// Runs the blocking `probe` on every target at once, each bounded by `probe_timeout`.
+///
+/// Results are returned in target order; a probe that times out yields an
+/// error while its blocking task is left to finish in the background.
+async fn probe_concurrently<T, F>(
+    targets: Vec<T>,
+    probe_timeout: Duration,
+    probe: F,
+) -> Vec<Result<(), String>>
+where
+    T: Send + 'static,
+    F: Fn(T) -> Result<(), String> + Clone + Send + 'static,
+{
+    join_all(targets.into_iter().map(|target| {
+        let probe = probe.clone();
+        async move {
+            match timeout(
+                probe_timeout,
+                tokio::task::spawn_blocking(move || probe(target)),
+            )
+            .await
+            {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => Err(format!("Health probe failed: {}", e)),
+                Err(_) => Err(format!("Timed out after {}ms", probe_timeout.as_millis())),
+            }
+        }
+    }))
+    .await
+}
+
+/// Status of the tenant databases as a whole: `Degraded` when only some are unhealthy.
+fn tenants_status(tenants: &[TenantHealth]) -> Status {
+    let unhealthy = tenants.iter().filter(|t| !t.status.is_healthy()).count();
+    match unhealthy {
+        0 => Status::Healthy,
+        n if n == tenants.len() => Status::Unhealthy,
+        _ => Status::Degraded,
+    }
 }
 
 /// Checks database connectivity by acquiring a connection from the pool and executing `SELECT 1`.
@@ -313,9 +517,7 @@ async fn health_detailed(
 /// let _ = crate::check_database_health(pool);
 /// # }
 /// ```
-fn check_database_health(
-    pool: web::Data<DatabasePool>,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+fn check_database_health(pool: web::Data<DatabasePool>) -> ProbeResult {
     match pool.get() {
         Ok(mut conn) => {
             diesel::sql_query("SELECT 1").execute(&mut conn)?;
@@ -347,9 +549,7 @@ fn check_database_health(
 /// // let redis_pool = RedisPool::new("redis://127.0.0.1").unwrap();
 /// // assert!(check_cache_health(&redis_pool).is_ok());
 /// ```
-fn check_cache_health(
-    redis_pool: &RedisPool,
-) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+fn check_cache_health(redis_pool: &RedisPool) -> ProbeResult {
     let mut conn = redis_pool
         .get()
         .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync + 'static>)?;
@@ -359,13 +559,26 @@ fn check_cache_health(
     Ok(())
 }
 
+/// Reports the configuration the process parsed at startup.
+///
+/// Mounted under `/api/admin`, so it requires authentication. Database and Redis
+/// URLs are masked with `UrlMasker` and secrets are never included, which lets
+/// operators check what a running pod actually parsed without shell access.
+#[get("/health/config")]
+async fn effective_config() -> Result<HttpResponse, ServiceError> {
+    let config: &EffectiveConfig = &EFFECTIVE_CONFIG;
+    Ok(HttpResponse::Ok().json(ResponseBody::new(constants::MESSAGE_OK, config)))
+}
+
 /// Streams the application's log file to clients over Server-Sent Events (SSE).
 ///
 /// When `ENABLE_LOG_STREAM` is set to `"true"` and the file at `LOG_FILE` (defaults to
 /// `/var/log/app.log`) exists, this handler returns an `HttpResponse` that continuously
-/// streams new log lines as SSE `data:` frames. If streaming is disabled, the handler
-/// responds with `405 MethodNotAllowed`. If the configured log file does not exist, the
-/// handler responds with `404 NotFound`.
+/// streams new log lines as SSE `data:` frames. All clients share a single tail of the
+/// file (see `utils::log_stream`). If streaming is disabled, the handler responds with
+/// `405 MethodNotAllowed`. If the configured log file does not exist, the handler responds
+/// with `404 NotFound`. When `MAX_LOG_STREAM_CLIENTS` clients are already connected, it
+/// responds with `503 Service Unavailable` and a `Retry-After` header.
 ///
 /// # Examples
 ///
@@ -403,136 +616,23 @@ async fn logs() -> Result<HttpResponse, ServiceError> {
         return Ok(HttpResponse::NotFound().body("Log file not found"));
     }
 
-    // Channel for streaming log lines
-    let (tx, rx) = mpsc::channel::<Result<Bytes, IoError>>(100);
-
-    // Spawn a task to tail the log file
-    let log_file_clone = log_file.clone();
-    tokio::spawn(async move {
-        let path = Path::new(&log_file_clone);
-
-        // Open log file and seek to end
-        let mut file = match tokio::fs::File::open(&path).await {
-            Ok(f) => f,
-            Err(e) => {
-                error!("Failed to open log file: {}", e);
-                let _ = tx.send(Err(e)).await;
-                return;
-            }
-        };
-
-        if let Err(e) = file.seek(SeekFrom::End(0)).await {
-            error!("Failed to seek to end of log file: {}", e);
-            let _ = tx
-                .send(Err(std::io::Error::new(
-                    std::io::ErrorKind::Other,
-                    e.to_string(),
-                )))
-                .await;
-            return;
-        }
-
-        let mut buffer = [0u8; 8192];
-        let mut pending_data = Vec::new();
-
-        // Send initial message
-        if tx
-            .send(Ok(Bytes::from(
-                "data: Log streaming started for ".to_string() + &log_file + "\n\n",
-            )))
-            .await
-            .is_err()
-        {
-            return;
-        }
-
-        // If in test mode, send end message and close stream
-        if std::env::var("TEST_MODE")
-            .map(|v| v == "true")
-            .unwrap_or(false)
-        {
-            if tx.send(Ok(Bytes::from("data: end\n\n"))).await.is_err() {
-                return;
-            }
-            return;
-        }
-
-        let mut keep_alive_count = 0;
-
-        loop {
-            // Sleep for 10 seconds
-            tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-
-            // Check if file has grown
-            let metadata = match file.metadata().await {
-                Ok(m) => m,
-                Err(e) => {
-                    error!("Error getting file metadata: {}", e);
-                    continue;
-                }
-            };
-
-            let current_pos = match file.seek(SeekFrom::Current(0)).await {
-                Ok(p) => p,
-                Err(e) => {
-                    error!("Error getting current position: {}", e);
-                    continue;
-                }
-            };
-
-            if metadata.len() > current_pos {
-                let to_read = (metadata.len() - current_pos) as usize;
-                if to_read <= buffer.len() {
-                    match file.read(&mut buffer[..to_read]).await {
-                        Ok(n) if n == to_read => {
-                            pending_data.extend_from_slice(&buffer[..n]);
-                        }
-                        _ => {
-                            error!("Failed to read expected data");
-                            continue;
-                        }
-                    }
-                } else {
-                    // File grew too much, skip or handle
-                    if file.seek(SeekFrom::End(0)).await.is_ok() {
-                        pending_data.clear(); // Reset to end
-                    }
-                    continue;
-                }
-
-                // Process complete lines
-                while let Some(pos) = pending_data.iter().position(|&b| b == b'\n') {
-                    let line_bytes = pending_data.drain(..=pos).collect::<Vec<_>>();
-                    if let Ok(line) = String::from_utf8(line_bytes) {
-                        let trimmed = line.trim_end_matches('\n').trim_end_matches('\r');
-                        if !trimmed.is_empty() {
-                            // Channel saturation is expected under high load, reducing log noise
-                            if tx
-                                .send(Ok(Bytes::from(format!("data: {}\n\n", trimmed))))
-                                .await
-                                .is_err()
-                            {
-                                debug!("failed to send log line '{}' to watcher channel", trimmed);
-                                return;
-                            }
-                        }
-                    }
-                }
-            }
-
-            keep_alive_count += 1;
-            if keep_alive_count >= 3 {
-                // Every 30 seconds
-                keep_alive_count = 0;
-                if tx.send(Ok(Bytes::from("data: \n\n"))).await.is_err() {
-                    return;
-                }
-            }
-        }
-    });
+    // Join the shared tail; refused with 503 once MAX_LOG_STREAM_CLIENTS are connected
+    let subscription = log_stream_hub().subscribe(&log_file)?;
+    let greeting = Bytes::from(format!("data: Log streaming started for {}\n\n", log_file));
 
-    // Create the streaming response
-    let stream = ReceiverStream::new(rx);
+    // If in test mode, send end message and close stream
+    let stream = if std::env::var("TEST_MODE")
+        .map(|v| v == "true")
+        .unwrap_or(false)
+    {
+        subscription
+            .into_stream(greeting)
+            .take(1)
+            .chain(stream::once(async { Ok(Bytes::from("data: end\n\n")) }))
+            .boxed_local()
+    } else {
+        subscription.into_stream(greeting).boxed_local()
+    };
 
     Ok(HttpResponse::Ok()
         .insert_header(("Content-Type", "text/event-stream"))
@@ -563,6 +663,9 @@ async fn logs() -> Result<HttpResponse, ServiceError> {
 /// - Per-operation type metrics (execution count, average duration, memory usage)
 /// - Threshold violations and performance warnings
 /// - Memory allocation patterns and garbage collection stats
+/// - Log streaming activity (active SSE clients, frames sent and dropped, reopens)
+///
+/// Builds without the `performance_monitoring` feature answer `503 Service Unavailable`.
 ///
 /// # Examples
 ///
@@ -598,11 +701,23 @@ async fn logs() -> Result<HttpResponse, ServiceError> {
 /// let resp = test::call_service(&app, req).await;
 /// assert_eq!(resp.status(), StatusCode::OK);
 /// ```
-#[cfg(feature = "performance_monitoring")]
 #[get("/health/performance")]
 async fn performance_metrics(req: HttpRequest) -> Result<HttpResponse, ServiceError> {
     info!("Performance metrics requested");
 
+    // Get performance monitor instance
+    let monitor = get_performance_monitor();
+    if monitor.is_noop() {
+        return Ok(HttpResponse::ServiceUnavailable().json(ResponseBody::new(
+            "Performance monitoring feature not enabled",
+            serde_json::json!({
+                "error": "Performance monitoring is not compiled into this build",
+                "suggestion": "Rebuild with --features performance_monitoring",
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }),
+        )));
+    }
+
     // Parse query parameters
     let query =
         web::Query::<std::collections::HashMap<String, String>>::from_query(req.query_string())
@@ -618,9 +733,6 @@ async fn performance_metrics(req: HttpRequest) -> Result<HttpResponse, ServiceEr
         .and_then(|v| v.parse::<bool>().ok())
         .unwrap_or(false);
 
-    // Get performance monitor instance
-    let monitor = get_performance_monitor();
-
     // Generate comprehensive performance report
     let performance_summary = monitor.get_health_summary();
     let all_metrics = monitor.get_all_metrics();
@@ -636,6 +748,7 @@ async fn performance_metrics(req: HttpRequest) -> Result<HttpResponse, ServiceEr
             "state_transition" => Some(OperationType::StateTransition),
             "lazy_pipeline" => Some(OperationType::LazyPipeline),
             "pure_function_call" => Some(OperationType::PureFunctionCall),
+            "database_query" => Some(OperationType::DatabaseQuery),
             _ => None,
         };
 
@@ -669,69 +782,138 @@ async fn performance_metrics(req: HttpRequest) -> Result<HttpResponse, ServiceEr
         .sum::<u64>()
         / (1024 * 1024);
 
-    let operations_by_type: Vec<serde_json::Value> = filtered_metrics.iter().map(|(op_type, metrics)| {
-        serde_json::json!({
-            "operation": format!("{:?}", op_type),
-            "execution_count": metrics.operation_count,
-            "average_duration_ms": metrics.avg_execution_time.as_secs_f64() * 1000.0,
-            "min_duration_ms": metrics.min_execution_time.as_secs_f64() * 1000.0,
-            "max_duration_ms": metrics.max_execution_time.as_secs_f64() * 1000.0,
-            "memory_allocated_mb": metrics.memory_stats.total_allocated / (1024 * 1024),
-            "memory_peak_mb": metrics.memory_stats.peak_memory_bytes / (1024 * 1024),
-            "success_rate": if metrics.operation_count > 0 {
-                ((metrics.operation_count - metrics.error_count) as f64 / metrics.operation_count as f64) * 100.0
-            } else { 100.0 },
-            "error_count": metrics.error_count,
-            "last_execution": chrono::DateTime::<chrono::Utc>::from(
-                std::time::UNIX_EPOCH + metrics.last_updated.elapsed()
-            ).to_rfc3339(),
+    let operations_by_type = filtered_metrics
+        .iter()
+        .map(|(op_type, metrics)| OperationMetricsView {
+            operation: format!("{:?}", op_type),
+            execution_count: metrics.operation_count,
+            average_duration_ms: metrics.avg_execution_time.as_secs_f64() * 1000.0,
+            min_duration_ms: metrics.min_execution_time.as_secs_f64() * 1000.0,
+            max_duration_ms: metrics.max_execution_time.as_secs_f64() * 1000.0,
+            memory_allocated_mb: metrics.memory_stats.total_allocated / (1024 * 1024),
+            memory_peak_mb: metrics.memory_stats.peak_memory_bytes / (1024 * 1024),
+            success_rate: if metrics.operation_count > 0 {
+                ((metrics.operation_count - metrics.error_count) as f64
+                    / metrics.operation_count as f64)
+                    * 100.0
+            } else {
+                100.0
+            },
+            error_count: metrics.error_count,
+            last_execution: chrono::DateTime::<chrono::Utc>::from(
+                std::time::UNIX_EPOCH + metrics.last_updated.elapsed(),
+            )
+            .to_rfc3339(),
         })
-    }).collect();
-
-    let mut response_data = serde_json::json!({
-        "performance_health": performance_summary,
-        "metrics_summary": {
-            "total_operations": total_operations,
-            "average_duration_ms": average_duration_ms,
-            "total_memory_allocated_mb": total_memory_allocated_mb,
-            "operations_by_type": operations_by_type
+        .collect();
+
+    let mut report = HealthReport {
+        performance_health: performance_summary,
+        metrics_summary: MetricsSummary {
+            total_operations,
+            average_duration_ms,
+            total_memory_allocated_mb,
+            operations_by_type,
         },
-        "timestamp": chrono::Utc::now().to_rfc3339(),
-    });
+        log_stream: log_stream_hub().stats(),
+        state_transitions: get_state_manager().get_metrics().map_err(|e| {
+            ServiceError::internal_server_error(format!(
+                "Failed to read state transition metrics: {}",
+                e
+            ))
+        })?,
+        errors_by_tag: monitor.errors_by_tag(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        historical_data: None,
+        counters_reset: None,
+    };
 
     // Add historical data if requested
     if include_history {
-        response_data["historical_data"] = serde_json::json!({
-            "note": "Historical data tracking not yet implemented",
-            "future_enhancements": [
-                "Time-series performance data",
-                "Performance trend analysis",
-                "Bottleneck identification",
-                "Capacity planning metrics"
-            ]
+        report.historical_data = Some(HistoricalData {
+            note: "Historical data tracking not yet implemented".to_string(),
+            future_enhancements: vec![
+                "Time-series performance data".to_string(),
+                "Performance trend analysis".to_string(),
+                "Bottleneck identification".to_string(),
+                "Capacity planning metrics".to_string(),
+            ],
         });
     }
 
     // Reset counters if requested
     if reset_counters {
         monitor.reset_metrics();
-        response_data["counters_reset"] = serde_json::Value::Bool(true);
+        get_state_manager().reset_metrics().map_err(|e| {
+            ServiceError::internal_server_error(format!(
+                "Failed to reset state transition metrics: {}",
+                e
+            ))
+        })?;
+        report.counters_reset = Some(true);
     }
 
-    Ok(HttpResponse::Ok().json(ResponseBody::new(constants::MESSAGE_OK, response_data)))
+    Ok(HttpResponse::Ok().json(ResponseBody::new(constants::MESSAGE_OK, report)))
 }
 
-#[cfg(not(feature = "performance_monitoring"))]
-#[get("/health/performance")]
-async fn performance_metrics(_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
-    Ok(HttpResponse::ServiceUnavailable().json(ResponseBody::new(
-        "Performance monitoring feature not enabled",
-        serde_json::json!({
-            "error": "Performance monitoring is not compiled into this build",
-            "suggestion": "Rebuild with --features performance_monitoring",
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-        }),
-    )))
+/// Report flavours returned by `GET /api/health/compatibility`.
+#[cfg(feature = "functional")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    /// Structured results as `report`
+    Json,
+    /// Rendered Markdown as `full_report`
+    Markdown,
+    Both,
+}
+
+#[cfg(feature = "functional")]
+impl ReportFormat {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Some(ReportFormat::Json),
+            "markdown" => Some(ReportFormat::Markdown),
+            "both" => Some(ReportFormat::Both),
+            _ => None,
+        }
+    }
+}
+
+/// Builds the compatibility response body: summary counts always, plus the reports chosen by
+/// `format` unless `include_report` is false.
+#[cfg(feature = "functional")]
+fn compatibility_response(
+    results: &crate::functional::backward_compatibility::CompatibilityTestResults,
+    format: ReportFormat,
+    include_report: bool,
+) -> serde_json::Value {
+    let mut response_data = serde_json::json!({
+        "compatibility_status": results.overall_compatibility,
+        "test_summary": {
+            "api_endpoints": format!("{} passed, {} failed", results.api_endpoints_passed, results.api_endpoints_failed),
+            "authentication": format!("{} passed, {} failed", results.auth_tests_passed, results.auth_tests_failed),
+            "tenant_isolation": format!("{} passed, {} failed", results.tenant_isolation_passed, results.tenant_isolation_failed),
+            "database_operations": format!("{} passed, {} failed", results.database_tests_passed, results.database_tests_failed),
+            "frontend_compatibility": format!("{} passed, {} failed", results.frontend_compatibility_passed, results.frontend_compatibility_failed),
+            "performance_regressions": results.performance_regressions.len()
+        },
+        "failed_tests": results.failed_tests,
+        "performance_regressions": results.performance_regressions,
+        "timestamp": chrono::Utc::now().to_rfc3339()
+    });
+
+    if include_report {
+        if format != ReportFormat::Markdown {
+            response_data["report"] = serde_json::json!(results);
+        }
+        if format != ReportFormat::Json {
+            response_data["full_report"] = serde_json::Value::String(
+                crate::functional::backward_compatibility::generate_compatibility_report(results),
+            );
+        }
+    }
+
+    response_data
 }
 
 /// # Backward Compatibility Validation Endpoint
@@ -745,6 +927,8 @@ async fn performance_metrics(_req: HttpRequest) -> Result<HttpResponse, ServiceE
 /// - `run_tests`: Execute the full test suite (default: false for safety)
 /// - `test_category`: Run specific test category (api, auth, tenant, database, frontend)
 /// - `include_performance`: Include performance regression tests
+/// - `format`: Report flavour to include: `json`, `markdown` or `both` (default: both)
+/// - `include_report`: Set to `false` to return only the summary counts (default: true)
 ///
 /// ## Example Usage
 ///
@@ -757,12 +941,16 @@ async fn performance_metrics(_req: HttpRequest) -> Result<HttpResponse, ServiceE
 ///
 /// # Run full test suite including performance tests
 /// GET /api/health/compatibility?run_tests=true&include_performance=true
+///
+/// # Poll only the summary counts
+/// GET /api/health/compatibility?run_tests=true&include_report=false
 /// ```
 ///
 /// ## Response Format
 ///
 /// Returns test results with pass/fail status, detailed breakdown by category,
-/// and recommendations for any issues found.
+/// and recommendations for any issues found. The structured results are returned as
+/// `report` and the Markdown rendering as `full_report`, as selected by `format`.
 #[get("/health/compatibility")]
 pub async fn backward_compatibility_validation(
     _req: HttpRequest,
@@ -783,6 +971,20 @@ pub async fn backward_compatibility_validation(
             .get("include_performance")
             .map(|s| s == "true")
             .unwrap_or(true);
+        let format = match query.get("format") {
+            Some(format) => ReportFormat::parse(format).ok_or_else(|| {
+                ServiceError::bad_request(format!(
+                    "Unknown report format: {} (expected json, markdown or both)",
+                    format
+                ))
+                .with_tag(ErrorTag::Validation)
+            })?,
+            None => ReportFormat::Both,
+        };
+        let include_report = query
+            .get("include_report")
+            .map(|s| s != "false")
+            .unwrap_or(true);
 
         if !run_tests {
             // Return configuration info without running tests
@@ -792,7 +994,9 @@ pub async fn backward_compatibility_validation(
                 "usage": {
                     "run_tests": "Set to 'true' to execute all tests",
                     "test_category": "Specify category to run only that test (optional)",
-                    "include_performance": "Set to 'false' to skip performance tests (default: true)"
+                    "include_performance": "Set to 'false' to skip performance tests (default: true)",
+                    "format": "Report to include: json, markdown or both (default: both)",
+                    "include_report": "Set to 'false' to return only the summary counts (default: true)"
                 },
                 "note": "Running tests may create test data and affect performance metrics"
             });
@@ -802,8 +1006,8 @@ pub async fn backward_compatibility_validation(
             );
         }
 
-        // Create validator with default config
-        let config = CompatibilityTestConfig::default();
+        // Target and credentials from COMPAT_* variables, defaults otherwise
+        let config = CompatibilityTestConfig::from_env();
         let validator = BackwardCompatibilityValidator::new(config);
 
         // Run appropriate tests based on parameters
@@ -895,7 +1099,7 @@ pub async fn backward_compatibility_validation(
                         "Unknown test category: {}",
                         category
                     ))
-                    .with_tag("validation"));
+                    .with_tag(ErrorTag::Validation));
                 }
             }
         } else {
@@ -908,25 +1112,7 @@ pub async fn backward_compatibility_validation(
             results
         };
 
-        // Generate report
-        let report =
-            crate::functional::backward_compatibility::generate_compatibility_report(&results);
-
-        let response_data = serde_json::json!({
-            "compatibility_status": results.overall_compatibility,
-            "test_summary": {
-                "api_endpoints": format!("{} passed, {} failed", results.api_endpoints_passed, results.api_endpoints_failed),
-                "authentication": format!("{} passed, {} failed", results.auth_tests_passed, results.auth_tests_failed),
-                "tenant_isolation": format!("{} passed, {} failed", results.tenant_isolation_passed, results.tenant_isolation_failed),
-                "database_operations": format!("{} passed, {} failed", results.database_tests_passed, results.database_tests_failed),
-                "frontend_compatibility": format!("{} passed, {} failed", results.frontend_compatibility_passed, results.frontend_compatibility_failed),
-                "performance_regressions": results.performance_regressions.len()
-            },
-            "failed_tests": results.failed_tests,
-            "performance_regressions": results.performance_regressions,
-            "full_report": report,
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        });
+        let response_data = compatibility_response(&results, format, include_report);
 
         Ok(HttpResponse::Ok().json(ResponseBody::new(constants::MESSAGE_OK, response_data)))
     }
@@ -946,6 +1132,220 @@ pub async fn backward_compatibility_validation(
     }
 }
 
+/// Formats `data` as one Server-Sent Events frame of type `event`.
+#[cfg(feature = "functional")]
+fn sse_event(event: &str, data: &impl Serialize) -> Result<Bytes, actix_web::Error> {
+    let json = serde_json::to_string(data).map_err(actix_web::error::ErrorInternalServerError)?;
+    Ok(Bytes::from(format!("event: {}\ndata: {}\n\n", event, json)))
+}
+
+/// Runs `groups` one at a time through `run_group`, yielding a `progress` event as each one
+/// completes and a final `summary` event with the combined results.
+///
+/// Groups run lazily as the stream is polled, so a disconnected client stops the suite.
+#[cfg(feature = "functional")]
+fn compatibility_progress_stream<F, Fut>(
+    groups: Vec<crate::functional::backward_compatibility::CompatibilityGroup>,
+    run_group: F,
+) -> impl futures::Stream<Item = Result<Bytes, actix_web::Error>>
+where
+    F: Fn(crate::functional::backward_compatibility::CompatibilityGroup) -> Fut,
+    Fut: std::future::Future<Output = crate::functional::backward_compatibility::GroupOutcome>,
+{
+    use crate::functional::backward_compatibility::{
+        CompatibilityTestResults, GroupProgress, GroupStatus,
+    };
+    use std::time::Instant;
+
+    let started = Instant::now();
+    let state = (
+        groups.into_iter(),
+        CompatibilityTestResults::default(),
+        Some(run_group),
+    );
+
+    stream::unfold(
+        state,
+        move |(mut pending, mut results, run_group)| async move {
+            // `None` once the summary has been sent
+            let run_group = run_group?;
+
+            match pending.next() {
+                Some(group) => {
+                    let group_started = Instant::now();
+                    let outcome = run_group(group).await;
+                    let status = if results.record(group, outcome) {
+                        GroupStatus::Passed
+                    } else {
+                        GroupStatus::Failed
+                    };
+                    let progress = GroupProgress {
+                        group,
+                        status,
+                        elapsed_ms: group_started.elapsed().as_millis() as u64,
+                    };
+                    let frame = sse_event("progress", &progress);
+                    Some((frame, (pending, results, Some(run_group))))
+                }
+                None => {
+                    results.overall_compatibility = results.overall_status();
+                    let summary = serde_json::json!({
+                        "compatibility_status": results.overall_compatibility,
+                        "results": results,
+                        "elapsed_ms": started.elapsed().as_millis() as u64,
+                        "timestamp": chrono::Utc::now().to_rfc3339(),
+                    });
+                    let frame = sse_event("summary", &summary);
+                    Some((frame, (pending, results, None)))
+                }
+            }
+        },
+    )
+}
+
+/// # Backward Compatibility Progress Stream
+///
+/// Runs the same suite as `GET /api/health/compatibility?run_tests=true`, but reports over
+/// Server-Sent Events instead of blocking until the whole suite finishes.
+///
+/// Each completed test group emits a `progress` event with `{ group, status, elapsed_ms }`,
+/// followed by one `summary` event carrying the overall status and the full results.
+///
+/// ## Query Parameters
+///
+/// - `include_performance`: Set to `false` to skip the performance regression group
+///   (default: true)
+///
+/// ## Example Usage
+///
+/// ```bash
+/// curl -N http://localhost:8000/api/health/compatibility/stream
+/// ```
+#[get("/health/compatibility/stream")]
+pub async fn backward_compatibility_stream(
+    query: web::Query<std::collections::HashMap<String, String>>,
+) -> Result<HttpResponse, ServiceError> {
+    info!("Backward compatibility stream endpoint called");
+
+    #[cfg(feature = "functional")]
+    {
+        use crate::functional::backward_compatibility::{
+            BackwardCompatibilityValidator, CompatibilityGroup, CompatibilityTestConfig,
+        };
+        use std::rc::Rc;
+
+        let include_performance = query
+            .get("include_performance")
+            .map(|s| s == "true")
+            .unwrap_or(true);
+        let groups = CompatibilityGroup::ALL
+            .into_iter()
+            .filter(|group| {
+                include_performance || *group != CompatibilityGroup::PerformanceRegression
+            })
+            .collect();
+
+        let validator = Rc::new(BackwardCompatibilityValidator::new(
+            CompatibilityTestConfig::from_env(),
+        ));
+        let stream = compatibility_progress_stream(groups, move |group| {
+            let validator = Rc::clone(&validator);
+            async move { validator.run_group(group).await }
+        });
+
+        Ok(HttpResponse::Ok()
+            .insert_header(("Content-Type", "text/event-stream"))
+            .insert_header(("Cache-Control", "no-cache"))
+            .insert_header(("Connection", "keep-alive"))
+            .streaming(stream))
+    }
+
+    #[cfg(not(feature = "functional"))]
+    {
+        let _ = query;
+        let error_data = serde_json::json!({
+            "error": "Backward compatibility testing not available",
+            "reason": "Functional programming features not enabled",
+            "solution": "Enable the 'functional' feature flag to access compatibility testing"
+        });
+
+        Ok(HttpResponse::ServiceUnavailable().json(ResponseBody::new(
+            "Backward compatibility testing not enabled in this build",
+            error_data,
+        )))
+    }
+}
+
+/// Content type of the Prometheus text exposition format
+#[cfg(feature = "performance_monitoring")]
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Serves the metrics of `/health/performance`, HTTP request counts and
+/// durations, and database pool gauges in the Prometheus text format.
+///
+/// Pools are labelled `main`, and `tenants` for all tenant pools together.
+/// Like `/health`, the endpoint needs no token so scrapers can reach it, which
+/// is why tenant ids are never used as labels.
+#[cfg(feature = "performance_monitoring")]
+#[get("/metrics")]
+async fn prometheus_metrics(req: HttpRequest) -> HttpResponse {
+    use crate::functional::performance_monitoring::{http_metrics, PrometheusRegistry};
+
+    let monitor = get_performance_monitor();
+    // Tenant pools are summed: /metrics needs no token, so tenant ids must not appear
+    let pools: Vec<(&str, u32, u32)> = req
+        .app_data::<web::Data<TenantPoolManager>>()
+        .map(|manager| {
+            let main = manager.main_pool.state();
+            let (connections, idle) = manager
+                .tenant_pools
+                .read()
+                .map(|pools| {
+                    pools.values().map(|pool| pool.state()).fold(
+                        (0, 0),
+                        |(connections, idle), state| {
+                            (
+                                connections + state.connections,
+                                idle + state.idle_connections,
+                            )
+                        },
+                    )
+                })
+                .unwrap_or_default();
+            vec![
+                ("main", main.connections, main.idle_connections),
+                ("tenants", connections, idle),
+            ]
+        })
+        .unwrap_or_default();
+
+    let mut registry = PrometheusRegistry::new();
+    registry
+        .operations(&monitor.get_all_metrics())
+        .error_tags(&monitor.errors_by_tag())
+        .http(http_metrics())
+        .family(
+            "db_pool_connections",
+            "gauge",
+            "Open connections of the main pool, or of all tenant pools together",
+            pools.iter().map(|(pool, connections, _)| {
+                (vec![("pool", pool.to_string())], f64::from(*connections))
+            }),
+        )
+        .family(
+            "db_pool_idle_connections",
+            "gauge",
+            "Idle connections of the main pool, or of all tenant pools together",
+            pools
+                .iter()
+                .map(|(pool, _, idle)| (vec![("pool", pool.to_string())], f64::from(*idle))),
+        );
+
+    HttpResponse::Ok()
+        .content_type(PROMETHEUS_CONTENT_TYPE)
+        .body(registry.render().to_string())
+}
+
 #[cfg(test)]
 mod tests {
     //! Integration tests for health and logging endpoints.
@@ -968,6 +1368,7 @@ mod tests {
     use testcontainers::Container;
 
     use crate::config;
+    use crate::test_support::{assert_error_envelope, assert_ok_envelope};
     use std::env;
     use tempfile::NamedTempFile;
     use tokio::time::{timeout, Duration};
@@ -980,6 +1381,245 @@ mod tests {
         catch_unwind(AssertUnwindSafe(|| docker.run(Redis))).ok()
     }
 
+    #[actix_web::test]
+    async fn test_overall_status_weighs_component_criticality() {
+        use Status::{Degraded, Healthy, Unhealthy};
+
+        let defaults = HealthCriticality::default();
+        let cases = [
+            ((Healthy, Healthy, Healthy), Healthy),
+            ((Healthy, Unhealthy, Healthy), Degraded),
+            ((Healthy, Healthy, Unhealthy), Degraded),
+            ((Healthy, Unhealthy, Unhealthy), Degraded),
+            ((Unhealthy, Healthy, Healthy), Unhealthy),
+            ((Unhealthy, Unhealthy, Unhealthy), Unhealthy),
+        ];
+        for ((database, cache, tenants), expected) in cases {
+            let overall = overall_status(
+                &defaults,
+                [
+                    ("database", &database),
+                    ("cache", &cache),
+                    ("tenants", &tenants),
+                ],
+            );
+            assert_eq!(
+                overall, expected,
+                "{:?}/{:?}/{:?}",
+                database, cache, tenants
+            );
+        }
+
+        let strict_cache = HealthCriticality::default().with("cache", Criticality::Critical);
+        assert_eq!(
+            overall_status(
+                &strict_cache,
+                [("database", &Healthy), ("cache", &Unhealthy)]
+            ),
+            Unhealthy
+        );
+        let lenient_db = HealthCriticality::default().with("database", Criticality::NonCritical);
+        assert_eq!(
+            overall_status(&lenient_db, [("database", &Unhealthy), ("cache", &Healthy)]),
+            Degraded
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_unreachable_tenant_does_not_block_healthy_ones() {
+        let started = std::time::Instant::now();
+        let results = probe_concurrently(
+            vec![0_u64, 1_500, 0],
+            Duration::from_millis(100),
+            |delay_ms| {
+                std::thread::sleep(Duration::from_millis(delay_ms));
+                Ok(())
+            },
+        )
+        .await;
+
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(results[0], Ok(()));
+        assert_eq!(results[1], Err("Timed out after 100ms".to_string()));
+        assert_eq!(results[2], Ok(()));
+    }
+
+    #[actix_web::test]
+    async fn test_detailed_probes_run_concurrently() {
+        let delay = Duration::from_millis(300);
+        let slow_probe = || async move {
+            tokio::time::sleep(delay).await;
+            Ok(())
+        };
+        let started = std::time::Instant::now();
+
+        let (db_status, cache_status, tenants) =
+            probe_components(slow_probe(), slow_probe(), async {
+                tokio::time::sleep(delay).await;
+                Some(Vec::new())
+            })
+            .await;
+
+        // The slowest probe bounds the check, not the 900ms the probes add up to
+        assert!(started.elapsed() < delay * 2);
+        assert_eq!(db_status, Status::Healthy);
+        assert_eq!(cache_status, Status::Healthy);
+        assert!(tenants.is_some());
+    }
+
+    #[actix_web::test]
+    async fn test_partial_tenant_failure_is_degraded() {
+        let tenant = |status| TenantHealth {
+            tenant_id: "t".to_string(),
+            name: "t".to_string(),
+            status,
+            reason: None,
+        };
+
+        assert_eq!(
+            tenants_status(&[tenant(Status::Healthy), tenant(Status::Healthy)]),
+            Status::Healthy
+        );
+        assert_eq!(
+            tenants_status(&[tenant(Status::Healthy), tenant(Status::Unhealthy)]),
+            Status::Degraded
+        );
+        assert_eq!(
+            tenants_status(&[tenant(Status::Unhealthy), tenant(Status::Unhealthy)]),
+            Status::Unhealthy
+        );
+
+        // Even with critical tenants, only a complete outage is unhealthy
+        let critical = HealthCriticality::default().with("tenants", Criticality::Critical);
+        assert_eq!(
+            overall_status(&critical, [("tenants", &Status::Degraded)]),
+            Status::Degraded
+        );
+        assert_eq!(
+            overall_status(&critical, [("tenants", &Status::Unhealthy)]),
+            Status::Unhealthy
+        );
+    }
+
+    #[cfg(feature = "performance_monitoring")]
+    #[actix_web::test]
+    async fn test_metrics_serve_prometheus_text_without_token() {
+        let pool = DatabasePool::builder().build_unchecked(diesel::r2d2::ConnectionManager::new(
+            "postgres://127.0.0.1:1/unreachable",
+        ));
+        let manager = TenantPoolManager::new(pool.clone());
+        manager
+            .add_tenant_pool("secret_tenant".to_string(), pool)
+            .unwrap();
+        let app = test::init_service(
+            actix_web::App::new()
+                .app_data(Data::new(manager))
+                .wrap(crate::middleware::auth_middleware::Authentication)
+                .wrap(crate::middleware::request_log::RequestLog::default())
+                .service(prometheus_metrics),
+        )
+        .await;
+
+        // The first scrape is counted in the second
+        test::call_service(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+        let resp =
+            test::call_service(&app, test::TestRequest::get().uri("/metrics").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            PROMETHEUS_CONTENT_TYPE
+        );
+
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(body.contains("# TYPE http_request_duration_seconds histogram\n"));
+        assert!(body.contains("http_requests_total{method=\"GET\",status=\"200\"}"));
+        assert!(body.contains("db_pool_connections{pool=\"main\"} 0\n"));
+        assert!(body.contains("db_pool_connections{pool=\"tenants\"} 0\n"));
+        assert!(!body.contains("secret_tenant"));
+    }
+
+    #[actix_web::test]
+    async fn test_unhealthy_status_answers_service_unavailable() {
+        let response = |status| HealthResponse {
+            status,
+            timestamp: Utc::now().to_rfc3339(),
+            components: HealthStatus {
+                database: Status::Healthy,
+                cache: Status::Healthy,
+            },
+            tenants: None,
+            pending_tenants: Vec::new(),
+            performance: None,
+        };
+
+        assert_eq!(
+            health_response(response(Status::Unhealthy)).status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            health_response(response(Status::Degraded)).status(),
+            StatusCode::OK
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_live_answers_ok_without_database_or_cache() {
+        // No pools are registered: the liveness check must not need them
+        let app = test::init_service(
+            actix_web::App::new()
+                .wrap(crate::middleware::auth_middleware::Authentication)
+                .service(health_live)
+                .service(health),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/health/live").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["data"]["status"], "healthy");
+
+        let req = test::TestRequest::default()
+            .method(actix_web::http::Method::HEAD)
+            .uri("/health/live")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_head_is_routed_to_health_checks() {
+        let app = test::init_service(actix_web::App::new().service(health)).await;
+
+        // Methods the route does not accept are not found
+        let req = test::TestRequest::post().uri("/health").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        // Without pools the handler fails to extract them, which shows HEAD reached it
+        let req = test::TestRequest::default()
+            .method(actix_web::http::Method::HEAD)
+            .uri("/health")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[actix_web::test]
+    async fn test_effective_config_reports_masked_snapshot() {
+        let app = test::init_service(actix_web::App::new().service(effective_config)).await;
+
+        let req = test::TestRequest::get().uri("/health/config").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        let data = &body["data"];
+        assert_eq!(data["features"]["functional"], cfg!(feature = "functional"));
+        assert!(data["db_pool"]["max_size"].as_u64().unwrap() > 0);
+        if let Some(url) = data["database_url"].as_str() {
+            assert_eq!(url, crate::config::functional_config::UrlMasker::mask(url));
+        }
+    }
+
     /// Verifies that the /api/health endpoint returns HTTP 200 when PostgreSQL and Redis are available.
     ///
     /// Spawns PostgreSQL and Redis test containers, initializes the database and cache clients, mounts the application,
@@ -1044,6 +1684,13 @@ mod tests {
 
         assert_eq!(resp.status(), StatusCode::OK);
         // You can parse the JSON and check fields
+
+        let req = test::TestRequest::default()
+            .method(actix_web::http::Method::HEAD)
+            .uri("/health")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
     }
 
     /// Verifies that the `/api/logs` endpoint streams Server-Sent Events (SSE) when log streaming is enabled.
@@ -1227,14 +1874,15 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status(), StatusCode::OK);
 
-        let body_bytes = test::read_body(resp).await;
-        let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        let data = assert_ok_envelope(&test::read_body(resp).await);
 
         // Verify response structure
-        assert!(json["data"]["performance_health"].is_object());
-        assert!(json["data"]["metrics_summary"].is_object());
-        assert!(json["data"]["metrics_summary"]["total_operations"].is_number());
-        assert!(json["data"]["timestamp"].is_string());
+        assert!(data["performance_health"].is_object());
+        assert!(data["metrics_summary"].is_object());
+        assert!(data["metrics_summary"]["total_operations"].is_number());
+        assert!(data["state_transitions"]["transition_count"].is_number());
+        assert!(data["errors_by_tag"]["validation"].is_number());
+        assert!(data["timestamp"].is_string());
 
         // Test with operation type filter
         let req = test::TestRequest::get()
@@ -1250,9 +1898,8 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status(), StatusCode::OK);
 
-        let body_bytes = test::read_body(resp).await;
-        let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
-        assert!(json["data"]["historical_data"].is_object());
+        let data = assert_ok_envelope(&test::read_body(resp).await);
+        assert!(data["historical_data"].is_object());
 
         // Test with reset counters
         let req = test::TestRequest::get()
@@ -1261,9 +1908,56 @@ mod tests {
         let resp = test::call_service(&app, req).await;
         assert_eq!(resp.status(), StatusCode::OK);
 
-        let body_bytes = test::read_body(resp).await;
-        let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
-        assert_eq!(json["data"]["counters_reset"], true);
+        let data = assert_ok_envelope(&test::read_body(resp).await);
+        assert_eq!(data["counters_reset"], true);
+    }
+
+    #[cfg(feature = "performance_monitoring")]
+    #[actix_web::test]
+    async fn test_performance_metrics_matches_typed_report() {
+        use crate::functional::performance_monitoring::{get_performance_monitor, OperationType};
+        use actix_web::{http::StatusCode, test};
+        use std::time::Duration as StdDuration;
+
+        get_performance_monitor().record_operation(
+            OperationType::StateTransition,
+            StdDuration::from_millis(20),
+            2 * 1024 * 1024,
+            true,
+        );
+
+        let app = test::init_service(actix_web::App::new().service(performance_metrics)).await;
+        let req = test::TestRequest::get()
+            .uri("/health/performance?operation_type=state_transition&include_history=true")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: ResponseBody<HealthReport> = test::read_body_json(resp).await;
+        let report = body.data;
+
+        // Other tests may reset the global monitor concurrently, so only the
+        // shape and internal consistency of the report are asserted.
+        let summary = &report.metrics_summary;
+        assert!(summary.operations_by_type.len() <= 1);
+        assert!(summary
+            .operations_by_type
+            .iter()
+            .all(|view| view.operation == "StateTransition"));
+        assert_eq!(
+            summary.total_operations,
+            summary
+                .operations_by_type
+                .iter()
+                .map(|view| view.execution_count)
+                .sum::<u64>()
+        );
+        assert_eq!(
+            report.historical_data.map(|h| h.future_enhancements.len()),
+            Some(4)
+        );
+        assert_eq!(report.counters_reset, None);
+        assert!(chrono::DateTime::parse_from_rfc3339(&report.timestamp).is_ok());
     }
 
     #[cfg(not(feature = "performance_monitoring"))]
@@ -1283,4 +1977,126 @@ mod tests {
         let json: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
         assert!(json["message"].as_str().unwrap().contains("not enabled"));
     }
+
+    #[cfg(feature = "functional")]
+    #[actix_web::test]
+    async fn test_compatibility_stream_reports_progress_then_summary() {
+        use crate::functional::backward_compatibility::CompatibilityGroup;
+
+        let groups = vec![
+            CompatibilityGroup::ApiEndpoints,
+            CompatibilityGroup::JwtAuthentication,
+            CompatibilityGroup::DatabaseOperations,
+        ];
+        // Stands in for the HTTP client: only the JWT group fails
+        let frames: Vec<String> = compatibility_progress_stream(groups, |group| async move {
+            match group {
+                CompatibilityGroup::JwtAuthentication => Err("token rejected".to_string()),
+                _ => Ok(Vec::new()),
+            }
+        })
+        .map(|frame| String::from_utf8(frame.unwrap().to_vec()).unwrap())
+        .collect()
+        .await;
+
+        assert_eq!(frames.len(), 4);
+        let data = |frame: &str, event: &str| -> serde_json::Value {
+            let body = frame
+                .strip_prefix(&format!("event: {}\ndata: ", event))
+                .and_then(|rest| rest.strip_suffix("\n\n"))
+                .unwrap_or_else(|| panic!("not a {} frame: {:?}", event, frame));
+            serde_json::from_str(body).unwrap()
+        };
+
+        let progress: Vec<_> = frames[..3].iter().map(|f| data(f, "progress")).collect();
+        assert_eq!(progress[0]["group"], "api_endpoints");
+        assert_eq!(progress[0]["status"], "passed");
+        assert_eq!(progress[1]["group"], "jwt_authentication");
+        assert_eq!(progress[1]["status"], "failed");
+        assert_eq!(progress[2]["group"], "database_operations");
+        assert!(progress.iter().all(|p| p["elapsed_ms"].is_u64()));
+
+        let summary = data(&frames[3], "summary");
+        assert_eq!(summary["compatibility_status"], "Incompatible");
+        assert_eq!(summary["results"]["auth_tests_failed"], 3);
+        assert_eq!(
+            summary["results"]["failed_tests"][0],
+            "JWT authentication: token rejected"
+        );
+    }
+
+    #[cfg(feature = "functional")]
+    #[actix_web::test]
+    async fn test_compatibility_response_formats() {
+        use crate::functional::backward_compatibility::CompatibilityTestResults;
+
+        let results = CompatibilityTestResults {
+            api_endpoints_passed: 5,
+            failed_tests: vec!["JWT authentication: token rejected".into()],
+            ..CompatibilityTestResults::default()
+        };
+        let fields = |format, include_report| {
+            let response = compatibility_response(&results, format, include_report);
+            assert_eq!(
+                response["test_summary"]["api_endpoints"],
+                "5 passed, 0 failed"
+            );
+            assert_eq!(
+                response["failed_tests"][0],
+                "JWT authentication: token rejected"
+            );
+            (
+                response.get("report").cloned(),
+                response.get("full_report").cloned(),
+            )
+        };
+
+        let (report, full_report) = fields(ReportFormat::Both, true);
+        assert_eq!(report.unwrap()["api_endpoints_passed"], 5);
+        assert!(full_report
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .contains("Backward Compatibility Validation Report"));
+
+        let (report, full_report) = fields(ReportFormat::Json, true);
+        assert!(report.is_some());
+        assert!(full_report.is_none());
+
+        let (report, full_report) = fields(ReportFormat::Markdown, true);
+        assert!(report.is_none());
+        assert!(full_report.is_some());
+
+        for format in [
+            ReportFormat::Json,
+            ReportFormat::Markdown,
+            ReportFormat::Both,
+        ] {
+            assert_eq!(fields(format, false), (None, None));
+        }
+    }
+
+    #[cfg(feature = "functional")]
+    #[actix_web::test]
+    async fn test_compatibility_rejects_unknown_format() {
+        let app =
+            test::init_service(actix_web::App::new().service(backward_compatibility_validation))
+                .await;
+
+        let req = test::TestRequest::get()
+            .uri("/health/compatibility?run_tests=true&format=xml")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        assert_error_envelope(
+            &test::read_body(resp).await,
+            "Unknown report format: xml (expected json, markdown or both)",
+        );
+
+        assert_eq!(
+            ReportFormat::parse("Markdown"),
+            Some(ReportFormat::Markdown)
+        );
+        assert_eq!(ReportFormat::parse("yaml"), None);
+    }
 }
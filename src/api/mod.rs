@@ -1,6 +1,11 @@
 pub mod account_controller;
 pub mod address_book_controller;
+pub mod audit_controller;
+pub mod function_controller;
 pub mod health_controller;
+pub mod nfe_controller;
+pub mod openapi_controller;
 pub mod ping_controller;
+pub mod schema_controller;
 pub mod tenant_controller;
 pub mod user_controller;
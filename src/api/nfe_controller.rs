@@ -0,0 +1,223 @@
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse, Responder, Result};
+use std::borrow::Cow;
+use std::time::Instant;
+
+use crate::{
+    config::db::Pool,
+    constants,
+    error::{ErrorTag, ServiceError},
+    functional::response_transformers::ResponseTransformer,
+    middleware::{
+        request_trace,
+        server_timing::{time_phase, ServerTiming},
+    },
+    models::nfe_document::NfeDocumentPayload,
+    services::{
+        functional_service_base::FunctionalErrorHandling,
+        nfe_service::{self, NfeBatchEntry},
+        nfe_xml::NfeSigner,
+    },
+    utils::token_utils,
+};
+
+/// Extract the database pool from the request extensions.
+fn extract_pool(req: &HttpRequest) -> Result<Pool, ServiceError> {
+    req.extensions().get::<Pool>().cloned().ok_or_else(|| {
+        ServiceError::internal_server_error("Pool not found")
+            .with_detail("Missing tenant pool in request extensions")
+            .with_tag(ErrorTag::Tenant)
+    })
+}
+
+/// Resolve the tenant id from the bearer token on the request.
+///
+/// The auth middleware has already verified the token; this only decodes it
+/// again to read the `tenant_id` claim.
+fn extract_tenant_id(req: &HttpRequest) -> Result<String, ServiceError> {
+    req.headers()
+        .get(constants::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|value| value.split_whitespace().nth(1))
+        .ok_or_else(|| {
+            ServiceError::unauthorized(constants::MESSAGE_TOKEN_MISSING).with_tag(ErrorTag::Auth)
+        })
+        .and_then(|token| {
+            token_utils::decode_token(token.to_string())
+                .map(|data| data.claims.tenant_id)
+                .map_err(|_| {
+                    ServiceError::unauthorized(constants::MESSAGE_INVALID_TOKEN)
+                        .with_tag(ErrorTag::Auth)
+                })
+        })
+}
+
+/// Decode a batch body as either a JSON array or NDJSON (one document per line).
+///
+/// Each element is decoded on its own so a malformed document becomes a
+/// rejected entry instead of failing the whole batch. Only a body that is not
+/// a JSON array at all (in JSON mode) is a request error.
+fn parse_batch(body: &[u8], ndjson: bool) -> Result<Vec<NfeBatchEntry>, ServiceError> {
+    let decode = |value: Result<serde_json::Value, serde_json::Error>| {
+        value
+            .and_then(serde_json::from_value::<NfeDocumentPayload>)
+            .map_err(|e| e.to_string())
+    };
+
+    if ndjson {
+        let text = std::str::from_utf8(body).map_err(|e| {
+            ServiceError::bad_request("Batch body must be valid UTF-8")
+                .with_tag(ErrorTag::Validation)
+                .with_detail(e.to_string())
+        })?;
+        Ok(text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| decode(serde_json::from_str(line)))
+            .collect())
+    } else {
+        serde_json::from_slice::<Vec<serde_json::Value>>(body)
+            .map(|values| values.into_iter().map(|v| decode(Ok(v))).collect())
+            .map_err(|e| {
+                ServiceError::bad_request("Batch body must be a JSON array of documents")
+                    .with_tag(ErrorTag::Validation)
+                    .with_detail(e.to_string())
+            })
+    }
+}
+
+// POST api/nfe/batch
+/// Imports a batch of NFe documents and reports the outcome of each one.
+///
+/// Accepts a JSON array of documents or, with `Content-Type: application/x-ndjson`,
+/// one document per line. Every document is validated and valid ones are
+/// persisted in their own transaction; invalid ones are reported and skipped.
+///
+/// # Returns
+///
+/// `200 OK` with an array of `{ index, status, access_key?, errors? }`, or a
+/// `ServiceError` when the body cannot be read, the batch is empty or larger
+/// than `nfe_service::MAX_BATCH_SIZE`, or the tenant cannot be resolved.
+pub async fn import_batch(
+    req: HttpRequest,
+    body: web::Bytes,
+) -> Result<HttpResponse, ServiceError> {
+    let ndjson = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.contains("ndjson"));
+
+    let entries = parse_batch(&body, ndjson)?;
+    let tenant_id = extract_tenant_id(&req)?;
+    let pool = extract_pool(&req)?;
+
+    let db_started = Instant::now();
+    let results =
+        request_trace::block(move || nfe_service::import_batch(&tenant_id, entries, &pool)).await;
+    if let Some(timing) = ServerTiming::from_request(&req) {
+        timing.record("db", db_started.elapsed());
+    }
+
+    results
+        .map_err(|e| {
+            ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR)
+                .with_tag(ErrorTag::Internal)
+                .with_detail(e.to_string())
+        })?
+        .log_error("nfe_controller::import_batch")
+        .map(|results| {
+            ResponseTransformer::new(results)
+                .with_message(Cow::Borrowed(constants::MESSAGE_OK))
+                .respond_to(&req)
+        })
+}
+
+// GET api/nfe/{access_key}
+/// Fetches a document of the caller's tenant by its 44-digit access key.
+///
+/// # Returns
+///
+/// `200 OK` with the document, its items and their tax groups; `400 Bad Request`
+/// when the key is malformed or its check digit is wrong; `404 Not Found` when
+/// the tenant has no document with that key.
+pub async fn find_by_access_key(
+    access_key: web::Path<String>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let pool = extract_pool(&req)?;
+    time_phase(&req, "db", || {
+        nfe_service::find_by_access_key(&tenant_id, &access_key, &pool)
+    })
+    .log_error("nfe_controller::find_by_access_key")
+    .map(|detail| {
+        ResponseTransformer::new(detail)
+            .with_message(Cow::Borrowed(constants::MESSAGE_OK))
+            .respond_to(&req)
+    })
+}
+
+// GET api/nfe/{access_key}/xml
+/// Renders a document of the caller's tenant as NFe XML, signed by the
+/// server's configured `NfeSigner` (see `nfe_xml::signer_from_env`).
+///
+/// # Returns
+///
+/// `200 OK` with the `application/xml` document; the errors of
+/// `find_by_access_key`, plus `422 Unprocessable Entity` when the document has
+/// no emitter on record and `500 Internal Server Error` when signing fails.
+pub async fn export_xml(
+    access_key: web::Path<String>,
+    signer: web::Data<dyn NfeSigner + Send + Sync>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    let tenant_id = extract_tenant_id(&req)?;
+    let pool = extract_pool(&req)?;
+    time_phase(&req, "db", || {
+        nfe_service::export_xml(&tenant_id, &access_key, &pool, signer.as_ref())
+    })
+    .log_error("nfe_controller::export_xml")
+    .map(|xml| {
+        HttpResponse::Ok()
+            .content_type("application/xml; charset=utf-8")
+            .body(xml)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"{"access_key":"1","serie":"1","numero":"1","emitter":{"razao_social":"A"},"items":[],"valor_produtos":"0","valor_total":"0","valor_impostos":"0"}"#;
+
+    #[test]
+    fn test_parse_batch_json_array_keeps_malformed_entries() {
+        let body = format!(r#"[{}, {{"serie": 1}}]"#, VALID);
+
+        let entries = parse_batch(body.as_bytes(), false).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_ok());
+        assert!(entries[1].is_err());
+    }
+
+    #[test]
+    fn test_parse_batch_ndjson_one_document_per_line() {
+        let body = format!("{}\nnot json\n\n{}\n", VALID, VALID);
+
+        let entries = parse_batch(body.as_bytes(), true).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert!(entries[0].is_ok());
+        assert!(entries[1].is_err());
+        assert!(entries[2].is_ok());
+    }
+
+    #[test]
+    fn test_parse_batch_rejects_non_array_body() {
+        let result = parse_batch(VALID.as_bytes(), false);
+
+        assert!(matches!(result, Err(ServiceError::BadRequest { .. })));
+    }
+}
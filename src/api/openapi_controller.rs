@@ -0,0 +1,578 @@
+use std::collections::BTreeMap;
+
+use actix_web::{get, HttpResponse};
+use once_cell::sync::Lazy;
+use schemars::{
+    gen::{SchemaGenerator, SchemaSettings},
+    schema::Schema,
+    JsonSchema,
+};
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+
+use crate::{
+    api::audit_controller::AuditPage,
+    constants,
+    models::{
+        login_history::SessionDTO,
+        person::{Person, PersonDTO, PersonPatch},
+        response::{Page, ResponseBody},
+        tenant::{Tenant, TenantDTO, UpdateTenant},
+        user::{LoginDTO, LoginInfoDTO, SignupDTO},
+    },
+    services::{
+        account_service::{RefreshTokenRequest, TokenBodyResponse},
+        address_book_service::{PersonBulkDelete, PersonBulkDeleteRequest},
+        tenant_service::DeprovisionReport,
+    },
+};
+
+/// Header carrying the tenant a request is scoped to.
+const TENANT_HEADER: &str = "x-tenant-id";
+
+/// Name of the bearer-token security scheme in `components.securitySchemes`.
+const BEARER_AUTH: &str = "bearerAuth";
+
+/// Produces the schema (usually a `$ref`) of a request or response body.
+type BodySchema = fn(&mut SchemaGenerator) -> Schema;
+
+fn body<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    gen.subschema_for::<T>()
+}
+
+/// Documented endpoint.
+struct Route {
+    method: &'static str,
+    path: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    request: Option<BodySchema>,
+    response: Option<BodySchema>,
+    /// Status code of a successful response.
+    status: &'static str,
+    /// Whether the handler resolves a tenant database for the request.
+    tenant_scoped: bool,
+}
+
+const fn route(
+    method: &'static str,
+    path: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+) -> Route {
+    Route {
+        method,
+        path,
+        tag,
+        summary,
+        request: None,
+        response: None,
+        status: "200",
+        tenant_scoped: false,
+    }
+}
+
+impl Route {
+    const fn request(mut self, schema: BodySchema) -> Self {
+        self.request = Some(schema);
+        self
+    }
+
+    const fn response(mut self, schema: BodySchema) -> Self {
+        self.response = Some(schema);
+        self
+    }
+
+    const fn created(mut self) -> Self {
+        self.status = "201";
+        self
+    }
+
+    const fn tenant_scoped(mut self) -> Self {
+        self.tenant_scoped = true;
+        self
+    }
+
+    /// Routes listed in `constants::IGNORE_ROUTES` skip the auth middleware.
+    fn is_public(&self) -> bool {
+        constants::IGNORE_ROUTES
+            .iter()
+            .any(|prefix| self.path.starts_with(prefix))
+    }
+
+    /// Names of the `{param}` segments of the path.
+    fn path_params(&self) -> impl Iterator<Item = &'static str> {
+        self.path
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+    }
+}
+
+/// Endpoints described by the OpenAPI document.
+static ROUTES: [Route; 41] = [
+    route("get", "/health", "health", "Readiness: component health").response(body::<JsonValue>),
+    route(
+        "get",
+        "/health/live",
+        "health",
+        "Liveness, without checking dependencies",
+    ),
+    route("get", "/api/ping", "health", "Ping the service"),
+    route(
+        "get",
+        "/metrics",
+        "health",
+        "Metrics in Prometheus text format",
+    ),
+    route(
+        "get",
+        "/api/health/detailed",
+        "health",
+        "Detailed health report",
+    )
+    .response(body::<JsonValue>),
+    route(
+        "get",
+        "/api/health/performance",
+        "health",
+        "Performance metrics",
+    )
+    .response(body::<JsonValue>),
+    route(
+        "get",
+        "/api/health/compatibility",
+        "health",
+        "Backward compatibility validation report",
+    )
+    .response(body::<JsonValue>),
+    route(
+        "get",
+        "/api/admin/health/config",
+        "health",
+        "Effective configuration with secrets masked",
+    )
+    .response(body::<JsonValue>),
+    route(
+        "get",
+        "/api/admin/functions",
+        "functions",
+        "Pure functions with their call counts",
+    )
+    .response(body::<ResponseBody<JsonValue>>),
+    route("post", "/api/auth/signup", "auth", "Create a user account")
+        .request(body::<SignupDTO>)
+        .response(body::<ResponseBody<String>>),
+    route(
+        "post",
+        "/api/auth/login",
+        "auth",
+        "Log in and obtain tokens",
+    )
+    .request(body::<LoginDTO>)
+    .response(body::<ResponseBody<TokenBodyResponse>>),
+    route(
+        "post",
+        "/api/auth/logout",
+        "auth",
+        "Invalidate the current session",
+    )
+    .response(body::<ResponseBody<String>>)
+    .tenant_scoped(),
+    route(
+        "post",
+        "/api/auth/refresh",
+        "auth",
+        "Refresh the login session",
+    )
+    .response(body::<ResponseBody<LoginInfoDTO>>)
+    .tenant_scoped(),
+    route(
+        "post",
+        "/api/auth/refresh-token",
+        "auth",
+        "Exchange a refresh token for new tokens",
+    )
+    .request(body::<RefreshTokenRequest>)
+    .response(body::<ResponseBody<TokenBodyResponse>>)
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/auth/me",
+        "auth",
+        "Current user's login information",
+    )
+    .response(body::<ResponseBody<LoginInfoDTO>>)
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/auth/sessions",
+        "auth",
+        "Active sessions of the current user",
+    )
+    .response(body::<ResponseBody<Vec<SessionDTO>>>)
+    .tenant_scoped(),
+    route(
+        "delete",
+        "/api/auth/sessions",
+        "auth",
+        "Revoke all other sessions of the current user",
+    )
+    .response(body::<ResponseBody<JsonValue>>)
+    .tenant_scoped(),
+    route(
+        "delete",
+        "/api/auth/sessions/{id}",
+        "auth",
+        "Revoke a session of the current user",
+    )
+    .response(body::<ResponseBody<String>>)
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/address-book",
+        "address-book",
+        "List a page of people, or search them with q",
+    )
+    .response(body::<ResponseBody<Vec<Person>>>)
+    .tenant_scoped(),
+    route(
+        "post",
+        "/api/address-book",
+        "address-book",
+        "Create a person",
+    )
+    .request(body::<PersonDTO>)
+    .response(body::<ResponseBody<String>>)
+    .created()
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/address-book/filter",
+        "address-book",
+        "Filter people",
+    )
+    .response(body::<Page<Person>>)
+    .tenant_scoped(),
+    route(
+        "post",
+        "/api/address-book/validate-batch",
+        "address-book",
+        "Validate a batch of people without saving",
+    )
+    .request(body::<Vec<PersonDTO>>)
+    .response(body::<ResponseBody<JsonValue>>)
+    .tenant_scoped(),
+    route(
+        "post",
+        "/api/address-book/batch",
+        "address-book",
+        "Create a batch of people",
+    )
+    .request(body::<Vec<PersonDTO>>)
+    .response(body::<ResponseBody<JsonValue>>)
+    .created()
+    .tenant_scoped(),
+    route(
+        "post",
+        "/api/address-book/bulk-delete",
+        "address-book",
+        "Delete a batch of people",
+    )
+    .request(body::<PersonBulkDeleteRequest>)
+    .response(body::<ResponseBody<PersonBulkDelete>>)
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/address-book/export.csv",
+        "address-book",
+        "Export all people as CSV",
+    )
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/address-book/ws",
+        "address-book",
+        "Subscribe to contact changes over a WebSocket",
+    )
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/address-book/{id}",
+        "address-book",
+        "Find a person",
+    )
+    .response(body::<ResponseBody<Person>>)
+    .tenant_scoped(),
+    route(
+        "put",
+        "/api/address-book/{id}",
+        "address-book",
+        "Update a person",
+    )
+    .request(body::<PersonDTO>)
+    .response(body::<ResponseBody<String>>)
+    .tenant_scoped(),
+    route(
+        "patch",
+        "/api/address-book/{id}",
+        "address-book",
+        "Update some fields of a person with a merge patch or a JSON Patch",
+    )
+    .request(body::<PersonPatch>)
+    .response(body::<ResponseBody<Person>>)
+    .tenant_scoped(),
+    route(
+        "delete",
+        "/api/address-book/{id}",
+        "address-book",
+        "Delete a person",
+    )
+    .response(body::<ResponseBody<String>>)
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/audit",
+        "audit",
+        "Audit trail of the tenant's mutating requests",
+    )
+    .response(body::<ResponseBody<AuditPage>>)
+    .tenant_scoped(),
+    route(
+        "get",
+        "/api/admin/tenant/stats",
+        "tenants",
+        "System-wide tenant statistics",
+    )
+    .response(body::<JsonValue>),
+    route(
+        "get",
+        "/api/admin/tenant/health",
+        "tenants",
+        "Database health per tenant",
+    )
+    .response(body::<JsonValue>),
+    route(
+        "get",
+        "/api/admin/tenant/status",
+        "tenants",
+        "Connection status per tenant",
+    )
+    .response(body::<JsonValue>),
+    route("get", "/api/admin/tenants", "tenants", "List tenants")
+        .response(body::<ResponseBody<JsonValue>>),
+    route("post", "/api/admin/tenants", "tenants", "Create a tenant")
+        .request(body::<TenantDTO>)
+        .response(body::<ResponseBody<Tenant>>)
+        .created(),
+    route(
+        "get",
+        "/api/admin/tenants/filter",
+        "tenants",
+        "Filter tenants",
+    )
+    .response(body::<JsonValue>),
+    route("get", "/api/admin/tenants/{id}", "tenants", "Find a tenant")
+        .response(body::<ResponseBody<Tenant>>),
+    route(
+        "put",
+        "/api/admin/tenants/{id}",
+        "tenants",
+        "Update a tenant",
+    )
+    .request(body::<UpdateTenant>)
+    .response(body::<ResponseBody<Tenant>>),
+    route(
+        "delete",
+        "/api/admin/tenants/{id}",
+        "tenants",
+        "Delete a tenant; cascade=true also deletes its data",
+    )
+    .response(body::<ResponseBody<DeprovisionReport>>),
+    route("get", "/api/openapi.json", "meta", "This OpenAPI document").response(body::<JsonValue>),
+];
+
+#[derive(Serialize)]
+struct OpenApiDocument {
+    openapi: &'static str,
+    info: Info,
+    paths: BTreeMap<&'static str, BTreeMap<&'static str, Operation>>,
+    components: Components,
+}
+
+#[derive(Serialize)]
+struct Info {
+    title: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Operation {
+    tags: [&'static str; 1],
+    summary: &'static str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    parameters: Vec<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_body: Option<JsonValue>,
+    responses: BTreeMap<&'static str, JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    security: Option<Vec<JsonValue>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Components {
+    schemas: BTreeMap<String, Schema>,
+    security_schemes: JsonValue,
+}
+
+fn json_content(schema: Schema) -> JsonValue {
+    json!({ "application/json": { "schema": schema } })
+}
+
+fn operation(route: &Route, gen: &mut SchemaGenerator) -> Operation {
+    let mut parameters: Vec<JsonValue> = route
+        .path_params()
+        .map(|name| json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } }))
+        .collect();
+    if route.tenant_scoped {
+        parameters.push(json!({
+            "name": TENANT_HEADER,
+            "in": "header",
+            "required": false,
+            "description": "Tenant the request is scoped to; defaults to the tenant of the bearer token",
+            "schema": { "type": "string" },
+        }));
+    }
+
+    let mut success = json!({ "description": "Successful response" });
+    if let Some(schema) = route.response {
+        success["content"] = json_content(schema(gen));
+    }
+    let mut responses = BTreeMap::from([(route.status, success)]);
+    responses.insert("400", json!({ "description": "Invalid request" }));
+
+    let public = route.is_public();
+    if !public {
+        responses.insert(
+            "401",
+            json!({ "description": "Missing or invalid bearer token" }),
+        );
+    }
+
+    Operation {
+        tags: [route.tag],
+        summary: route.summary,
+        parameters,
+        request_body: route
+            .request
+            .map(|schema| json!({ "required": true, "content": json_content(schema(gen)) })),
+        responses,
+        security: (!public).then(|| vec![json!({ BEARER_AUTH: [] })]),
+    }
+}
+
+/// Builds the OpenAPI 3.0 document describing the routes in `ROUTES`.
+pub fn openapi_document() -> JsonValue {
+    let mut gen = SchemaSettings::openapi3().into_generator();
+    let mut paths: BTreeMap<&'static str, BTreeMap<&'static str, Operation>> = BTreeMap::new();
+    for route in ROUTES.iter() {
+        paths
+            .entry(route.path)
+            .or_default()
+            .insert(route.method, operation(route, &mut gen));
+    }
+
+    let document = OpenApiDocument {
+        openapi: "3.0.3",
+        info: Info {
+            title: env!("CARGO_PKG_NAME"),
+            version: env!("CARGO_PKG_VERSION"),
+        },
+        paths,
+        components: Components {
+            schemas: gen.take_definitions().into_iter().collect(),
+            security_schemes: json!({
+                BEARER_AUTH: { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }
+            }),
+        },
+    };
+    serde_json::to_value(document).expect("OpenAPI document serializes to JSON")
+}
+
+static OPENAPI_DOCUMENT: Lazy<JsonValue> = Lazy::new(openapi_document);
+
+// GET api/openapi.json
+/// Serves the OpenAPI 3.0 description of the API.
+///
+/// # Returns
+///
+/// `200 OK` with the OpenAPI document.
+#[get("/openapi.json")]
+async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(&*OPENAPI_DOCUMENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, web, App};
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_openapi_document_is_served() {
+        let app =
+            test::init_service(App::new().service(web::scope("/api").service(openapi_json))).await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/openapi.json")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let bytes = test::read_body(resp).await;
+        let document: JsonValue = serde_json::from_slice(&bytes).unwrap();
+        assert!(document["openapi"].as_str().unwrap().starts_with("3.0"));
+        for path in [
+            "/health",
+            "/api/auth/login",
+            "/api/address-book",
+            "/api/address-book/{id}",
+            "/api/admin/tenants",
+            "/api/admin/functions",
+        ] {
+            assert!(document["paths"][path].is_object(), "missing path {}", path);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_openapi_references_resolve_to_components() {
+        let document = openapi_document();
+        let login = &document["paths"]["/api/auth/login"]["post"];
+        let reference = login["requestBody"]["content"]["application/json"]["schema"]["$ref"]
+            .as_str()
+            .unwrap();
+        assert_eq!(reference, "#/components/schemas/LoginDTO");
+        assert!(
+            document["components"]["schemas"]["LoginDTO"]["properties"]["tenant_id"].is_object()
+        );
+        assert!(document["components"]["schemas"]["Person"].is_object());
+        // public routes carry no security requirement
+        assert!(login.get("security").is_none());
+    }
+
+    #[actix_web::test]
+    async fn test_tenant_scoped_routes_document_tenant_header() {
+        let document = openapi_document();
+        let find_by_id = &document["paths"]["/api/address-book/{id}"]["get"];
+        let parameters = find_by_id["parameters"].as_array().unwrap();
+
+        assert!(parameters
+            .iter()
+            .any(|param| param["name"] == "id" && param["in"] == "path"));
+        assert!(parameters
+            .iter()
+            .any(|param| param["name"] == TENANT_HEADER && param["in"] == "header"));
+        assert_eq!(find_by_id["security"][0][BEARER_AUTH], json!([]));
+    }
+}
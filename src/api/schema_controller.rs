@@ -0,0 +1,145 @@
+use actix_web::{get, web, HttpResponse};
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
+use serde::Serialize;
+
+use crate::{
+    error::{ErrorTag, ServiceError},
+    models::{
+        nfe_document::NfeDocumentPayload,
+        person::{Person, PersonDTO},
+        tenant::{Tenant, TenantDTO, UpdateTenant},
+        user::{LoginDTO, SignupDTO},
+    },
+};
+
+/// Generates the root schema of one model.
+type SchemaGenerator = fn() -> RootSchema;
+
+/// Models whose JSON Schema is exported, by the name used in `/api/schema/{model}`.
+const MODEL_SCHEMAS: [(&str, SchemaGenerator); 8] = [
+    ("person", schema_of::<Person>),
+    ("person_dto", schema_of::<PersonDTO>),
+    ("tenant", schema_of::<Tenant>),
+    ("tenant_dto", schema_of::<TenantDTO>),
+    ("tenant_update", schema_of::<UpdateTenant>),
+    ("nfe_document", schema_of::<NfeDocumentPayload>),
+    ("signup", schema_of::<SignupDTO>),
+    ("login", schema_of::<LoginDTO>),
+];
+
+fn schema_of<T: JsonSchema>() -> RootSchema {
+    schema_for!(T)
+}
+
+/// Entry of the schema index.
+#[derive(Serialize)]
+struct SchemaIndexEntry {
+    name: &'static str,
+    href: String,
+}
+
+/// Generates the JSON Schema of the model registered under `name`.
+pub fn schema_for_model(name: &str) -> Option<RootSchema> {
+    MODEL_SCHEMAS
+        .iter()
+        .find(|(model, _)| *model == name)
+        .map(|(_, schema)| schema())
+}
+
+// GET api/schema
+/// Lists the models whose JSON Schema can be fetched.
+///
+/// # Returns
+///
+/// `200 OK` with `{ "models": [{ "name", "href" }] }`.
+#[get("/schema")]
+async fn index() -> HttpResponse {
+    let models: Vec<SchemaIndexEntry> = MODEL_SCHEMAS
+        .iter()
+        .map(|(name, _)| SchemaIndexEntry {
+            name,
+            href: format!("/api/schema/{}", name),
+        })
+        .collect();
+
+    HttpResponse::Ok().json(serde_json::json!({ "models": models }))
+}
+
+// GET api/schema/{model}
+/// Returns the raw JSON Schema (draft-07) of a request/response model.
+///
+/// # Returns
+///
+/// `200 OK` with the schema document, or `404 Not Found` when no model is
+/// registered under that name.
+#[get("/schema/{model}")]
+async fn find_by_model(model: web::Path<String>) -> Result<HttpResponse, ServiceError> {
+    schema_for_model(&model)
+        .map(|schema| HttpResponse::Ok().json(schema))
+        .ok_or_else(|| {
+            ServiceError::not_found(format!("Unknown model '{}'", model.as_str()))
+                .with_tag(ErrorTag::Validation)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test, App};
+    use serde_json::Value;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_known_model_returns_schema() {
+        let app = test::init_service(
+            App::new().service(web::scope("/api").service(index).service(find_by_model)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/schema/nfe_document")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let schema: Value = test::read_body_json(resp).await;
+        assert_eq!(schema["title"], "NfeDocumentPayload");
+        assert_eq!(schema["type"], "object");
+        assert!(schema["properties"]["access_key"].is_object());
+        assert!(schema["definitions"]["NfeItemPayload"].is_object());
+        let required = schema["required"].as_array().unwrap();
+        assert!(required.contains(&Value::from("access_key")));
+        assert!(!required.contains(&Value::from("recipient")));
+    }
+
+    #[actix_web::test]
+    async fn test_unknown_model_is_not_found() {
+        let app = test::init_service(
+            App::new().service(web::scope("/api").service(index).service(find_by_model)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/api/schema/unicorn")
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn test_index_lists_every_model() {
+        let app = test::init_service(
+            App::new().service(web::scope("/api").service(index).service(find_by_model)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/api/schema").to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        let models = body["models"].as_array().unwrap();
+
+        assert_eq!(models.len(), MODEL_SCHEMAS.len());
+        assert!(models
+            .iter()
+            .any(|model| model["name"] == "person" && model["href"] == "/api/schema/person"));
+    }
+}
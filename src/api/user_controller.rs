@@ -7,15 +7,18 @@ use serde_json::json;
 use crate::{
     config::db::Pool,
     constants,
-    error::ServiceError,
+    error::{ErrorTag, ServiceError},
     functional::response_transformers::{ResponseTransformError, ResponseTransformer},
-    models::user::UserUpdateDTO,
+    models::{
+        pagination::parse_sort,
+        user::{User, UserUpdateDTO},
+    },
     services::{account_service, functional_service_base::FunctionalErrorHandling},
 };
 
 fn response_composition_error(err: ResponseTransformError) -> ServiceError {
     ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR)
-        .with_tag("response")
+        .with_tag(ErrorTag::Internal)
         .with_detail(err.to_string())
 }
 
@@ -44,7 +47,7 @@ fn extract_tenant_pool(req: &HttpRequest) -> Result<Pool, ServiceError> {
     match req.extensions().get::<Pool>() {
         Some(pool) => Ok(pool.clone()),
         None => Err(ServiceError::unauthorized("Tenant not found")
-            .with_tag("tenant")
+            .with_tag(ErrorTag::Tenant)
             .with_detail("Missing tenant pool in request extensions")),
     }
 }
@@ -53,6 +56,9 @@ fn extract_tenant_pool(req: &HttpRequest) -> Result<Pool, ServiceError> {
 ///
 /// Returns a paginated list of users for the current tenant.
 /// Supports query parameters: `limit` (default 50, max 500) and `offset` (default 0).
+/// `sort=username,-id` orders by the listed [`User::SORTABLE_COLUMNS`], a leading
+/// `-` meaning descending; users are listed by `id` without it, and `id` always
+/// breaks ties. An unknown column is a 400.
 ///
 /// # Examples
 ///
@@ -60,7 +66,7 @@ fn extract_tenant_pool(req: &HttpRequest) -> Result<Pool, ServiceError> {
 /// use actix_web::{test, web, HttpRequest};
 ///
 /// // GET /api/users
-/// // GET /api/users?limit=10&offset=20
+/// // GET /api/users?limit=10&offset=20&sort=-active,username
 /// ```
 pub async fn find_all(
     query: web::Query<std::collections::HashMap<String, String>>,
@@ -88,9 +94,13 @@ pub async fn find_all(
         offset = 0;
     }
 
+    let sort = parse_sort(
+        query.get("sort").map_or("", String::as_str),
+        User::SORTABLE_COLUMNS,
+    )?;
     let pool = extract_tenant_pool(&req)?;
 
-    account_service::find_all_users(limit, offset, &pool)
+    account_service::find_all_users(limit, offset, &sort, &pool)
         .log_error("user_controller::find_all")
         .and_then(|users| {
             ResponseTransformer::new(json!(users))
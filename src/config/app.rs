@@ -1,13 +1,281 @@
 use crate::api::*;
-use crate::config::functional_config::RouteBuilder;
+use crate::config::functional_config::{cors_allowed_origins, BodyLimits, RouteBuilder};
+use crate::error::ServiceError;
+use crate::services::nfe_service;
+use actix_cors::Cors;
+use actix_web::guard::{self, GuardContext};
+use actix_web::http::header::{self, HeaderName};
+use actix_web::http::Method;
 use actix_web::web;
 
+/// JSON extractor settings accepting bodies up to `limit` bytes.
+///
+/// Rejected bodies are answered through `ServiceError`, so an oversized body is
+/// a 413 and malformed JSON a 400 in the standard error body.
+fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .error_handler(|err, _req| ServiceError::from(err).into())
+}
+
+/// Whether the request body is a JSON Patch document (`application/json-patch+json`).
+fn is_json_patch(ctx: &GuardContext) -> bool {
+    ctx.header::<header::ContentType>()
+        .is_some_and(|content_type| {
+            content_type.essence_str() == address_book_controller::JSON_PATCH_CONTENT_TYPE
+        })
+}
+
+/// Deployment environment named by `APP_ENV`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppEnv {
+    Production,
+    /// Any other value, including an unset `APP_ENV`
+    Development,
+}
+
+impl AppEnv {
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("APP_ENV").ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("production") => AppEnv::Production,
+            _ => AppEnv::Development,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AppEnv::Production => "production",
+            AppEnv::Development => "development",
+        }
+    }
+}
+
+/// Why the CORS settings were rejected at startup.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum CorsConfigError {
+    #[error("CORS origin \"*\" cannot be combined with CORS_ALLOW_CREDENTIALS=true")]
+    WildcardWithCredentials,
+    #[error(
+        "invalid CORS origin {0:?}; expected scheme://host[:port], optionally scheme://*.domain"
+    )]
+    InvalidOrigin(String),
+    #[error("invalid CORS method {0:?}")]
+    InvalidMethod(String),
+    #[error("invalid CORS header {0:?}")]
+    InvalidHeader(String),
+    #[error("invalid CORS_MAX_AGE {0:?}")]
+    InvalidMaxAge(String),
+}
+
+/// An allowed origin: exact, any subdomain of a domain, or any origin at all.
+#[derive(Debug, Clone)]
+pub enum OriginPattern {
+    Exact(String),
+    /// `scheme://*.domain`, matching one or more labels in front of the domain
+    Subdomains(regex::Regex),
+    Any,
+}
+
+impl OriginPattern {
+    /// Parses `*`, `https://app.example.com` or `https://*.example.com`.
+    pub fn parse(origin: &str) -> Result<Self, CorsConfigError> {
+        let invalid = || CorsConfigError::InvalidOrigin(origin.to_string());
+        if origin == "*" {
+            return Ok(OriginPattern::Any);
+        }
+
+        let (scheme, host) = origin.split_once("://").ok_or_else(invalid)?;
+        let valid_host = |host: &str| {
+            !host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':' | '[' | ']'))
+        };
+        if scheme.is_empty() || !scheme.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(invalid());
+        }
+
+        match host.strip_prefix("*.") {
+            Some(domain) if valid_host(domain) => {
+                let pattern = format!(
+                    r"^{}://(?:[A-Za-z0-9-]+\.)+{}$",
+                    regex::escape(scheme),
+                    regex::escape(domain)
+                );
+                regex::Regex::new(&pattern)
+                    .map(OriginPattern::Subdomains)
+                    .map_err(|_| invalid())
+            }
+            None if valid_host(host) => Ok(OriginPattern::Exact(origin.to_string())),
+            _ => Err(invalid()),
+        }
+    }
+
+    pub fn matches(&self, origin: &str) -> bool {
+        match self {
+            OriginPattern::Exact(allowed) => allowed == origin,
+            OriginPattern::Subdomains(pattern) => pattern.is_match(origin),
+            OriginPattern::Any => true,
+        }
+    }
+}
+
+/// CORS settings, parsed and validated once at startup.
+///
+/// Read from `CORS_ALLOWED_ORIGINS` (see [`cors_allowed_origins`] for the
+/// defaults per environment), `CORS_ALLOWED_METHODS`, `CORS_ALLOWED_HEADERS`,
+/// `CORS_ALLOW_CREDENTIALS` and `CORS_MAX_AGE` (seconds). Method and header
+/// lists replace the defaults when set.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<OriginPattern>,
+    pub allowed_methods: Vec<Method>,
+    pub allowed_headers: Vec<HeaderName>,
+    pub expose_headers: Vec<HeaderName>,
+    pub allow_credentials: bool,
+    pub max_age: usize,
+}
+
+impl CorsConfig {
+    /// Seconds browsers may cache a preflight response by default
+    pub const DEFAULT_MAX_AGE: usize = 3600;
+
+    pub fn from_env(env: AppEnv) -> Result<Self, CorsConfigError> {
+        Self::from_vars(env, |name| std::env::var(name).ok())
+    }
+
+    fn from_vars(
+        env: AppEnv,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, CorsConfigError> {
+        let allowed_origins =
+            cors_allowed_origins(env.as_str(), var("CORS_ALLOWED_ORIGINS").as_deref())
+                .iter()
+                .map(|origin| OriginPattern::parse(origin))
+                .collect::<Result<Vec<_>, _>>()?;
+        let allow_credentials = var("CORS_ALLOW_CREDENTIALS").as_deref() == Some("true");
+        if allow_credentials
+            && allowed_origins
+                .iter()
+                .any(|origin| matches!(origin, OriginPattern::Any))
+        {
+            return Err(CorsConfigError::WildcardWithCredentials);
+        }
+
+        let allowed_methods = match var("CORS_ALLOWED_METHODS") {
+            Some(methods) => list(&methods)
+                .map(|method| {
+                    Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+                        .map_err(|_| CorsConfigError::InvalidMethod(method.to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+            None => vec![
+                Method::GET,
+                Method::POST,
+                Method::PUT,
+                Method::PATCH,
+                Method::DELETE,
+                Method::OPTIONS,
+            ],
+        };
+        let allowed_headers = match var("CORS_ALLOWED_HEADERS") {
+            Some(headers) => list(&headers)
+                .map(|name| {
+                    HeaderName::from_bytes(name.as_bytes())
+                        .map_err(|_| CorsConfigError::InvalidHeader(name.to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+            None => vec![
+                header::AUTHORIZATION,
+                header::ACCEPT,
+                header::CONTENT_TYPE,
+                header::IF_NONE_MATCH,
+                HeaderName::from_static("x-tenant-id"),
+                crate::middleware::bigint_ids::X_BIGINT_AS_STRING,
+            ],
+        };
+        let max_age = match var("CORS_MAX_AGE") {
+            Some(value) => value
+                .trim()
+                .parse()
+                .map_err(|_| CorsConfigError::InvalidMaxAge(value))?,
+            None => Self::DEFAULT_MAX_AGE,
+        };
+
+        Ok(Self {
+            allowed_origins,
+            allowed_methods,
+            allowed_headers,
+            expose_headers: vec![
+                header::AUTHORIZATION,
+                header::CONTENT_TYPE,
+                header::ETAG,
+                HeaderName::from_static("x-tenant-id"),
+                crate::middleware::request_log::X_REQUEST_ID,
+            ],
+            allow_credentials,
+            max_age,
+        })
+    }
+
+    /// Whether a request from `origin` is allowed.
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.allowed_origins
+            .iter()
+            .any(|allowed| allowed.matches(origin))
+    }
+}
+
+/// Non-empty, trimmed entries of a comma-separated list
+fn list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+}
+
+/// CORS middleware for `config`, built by every worker from the shared settings.
+pub fn build_cors(config: &CorsConfig) -> Cors {
+    let any_origin = config
+        .allowed_origins
+        .iter()
+        .any(|origin| matches!(origin, OriginPattern::Any));
+    let cors = if any_origin {
+        Cors::default().allow_any_origin()
+    } else {
+        let allowed = config.clone();
+        Cors::default().allowed_origin_fn(move |origin, _req_head| {
+            origin
+                .to_str()
+                .is_ok_and(|origin| allowed.allows_origin(origin))
+        })
+    };
+
+    let cors = cors
+        .allowed_methods(config.allowed_methods.clone())
+        .allowed_headers(config.allowed_headers.clone())
+        .expose_headers(config.expose_headers.clone())
+        .max_age(config.max_age);
+    if config.allow_credentials {
+        cors.supports_credentials()
+    } else {
+        cors
+    }
+}
+
 /// Configure application HTTP routes using functional composition patterns.
 ///
 /// This function uses the RouteBuilder pattern to compose route configurations
 /// in a functional, composable manner. It uses functional composition for
 /// logging and error handling.
 ///
+/// JSON bodies are limited to [`BodyLimits::json_bytes`] (`MAX_JSON_BODY_BYTES`);
+/// the address book batch routes raise this to [`BodyLimits::batch_json_bytes`].
+///
 /// # Examples
 ///
 /// ```
@@ -17,14 +285,23 @@ use actix_web::web;
 /// let app = App::new().configure(config_services);
 /// ```
 pub fn config_services(cfg: &mut web::ServiceConfig) {
+    cfg.app_data(json_config(BodyLimits::from_env().json_bytes));
+
     // Build routes using functional composition
     let route_builder: RouteBuilder = RouteBuilder::new()
         .add_route(|cfg| {
             cfg.service(health_controller::health);
         })
+        .add_route(|cfg| {
+            cfg.service(health_controller::health_live);
+        })
         .add_route(|cfg| {
             cfg.service(web::scope("/api").configure(configure_api_routes));
         });
+    #[cfg(feature = "performance_monitoring")]
+    let route_builder = route_builder.add_route(|cfg| {
+        cfg.service(health_controller::prometheus_metrics);
+    });
 
     // Build routes directly
     route_builder.build(cfg);
@@ -57,9 +334,24 @@ fn configure_api_routes(cfg: &mut web::ServiceConfig) {
         .add_route(|cfg| {
             cfg.service(health_controller::backward_compatibility_validation);
         })
+        .add_route(|cfg| {
+            cfg.service(health_controller::backward_compatibility_stream);
+        })
         .add_route(|cfg| {
             cfg.service(health_controller::logs);
         })
+        .add_route(|cfg| {
+            cfg.service(schema_controller::index);
+        })
+        .add_route(|cfg| {
+            cfg.service(schema_controller::find_by_model);
+        })
+        .add_route(|cfg| {
+            cfg.service(openapi_controller::openapi_json);
+        })
+        .add_route(|cfg| {
+            cfg.service(web::resource("/audit").route(web::get().to(audit_controller::find_all)));
+        })
         // Scoped routes
         .add_route(|cfg| {
             cfg.service(web::scope("/auth").configure(configure_auth_routes));
@@ -73,6 +365,9 @@ fn configure_api_routes(cfg: &mut web::ServiceConfig) {
         .add_route(|cfg| {
             cfg.service(web::scope("/users").configure(configure_user_routes));
         })
+        .add_route(|cfg| {
+            cfg.service(web::scope("/nfe").configure(configure_nfe_routes));
+        })
         .build(cfg);
 }
 
@@ -80,6 +375,9 @@ fn configure_api_routes(cfg: &mut web::ServiceConfig) {
 ///
 /// Uses functional composition to build authentication routes in a composable manner.
 ///
+/// `/sessions` lists (GET) or revokes all other (DELETE) sessions of the caller;
+/// `/sessions/{id}` (DELETE) revokes one of them.
+///
 /// # Examples
 ///
 /// ```
@@ -112,6 +410,19 @@ fn configure_auth_routes(cfg: &mut web::ServiceConfig) {
         .add_route(|cfg| {
             cfg.service(web::resource("/me").route(web::get().to(account_controller::me)));
         })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/sessions")
+                    .route(web::get().to(account_controller::sessions))
+                    .route(web::delete().to(account_controller::revoke_other_sessions)),
+            );
+        })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/sessions/{id}")
+                    .route(web::delete().to(account_controller::revoke_session)),
+            );
+        })
         .build(cfg);
 }
 
@@ -122,8 +433,16 @@ fn configure_auth_routes(cfg: &mut web::ServiceConfig) {
 /// - POST `/` → `address_book_controller::insert`
 /// - GET `/{id}` → `address_book_controller::find_by_id`
 /// - PUT `/{id}` → `address_book_controller::update`
+/// - PATCH `/{id}` → `address_book_controller::patch`
 /// - DELETE `/{id}` → `address_book_controller::delete`
 /// - GET `/filter` → `address_book_controller::filter`
+/// - POST `/validate-batch` → `address_book_controller::validate_batch`
+/// - POST `/batch` → `address_book_controller::insert_batch`
+/// - POST `/bulk-delete` → `address_book_controller::bulk_delete`
+/// - GET `/export.csv` → `address_book_controller::export_csv`
+/// - GET `/ws` → `address_book_controller::watch` (WebSocket)
+///
+/// The two batch routes accept bodies up to [`BodyLimits::batch_json_bytes`].
 ///
 /// # Examples
 ///
@@ -147,11 +466,46 @@ fn configure_address_book_routes(cfg: &mut web::ServiceConfig) {
                 web::resource("/filter").route(web::get().to(address_book_controller::filter)),
             );
         })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/validate-batch")
+                    .app_data(json_config(BodyLimits::from_env().batch_json_bytes))
+                    .route(web::post().to(address_book_controller::validate_batch)),
+            );
+        })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/batch")
+                    .app_data(json_config(BodyLimits::from_env().batch_json_bytes))
+                    .route(web::post().to(address_book_controller::insert_batch)),
+            );
+        })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/bulk-delete")
+                    .route(web::post().to(address_book_controller::bulk_delete)),
+            );
+        })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/export.csv")
+                    .route(web::get().to(address_book_controller::export_csv)),
+            );
+        })
+        .add_route(|cfg| {
+            cfg.service(web::resource("/ws").route(web::get().to(address_book_controller::watch)));
+        })
         .add_route(|cfg| {
             cfg.service(
                 web::resource("/{id}")
                     .route(web::get().to(address_book_controller::find_by_id))
                     .route(web::put().to(address_book_controller::update))
+                    .route(
+                        web::patch()
+                            .guard(guard::fn_guard(is_json_patch))
+                            .to(address_book_controller::json_patch),
+                    )
+                    .route(web::patch().to(address_book_controller::patch))
                     .route(web::delete().to(address_book_controller::delete)),
             );
         })
@@ -164,10 +518,15 @@ fn configure_address_book_routes(cfg: &mut web::ServiceConfig) {
 /// - `/tenant` - System-level monitoring and health checks (stats, health, status)
 /// - `/tenants` - RESTful CRUD operations for tenant resource management
 ///
+/// the masked startup configuration at `/health/config` and the pure function
+/// registry at `/functions`.
+///
 /// # Route Structure
 ///
 /// ```text
 /// /api/admin
+///   ├── /health/config   GET: Effective configuration, secrets masked
+///   ├── /functions       GET: Pure functions with their call counts
 ///   ├── /tenant          (System operations - read-only monitoring)
 ///   │   ├── /stats       GET: System-wide tenant statistics
 ///   │   ├── /health      GET: All tenant database health checks
@@ -190,6 +549,16 @@ fn configure_address_book_routes(cfg: &mut web::ServiceConfig) {
 /// ```
 fn configure_admin_routes(cfg: &mut web::ServiceConfig) {
     RouteBuilder::new()
+        .add_route(|cfg| {
+            // Authenticated like the rest of /api/admin, unlike the public /api/health routes
+            cfg.service(health_controller::effective_config);
+        })
+        .add_route(|cfg| {
+            // Registry internals and call counts are for operators, not tenants
+            cfg.service(
+                web::resource("/functions").route(web::get().to(function_controller::find_all)),
+            );
+        })
         .add_route(|cfg| {
             // System-level monitoring endpoints: stats, health, status (read-only)
             cfg.service(web::scope("/tenant").configure(configure_tenant_admin_routes));
@@ -253,7 +622,7 @@ fn configure_tenant_admin_routes(cfg: &mut web::ServiceConfig) {
 /// - POST `/` -> `tenant_controller::create` - Create a new tenant
 /// - GET `/{id}` -> `tenant_controller::find_by_id` - Get specific tenant by ID
 /// - PUT `/{id}` -> `tenant_controller::update` - Update existing tenant
-/// - DELETE `/{id}` -> `tenant_controller::delete` - Delete tenant
+/// - DELETE `/{id}` -> `tenant_controller::delete` - Delete tenant (`?cascade=true` also deletes its data)
 ///
 /// # Distinction from System Monitoring Routes
 ///
@@ -317,3 +686,179 @@ fn configure_user_routes(cfg: &mut web::ServiceConfig) {
         })
         .build(cfg);
 }
+
+/// Registers NFe HTTP routes using functional composition patterns.
+///
+/// - POST `/batch` -> `nfe_controller::import_batch` - Validate and import many documents
+/// - GET `/{access_key}` -> `nfe_controller::find_by_access_key` - Fetch one document by access key
+/// - GET `/{access_key}/xml` -> `nfe_controller::export_xml` - Render one document as NFe XML
+///
+/// The batch resource raises the request body limit to
+/// `nfe_service::MAX_BATCH_PAYLOAD_BYTES`; the document count is capped
+/// separately by `nfe_service::MAX_BATCH_SIZE`.
+///
+/// # Examples
+///
+/// ```
+/// use actix_web::web;
+///
+/// let _scope = web::scope("/nfe").configure(configure_nfe_routes);
+/// ```
+fn configure_nfe_routes(cfg: &mut web::ServiceConfig) {
+    RouteBuilder::new()
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/batch")
+                    .app_data(web::PayloadConfig::new(
+                        nfe_service::MAX_BATCH_PAYLOAD_BYTES,
+                    ))
+                    .route(web::post().to(nfe_controller::import_batch)),
+            );
+        })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/{access_key}")
+                    .route(web::get().to(nfe_controller::find_by_access_key)),
+            );
+        })
+        .add_route(|cfg| {
+            cfg.service(
+                web::resource("/{access_key}/xml").route(web::get().to(nfe_controller::export_xml)),
+            );
+        })
+        .build(cfg);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use actix_web::{test as actix_test, App, HttpResponse};
+
+    use super::*;
+
+    fn cors_config(env: AppEnv, vars: &[(&str, &str)]) -> Result<CorsConfig, CorsConfigError> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        CorsConfig::from_vars(env, |name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_subdomain_pattern_matches_only_subdomains() {
+        let pattern = OriginPattern::parse("https://*.example.com").unwrap();
+
+        assert!(pattern.matches("https://app.example.com"));
+        assert!(pattern.matches("https://eu.app.example.com"));
+        assert!(!pattern.matches("https://example.com"));
+        assert!(!pattern.matches("http://app.example.com"));
+        assert!(!pattern.matches("https://app.example.com.evil.io"));
+        assert!(!pattern.matches("https://appexample.com"));
+        assert!(!pattern.matches("https://app.example.com:8443"));
+
+        let exact = OriginPattern::parse("http://localhost:3000").unwrap();
+        assert!(exact.matches("http://localhost:3000"));
+        assert!(!exact.matches("http://localhost:3001"));
+
+        for invalid in [
+            "example.com",
+            "https://",
+            "https://app.*.com",
+            "https://*",
+            "ht tp://a",
+        ] {
+            assert_eq!(
+                OriginPattern::parse(invalid).unwrap_err(),
+                CorsConfigError::InvalidOrigin(invalid.to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_wildcard_origin_rejected_with_credentials() {
+        let production = |origins: &str, credentials: &str| {
+            cors_config(
+                AppEnv::Production,
+                &[
+                    ("CORS_ALLOWED_ORIGINS", origins),
+                    ("CORS_ALLOW_CREDENTIALS", credentials),
+                ],
+            )
+        };
+
+        assert_eq!(
+            production("https://app.example.com, *", "true").unwrap_err(),
+            CorsConfigError::WildcardWithCredentials
+        );
+        assert!(production("*", "false").is_ok());
+        assert!(production("https://*.example.com", "true").is_ok());
+    }
+
+    #[test]
+    fn test_cors_config_parses_lists_and_defaults() {
+        let config = cors_config(
+            AppEnv::Production,
+            &[
+                ("CORS_ALLOWED_ORIGINS", "https://*.example.com"),
+                ("CORS_ALLOWED_METHODS", "get, post"),
+                ("CORS_ALLOWED_HEADERS", "authorization,x-custom"),
+                ("CORS_MAX_AGE", "60"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(config.allowed_methods, vec![Method::GET, Method::POST]);
+        assert_eq!(config.allowed_headers[1], "x-custom");
+        assert_eq!(config.max_age, 60);
+        assert!(!config.allow_credentials);
+
+        let development = cors_config(AppEnv::Development, &[]).unwrap();
+        assert!(development.allows_origin("http://localhost:5173"));
+        assert_eq!(development.max_age, CorsConfig::DEFAULT_MAX_AGE);
+
+        assert_eq!(
+            cors_config(AppEnv::Development, &[("CORS_MAX_AGE", "soon")]).unwrap_err(),
+            CorsConfigError::InvalidMaxAge("soon".to_string())
+        );
+        assert_eq!(
+            cors_config(
+                AppEnv::Development,
+                &[("CORS_ALLOWED_HEADERS", "bad header")]
+            )
+            .unwrap_err(),
+            CorsConfigError::InvalidHeader("bad header".to_string())
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_build_cors_allows_matching_subdomain() {
+        let config = cors_config(
+            AppEnv::Production,
+            &[("CORS_ALLOWED_ORIGINS", "https://*.example.com")],
+        )
+        .unwrap();
+        let app = actix_test::init_service(
+            App::new()
+                .wrap(build_cors(&config))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let request = |origin: &str| {
+            actix_test::TestRequest::get()
+                .uri("/")
+                .insert_header((header::ORIGIN, origin))
+                .to_request()
+        };
+        let resp = actix_test::call_service(&app, request("https://app.example.com")).await;
+        assert_eq!(
+            resp.headers()
+                .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                .unwrap(),
+            "https://app.example.com"
+        );
+
+        let resp = actix_test::try_call_service(&app, request("https://evil.io")).await;
+        assert!(resp.map_or(true, |resp| resp
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .is_none()));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::functional_config::EitherConvert;
+use crate::config::functional_config::{EitherConvert, UrlMasker};
 use crate::services::functional_patterns::Either;
 use r2d2;
 use redis;
@@ -97,8 +97,8 @@ pub fn init_redis_client(url: &str) -> Pool {
     use log::info;
     info!("Initializing Redis client with functional patterns...");
 
-    // Use functional URL masking
-    let masked_url = mask_redis_url_functional(url);
+    // Never log the password of the Redis URL
+    let masked_url = UrlMasker::mask(url);
 
     // Functional client creation with Either pattern
     let client_result = Either::from_result(redis::Client::open(url));
@@ -123,25 +123,3 @@ pub fn init_redis_client(url: &str) -> Pool {
         }
     }
 }
-
-/// Functional URL masking using composition patterns.
-///
-/// Uses functional composition to mask sensitive credentials in URLs.
-fn mask_redis_url_functional(input: &str) -> String {
-    // Functional approach to URL masking
-    let find_credentials = |url: &str| -> Option<(usize, usize)> {
-        let at_pos = url.find('@')?;
-        let colon_pos = url[..at_pos].rfind(':')?;
-        Some((colon_pos, at_pos))
-    };
-
-    let mask_url = |(colon_pos, at_pos): (usize, usize)| -> String {
-        format!("{}:<redacted>{}", &input[..colon_pos], &input[at_pos..])
-    };
-
-    // Apply functional composition
-    find_credentials(input)
-        .map(mask_url)
-        .unwrap_or_else(|| input.to_string())
-}
-
@@ -2,5 +2,6 @@ pub mod app;
 pub mod cache;
 pub mod db;
 pub mod functional_config;
+pub mod telemetry;
 
 // Re-export functional config utilities for convenience
@@ -0,0 +1,107 @@
+//! Tracing setup.
+//!
+//! Request spans opened by
+//! [`RequestTrace`](crate::middleware::request_trace::RequestTrace) are
+//! handed to OpenTelemetry. `TRACING_EXPORTER` chooses where they go:
+//!
+//! - `none` (the default): spans carry trace ids, so incoming `traceparent`
+//!   headers are still continued, but nothing is exported.
+//! - `otlp`: spans are batched and sent over OTLP/HTTP to
+//!   `OTEL_EXPORTER_OTLP_ENDPOINT` (`http://localhost:4318` when unset), with
+//!   the service named by `OTEL_SERVICE_NAME`.
+//!
+//! Only spans go through `tracing`; `log` calls keep being written by
+//! `env_logger` as before.
+
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{ExporterBuildError, SpanExporter};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use tracing::Subscriber;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Name of the OpenTelemetry tracer, reported as the instrumentation scope
+const TRACER_NAME: &str = "rcs";
+
+/// Where finished spans are sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TracingExporter {
+    /// Spans are dropped once they end
+    #[default]
+    None,
+    /// Spans are sent to an OTLP collector over HTTP
+    Otlp,
+}
+
+impl TracingExporter {
+    /// Exporter named by `TRACING_EXPORTER`; unset or unknown values export nothing.
+    pub fn from_env() -> Self {
+        let value = std::env::var("TRACING_EXPORTER").ok();
+        let exporter = Self::parse(value.as_deref());
+        let unknown = value
+            .as_deref()
+            .is_some_and(|v| !v.trim().eq_ignore_ascii_case("none"));
+        if exporter == TracingExporter::None && unknown {
+            log::warn!(
+                "Unknown TRACING_EXPORTER '{}', spans will not be exported",
+                value.unwrap_or_default()
+            );
+        }
+        exporter
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()) {
+            Some(v) if v == "otlp" => TracingExporter::Otlp,
+            _ => TracingExporter::None,
+        }
+    }
+}
+
+/// Builds the tracer provider for `exporter`.
+pub fn tracer_provider(exporter: TracingExporter) -> Result<SdkTracerProvider, ExporterBuildError> {
+    let builder = SdkTracerProvider::builder();
+    let builder = match exporter {
+        TracingExporter::None => builder,
+        TracingExporter::Otlp => {
+            builder.with_batch_exporter(SpanExporter::builder().with_http().build()?)
+        }
+    };
+    Ok(builder.build())
+}
+
+/// Subscriber turning `tracing` spans into OpenTelemetry spans of `provider`.
+pub fn subscriber(provider: &SdkTracerProvider) -> impl Subscriber + Send + Sync {
+    tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(TRACER_NAME)))
+}
+
+/// Installs the global subscriber for `exporter`.
+///
+/// Returns the provider so `main` can flush pending spans on shutdown.
+pub fn init(exporter: TracingExporter) -> Result<SdkTracerProvider, ExporterBuildError> {
+    let provider = tracer_provider(exporter)?;
+    if tracing::subscriber::set_global_default(subscriber(&provider)).is_err() {
+        log::warn!("A tracing subscriber is already installed, request spans use it");
+    }
+    Ok(provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exporter_from_env_value() {
+        assert_eq!(TracingExporter::parse(Some("otlp")), TracingExporter::Otlp);
+        assert_eq!(
+            TracingExporter::parse(Some(" OTLP ")),
+            TracingExporter::Otlp
+        );
+        assert_eq!(TracingExporter::parse(Some("none")), TracingExporter::None);
+        assert_eq!(
+            TracingExporter::parse(Some("jaeger")),
+            TracingExporter::None
+        );
+        assert_eq!(TracingExporter::parse(None), TracingExporter::None);
+    }
+}
@@ -1,5 +1,6 @@
 // Messages
 pub const MESSAGE_OK: &str = "ok";
+pub const MESSAGE_CAN_NOT_FETCH_DATA: &str = "Can not fetch data";
 pub const MESSAGE_CAN_NOT_INSERT_DATA: &str = "Can not insert data";
 pub const MESSAGE_CAN_NOT_UPDATE_DATA: &str = "Can not update data";
 pub const MESSAGE_CAN_NOT_DELETE_DATA: &str = "Can not delete data";
@@ -22,7 +23,7 @@ pub const AUTHORIZATION: &str = "Authorization";
 pub const EMPTY: &str = "";
 
 // ignore routes
-pub const IGNORE_ROUTES: [&str; 9] = [
+pub const IGNORE_ROUTES: [&str; 12] = [
     "/api/ping",
     "/api/auth/signup",
     "/api/auth/login",
@@ -31,8 +32,14 @@ pub const IGNORE_ROUTES: [&str; 9] = [
     "/health",
     "/api/health",
     "/api/logs",
+    "/metrics",
     "/api-doc",
+    "/api/schema",
+    "/api/openapi.json",
 ];
 
+// routes exempt from the global concurrency limit (health checks and streaming)
+pub const CONCURRENCY_LIMIT_EXEMPT_ROUTES: [&str; 3] = ["/health", "/api/health", "/api/logs"];
+
 // Default number of items per page
 pub const DEFAULT_PER_PAGE: i64 = 10;
@@ -1,7 +1,10 @@
 use crate::models::response::ResponseBody;
 use actix_web::{
     error,
-    http::{header::ContentType, StatusCode},
+    http::{
+        header::{ContentType, RETRY_AFTER},
+        StatusCode,
+    },
     HttpResponse,
 };
 use chrono::{DateTime, Utc};
@@ -10,9 +13,68 @@ use log::{debug, error as log_error, info as log_info, warn as log_warn, Level};
 use serde::Serialize;
 use serde_json::to_string as to_json_string;
 use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::functional::performance_monitoring::get_performance_monitor;
+use crate::functional::validation_rules::ValidationError;
 
 pub type ServiceResult<T> = Result<T, ServiceError>;
 
+/// Seconds clients are asked to wait (via `Retry-After`) when the pool is saturated
+pub const POOL_EXHAUSTED_RETRY_AFTER_SECS: u64 = 2;
+
+/// Metadata key carrying the `Retry-After` value of a `ServiceUnavailable` error
+const RETRY_AFTER_METADATA: &str = "retry_after";
+
+/// Category of a [`ServiceError`], used to group errors in logs and metrics.
+///
+/// Tags are rendered in the error body's `tags` and every error response
+/// increments the per-tag counter of the performance monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorTag {
+    /// Malformed or rejected client input
+    Validation,
+    /// Missing, invalid or expired credentials
+    Auth,
+    /// Database queries and the connection pool
+    Db,
+    /// Tenant resolution and tenant management
+    Tenant,
+    /// Services outside this process (Redis, HTTP APIs)
+    External,
+    /// Server-side failures: serialization, overload, bugs
+    Internal,
+}
+
+impl ErrorTag {
+    pub const ALL: [ErrorTag; 6] = [
+        ErrorTag::Validation,
+        ErrorTag::Auth,
+        ErrorTag::Db,
+        ErrorTag::Tenant,
+        ErrorTag::External,
+        ErrorTag::Internal,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorTag::Validation => "validation",
+            ErrorTag::Auth => "auth",
+            ErrorTag::Db => "db",
+            ErrorTag::Tenant => "tenant",
+            ErrorTag::External => "external",
+            ErrorTag::Internal => "internal",
+        }
+    }
+}
+
+impl fmt::Display for ErrorTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, PartialEq, Eq)]
 pub struct ErrorContext {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -25,6 +87,9 @@ pub struct ErrorContext {
     pub metadata: BTreeMap<String, String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub code_override: Option<String>,
+    /// Every field-level problem of a rejected payload
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ValidationError>,
 }
 
 impl ErrorContext {
@@ -35,18 +100,50 @@ impl ErrorContext {
     }
 
     #[must_use]
-    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
-        self.tags.push(tag.into());
+    pub fn with_correlation_id(mut self, correlation_id: impl Into<String>) -> Self {
+        self.correlation_id = Some(correlation_id.into());
+        self
+    }
+
+    /// Replaces the error's default `code` in the response body.
+    #[must_use]
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code_override = Some(code.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_tag(mut self, tag: ErrorTag) -> Self {
+        self.tags.push(tag.as_str().to_string());
         self.dedup_tags();
         self
     }
 
+    /// Adds several tags at once.
+    #[must_use]
+    pub fn with_tags(mut self, tags: impl IntoIterator<Item = ErrorTag>) -> Self {
+        self.tags
+            .extend(tags.into_iter().map(|tag| tag.as_str().to_string()));
+        self.dedup_tags();
+        self
+    }
+
+    pub fn has_tag(&self, tag: ErrorTag) -> bool {
+        self.tags.iter().any(|t| t == tag.as_str())
+    }
+
     #[must_use]
     pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
         self.metadata.insert(key.into(), value.into());
         self
     }
 
+    #[must_use]
+    pub fn with_errors(mut self, errors: Vec<ValidationError>) -> Self {
+        self.errors.extend(errors);
+        self
+    }
+
     fn dedup_tags(&mut self) {
         let tags = std::mem::take(&mut self.tags);
         let set: BTreeSet<String> = tags.into_iter().collect();
@@ -69,6 +166,8 @@ pub struct ErrorEnvelope {
     pub tags: Vec<String>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<ValidationError>,
 }
 
 impl ErrorEnvelope {
@@ -87,6 +186,7 @@ impl ErrorEnvelope {
             correlation_id: context.correlation_id.clone(),
             tags: context.tags.clone(),
             metadata: context.metadata.clone(),
+            errors: context.errors.clone(),
         }
     }
 }
@@ -123,6 +223,30 @@ pub enum ServiceError {
         #[error(ignore)]
         context: ErrorContext,
     },
+    #[display(fmt = "{error_message}")]
+    ServiceUnavailable {
+        error_message: String,
+        #[error(ignore)]
+        context: ErrorContext,
+    },
+    #[display(fmt = "{error_message}")]
+    PayloadTooLarge {
+        error_message: String,
+        #[error(ignore)]
+        context: ErrorContext,
+    },
+    #[display(fmt = "{error_message}")]
+    UnprocessableEntity {
+        error_message: String,
+        #[error(ignore)]
+        context: ErrorContext,
+    },
+    #[display(fmt = "{error_message}")]
+    Forbidden {
+        error_message: String,
+        #[error(ignore)]
+        context: ErrorContext,
+    },
 }
 
 impl ServiceError {
@@ -161,13 +285,68 @@ impl ServiceError {
         }
     }
 
+    /// Input failing field validation; rendered as 422.
+    ///
+    /// The message lists every failure and the error body repeats them as
+    /// structured `errors` of `{field, code, message}`.
+    pub fn validation(errors: Vec<ValidationError>) -> Self {
+        let message = errors
+            .iter()
+            .map(|error| error.message.as_str())
+            .collect::<Vec<_>>()
+            .join("; ");
+        Self::unprocessable_entity(message)
+            .with_tag(ErrorTag::Validation)
+            .with_context(|ctx| ctx.with_errors(errors))
+    }
+
+    /// Transient unavailability (e.g. pool saturation); rendered as 503 with
+    /// `Retry-After: retry_after_secs`.
+    pub fn service_unavailable(message: impl Into<String>, retry_after_secs: u64) -> Self {
+        Self::ServiceUnavailable {
+            error_message: message.into(),
+            context: ErrorContext::default()
+                .with_metadata(RETRY_AFTER_METADATA, retry_after_secs.to_string()),
+        }
+    }
+
+    /// Request body over the configured size limit; rendered as 413.
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        Self::PayloadTooLarge {
+            error_message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
+    /// Well-formed request that cannot be applied, e.g. a JSON Patch
+    /// operation on a missing path; rendered as 422.
+    pub fn unprocessable_entity(message: impl Into<String>) -> Self {
+        Self::UnprocessableEntity {
+            error_message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
+    /// Authenticated caller acting on something that is not theirs, e.g.
+    /// another tenant's contact; rendered as 403.
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::Forbidden {
+            error_message: message.into(),
+            context: ErrorContext::default(),
+        }
+    }
+
     pub fn with_context(mut self, updater: impl FnOnce(ErrorContext) -> ErrorContext) -> Self {
         match &mut self {
             ServiceError::Unauthorized { context, .. }
             | ServiceError::InternalServerError { context, .. }
             | ServiceError::BadRequest { context, .. }
             | ServiceError::NotFound { context, .. }
-            | ServiceError::Conflict { context, .. } => {
+            | ServiceError::Conflict { context, .. }
+            | ServiceError::ServiceUnavailable { context, .. }
+            | ServiceError::PayloadTooLarge { context, .. }
+            | ServiceError::UnprocessableEntity { context, .. }
+            | ServiceError::Forbidden { context, .. } => {
                 let current = std::mem::take(context);
                 *context = updater(current);
             }
@@ -183,7 +362,11 @@ impl ServiceError {
         self.with_context(|ctx| ctx.with_metadata(key, value))
     }
 
-    pub fn with_tag(self, tag: impl Into<String>) -> Self {
+    pub fn with_correlation_id(self, correlation_id: impl Into<String>) -> Self {
+        self.with_context(|ctx| ctx.with_correlation_id(correlation_id))
+    }
+
+    pub fn with_tag(self, tag: ErrorTag) -> Self {
         self.with_context(|ctx| ctx.with_tag(tag))
     }
 
@@ -193,7 +376,11 @@ impl ServiceError {
             | ServiceError::InternalServerError { context, .. }
             | ServiceError::BadRequest { context, .. }
             | ServiceError::NotFound { context, .. }
-            | ServiceError::Conflict { context, .. } => context,
+            | ServiceError::Conflict { context, .. }
+            | ServiceError::ServiceUnavailable { context, .. }
+            | ServiceError::PayloadTooLarge { context, .. }
+            | ServiceError::UnprocessableEntity { context, .. }
+            | ServiceError::Forbidden { context, .. } => context,
         }
     }
 
@@ -204,6 +391,10 @@ impl ServiceError {
             ServiceError::BadRequest { .. } => StatusCode::BAD_REQUEST,
             ServiceError::NotFound { .. } => StatusCode::NOT_FOUND,
             ServiceError::Conflict { .. } => StatusCode::CONFLICT,
+            ServiceError::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ServiceError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            ServiceError::UnprocessableEntity { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ServiceError::Forbidden { .. } => StatusCode::FORBIDDEN,
         }
     }
 
@@ -214,6 +405,10 @@ impl ServiceError {
             ServiceError::BadRequest { .. } => "REQ-400",
             ServiceError::NotFound { .. } => "REQ-404",
             ServiceError::Conflict { .. } => "REQ-409",
+            ServiceError::ServiceUnavailable { .. } => "SRV-503",
+            ServiceError::PayloadTooLarge { .. } => "REQ-413",
+            ServiceError::UnprocessableEntity { .. } => "REQ-422",
+            ServiceError::Forbidden { .. } => "AUTH-403",
         }
     }
 
@@ -222,8 +417,12 @@ impl ServiceError {
             ServiceError::InternalServerError { .. } => Level::Error,
             ServiceError::Unauthorized { .. } => Level::Warn,
             ServiceError::Conflict { .. } => Level::Warn,
+            ServiceError::ServiceUnavailable { .. } => Level::Warn,
+            ServiceError::Forbidden { .. } => Level::Warn,
             ServiceError::BadRequest { .. } => Level::Info,
             ServiceError::NotFound { .. } => Level::Info,
+            ServiceError::PayloadTooLarge { .. } => Level::Info,
+            ServiceError::UnprocessableEntity { .. } => Level::Info,
         }
     }
 
@@ -251,9 +450,82 @@ impl error::ResponseError for ServiceError {
     fn error_response(&self) -> HttpResponse {
         let envelope = ErrorEnvelope::from_error(self);
         self.log();
-        HttpResponse::build(self.http_status())
-            .insert_header(ContentType::json())
-            .json(ResponseBody::new(&envelope.message.clone(), envelope))
+        let monitor = get_performance_monitor();
+        ErrorTag::ALL
+            .into_iter()
+            .filter(|tag| self.context().has_tag(*tag))
+            .for_each(|tag| monitor.record_error_tag(tag));
+        let mut builder = HttpResponse::build(self.http_status());
+        builder.insert_header(ContentType::json());
+        if let ServiceError::ServiceUnavailable { context, .. } = self {
+            if let Some(retry_after) = context.metadata.get(RETRY_AFTER_METADATA) {
+                builder.insert_header((RETRY_AFTER, retry_after.as_str()));
+            }
+        }
+        builder.json(ResponseBody::new(&envelope.message.clone(), envelope))
+    }
+}
+
+impl ServiceError {
+    /// Central mapping for a failed `pool.get()`.
+    ///
+    /// r2d2 only fails `get()` after its connection timeout elapses. When every
+    /// connection the pool may open is open and checked out, the pool was merely
+    /// saturated, which is transient backpressure: 503 with `Retry-After`.
+    /// Otherwise the pool could not open a connection, so the database itself is
+    /// unhealthy and the error stays a 500.
+    pub fn from_pool_error<M: r2d2::ManageConnection>(
+        err: r2d2::Error,
+        pool: &r2d2::Pool<M>,
+    ) -> Self {
+        let state = pool.state();
+        let saturated = state.idle_connections == 0 && state.connections >= pool.max_size();
+
+        if saturated {
+            ServiceError::service_unavailable(
+                "Database connection pool exhausted, please retry",
+                POOL_EXHAUSTED_RETRY_AFTER_SECS,
+            )
+            .with_tag(ErrorTag::Db)
+            .with_detail(err.to_string())
+        } else {
+            ServiceError::internal_server_error("Failed to get database connection")
+                .with_tag(ErrorTag::Db)
+                .with_detail(err.to_string())
+        }
+    }
+}
+
+/// Central mapping for rejected JSON bodies, installed as the `web::JsonConfig`
+/// error handler so extractor failures use the standard error body.
+///
+/// Bodies over the configured limit become 413; malformed JSON, a wrong
+/// `Content-Type` and other read failures become 400.
+impl From<error::JsonPayloadError> for ServiceError {
+    fn from(err: error::JsonPayloadError) -> Self {
+        use error::JsonPayloadError;
+
+        let detail = err.to_string();
+        let error = match &err {
+            JsonPayloadError::OverflowKnownLength { limit, .. }
+            | JsonPayloadError::Overflow { limit } => ServiceError::payload_too_large(format!(
+                "Request body is too large (limit: {} bytes)",
+                limit
+            )),
+            JsonPayloadError::Payload(_)
+                if error::ResponseError::status_code(&err) == StatusCode::PAYLOAD_TOO_LARGE =>
+            {
+                ServiceError::payload_too_large("Request body is too large")
+            }
+            JsonPayloadError::ContentType => {
+                ServiceError::bad_request("Content-Type must be application/json")
+            }
+            JsonPayloadError::Deserialize(e) => {
+                ServiceError::bad_request(format!("Invalid JSON body: {}", e))
+            }
+            _ => ServiceError::bad_request("Failed to read request body"),
+        };
+        error.with_tag(ErrorTag::Validation).with_detail(detail)
     }
 }
 
@@ -267,6 +539,17 @@ pub trait ServiceResultExt<T> {
     fn log_on_error(self, level: Level) -> ServiceResult<T>;
 
     fn tap_error(self, tap: impl Fn(&ServiceError)) -> ServiceResult<T>;
+
+    /// Updates the context of the error, if any.
+    fn attach_context(self, updater: impl FnOnce(ErrorContext) -> ErrorContext)
+        -> ServiceResult<T>;
+
+    /// Fails with `error()` when the value does not satisfy `predicate`.
+    fn ensure(
+        self,
+        predicate: impl FnOnce(&T) -> bool,
+        error: impl FnOnce() -> ServiceError,
+    ) -> ServiceResult<T>;
 }
 
 impl<T> ServiceResultExt<T> for Result<T, ServiceError> {
@@ -287,6 +570,27 @@ impl<T> ServiceResultExt<T> for Result<T, ServiceError> {
         }
         self
     }
+
+    fn attach_context(
+        self,
+        updater: impl FnOnce(ErrorContext) -> ErrorContext,
+    ) -> ServiceResult<T> {
+        self.map_err(|err| err.with_context(updater))
+    }
+
+    fn ensure(
+        self,
+        predicate: impl FnOnce(&T) -> bool,
+        error: impl FnOnce() -> ServiceError,
+    ) -> ServiceResult<T> {
+        self.and_then(|value| {
+            if predicate(&value) {
+                Ok(value)
+            } else {
+                Err(error())
+            }
+        })
+    }
 }
 
 pub mod monadic {
@@ -301,6 +605,20 @@ pub mod monadic {
     {
         option.map(f).ok_or_else(default_error)
     }
+
+    /// Turns a fallible lookup into a plain result: errors go through `map_err`,
+    /// a missing value becomes `missing()`.
+    pub fn flatten_option<T, E, E2, F, G>(
+        result: Result<Option<T>, E>,
+        map_err: F,
+        missing: G,
+    ) -> Result<T, E2>
+    where
+        F: FnOnce(E) -> E2,
+        G: FnOnce() -> E2,
+    {
+        result.map_err(map_err)?.ok_or_else(missing)
+    }
 }
 
 pub mod error_pipeline {
@@ -329,6 +647,39 @@ pub mod error_pipeline {
             .collect()
     }
 
+    /// One step of a [`Pipeline`]
+    type Step<T> = Box<dyn Fn(ServiceResult<T>) -> ServiceResult<T>>;
+
+    /// Steps applied in order to a [`ServiceResult`], each seeing the result of the previous one
+    pub struct Pipeline<T> {
+        steps: Vec<Step<T>>,
+    }
+
+    impl<T> Default for Pipeline<T> {
+        fn default() -> Self {
+            Self { steps: Vec::new() }
+        }
+    }
+
+    impl<T> Pipeline<T> {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        #[must_use]
+        pub fn step(
+            mut self,
+            step: impl Fn(ServiceResult<T>) -> ServiceResult<T> + 'static,
+        ) -> Self {
+            self.steps.push(Box::new(step));
+            self
+        }
+
+        pub fn execute(&self, result: ServiceResult<T>) -> ServiceResult<T> {
+            self.steps.iter().fold(result, |result, step| step(result))
+        }
+    }
+
     pub fn build_error_reporter<E: Clone + Send + 'static>(
         errors: Arc<Mutex<Vec<E>>>,
         reporter: impl Fn(&E) + Send + 'static,
@@ -342,7 +693,6 @@ pub mod error_pipeline {
             }
         }
     }
-
 }
 
 pub mod error_logging {
@@ -427,15 +777,16 @@ mod tests {
             .with_correlation_id("corr-123")
             .with_code("REQ-401")
             .with_metadata("field", "email")
-            .with_tag("validation")
-            .with_tags(["api", "validation"]);
+            .with_tag(ErrorTag::Validation)
+            .with_tags([ErrorTag::Auth, ErrorTag::Validation]);
 
         assert_eq!(context.detail.as_deref(), Some("missing field"));
         assert_eq!(context.correlation_id.as_deref(), Some("corr-123"));
         assert_eq!(context.code_override.as_deref(), Some("REQ-401"));
         assert!(context.metadata.get("field").is_some());
         assert!(context.tags.contains(&"validation".to_string()));
-        assert!(context.tags.contains(&"api".to_string()));
+        assert!(context.has_tag(ErrorTag::Auth));
+        assert_eq!(context.tags.len(), 2);
     }
 
     #[test]
@@ -444,7 +795,7 @@ mod tests {
             .with_detail("email format is invalid")
             .with_correlation_id("abc-123")
             .with_metadata("field", "email")
-            .with_tag("validation");
+            .with_tag(ErrorTag::Validation);
 
         let context = error.context();
         assert_eq!(context.detail.as_deref(), Some("email format is invalid"));
@@ -477,6 +828,88 @@ mod tests {
         );
     }
 
+    mod pool_errors {
+        use super::*;
+        use std::time::Duration;
+
+        #[derive(Debug)]
+        struct ConnectError;
+
+        impl std::fmt::Display for ConnectError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("connection refused")
+            }
+        }
+
+        impl std::error::Error for ConnectError {}
+
+        /// Manager whose connections are unit values; `fail` makes every connect fail.
+        struct TestManager {
+            fail: bool,
+        }
+
+        impl r2d2::ManageConnection for TestManager {
+            type Connection = ();
+            type Error = ConnectError;
+
+            fn connect(&self) -> Result<(), ConnectError> {
+                if self.fail {
+                    Err(ConnectError)
+                } else {
+                    Ok(())
+                }
+            }
+
+            fn is_valid(&self, _conn: &mut ()) -> Result<(), ConnectError> {
+                Ok(())
+            }
+
+            fn has_broken(&self, _conn: &mut ()) -> bool {
+                false
+            }
+        }
+
+        fn pool(fail: bool) -> r2d2::Pool<TestManager> {
+            r2d2::Pool::builder()
+                .max_size(1)
+                .min_idle(Some(0))
+                .connection_timeout(Duration::from_millis(50))
+                .build_unchecked(TestManager { fail })
+        }
+
+        #[test]
+        fn exhausted_pool_maps_to_503_with_retry_after() {
+            let pool = pool(false);
+            let _held = pool.get().expect("first connection");
+
+            let error = ServiceError::from_pool_error(pool.get().unwrap_err(), &pool);
+            let response = error.error_response();
+
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(
+                response.headers().get(RETRY_AFTER).unwrap(),
+                POOL_EXHAUSTED_RETRY_AFTER_SECS.to_string().as_str()
+            );
+            assert_eq!(error.default_code(), "SRV-503");
+        }
+
+        #[test]
+        fn failing_database_stays_500() {
+            let pool = pool(true);
+            let error = ServiceError::from_pool_error(pool.get().unwrap_err(), &pool);
+            let response = error.error_response();
+
+            assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            assert!(response.headers().get(RETRY_AFTER).is_none());
+            assert!(error
+                .context()
+                .detail
+                .as_deref()
+                .unwrap()
+                .contains("connection refused"));
+        }
+    }
+
     #[test]
     fn service_result_ext_attach_context() {
         let result: ServiceResult<()> = Err(ServiceError::unauthorized("no token"));
@@ -874,4 +1307,25 @@ mod tests {
             _ => panic!("Wrong variant"),
         }
     }
+
+    #[actix_web::test]
+    async fn service_error_response_counts_each_tag() {
+        for tag in ErrorTag::ALL {
+            let before = get_performance_monitor().error_count_for_tag(tag);
+
+            let response = ServiceError::bad_request("Invalid payload")
+                .with_tag(tag)
+                .error_response();
+            let body = actix_web::body::to_bytes(response.into_body())
+                .await
+                .unwrap();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+            assert_eq!(json["data"]["tags"], serde_json::json!([tag.as_str()]));
+            // Counters are process-wide and never reset, so only growth is asserted
+            if !get_performance_monitor().is_noop() {
+                assert!(get_performance_monitor().error_count_for_tag(tag) > before);
+            }
+        }
+    }
 }
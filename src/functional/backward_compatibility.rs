@@ -47,6 +47,38 @@ impl Default for CompatibilityTestConfig {
     }
 }
 
+impl CompatibilityTestConfig {
+    /// Reads `COMPAT_BASE_URL`, `COMPAT_TENANT_ID`, `COMPAT_USERNAME`, `COMPAT_PASSWORD`
+    /// and `COMPAT_PERFORMANCE_BASELINES`, a JSON object of endpoint to maximum
+    /// milliseconds (`{"/api/ping": 50}`) replacing the default baselines.
+    ///
+    /// Each field keeps its [`Default`] value when its variable is unset or, for
+    /// the baselines, not a valid JSON object.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str, default: String| std::env::var(name).unwrap_or(default);
+        let performance_baselines = std::env::var("COMPAT_PERFORMANCE_BASELINES")
+            .ok()
+            .and_then(|json| match serde_json::from_str(&json) {
+                Ok(baselines) => Some(baselines),
+                Err(e) => {
+                    log::warn!("Ignoring invalid COMPAT_PERFORMANCE_BASELINES: {}", e);
+                    None
+                }
+            })
+            .unwrap_or(defaults.performance_baselines);
+
+        Self {
+            test_tenant_id: var("COMPAT_TENANT_ID", defaults.test_tenant_id),
+            test_username: var("COMPAT_USERNAME", defaults.test_username),
+            test_password: var("COMPAT_PASSWORD", defaults.test_password),
+            jwt_secret: defaults.jwt_secret,
+            base_url: var("COMPAT_BASE_URL", defaults.base_url),
+            performance_baselines,
+        }
+    }
+}
+
 /// Overall compatibility status
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CompatibilityStatus {
@@ -117,6 +149,159 @@ impl Default for CompatibilityTestResults {
     }
 }
 
+impl CompatibilityTestResults {
+    /// Adds the outcome of one test group, returning whether the group passed.
+    ///
+    /// A performance run that completes but reports regressions counts as not passed.
+    pub fn record(&mut self, group: CompatibilityGroup, outcome: GroupOutcome) -> bool {
+        let tests = group.test_count();
+        match outcome {
+            Ok(regressions) => {
+                if let Some((passed, _)) = self.counters_mut(group) {
+                    *passed += tests;
+                }
+                let clean = regressions.is_empty();
+                self.performance_regressions.extend(regressions);
+                clean
+            }
+            Err(e) => {
+                if let Some((_, failed)) = self.counters_mut(group) {
+                    *failed += tests;
+                }
+                self.failed_tests.push(format!("{}: {}", group.label(), e));
+                false
+            }
+        }
+    }
+
+    /// Passed/failed counters of `group`; `None` for groups that only report regressions
+    fn counters_mut(&mut self, group: CompatibilityGroup) -> Option<(&mut u32, &mut u32)> {
+        match group {
+            CompatibilityGroup::ApiEndpoints => Some((
+                &mut self.api_endpoints_passed,
+                &mut self.api_endpoints_failed,
+            )),
+            CompatibilityGroup::JwtAuthentication => {
+                Some((&mut self.auth_tests_passed, &mut self.auth_tests_failed))
+            }
+            CompatibilityGroup::MultiTenantIsolation => Some((
+                &mut self.tenant_isolation_passed,
+                &mut self.tenant_isolation_failed,
+            )),
+            CompatibilityGroup::DatabaseOperations => Some((
+                &mut self.database_tests_passed,
+                &mut self.database_tests_failed,
+            )),
+            CompatibilityGroup::FrontendIntegration => Some((
+                &mut self.frontend_compatibility_passed,
+                &mut self.frontend_compatibility_failed,
+            )),
+            CompatibilityGroup::PerformanceRegression => None,
+        }
+    }
+
+    /// Overall status implied by the recorded pass rate and regressions
+    pub fn overall_status(&self) -> CompatibilityStatus {
+        let total_tests = self.api_endpoints_passed
+            + self.api_endpoints_failed
+            + self.auth_tests_passed
+            + self.auth_tests_failed
+            + self.tenant_isolation_passed
+            + self.tenant_isolation_failed
+            + self.database_tests_passed
+            + self.database_tests_failed
+            + self.frontend_compatibility_passed
+            + self.frontend_compatibility_failed;
+
+        let total_passed = self.api_endpoints_passed
+            + self.auth_tests_passed
+            + self.tenant_isolation_passed
+            + self.database_tests_passed
+            + self.frontend_compatibility_passed;
+
+        let pass_rate = if total_tests > 0 {
+            (total_passed as f64) / (total_tests as f64)
+        } else {
+            0.0
+        };
+
+        if pass_rate >= 1.0 && self.performance_regressions.is_empty() {
+            CompatibilityStatus::FullyCompatible
+        } else if pass_rate >= 0.8 {
+            CompatibilityStatus::PartiallyCompatible
+        } else {
+            CompatibilityStatus::Incompatible
+        }
+    }
+}
+
+/// Test groups of the compatibility suite, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompatibilityGroup {
+    ApiEndpoints,
+    JwtAuthentication,
+    MultiTenantIsolation,
+    DatabaseOperations,
+    FrontendIntegration,
+    PerformanceRegression,
+}
+
+impl CompatibilityGroup {
+    /// Every group, in suite order
+    pub const ALL: [CompatibilityGroup; 6] = [
+        CompatibilityGroup::ApiEndpoints,
+        CompatibilityGroup::JwtAuthentication,
+        CompatibilityGroup::MultiTenantIsolation,
+        CompatibilityGroup::DatabaseOperations,
+        CompatibilityGroup::FrontendIntegration,
+        CompatibilityGroup::PerformanceRegression,
+    ];
+
+    /// Name used in failed test descriptions
+    pub fn label(self) -> &'static str {
+        match self {
+            CompatibilityGroup::ApiEndpoints => "API endpoints",
+            CompatibilityGroup::JwtAuthentication => "JWT authentication",
+            CompatibilityGroup::MultiTenantIsolation => "Multi-tenant isolation",
+            CompatibilityGroup::DatabaseOperations => "Database operations",
+            CompatibilityGroup::FrontendIntegration => "Frontend integration",
+            CompatibilityGroup::PerformanceRegression => "Performance regression",
+        }
+    }
+
+    /// Number of tests the group counts as passed or failed
+    pub fn test_count(self) -> u32 {
+        match self {
+            CompatibilityGroup::ApiEndpoints => 5,
+            CompatibilityGroup::JwtAuthentication => 3,
+            CompatibilityGroup::MultiTenantIsolation => 2,
+            CompatibilityGroup::DatabaseOperations => 3,
+            CompatibilityGroup::FrontendIntegration => 3,
+            CompatibilityGroup::PerformanceRegression => 0,
+        }
+    }
+}
+
+/// Result of running one group: the regressions found, or why the group failed
+pub type GroupOutcome = Result<Vec<PerformanceRegression>, String>;
+
+/// Whether a group passed, as reported in progress events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GroupStatus {
+    Passed,
+    Failed,
+}
+
+/// Progress event emitted once a group completes
+#[derive(Debug, Clone, Serialize)]
+pub struct GroupProgress {
+    pub group: CompatibilityGroup,
+    pub status: GroupStatus,
+    pub elapsed_ms: u64,
+}
+
 /// Backward compatibility validator
 pub struct BackwardCompatibilityValidator {
     config: CompatibilityTestConfig,
@@ -132,73 +317,35 @@ impl BackwardCompatibilityValidator {
     pub async fn run_full_compatibility_suite(&self) -> CompatibilityTestResults {
         let mut results = CompatibilityTestResults::default();
 
-        // Test API endpoints
-        match self.test_api_endpoints().await {
-            Ok(_) => results.api_endpoints_passed += 5,
-            Err(e) => {
-                results.api_endpoints_failed += 5;
-                results.failed_tests.push(format!("API endpoints: {}", e));
-            }
+        for group in CompatibilityGroup::ALL {
+            let outcome = self.run_group(group).await;
+            results.record(group, outcome);
         }
 
-        // Test JWT authentication
-        match self.test_jwt_authentication().await {
-            Ok(_) => results.auth_tests_passed += 3,
-            Err(e) => {
-                results.auth_tests_failed += 3;
-                results
-                    .failed_tests
-                    .push(format!("JWT authentication: {}", e));
-            }
-        }
+        // Calculate overall status
+        results.overall_compatibility = self.calculate_overall_status(&results);
 
-        // Test multi-tenant isolation
-        match self.test_multi_tenant_isolation().await {
-            Ok(_) => results.tenant_isolation_passed += 2,
-            Err(e) => {
-                results.tenant_isolation_failed += 2;
-                results
-                    .failed_tests
-                    .push(format!("Multi-tenant isolation: {}", e));
-            }
-        }
+        results
+    }
 
-        // Test database operations
-        match self.test_database_operations().await {
-            Ok(_) => results.database_tests_passed += 3,
-            Err(e) => {
-                results.database_tests_failed += 3;
-                results
-                    .failed_tests
-                    .push(format!("Database operations: {}", e));
+    /// Run a single test group
+    pub async fn run_group(&self, group: CompatibilityGroup) -> GroupOutcome {
+        match group {
+            CompatibilityGroup::ApiEndpoints => self.test_api_endpoints().await.map(|_| Vec::new()),
+            CompatibilityGroup::JwtAuthentication => {
+                self.test_jwt_authentication().await.map(|_| Vec::new())
             }
-        }
-
-        // Test frontend integration
-        match self.test_frontend_integration().await {
-            Ok(_) => results.frontend_compatibility_passed += 3,
-            Err(e) => {
-                results.frontend_compatibility_failed += 3;
-                results
-                    .failed_tests
-                    .push(format!("Frontend integration: {}", e));
+            CompatibilityGroup::MultiTenantIsolation => {
+                self.test_multi_tenant_isolation().await.map(|_| Vec::new())
             }
-        }
-
-        // Test performance regression
-        match self.test_performance_regression().await {
-            Ok(regressions) => results.performance_regressions = regressions,
-            Err(e) => {
-                results
-                    .failed_tests
-                    .push(format!("Performance regression: {}", e));
+            CompatibilityGroup::DatabaseOperations => {
+                self.test_database_operations().await.map(|_| Vec::new())
+            }
+            CompatibilityGroup::FrontendIntegration => {
+                self.test_frontend_integration().await.map(|_| Vec::new())
             }
+            CompatibilityGroup::PerformanceRegression => self.test_performance_regression().await,
         }
-
-        // Calculate overall status
-        results.overall_compatibility = self.calculate_overall_status(&results);
-
-        results
     }
 
     /// Test all existing API endpoints for backward compatibility
@@ -1014,36 +1161,7 @@ impl BackwardCompatibilityValidator {
         &self,
         results: &CompatibilityTestResults,
     ) -> CompatibilityStatus {
-        let total_tests = results.api_endpoints_passed
-            + results.api_endpoints_failed
-            + results.auth_tests_passed
-            + results.auth_tests_failed
-            + results.tenant_isolation_passed
-            + results.tenant_isolation_failed
-            + results.database_tests_passed
-            + results.database_tests_failed
-            + results.frontend_compatibility_passed
-            + results.frontend_compatibility_failed;
-
-        let total_passed = results.api_endpoints_passed
-            + results.auth_tests_passed
-            + results.tenant_isolation_passed
-            + results.database_tests_passed
-            + results.frontend_compatibility_passed;
-
-        let pass_rate = if total_tests > 0 {
-            (total_passed as f64) / (total_tests as f64)
-        } else {
-            0.0
-        };
-
-        if pass_rate >= 1.0 && results.performance_regressions.is_empty() {
-            CompatibilityStatus::FullyCompatible
-        } else if pass_rate >= 0.8 {
-            CompatibilityStatus::PartiallyCompatible
-        } else {
-            CompatibilityStatus::Incompatible
-        }
+        results.overall_status()
     }
 }
 
@@ -1135,6 +1253,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_config_from_env_overrides_set_fields() {
+        const VARS: [&str; 5] = [
+            "COMPAT_BASE_URL",
+            "COMPAT_TENANT_ID",
+            "COMPAT_USERNAME",
+            "COMPAT_PASSWORD",
+            "COMPAT_PERFORMANCE_BASELINES",
+        ];
+        let clear = || VARS.iter().for_each(|name| std::env::remove_var(name));
+
+        clear();
+        let config = CompatibilityTestConfig::from_env();
+        let defaults = CompatibilityTestConfig::default();
+        assert_eq!(config.base_url, defaults.base_url);
+        assert_eq!(config.test_tenant_id, defaults.test_tenant_id);
+        assert_eq!(config.test_username, defaults.test_username);
+        assert_eq!(config.test_password, defaults.test_password);
+        assert_eq!(config.performance_baselines, defaults.performance_baselines);
+
+        std::env::set_var("COMPAT_BASE_URL", "https://staging.example.com");
+        std::env::set_var("COMPAT_USERNAME", "staging-user");
+        std::env::set_var("COMPAT_PERFORMANCE_BASELINES", r#"{"/api/ping": 80}"#);
+        let config = CompatibilityTestConfig::from_env();
+        assert_eq!(config.base_url, "https://staging.example.com");
+        assert_eq!(config.test_username, "staging-user");
+        assert_eq!(
+            config.performance_baselines,
+            HashMap::from([("/api/ping".to_string(), 80)])
+        );
+        // Unset variables keep their defaults
+        assert_eq!(config.test_tenant_id, defaults.test_tenant_id);
+        assert_eq!(config.test_password, defaults.test_password);
+
+        std::env::set_var("COMPAT_PERFORMANCE_BASELINES", "not json");
+        let config = CompatibilityTestConfig::from_env();
+        assert_eq!(config.performance_baselines, defaults.performance_baselines);
+        clear();
+    }
+
     #[tokio::test]
     #[ignore] // Requires running server
     async fn test_mock_login_flow() {
@@ -1284,6 +1442,35 @@ mod tests {
         assert!(report.contains("Incompatible"));
     }
 
+    #[test]
+    fn test_record_group_outcomes() {
+        let mut results = CompatibilityTestResults::default();
+
+        assert!(results.record(CompatibilityGroup::ApiEndpoints, Ok(Vec::new())));
+        assert!(!results.record(
+            CompatibilityGroup::JwtAuthentication,
+            Err("token rejected".into())
+        ));
+        assert!(!results.record(
+            CompatibilityGroup::PerformanceRegression,
+            Ok(vec![PerformanceRegression {
+                endpoint: "/api/ping".into(),
+                expected_max_ms: 50,
+                actual_ms: 80,
+                regression_percentage: 60.0,
+            }])
+        ));
+
+        assert_eq!(results.api_endpoints_passed, 5);
+        assert_eq!(results.auth_tests_failed, 3);
+        assert_eq!(
+            results.failed_tests,
+            vec!["JWT authentication: token rejected"]
+        );
+        assert_eq!(results.performance_regressions.len(), 1);
+        assert_eq!(results.overall_status(), CompatibilityStatus::Incompatible);
+    }
+
     #[tokio::test]
     #[ignore] // Requires running server
     async fn test_run_full_suite_has_expected_counts() {
@@ -13,7 +13,9 @@
 //! - Async bridging helpers that connect Rayon workloads to Actix/Tokio
 //!   runtimes (`map_async`, `fold_async`, `map_actix_blocking`)
 //! - Batch orchestration utilities such as `process_batch` for service
-//!   pipelines that need convenient parallelisation
+//!   pipelines that need convenient parallelisation, and the free
+//!   [`process_batch`] / [`process_batch_blocking`] for per-item work
+//!   with a bounded number of items in flight
 //! - Metrics aggregation helpers (`aggregate_metrics`) to summarise
 //!   concurrent workloads at runtime
 //!
@@ -22,13 +24,16 @@
 //! can scale without manual thread management.
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 #[cfg(test)]
 use std::time::Duration;
 
 use actix_web::error::BlockingError;
 use actix_web::web;
+use futures::future::join_all;
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use tokio::sync::Semaphore;
 use tokio::task;
 
 use super::parallel_iterators::{
@@ -503,6 +508,77 @@ impl ConcurrentProcessor {
     }
 }
 
+/// Number of items [`process_batch`] runs at once for a requested `concurrency`; `0` means the CPU count.
+fn effective_concurrency(concurrency: usize) -> usize {
+    if concurrency == 0 {
+        num_cpus::get()
+    } else {
+        concurrency
+    }
+}
+
+/// Runs `f` on every item with at most `concurrency` items in flight.
+///
+/// Items are processed concurrently on the current task, so nothing is spawned: a semaphore
+/// holds back each item until a permit frees up. The results come back in input order, one per
+/// item, so a failing item does not stop the others. A `concurrency` of `0` uses the CPU count.
+///
+/// # Examples
+///
+/// ```
+/// let results = process_batch(vec![1, 2, 3], 2, |x| async move {
+///     if x == 2 { Err("two") } else { Ok(x * 10) }
+/// })
+/// .await;
+/// assert_eq!(results, vec![Ok(10), Err("two"), Ok(30)]);
+/// ```
+pub async fn process_batch<T, U, E, F, Fut>(
+    items: Vec<T>,
+    concurrency: usize,
+    f: F,
+) -> Vec<Result<U, E>>
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = Result<U, E>>,
+{
+    let permits = Semaphore::new(effective_concurrency(concurrency));
+    let (permits, f) = (&permits, &f);
+    join_all(items.into_iter().map(|item| async move {
+        // The semaphore is never closed, so acquiring only waits
+        let _permit = permits.acquire().await;
+        f(item).await
+    }))
+    .await
+}
+
+/// [`process_batch`] for blocking work, such as database calls.
+///
+/// Each item runs on Tokio's blocking pool, with at most `concurrency` of them running at once.
+/// An item whose closure panics is reported as [`ConcurrentProcessingError::JoinError`],
+/// converted into `E`.
+pub async fn process_batch_blocking<T, U, E, F>(
+    items: Vec<T>,
+    concurrency: usize,
+    f: F,
+) -> Vec<Result<U, E>>
+where
+    T: Send + 'static,
+    U: Send + 'static,
+    E: From<ConcurrentProcessingError> + Send + 'static,
+    F: Fn(T) -> Result<U, E> + Send + Sync + 'static,
+{
+    let f = Arc::new(f);
+    process_batch(items, concurrency, |item| {
+        let f = f.clone();
+        async move {
+            task::spawn_blocking(move || f(item))
+                .await
+                .unwrap_or_else(|_| Err(ConcurrentProcessingError::JoinError.into()))
+        }
+    })
+    .await
+}
+
 /// Produces a consolidated ParallelMetrics by combining an iterator of metrics.
 ///
 /// Returns a `ParallelMetrics` whose `total_time`, `throughput`, and `memory_usage` are summed,
@@ -581,6 +657,8 @@ pub fn processor() -> ConcurrentProcessor {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
     use super::*;
 
     #[test]
@@ -641,6 +719,105 @@ mod tests {
         assert_eq!(result.data[10], 100);
     }
 
+    /// Counts the items in flight and the most seen at once.
+    #[derive(Default)]
+    struct InFlight {
+        current: AtomicUsize,
+        max: AtomicUsize,
+    }
+
+    impl InFlight {
+        fn enter(&self) {
+            let current = self.current.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max.fetch_max(current, Ordering::SeqCst);
+        }
+
+        fn leave(&self) {
+            self.current.fetch_sub(1, Ordering::SeqCst);
+        }
+
+        fn max(&self) -> usize {
+            self.max.load(Ordering::SeqCst)
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum ItemError {
+        Odd(i32),
+        Panicked,
+    }
+
+    impl From<ConcurrentProcessingError> for ItemError {
+        fn from(_: ConcurrentProcessingError) -> Self {
+            ItemError::Panicked
+        }
+    }
+
+    #[actix_rt::test]
+    async fn process_batch_keeps_input_order_and_reports_errors_per_item() {
+        // Later items finish first
+        let results = process_batch((0..6).collect(), 0, |x: i32| async move {
+            tokio::time::sleep(Duration::from_millis(5 * (6 - x) as u64)).await;
+            if x % 2 == 1 {
+                Err(ItemError::Odd(x))
+            } else {
+                Ok(x * 10)
+            }
+        })
+        .await;
+
+        assert_eq!(
+            results,
+            vec![
+                Ok(0),
+                Err(ItemError::Odd(1)),
+                Ok(20),
+                Err(ItemError::Odd(3)),
+                Ok(40),
+                Err(ItemError::Odd(5)),
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn process_batch_respects_concurrency_cap() {
+        let in_flight = InFlight::default();
+        let results = process_batch((0..12).collect(), 3, |x: i32| {
+            let in_flight = &in_flight;
+            async move {
+                in_flight.enter();
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                in_flight.leave();
+                Ok::<_, ItemError>(x)
+            }
+        })
+        .await;
+
+        assert_eq!(results.len(), 12);
+        assert_eq!(in_flight.max(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn process_batch_blocking_respects_cap_and_reports_panics() {
+        let in_flight = Arc::new(InFlight::default());
+        let counter = in_flight.clone();
+        let results = process_batch_blocking((0..8).collect(), 2, move |x: i32| {
+            counter.enter();
+            std::thread::sleep(Duration::from_millis(5));
+            counter.leave();
+            if x == 5 {
+                panic!("item {} failed", x);
+            }
+            Ok::<_, ItemError>(x + 1)
+        })
+        .await;
+
+        assert!(in_flight.max() <= 2);
+        assert_eq!(results[0], Ok(1));
+        assert_eq!(results[5], Err(ItemError::Panicked));
+        assert_eq!(results[7], Ok(8));
+    }
+
     #[actix_rt::test]
     async fn map_actix_blocking_integrates_with_runtime() {
         let processor = processor();
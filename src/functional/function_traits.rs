@@ -8,6 +8,9 @@
 #[allow(dead_code)]
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
 
 /// Core trait for pure functions that can be registered and composed.
 ///
@@ -62,7 +65,8 @@ pub trait PureFunction<Input, Output>: Send + Sync + 'static {
 }
 
 /// Function categories for organization and lookup optimization.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FunctionCategory {
     /// Data transformation functions (map, filter, etc.)
     Transformation,
@@ -203,6 +207,11 @@ pub struct FunctionContainer {
     /// Type information for composition checking
     input_type_id: std::any::TypeId,
     output_type_id: std::any::TypeId,
+    /// Type names for reporting, e.g. `i32 -> i32`
+    input_type_name: &'static str,
+    output_type_name: &'static str,
+    /// Number of successful invocations through `try_call`
+    calls: AtomicU64,
 }
 
 impl FunctionContainer {
@@ -245,6 +254,9 @@ impl FunctionContainer {
             category,
             input_type_id: std::any::TypeId::of::<Input>(),
             output_type_id: std::any::TypeId::of::<Output>(),
+            input_type_name: std::any::type_name::<Input>(),
+            output_type_name: std::any::type_name::<Output>(),
+            calls: AtomicU64::new(0),
         }
     }
 
@@ -331,9 +343,20 @@ impl FunctionContainer {
             return None;
         }
 
+        self.calls.fetch_add(1, Ordering::Relaxed);
         // Call the function using the Callable trait
         Some(self.callable.call_boxed(input))
     }
+
+    /// Input and output type names of the wrapped function, e.g. `"i32 -> i32"`.
+    pub fn type_signature(&self) -> String {
+        format!("{} -> {}", self.input_type_name, self.output_type_name)
+    }
+
+    /// Number of times the function has been invoked through [`FunctionContainer::try_call`].
+    pub fn call_count(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
 }
 
 /// Object-safe callable trait for type-erased function calls
@@ -27,10 +27,19 @@ pub struct StateTransitionMetrics {
     pub avg_transition_time_ns: u64,
     /// Total number of state transitions
     pub transition_count: u64,
+    /// Sum of all transition times in nanoseconds; the average is derived from it
+    #[serde(default)]
+    pub total_transition_time_ns: u64,
     /// Memory overhead percentage (vs mutable state)
     pub memory_overhead_percent: f64,
     /// Peak memory usage in bytes
     pub peak_memory_usage: usize,
+    /// Expired user sessions removed by [`ImmutableStateManager::remove_expired`]
+    #[serde(default)]
+    pub expired_sessions_removed: u64,
+    /// Expired cached queries removed by [`ImmutableStateManager::remove_expired`]
+    #[serde(default)]
+    pub expired_queries_removed: u64,
 }
 
 impl Default for StateTransitionMetrics {
@@ -42,6 +51,7 @@ impl Default for StateTransitionMetrics {
     /// let m = StateTransitionMetrics::default();
     /// assert_eq!(m.avg_transition_time_ns, 0);
     /// assert_eq!(m.transition_count, 0);
+    /// assert_eq!(m.total_transition_time_ns, 0);
     /// assert_eq!(m.memory_overhead_percent, 0.0);
     /// assert_eq!(m.peak_memory_usage, 0);
     /// ```
@@ -49,8 +59,11 @@ impl Default for StateTransitionMetrics {
         Self {
             avg_transition_time_ns: 0,
             transition_count: 0,
+            total_transition_time_ns: 0,
             memory_overhead_percent: 0.0,
             peak_memory_usage: 0,
+            expired_sessions_removed: 0,
+            expired_queries_removed: 0,
         }
     }
 }
@@ -295,6 +308,21 @@ impl<T: Clone> PersistentVector<T> {
             .as_ref()
             .map_or(Vec::new(), |vec| vec.iter().cloned().collect())
     }
+
+    /// Iterates over the elements in order without copying them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let pv = PersistentVector::from_vec(vec![1, 2, 3]);
+    /// assert_eq!(pv.iter().sum::<i32>(), 6);
+    /// ```
+    pub fn iter(&self) -> Box<dyn Iterator<Item = &T> + '_> {
+        match self.root.as_ref() {
+            Some(root) => Box::new(root.iter()),
+            None => Box::new(std::iter::empty()),
+        }
+    }
 }
 
 impl<T> Default for PersistentVector<T> {
@@ -558,6 +586,13 @@ pub struct QueryResult {
     pub expires_at: chrono::DateTime<chrono::Utc>,
 }
 
+/// Entries removed by one [`ImmutableStateManager::remove_expired`] pass
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ExpiredEntries {
+    pub sessions: usize,
+    pub queries: usize,
+}
+
 /// Global immutable state manager
 ///
 /// This manages the complete application state across all tenants
@@ -622,10 +657,37 @@ impl ImmutableStateManager {
     /// manager.initialize_tenant(tenant).expect("initialization failed");
     /// ```
     pub fn initialize_tenant(&self, tenant: Tenant) -> Result<(), String> {
+        let tenant_id = tenant.id.clone();
+        if self.initialize_tenant_if_absent(tenant)? {
+            Ok(())
+        } else {
+            Err(format!("Tenant '{}' already exists", tenant_id))
+        }
+    }
+
+    /// Initializes the tenant's state unless it already exists.
+    ///
+    /// The existence check and the insert happen under one write lock, so
+    /// concurrent onboarding of the same tenant creates its state exactly once
+    /// and never overwrites an existing state.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(true)` if the state was created, `Ok(false)` if the tenant already existed,
+    /// `Err(String)` if the internal lock is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let manager = ImmutableStateManager::new(100);
+    /// assert!(manager.initialize_tenant_if_absent(create_test_tenant("t1")).unwrap());
+    /// assert!(!manager.initialize_tenant_if_absent(create_test_tenant("t1")).unwrap());
+    /// ```
+    pub fn initialize_tenant_if_absent(&self, tenant: Tenant) -> Result<bool, String> {
         let mut states = self.tenant_states.write().map_err(|_| "Lock poisoned")?;
 
         if states.contains_key(&tenant.id) {
-            return Err(format!("Tenant '{}' already exists", tenant.id));
+            return Ok(false);
         }
 
         let state = Arc::new(TenantApplicationState {
@@ -637,7 +699,7 @@ impl ImmutableStateManager {
         });
 
         states.insert(state.tenant.id.clone(), state);
-        Ok(())
+        Ok(true)
     }
 
     /// Remove the tenant's state from the manager.
@@ -735,7 +797,7 @@ impl ImmutableStateManager {
 
         // Update metrics
         let duration = start.elapsed();
-        self.update_metrics(duration)?;
+        self.update_metrics(1, duration)?;
 
         Ok(())
     }
@@ -804,12 +866,8 @@ impl ImmutableStateManager {
         let new_state_arc = Arc::new(current_state);
         states.insert(tenant_id.to_string(), new_state_arc);
 
-        // Update metrics (weighted by number of transitions)
-        let total_duration = start.elapsed();
-        let avg_duration = total_duration / transition_count as u32;
-        for _ in 0..transition_count {
-            self.update_metrics(avg_duration)?;
-        }
+        // Counts as `transition_count` transitions sharing the batch duration
+        self.update_metrics(transition_count, start.elapsed())?;
 
         Ok(())
     }
@@ -832,6 +890,233 @@ impl ImmutableStateManager {
         Ok(metrics.clone())
     }
 
+    /// Resets the transition metrics to their zero values.
+    ///
+    /// Useful at the start of a measurement window; tenant states are left untouched
+    /// and the next transition starts a fresh average.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let manager = ImmutableStateManager::new(100);
+    /// manager.reset_metrics().unwrap();
+    /// assert_eq!(manager.get_metrics().unwrap().transition_count, 0);
+    /// ```
+    pub fn reset_metrics(&self) -> Result<(), String> {
+        let mut metrics = self.metrics.write().map_err(|_| "Lock poisoned")?;
+        *metrics = StateTransitionMetrics::default();
+        Ok(())
+    }
+
+    /// Exports a snapshot of the transition metrics as a JSON document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let manager = ImmutableStateManager::new(100);
+    /// let json = manager.export_metrics().unwrap();
+    /// assert_eq!(json["transition_count"], 0);
+    /// ```
+    pub fn export_metrics(&self) -> Result<serde_json::Value, String> {
+        let metrics = self.get_metrics()?;
+        serde_json::to_value(metrics).map_err(|e| format!("Failed to export metrics: {}", e))
+    }
+
+    /// Caches the serialized result of `query_id` for `ttl`.
+    ///
+    /// Replaces any previous entry for the same query; expired entries of the
+    /// tenant are dropped at the same time.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Err` if the tenant is not found, `ttl` is out of range, or the internal lock is poisoned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let manager = ImmutableStateManager::new(100);
+    /// manager.initialize_tenant(create_test_tenant("t1")).unwrap();
+    /// manager.cache_query("t1", "people:0", b"[]".to_vec(), Duration::from_secs(30)).unwrap();
+    /// assert_eq!(manager.get_cached_query("t1", "people:0"), Some(b"[]".to_vec()));
+    /// ```
+    pub fn cache_query(
+        &self,
+        tenant_id: &str,
+        query_id: &str,
+        data: Vec<u8>,
+        ttl: Duration,
+    ) -> Result<(), String> {
+        let now = chrono::Utc::now();
+        let expires_at = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl))
+            .ok_or_else(|| format!("Invalid cache TTL: {:?}", ttl))?;
+
+        let mut states = self.tenant_states.write().map_err(|_| "Lock poisoned")?;
+        let current = states
+            .get(tenant_id)
+            .ok_or_else(|| format!("Tenant '{}' not found", tenant_id))?;
+
+        let mut entries: Vec<QueryResult> = current
+            .query_cache
+            .iter()
+            .filter(|entry| entry.query_id != query_id && entry.expires_at > now)
+            .cloned()
+            .collect();
+        entries.push(QueryResult {
+            query_id: query_id.to_string(),
+            data,
+            expires_at,
+        });
+
+        let mut next = (**current).clone();
+        next.query_cache = PersistentVector::from_vec(entries);
+        next.last_updated = now;
+        states.insert(tenant_id.to_string(), Arc::new(next));
+        Ok(())
+    }
+
+    /// Returns the cached result of `query_id`, or `None` if it is missing or expired.
+    ///
+    /// Finding an expired entry evicts the tenant's expired entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let manager = ImmutableStateManager::new(100);
+    /// manager.initialize_tenant(create_test_tenant("t1")).unwrap();
+    /// assert_eq!(manager.get_cached_query("t1", "people:0"), None);
+    /// ```
+    pub fn get_cached_query(&self, tenant_id: &str, query_id: &str) -> Option<Vec<u8>> {
+        let now = chrono::Utc::now();
+        let state = self.get_tenant_state(tenant_id)?;
+        let entry = state
+            .query_cache
+            .iter()
+            .find(|entry| entry.query_id == query_id)?;
+
+        if entry.expires_at > now {
+            return Some(entry.data.clone());
+        }
+
+        if let Err(e) = self.evict_expired_queries(tenant_id, now) {
+            log::warn!(
+                "Failed to evict expired queries of tenant {}: {}",
+                tenant_id,
+                e
+            );
+        }
+        None
+    }
+
+    /// Drops every cached query result of the tenant, e.g. after a write that
+    /// makes them stale. Unknown tenants have nothing to drop.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let manager = ImmutableStateManager::new(100);
+    /// manager.clear_query_cache("t1").unwrap();
+    /// ```
+    pub fn clear_query_cache(&self, tenant_id: &str) -> Result<(), String> {
+        self.retain_queries(tenant_id, |_| false)
+    }
+
+    fn evict_expired_queries(
+        &self,
+        tenant_id: &str,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), String> {
+        self.retain_queries(tenant_id, |entry| entry.expires_at > now)
+    }
+
+    /// Keeps the tenant's cached queries matching `keep`; the state is left
+    /// untouched when nothing is removed.
+    fn retain_queries<F>(&self, tenant_id: &str, keep: F) -> Result<(), String>
+    where
+        F: Fn(&QueryResult) -> bool,
+    {
+        let mut states = self.tenant_states.write().map_err(|_| "Lock poisoned")?;
+        let current = match states.get(tenant_id) {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+
+        let kept: Vec<QueryResult> = current
+            .query_cache
+            .iter()
+            .filter(|entry| keep(entry))
+            .cloned()
+            .collect();
+        if kept.len() == current.query_cache.len() {
+            return Ok(());
+        }
+
+        let mut next = (**current).clone();
+        next.query_cache = PersistentVector::from_vec(kept);
+        next.last_updated = chrono::Utc::now();
+        states.insert(tenant_id.to_string(), Arc::new(next));
+        Ok(())
+    }
+
+    /// Removes the user sessions and cached queries of every tenant that expired by `now`.
+    ///
+    /// Tenants with nothing expired keep their state untouched. The removed
+    /// entries are added to the `expired_sessions_removed` and
+    /// `expired_queries_removed` metrics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let manager = ImmutableStateManager::new(100);
+    /// manager.initialize_tenant(create_test_tenant("t1")).unwrap();
+    /// let removed = manager.remove_expired(chrono::Utc::now()).unwrap();
+    /// assert_eq!(removed, ExpiredEntries::default());
+    /// ```
+    pub fn remove_expired(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<ExpiredEntries, String> {
+        let mut removed = ExpiredEntries::default();
+        {
+            let mut states = self.tenant_states.write().map_err(|_| "Lock poisoned")?;
+            for state in states.values_mut() {
+                let expired_sessions: Vec<String> = state
+                    .user_sessions
+                    .iter()
+                    .filter(|(_, session)| session.expires_at <= now)
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                let kept_queries: Vec<QueryResult> = state
+                    .query_cache
+                    .iter()
+                    .filter(|entry| entry.expires_at > now)
+                    .cloned()
+                    .collect();
+                let expired_queries = state.query_cache.len() - kept_queries.len();
+                if expired_sessions.is_empty() && expired_queries == 0 {
+                    continue;
+                }
+
+                let mut next = (**state).clone();
+                for key in &expired_sessions {
+                    next.user_sessions = next.user_sessions.remove(key);
+                }
+                next.query_cache = PersistentVector::from_vec(kept_queries);
+                next.last_updated = now;
+                *state = Arc::new(next);
+
+                removed.sessions += expired_sessions.len();
+                removed.queries += expired_queries;
+            }
+        }
+
+        let mut metrics = self.metrics.write().map_err(|_| "Lock poisoned")?;
+        metrics.expired_sessions_removed += removed.sessions as u64;
+        metrics.expired_queries_removed += removed.queries as u64;
+        Ok(removed)
+    }
+
     /// Determines whether a tenant state exists in the manager.
     ///
     /// # Returns
@@ -877,10 +1162,12 @@ impl ImmutableStateManager {
         Ok(memory_mb <= self.max_memory_mb)
     }
 
-    /// Record a state transition duration and update aggregated performance metrics.
+    /// Record `count` state transitions taking `duration` in total and update aggregated
+    /// performance metrics.
     ///
-    /// This updates the transition count and the running average transition duration.
-    /// Memory-related fields are set to documented estimates and are not sampled or
+    /// The average is recomputed from the accumulated total time, so a batch of K
+    /// transitions affects it exactly like K individual transitions with the same total
+    /// duration, without compounding rounding errors. Memory-related fields are set to documented estimates and are not sampled or
     /// measured at runtime to avoid performance costs.
     ///
     /// # Returns
@@ -893,19 +1180,19 @@ impl ImmutableStateManager {
     /// use std::time::Duration;
     ///
     /// let mgr = ImmutableStateManager::new(100);
-    /// mgr.update_metrics(Duration::from_millis(5)).unwrap();
+    /// mgr.update_metrics(1, Duration::from_millis(5)).unwrap();
     /// let metrics = mgr.get_metrics().unwrap();
     /// assert!(metrics.transition_count >= 1);
     /// ```
-    fn update_metrics(&self, duration: Duration) -> Result<(), String> {
+    fn update_metrics(&self, count: u64, duration: Duration) -> Result<(), String> {
         let mut metrics = self.metrics.write().map_err(|_| "Lock poisoned")?;
 
-        metrics.transition_count += 1;
-        let new_measurement = duration.as_nanos() as f64;
-        let count = metrics.transition_count as f64;
-        let old_avg = metrics.avg_transition_time_ns as f64;
+        metrics.transition_count += count;
+        let elapsed_ns = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        metrics.total_transition_time_ns =
+            metrics.total_transition_time_ns.saturating_add(elapsed_ns);
         metrics.avg_transition_time_ns =
-            ((old_avg * (count - 1.0) + new_measurement) / count) as u64;
+            metrics.total_transition_time_ns / metrics.transition_count.max(1);
 
         // Memory metrics: documented estimates (per task requirement option b)
         // These are not sampled at runtime due to performance/cost reasons
@@ -933,6 +1220,15 @@ impl Default for ImmutableStateManager {
     }
 }
 
+/// Process-wide state manager instance
+static GLOBAL_STATE_MANAGER: std::sync::OnceLock<ImmutableStateManager> =
+    std::sync::OnceLock::new();
+
+/// Get the process-wide state manager, whose metrics are reported by `/health/performance`
+pub fn get_state_manager() -> &'static ImmutableStateManager {
+    GLOBAL_STATE_MANAGER.get_or_init(ImmutableStateManager::default)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1106,6 +1402,81 @@ mod tests {
         assert_eq!(tenant2_state.app_data.get(&"config".to_string()), None);
     }
 
+    #[test]
+    fn test_batch_metrics_match_individual_transitions() {
+        let durations = [3, 5, 11, 2].map(Duration::from_micros);
+
+        let individual = ImmutableStateManager::new(100);
+        for duration in durations {
+            individual.update_metrics(1, duration).unwrap();
+        }
+        let batched = ImmutableStateManager::new(100);
+        batched
+            .update_metrics(durations.len() as u64, durations.iter().sum())
+            .unwrap();
+
+        let individual = individual.get_metrics().unwrap();
+        let batched = batched.get_metrics().unwrap();
+        assert_eq!(batched.transition_count, individual.transition_count);
+        assert_eq!(
+            batched.total_transition_time_ns,
+            individual.total_transition_time_ns
+        );
+        assert_eq!(
+            batched.avg_transition_time_ns,
+            individual.avg_transition_time_ns
+        );
+        assert_eq!(individual.avg_transition_time_ns, 5_250);
+
+        // A large batch counts every transition and keeps the average exact
+        let manager = ImmutableStateManager::new(100);
+        manager
+            .initialize_tenant(create_test_tenant("batch_test"))
+            .unwrap();
+        manager
+            .apply_transitions(
+                "batch_test",
+                (0..1_000).map(|_| |state: &TenantApplicationState| state.clone()),
+            )
+            .unwrap();
+        let metrics = manager.get_metrics().unwrap();
+        assert_eq!(metrics.transition_count, 1_000);
+        assert_eq!(
+            metrics.avg_transition_time_ns,
+            metrics.total_transition_time_ns / 1_000
+        );
+    }
+
+    #[test]
+    fn test_reset_metrics_restarts_counting() {
+        let manager = ImmutableStateManager::new(100);
+        manager
+            .initialize_tenant(create_test_tenant("reset_test"))
+            .unwrap();
+
+        let touch = |state: &TenantApplicationState| {
+            let mut new_state = state.clone();
+            new_state.last_updated = Utc::now();
+            Ok(new_state)
+        };
+        manager.apply_transition("reset_test", touch).unwrap();
+        manager.apply_transition("reset_test", touch).unwrap();
+        assert_eq!(manager.get_metrics().unwrap().transition_count, 2);
+
+        manager.reset_metrics().unwrap();
+        let metrics = manager.get_metrics().unwrap();
+        assert_eq!(metrics.transition_count, 0);
+        assert_eq!(metrics.avg_transition_time_ns, 0);
+        assert_eq!(metrics.peak_memory_usage, 0);
+        assert_eq!(metrics.memory_overhead_percent, 0.0);
+        assert!(manager.tenant_exists("reset_test"));
+
+        manager.apply_transition("reset_test", touch).unwrap();
+        let exported = manager.export_metrics().unwrap();
+        assert_eq!(exported["transition_count"], 1);
+        assert!(exported["avg_transition_time_ns"].is_u64());
+    }
+
     #[test]
     fn test_performance_metrics() {
         let manager = ImmutableStateManager::new(100);
@@ -1135,6 +1506,141 @@ mod tests {
         assert!(metrics.memory_overhead_percent < 20.0);
     }
 
+    #[test]
+    fn test_initialize_tenant_if_absent_creates_once_under_contention() {
+        use std::sync::Barrier;
+        use std::thread;
+
+        let manager = ImmutableStateManager::new(100);
+        let barrier = Barrier::new(16);
+
+        let created = thread::scope(|scope| {
+            let handles: Vec<_> = (0..16)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        manager
+                            .initialize_tenant_if_absent(create_test_tenant("onboarding"))
+                            .unwrap()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .filter(|created| *created)
+                .count()
+        });
+
+        assert_eq!(created, 1);
+        assert!(manager.tenant_exists("onboarding"));
+        assert_eq!(
+            manager.initialize_tenant(create_test_tenant("onboarding")),
+            Err("Tenant 'onboarding' already exists".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cached_query_hit() {
+        let manager = ImmutableStateManager::new(100);
+        manager
+            .initialize_tenant(create_test_tenant("cache_hit"))
+            .unwrap();
+
+        manager
+            .cache_query(
+                "cache_hit",
+                "people:0:50",
+                b"page-1".to_vec(),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+        manager
+            .cache_query(
+                "cache_hit",
+                "people:0:50",
+                b"page-2".to_vec(),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+
+        assert_eq!(
+            manager.get_cached_query("cache_hit", "people:0:50"),
+            Some(b"page-2".to_vec())
+        );
+        // Re-caching a query replaces its entry
+        let state = manager.get_tenant_state("cache_hit").unwrap();
+        assert_eq!(state.query_cache.len(), 1);
+    }
+
+    #[test]
+    fn test_cached_query_miss() {
+        let manager = ImmutableStateManager::new(100);
+        manager
+            .initialize_tenant(create_test_tenant("cache_a"))
+            .unwrap();
+        manager
+            .initialize_tenant(create_test_tenant("cache_b"))
+            .unwrap();
+        manager
+            .cache_query(
+                "cache_a",
+                "people:0:50",
+                b"a".to_vec(),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+
+        assert_eq!(manager.get_cached_query("cache_a", "people:50:50"), None);
+        // Entries are isolated per tenant
+        assert_eq!(manager.get_cached_query("cache_b", "people:0:50"), None);
+        assert_eq!(manager.get_cached_query("unknown", "people:0:50"), None);
+        assert!(manager
+            .cache_query(
+                "unknown",
+                "people:0:50",
+                Vec::new(),
+                Duration::from_secs(60)
+            )
+            .is_err());
+
+        manager.clear_query_cache("cache_a").unwrap();
+        assert_eq!(manager.get_cached_query("cache_a", "people:0:50"), None);
+    }
+
+    #[test]
+    fn test_expired_cached_query_is_evicted_on_read() {
+        let manager = ImmutableStateManager::new(100);
+        manager
+            .initialize_tenant(create_test_tenant("cache_expiry"))
+            .unwrap();
+        manager
+            .cache_query("cache_expiry", "stale", b"old".to_vec(), Duration::ZERO)
+            .unwrap();
+        manager
+            .cache_query(
+                "cache_expiry",
+                "fresh",
+                b"new".to_vec(),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+
+        assert_eq!(manager.get_cached_query("cache_expiry", "stale"), None);
+
+        let state = manager.get_tenant_state("cache_expiry").unwrap();
+        let remaining: Vec<&str> = state
+            .query_cache
+            .iter()
+            .map(|entry| entry.query_id.as_str())
+            .collect();
+        assert_eq!(remaining, vec!["fresh"]);
+        assert_eq!(
+            manager.get_cached_query("cache_expiry", "fresh"),
+            Some(b"new".to_vec())
+        );
+    }
+
     #[test]
     fn test_thread_safe_concurrent_access() {
         use std::sync::Arc;
@@ -3,6 +3,9 @@
 //! Core iterator processing system that leverages itertools' advanced features
 //! including chunk_by, kmerge, join operations and requires Rust 1.63.0 or later.
 //! This engine serves as the foundation for all data transformation operations.
+//!
+//! `chunk_by`, `kmerge` and `kmerge_all` are always available; without the `functional`
+//! feature they fall back to std-only implementations with the same output.
 
 use std::collections::HashMap;
 use std::fmt;
@@ -11,7 +14,6 @@ use std::hash::Hash;
 #[cfg(feature = "functional")]
 use itertools::Itertools;
 
-#[cfg(feature = "performance_monitoring")]
 use crate::functional::performance_monitoring::{
     get_performance_monitor, Measurable, OperationType,
 };
@@ -161,10 +163,134 @@ pub struct IteratorConfig {
     pub enable_parallel: bool,
     /// Buffer size for chunked operations
     pub buffer_size: usize,
-    /// Memory limit for lazy evaluation
+    /// Memory limit in bytes enforced by the bounded (`try_*`) terminals; `0` disables the limit
     pub memory_limit: usize,
 }
 
+/// Errors raised by the bounded iterator chain operations
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum IteratorError {
+    #[error("Memory limit exceeded: accumulated {used} bytes, limit is {limit} bytes")]
+    MemoryLimitExceeded { used: usize, limit: usize },
+}
+
+/// Memory footprint estimate used when `IteratorChain::collect_measured` records its metrics.
+///
+/// By default a value counts only its inline `size_of`; types owning heap data
+/// (strings, vectors, records built from them) override `heap_size` so that
+/// string-heavy results are not under-reported.
+pub trait HeapSize {
+    /// Bytes owned on the heap, not counting the value itself
+    fn heap_size(&self) -> usize {
+        0
+    }
+
+    /// Inline size plus heap size
+    fn memory_size(&self) -> usize {
+        std::mem::size_of_val(self) + self.heap_size()
+    }
+}
+
+macro_rules! impl_inline_heap_size {
+    ($($ty:ty),* $(,)?) => {
+        $(impl HeapSize for $ty {})*
+    };
+}
+
+impl_inline_heap_size!(
+    (),
+    bool,
+    char,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+    chrono::NaiveDate,
+    chrono::NaiveDateTime,
+    chrono::DateTime<chrono::Utc>,
+);
+
+/// Borrowed data is owned elsewhere, so only the reference itself counts.
+impl<T: ?Sized> HeapSize for &T {}
+
+impl HeapSize for String {
+    fn heap_size(&self) -> usize {
+        self.capacity()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Vec<T> {
+    fn heap_size(&self) -> usize {
+        self.capacity() * std::mem::size_of::<T>() + self.iter().map(T::heap_size).sum::<usize>()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Box<T> {
+    fn heap_size(&self) -> usize {
+        (**self).memory_size()
+    }
+}
+
+impl<T: HeapSize> HeapSize for Option<T> {
+    fn heap_size(&self) -> usize {
+        self.as_ref().map_or(0, T::heap_size)
+    }
+}
+
+impl<A: HeapSize, B: HeapSize> HeapSize for (A, B) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size()
+    }
+}
+
+impl<A: HeapSize, B: HeapSize, C: HeapSize> HeapSize for (A, B, C) {
+    fn heap_size(&self) -> usize {
+        self.0.heap_size() + self.1.heap_size() + self.2.heap_size()
+    }
+}
+
+/// Bytes recorded for a collected result: the sum of each item's `memory_size`.
+fn collected_memory<T: HeapSize>(items: &[T]) -> u64 {
+    items.iter().map(T::memory_size).sum::<usize>() as u64
+}
+
+/// Tracks the approximate number of bytes accumulated by an eager operation.
+///
+/// Sizes are estimated from `size_of` of the buffered items, so heap data owned
+/// by an item is not accounted for.
+struct MemoryBudget {
+    limit: usize,
+    used: usize,
+}
+
+impl MemoryBudget {
+    fn new(limit: usize) -> Self {
+        Self { limit, used: 0 }
+    }
+
+    /// Adds `bytes` to the running total, failing once the configured limit is crossed.
+    fn charge(&mut self, bytes: usize) -> Result<(), IteratorError> {
+        self.used = self.used.saturating_add(bytes);
+        if self.limit > 0 && self.used > self.limit {
+            return Err(IteratorError::MemoryLimitExceeded {
+                used: self.used,
+                limit: self.limit,
+            });
+        }
+        Ok(())
+    }
+}
+
 impl Default for IteratorConfig {
     /// Creates an IteratorConfig populated with the library's sensible defaults.
     ///
@@ -351,6 +477,105 @@ where
         }
     }
 
+    /// Keeps only the first occurrence of each item, wherever its duplicates appear.
+    ///
+    /// Lazy: items are yielded as they are first seen, and a clone of each distinct item is kept
+    /// to recognise later duplicates. Appends "unique" to the chain's operations log.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chain = IteratorChain::new(vec![1, 2, 2, 3, 1].into_iter());
+    /// assert_eq!(chain.unique().collect(), vec![1, 2, 3]);
+    /// ```
+    #[cfg(feature = "functional")]
+    pub fn unique(self) -> IteratorChain<T, impl Iterator<Item = T>>
+    where
+        T: Hash + Eq + Clone,
+    {
+        let mut operations = self.operations;
+        operations.push("unique".to_string());
+
+        IteratorChain {
+            iterator: Itertools::unique(self.iterator),
+            config: self.config,
+            operations,
+        }
+    }
+
+    /// Fallback for [`unique`](Self::unique) when itertools is not enabled.
+    #[cfg(not(feature = "functional"))]
+    pub fn unique(self) -> IteratorChain<T, impl Iterator<Item = T>>
+    where
+        T: Hash + Eq + Clone,
+    {
+        let mut operations = self.operations;
+        operations.push("unique".to_string());
+
+        let mut seen = std::collections::HashSet::new();
+        IteratorChain {
+            iterator: self.iterator.filter(move |item| seen.insert(item.clone())),
+            config: self.config,
+            operations,
+        }
+    }
+
+    /// Drops items whose key equals the key of the item right before them.
+    ///
+    /// Only consecutive duplicates are removed, so sort by the key first to deduplicate the whole
+    /// sequence; the first item of each run is kept. Lazy, and appends "dedup_by_key" to the
+    /// chain's operations log.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chain = IteratorChain::new(vec![(1, 'a'), (1, 'b'), (2, 'c'), (1, 'd')].into_iter());
+    /// let deduped = chain.dedup_by_key(|&(key, _)| key).collect();
+    /// assert_eq!(deduped, vec![(1, 'a'), (2, 'c'), (1, 'd')]);
+    /// ```
+    #[cfg(feature = "functional")]
+    pub fn dedup_by_key<K, F>(self, mut f: F) -> IteratorChain<T, impl Iterator<Item = T>>
+    where
+        F: FnMut(&T) -> K,
+        K: PartialEq,
+    {
+        let mut operations = self.operations;
+        operations.push("dedup_by_key".to_string());
+
+        IteratorChain {
+            iterator: self
+                .iterator
+                .dedup_by(move |previous, item| f(previous) == f(item)),
+            config: self.config,
+            operations,
+        }
+    }
+
+    /// Fallback for [`dedup_by_key`](Self::dedup_by_key) when itertools is not enabled.
+    ///
+    /// Remembers the key of the previous item, so `f` runs once per item.
+    #[cfg(not(feature = "functional"))]
+    pub fn dedup_by_key<K, F>(self, mut f: F) -> IteratorChain<T, impl Iterator<Item = T>>
+    where
+        F: FnMut(&T) -> K,
+        K: PartialEq,
+    {
+        let mut operations = self.operations;
+        operations.push("dedup_by_key".to_string());
+
+        let mut previous: Option<K> = None;
+        IteratorChain {
+            iterator: self.iterator.filter(move |item| {
+                let key = f(item);
+                let duplicate = previous.as_ref() == Some(&key);
+                previous = Some(key);
+                !duplicate
+            }),
+            config: self.config,
+            operations,
+        }
+    }
+
     /// Group consecutive elements by a derived key, yielding `(key, Vec<items>)` for each contiguous run.
     ///
     /// The resulting `IteratorChain` produces one `(key, Vec<T>)` tuple for each sequence of adjacent
@@ -364,6 +589,10 @@ where
     /// let groups: Vec<(i32, Vec<i32>)> = chain.chunk_by(|&x| x).collect();
     /// assert_eq!(groups, vec![(1, vec![1, 1]), (2, vec![2, 2, 2]), (3, vec![3])]);
     /// ```
+    ///
+    /// Without the `functional` feature a std-only fallback with the same output is used; it
+    /// compares each key against the previous group's key instead of using itertools' grouping
+    /// adaptor, which is somewhat slower but asymptotically the same.
     #[cfg(feature = "functional")]
     pub fn chunk_by<K, F>(
         self,
@@ -391,7 +620,90 @@ where
         }
     }
 
-    /// K-way merge sorted iterators using itertools two-way merge
+    /// Fallback for [`chunk_by`](Self::chunk_by) when itertools is not enabled.
+    #[cfg(not(feature = "functional"))]
+    pub fn chunk_by<K, F>(
+        self,
+        mut f: F,
+    ) -> IteratorChain<(K, Vec<T>), impl Iterator<Item = (K, Vec<T>)>>
+    where
+        F: FnMut(&T) -> K,
+        K: PartialEq,
+        T: Clone,
+    {
+        let mut operations = self.operations;
+        operations.push("chunk_by".to_string());
+
+        let mut chunks: Vec<(K, Vec<T>)> = Vec::new();
+        for item in self.iterator {
+            let key = f(&item);
+            match chunks.last_mut() {
+                Some((last_key, group)) if *last_key == key => group.push(item),
+                _ => chunks.push((key, vec![item])),
+            }
+        }
+
+        IteratorChain {
+            iterator: chunks.into_iter(),
+            config: self.config,
+            operations,
+        }
+    }
+
+    /// Bounded variant of [`chunk_by`](Self::chunk_by) that enforces `IteratorConfig::memory_limit`.
+    ///
+    /// Grouping is eager, so every buffered item and group is charged against the limit and
+    /// grouping stops with `IteratorError::MemoryLimitExceeded` as soon as it is crossed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chain = IteratorChain::new(vec![1, 1, 2].into_iter());
+    /// let groups = chain.try_chunk_by(|&x| x).unwrap().collect();
+    /// assert_eq!(groups, vec![(1, vec![1, 1]), (2, vec![2])]);
+    /// ```
+    #[cfg(feature = "functional")]
+    #[allow(clippy::type_complexity)]
+    pub fn try_chunk_by<K, F>(
+        self,
+        f: F,
+    ) -> Result<IteratorChain<(K, Vec<T>), impl Iterator<Item = (K, Vec<T>)>>, IteratorError>
+    where
+        F: FnMut(&T) -> K,
+        K: PartialEq,
+        T: Clone,
+    {
+        let mut operations = self.operations;
+        operations.push("try_chunk_by".to_string());
+
+        let mut budget = MemoryBudget::new(self.config.memory_limit);
+        let mut chunks: Vec<(K, Vec<T>)> = Vec::new();
+
+        for (key, group) in self.iterator.chunk_by(f).into_iter() {
+            budget.charge(std::mem::size_of::<(K, Vec<T>)>())?;
+            let mut items = Vec::new();
+            for item in group {
+                budget.charge(std::mem::size_of::<T>())?;
+                items.push(item);
+            }
+            chunks.push((key, items));
+        }
+
+        Ok(IteratorChain {
+            iterator: chunks.into_iter(),
+            config: self.config,
+            operations,
+        })
+    }
+
+    /// Two-way merge of this sorted sequence with one other sorted sequence
+    ///
+    /// Despite the name this only merges two inputs; use [`kmerge_all`](Self::kmerge_all)
+    /// to merge any number of sorted sequences at once.
+    ///
+    /// Without the `functional` feature a std-only fallback yields the same sequence: a lazy
+    /// two-way merge over `Peekable`s that compares heads on every step. It lacks itertools'
+    /// heap, size hints and `fold` specialisation, so collecting large inputs is slower.
     #[cfg(feature = "functional")]
     pub fn kmerge<J>(self, other: J) -> IteratorChain<T, impl Iterator<Item = T>>
     where
@@ -416,6 +728,113 @@ where
         }
     }
 
+    /// Fallback for [`kmerge`](Self::kmerge) when itertools is not enabled.
+    #[cfg(not(feature = "functional"))]
+    pub fn kmerge<J>(self, other: J) -> IteratorChain<T, impl Iterator<Item = T>>
+    where
+        T: Ord,
+        J: IntoIterator<Item = T>,
+        I: 'static,
+        <J as IntoIterator>::IntoIter: 'static,
+    {
+        let mut operations = self.operations;
+        operations.push("kmerge".to_string());
+
+        let mut left = self.iterator.peekable();
+        let mut right = other.into_iter().peekable();
+        // Take from the left on ties so equal elements keep their input order.
+        let merged = std::iter::from_fn(move || match (left.peek(), right.peek()) {
+            (Some(l), Some(r)) if r < l => right.next(),
+            (Some(_), _) => left.next(),
+            (None, _) => right.next(),
+        });
+
+        IteratorChain {
+            iterator: merged,
+            config: self.config,
+            operations,
+        }
+    }
+
+    /// K-way merge of this sorted sequence with any number of other sorted sequences
+    ///
+    /// Uses itertools' heap-based `kmerge`, so each step costs `O(log k)` for `k` inputs.
+    #[cfg(feature = "functional")]
+    pub fn kmerge_all<J>(self, others: Vec<J>) -> IteratorChain<T, impl Iterator<Item = T>>
+    where
+        T: Ord,
+        J: IntoIterator<Item = T>,
+        I: 'static,
+        <J as IntoIterator>::IntoIter: 'static,
+    {
+        let mut operations = self.operations;
+        operations.push("kmerge_all".to_string());
+
+        let mut iterators: Vec<Box<dyn Iterator<Item = T>>> = Vec::with_capacity(others.len() + 1);
+        iterators.push(Box::new(self.iterator));
+        iterators.extend(
+            others
+                .into_iter()
+                .map(|other| Box::new(other.into_iter()) as Box<dyn Iterator<Item = T>>),
+        );
+
+        IteratorChain {
+            iterator: iterators.into_iter().kmerge(),
+            config: self.config,
+            operations,
+        }
+    }
+
+    /// Fallback for [`kmerge_all`](Self::kmerge_all) when itertools is not enabled.
+    ///
+    /// Keeps the current head of every input in a `BinaryHeap`; ties are taken in input order.
+    #[cfg(not(feature = "functional"))]
+    pub fn kmerge_all<J>(self, others: Vec<J>) -> IteratorChain<T, impl Iterator<Item = T>>
+    where
+        T: Ord,
+        J: IntoIterator<Item = T>,
+        I: 'static,
+        <J as IntoIterator>::IntoIter: 'static,
+    {
+        use std::cmp::Reverse;
+        use std::collections::BinaryHeap;
+
+        let mut operations = self.operations;
+        operations.push("kmerge_all".to_string());
+
+        let mut sources: Vec<Box<dyn Iterator<Item = T>>> = Vec::with_capacity(others.len() + 1);
+        sources.push(Box::new(self.iterator));
+        sources.extend(
+            others
+                .into_iter()
+                .map(|other| Box::new(other.into_iter()) as Box<dyn Iterator<Item = T>>),
+        );
+
+        let mut heads = BinaryHeap::with_capacity(sources.len());
+        let mut primed = false;
+        let merged = std::iter::from_fn(move || {
+            if !primed {
+                primed = true;
+                for (index, source) in sources.iter_mut().enumerate() {
+                    if let Some(item) = source.next() {
+                        heads.push(Reverse((item, index)));
+                    }
+                }
+            }
+            let Reverse((item, index)) = heads.pop()?;
+            if let Some(next) = sources[index].next() {
+                heads.push(Reverse((next, index)));
+            }
+            Some(item)
+        });
+
+        IteratorChain {
+            iterator: merged,
+            config: self.config,
+            operations,
+        }
+    }
+
     /// Lockstep iteration over multiple iterators (zip all with equal lengths)
     #[cfg(feature = "functional")]
     pub fn lockstep_zip<J>(
@@ -508,6 +927,63 @@ where
         }
     }
 
+    /// Bounded variant of [`join`](Self::join) that enforces `IteratorConfig::memory_limit`.
+    ///
+    /// The right-hand side is buffered into a lookup map before joining, so each buffered item is
+    /// charged against the limit. The joined rows themselves are produced lazily; bound them with
+    /// [`try_collect`](Self::try_collect).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chain = IteratorChain::new(vec![1, 2].into_iter());
+    /// let joined = chain
+    ///     .try_join(vec![(1, 10)], |l: &i32| *l, |r: &(i32, i32)| r.0)
+    ///     .unwrap()
+    ///     .collect();
+    /// assert_eq!(joined, vec![(1, (1, 10))]);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn try_join<K, U, V, F, G>(
+        self,
+        other: U,
+        self_key: F,
+        other_key: G,
+    ) -> Result<IteratorChain<(T, V), impl Iterator<Item = (T, V)>>, IteratorError>
+    where
+        K: Hash + Eq,
+        U: IntoIterator<Item = V>,
+        F: Fn(&T) -> K,
+        G: Fn(&V) -> K,
+        T: Clone,
+        V: Clone,
+    {
+        let mut operations = self.operations;
+        operations.push("try_join".to_string());
+
+        let mut budget = MemoryBudget::new(self.config.memory_limit);
+        let mut right_map: HashMap<K, Vec<V>> = HashMap::new();
+        for item in other {
+            budget.charge(std::mem::size_of::<(K, V)>())?;
+            right_map.entry(other_key(&item)).or_default().push(item);
+        }
+
+        let joined = self.iterator.flat_map(move |left_item| {
+            let left_key = self_key(&left_item);
+            let right_items = right_map.get(&left_key).cloned().unwrap_or_default();
+
+            right_items
+                .into_iter()
+                .map(move |right_item| (left_item.clone(), right_item))
+        });
+
+        Ok(IteratorChain {
+            iterator: joined,
+            config: self.config,
+            operations,
+        })
+    }
+
     /// Cartesian product with another iterator
     #[cfg(feature = "functional")]
     pub fn cartesian_product<U>(
@@ -534,6 +1010,8 @@ where
     /// Collects all items from the chain into a `Vec`.
     ///
     /// Returns a `Vec<T>` containing every item produced by the chain's iterator.
+    /// The memory recorded for the operation is `size_of::<T>()` per item; use
+    /// [`collect_measured`](Self::collect_measured) to count heap data as well.
     ///
     /// # Examples
     ///
@@ -544,28 +1022,83 @@ where
     /// assert_eq!(v, vec![1, 2, 3]);
     /// ```
     pub fn collect(self) -> Vec<T> {
-        #[cfg(feature = "performance_monitoring")]
-        {
-            let start = std::time::Instant::now();
+        self.collect_recording(|items| std::mem::size_of_val(items) as u64)
+    }
 
-            let result: Vec<T> = self.iterator.collect();
+    /// Like [`collect`](Self::collect), but records the items'
+    /// [`HeapSize::memory_size`], so strings and records built from them are
+    /// not under-reported.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crate::functional::iterator_engine::IteratorChain;
+    /// let names = IteratorChain::new(vec!["Ada".to_string()].into_iter()).collect_measured();
+    /// assert_eq!(names, vec!["Ada".to_string()]);
+    /// ```
+    pub fn collect_measured(self) -> Vec<T>
+    where
+        T: HeapSize,
+    {
+        self.collect_recording(collected_memory)
+    }
 
-            let duration = start.elapsed();
-            let memory_usage = (result.len() * std::mem::size_of::<T>()) as u64;
+    fn collect_recording(self, memory: impl FnOnce(&[T]) -> u64) -> Vec<T> {
+        let start = std::time::Instant::now();
 
-            get_performance_monitor().record_operation(
-                OperationType::IteratorChain,
-                duration,
-                memory_usage,
-                false,
-            );
+        let result: Vec<T> = self.iterator.collect();
 
-            result
-        }
-        #[cfg(not(feature = "performance_monitoring"))]
-        {
-            self.iterator.collect()
+        let duration = start.elapsed();
+        let memory_usage = memory(&result);
+
+        get_performance_monitor().record_operation(
+            OperationType::IteratorChain,
+            duration,
+            memory_usage,
+            false,
+        );
+
+        result
+    }
+
+    /// Collects all items into a `Vec`, enforcing `IteratorConfig::memory_limit`.
+    ///
+    /// Each collected item is charged `size_of::<T>()` bytes; collection stops with
+    /// `IteratorError::MemoryLimitExceeded` once the accumulated size crosses the limit, so a
+    /// runaway pipeline cannot exhaust memory. A limit of `0` disables the check. Lazy adapters
+    /// such as `lockstep_zip` are bounded here, when their rows are materialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let config = IteratorConfig { memory_limit: 8, ..IteratorConfig::default() };
+    /// let chain = IteratorChain::new(vec![1u32, 2, 3].into_iter()).with_config(config);
+    /// assert!(chain.try_collect().is_err());
+    /// ```
+    pub fn try_collect(self) -> Result<Vec<T>, IteratorError> {
+        let start = std::time::Instant::now();
+
+        let mut budget = MemoryBudget::new(self.config.memory_limit);
+        let item_size = std::mem::size_of::<T>();
+        let mut result = Vec::new();
+        let mut outcome = Ok(());
+
+        for item in self.iterator {
+            if let Err(error) = budget.charge(item_size) {
+                outcome = Err(error);
+                break;
+            }
+            result.push(item);
         }
+
+        get_performance_monitor().record_operation(
+            OperationType::IteratorChain,
+            start.elapsed(),
+            budget.used as u64,
+            outcome.is_err(),
+        );
+
+        outcome.map(|_| result)
     }
 
     /// Counts the remaining elements in the chain.
@@ -613,6 +1146,52 @@ where
     {
         self.iterator.fold(init, f)
     }
+
+    /// Reduces the chain to a single value, seeding the accumulator with the first item.
+    ///
+    /// Like [`fold`](Self::fold) without an initial value, for sequences where the first
+    /// element is the natural starting point (e.g. a maximum).
+    ///
+    /// # Returns
+    ///
+    /// `Some` with the accumulated value, or `None` if the chain is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chain = IteratorChain::new(vec![3, 7, 2].into_iter());
+    /// let max = chain.reduce(|a, b| if b > a { b } else { a });
+    /// assert_eq!(max, Some(7));
+    /// ```
+    pub fn reduce<F>(self, f: F) -> Option<T>
+    where
+        F: FnMut(T, T) -> T,
+    {
+        self.iterator.reduce(f)
+    }
+
+    /// Reduces the chain with a fallible accumulator, stopping at the first error.
+    ///
+    /// Complements [`fold`](Self::fold) for accumulations that can fail partway (e.g. parsing).
+    /// Items after the failing one are never pulled from the underlying iterator.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` with the final accumulated value, or the first `Err` returned by `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let chain = IteratorChain::new(vec!["1", "2", "x"].into_iter());
+    /// let sum = chain.try_fold(0, |acc, s| s.parse::<i32>().map(|n| acc + n));
+    /// assert!(sum.is_err());
+    /// ```
+    pub fn try_fold<B, E, F>(mut self, init: B, f: F) -> Result<B, E>
+    where
+        F: FnMut(B, T) -> Result<B, E>,
+    {
+        Iterator::try_fold(&mut self.iterator, init, f)
+    }
 }
 
 impl<T, I> fmt::Debug for IteratorChain<T, I>
@@ -640,7 +1219,6 @@ where
     }
 }
 
-#[cfg(feature = "performance_monitoring")]
 impl<T, I> Measurable for IteratorChain<T, I>
 where
     I: Iterator<Item = T>,
@@ -829,6 +1407,43 @@ mod tests {
         assert_eq!(result, vec![4, 8]);
     }
 
+    #[test]
+    fn test_unique_keeps_first_occurrences() {
+        let chain = IteratorEngine::new().from_vec(vec![1, 2, 2, 3, 1]).unique();
+        assert_eq!(chain.operations, vec!["unique".to_string()]);
+        assert_eq!(chain.collect(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_dedup_by_key_collapses_consecutive_duplicates_only() {
+        let contacts = vec![
+            ("ann@example.com", "Ann"),
+            ("ann@example.com", "Ann B."),
+            ("bob@example.com", "Bob"),
+            ("ann@example.com", "Ann C."),
+        ];
+
+        let chain = IteratorEngine::new()
+            .from_vec(contacts)
+            .dedup_by_key(|&(email, _)| email);
+        assert_eq!(chain.operations, vec!["dedup_by_key".to_string()]);
+        assert_eq!(
+            chain.collect(),
+            vec![
+                ("ann@example.com", "Ann"),
+                ("bob@example.com", "Bob"),
+                ("ann@example.com", "Ann C."),
+            ]
+        );
+
+        // Sorted by key, every key is left once
+        let sorted = IteratorEngine::new()
+            .from_vec(vec![(1, 'a'), (1, 'b'), (2, 'c'), (3, 'd'), (3, 'e')])
+            .dedup_by_key(|&(key, _)| key)
+            .collect();
+        assert_eq!(sorted, vec![(1, 'a'), (2, 'c'), (3, 'd')]);
+    }
+
     #[test]
     fn test_chunk_by() {
         let engine = IteratorEngine::new();
@@ -843,6 +1458,68 @@ mod tests {
         assert_eq!(chunks, vec![vec![1, 1], vec![2, 2], vec![3, 3, 3]]);
     }
 
+    #[test]
+    fn test_chunk_by_matches_in_every_feature_configuration() {
+        let engine = IteratorEngine::new();
+        let words = vec!["apple", "avocado", "banana", "blueberry", "apricot"];
+
+        let chain = engine.from_vec(words).chunk_by(|word| word.chars().next());
+        assert!(chain.operations.contains(&"chunk_by".to_string()));
+
+        let groups: Vec<(Option<char>, Vec<&str>)> = chain.collect();
+        assert_eq!(
+            groups,
+            vec![
+                (Some('a'), vec!["apple", "avocado"]),
+                (Some('b'), vec!["banana", "blueberry"]),
+                (Some('a'), vec!["apricot"]),
+            ]
+        );
+
+        let empty: Vec<(i32, Vec<i32>)> = engine
+            .from_vec(Vec::<i32>::new())
+            .chunk_by(|&x| x)
+            .collect();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_kmerge_matches_in_every_feature_configuration() {
+        let engine = IteratorEngine::new();
+
+        let chain = engine.from_vec(vec![1, 4, 4, 9]).kmerge(vec![2, 4, 10]);
+        assert!(chain.operations.contains(&"kmerge".to_string()));
+        assert_eq!(chain.collect(), vec![1, 2, 4, 4, 4, 9, 10]);
+
+        let merged: Vec<i32> = engine.from_vec(vec![]).kmerge(vec![3, 5]).collect();
+        assert_eq!(merged, vec![3, 5]);
+    }
+
+    #[test]
+    fn test_kmerge_all_merges_three_sequences() {
+        let engine = IteratorEngine::new();
+
+        let chain = engine
+            .from_vec(vec![1, 4, 7])
+            .kmerge_all(vec![vec![2, 5, 8], vec![0, 3, 6, 9]]);
+        assert!(chain.operations.contains(&"kmerge_all".to_string()));
+        assert_eq!(chain.collect(), vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_kmerge_all_merges_five_sequences() {
+        let engine = IteratorEngine::new();
+        let shards = vec![vec![2, 12, 22], vec![], vec![1, 1, 30], vec![5, 6, 7, 8]];
+
+        let merged: Vec<i32> = engine
+            .from_vec(vec![0, 10, 20])
+            .kmerge_all(shards)
+            .collect();
+
+        assert_eq!(merged, vec![0, 1, 1, 2, 5, 6, 7, 8, 10, 12, 20, 22, 30]);
+    }
+
+    #[cfg(feature = "functional")]
     #[test]
     fn test_cartesian_product() {
         let engine = IteratorEngine::new();
@@ -1321,6 +1998,180 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_collect_memory_counts_string_heap_data() {
+        let names: Vec<String> = (0..100).map(|i| format!("{:064}", i)).collect();
+        let flat = (names.len() * std::mem::size_of::<String>()) as u64;
+
+        let collected = IteratorChain::new(names.clone().into_iter()).collect_measured();
+        let recorded = collected_memory(&collected);
+
+        assert_eq!(collected, names);
+        assert!(recorded > flat);
+        assert!(recorded >= flat + 100 * 64);
+        assert_eq!(collected_memory(&[1u64, 2, 3]), 3 * 8);
+    }
+
+    #[test]
+    fn test_heap_size_of_nested_values() {
+        let words = vec!["ab".to_string(), String::with_capacity(10)];
+        assert_eq!(
+            words.heap_size(),
+            words.capacity() * std::mem::size_of::<String>() + 2 + 10
+        );
+        assert_eq!(Some("abc".to_string()).heap_size(), 3);
+        assert_eq!(None::<String>.heap_size(), 0);
+        assert_eq!((7u8, "xyz".to_string()).heap_size(), 3);
+        assert_eq!("borrowed".heap_size(), 0);
+    }
+
+    #[test]
+    fn test_try_collect_within_limit() {
+        let config = IteratorConfig {
+            memory_limit: 4 * std::mem::size_of::<u64>(),
+            ..IteratorConfig::default()
+        };
+
+        let result = IteratorChain::new(vec![1u64, 2, 3, 4].into_iter())
+            .with_config(config)
+            .try_collect();
+
+        assert_eq!(result, Ok(vec![1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn test_try_collect_crossing_limit() {
+        let config = IteratorConfig {
+            memory_limit: 4 * std::mem::size_of::<u64>(),
+            ..IteratorConfig::default()
+        };
+
+        let result = IteratorChain::new(0u64..1_000_000)
+            .with_config(config)
+            .try_collect();
+
+        assert_eq!(
+            result,
+            Err(IteratorError::MemoryLimitExceeded {
+                used: 5 * std::mem::size_of::<u64>(),
+                limit: 4 * std::mem::size_of::<u64>(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_try_collect_unbounded_when_limit_is_zero() {
+        let config = IteratorConfig {
+            memory_limit: 0,
+            ..IteratorConfig::default()
+        };
+
+        let result = IteratorChain::new(0u64..10_000)
+            .with_config(config)
+            .try_collect()
+            .unwrap();
+
+        assert_eq!(result.len(), 10_000);
+    }
+
+    #[test]
+    fn test_try_join_crossing_limit() {
+        let config = IteratorConfig {
+            memory_limit: 2 * std::mem::size_of::<(i32, (i32, i32))>(),
+            ..IteratorConfig::default()
+        };
+
+        let result = IteratorChain::new(vec![1, 2, 3].into_iter())
+            .with_config(config)
+            .try_join(vec![(1, 10), (2, 20), (3, 30)], |&l| l, |&(r, _)| r);
+
+        assert!(matches!(
+            result,
+            Err(IteratorError::MemoryLimitExceeded { .. })
+        ));
+    }
+
+    #[cfg(feature = "functional")]
+    #[test]
+    fn test_try_chunk_by_crossing_limit() {
+        let config = IteratorConfig {
+            memory_limit: 64,
+            ..IteratorConfig::default()
+        };
+
+        let result = IteratorChain::new(vec![1u64; 100].into_iter())
+            .with_config(config)
+            .try_chunk_by(|&x| x);
+
+        assert!(matches!(
+            result,
+            Err(IteratorError::MemoryLimitExceeded { limit: 64, .. })
+        ));
+    }
+
+    #[test]
+    fn test_reduce_non_empty() {
+        let max =
+            IteratorChain::new(vec![4, 9, 1, 7].into_iter())
+                .reduce(|a, b| if b > a { b } else { a });
+
+        assert_eq!(max, Some(9));
+    }
+
+    #[test]
+    fn test_reduce_empty_is_none() {
+        let sum = IteratorChain::new(Vec::<i32>::new().into_iter()).reduce(|a, b| a + b);
+
+        assert_eq!(sum, None);
+    }
+
+    #[test]
+    fn test_try_fold_success() {
+        let result: Result<i32, String> = IteratorChain::new(vec!["1", "2", "3"].into_iter())
+            .try_fold(0, |acc, s| {
+                s.parse::<i32>().map(|n| acc + n).map_err(|e| e.to_string())
+            });
+
+        assert_eq!(result, Ok(6));
+    }
+
+    #[test]
+    fn test_try_fold_stops_at_first_error() {
+        struct PanicOnOverrun {
+            data: Vec<&'static str>,
+            index: usize,
+            max_calls: usize,
+        }
+
+        impl Iterator for PanicOnOverrun {
+            type Item = &'static str;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.index >= self.max_calls {
+                    panic!("Iterator advanced past the failing element");
+                }
+                let item = self.data.get(self.index).copied();
+                self.index += 1;
+                item
+            }
+        }
+
+        // Fails on the third element; the fourth must never be requested.
+        let source = PanicOnOverrun {
+            data: vec!["1", "2", "oops", "4"],
+            index: 0,
+            max_calls: 3,
+        };
+
+        let result = IteratorChain::new(source).try_fold(0, |acc, s| {
+            s.parse::<i32>()
+                .map(|n| acc + n)
+                .map_err(|_| format!("invalid number: {}", s))
+        });
+
+        assert_eq!(result, Err("invalid number: oops".to_string()));
+    }
+
     #[test]
     fn test_join() {
         let engine = IteratorEngine::new();
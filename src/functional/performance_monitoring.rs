@@ -6,16 +6,21 @@
 //! real-time insights into functional operation performance.
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+use crate::error::ErrorTag;
+
 /// Performance metrics for functional operations
 #[derive(Debug, Clone)]
 pub struct PerformanceMetrics {
-    /// Total number of operations performed
+    /// Total number of operations performed, scaled up by the sampling rate
     pub operation_count: u64,
+    /// Number of operations actually recorded before scaling
+    pub sampled_count: u64,
     /// Average execution time per operation
     pub avg_execution_time: Duration,
     /// Minimum execution time recorded
@@ -62,6 +67,8 @@ pub enum OperationType {
     ConcurrentProcessing,
     /// Response transformation operations
     ResponseTransformation,
+    /// Database queries slower than `SLOW_QUERY_THRESHOLD_MS`
+    DatabaseQuery,
     /// Custom operation type
     Custom(String),
 }
@@ -77,6 +84,7 @@ impl fmt::Display for OperationType {
             OperationType::LazyPipeline => write!(f, "lazy_pipeline"),
             OperationType::ConcurrentProcessing => write!(f, "concurrent_processing"),
             OperationType::ResponseTransformation => write!(f, "response_transformation"),
+            OperationType::DatabaseQuery => write!(f, "database_query"),
             OperationType::Custom(name) => write!(f, "custom_{}", name),
         }
     }
@@ -88,6 +96,8 @@ pub struct PerformanceMeasurement {
     operation_type: OperationType,
     start_time: Instant,
     initial_memory: u64,
+    /// Number of operations this sample stands for (`1 / sampling_rate`)
+    weight: f64,
     monitor: Arc<PerformanceMonitor>,
 }
 
@@ -97,11 +107,12 @@ impl PerformanceMeasurement {
         let duration = self.start_time.elapsed();
         let memory_used = self.get_current_memory_usage() - self.initial_memory;
 
-        self.monitor.record_operation(
+        self.monitor.record_weighted(
             self.operation_type,
             duration,
             memory_used,
             false, // no error
+            self.weight,
         );
     }
 
@@ -110,11 +121,12 @@ impl PerformanceMeasurement {
         let duration = self.start_time.elapsed();
         let memory_used = self.get_current_memory_usage() - self.initial_memory;
 
-        self.monitor.record_operation(
+        self.monitor.record_weighted(
             self.operation_type,
             duration,
             memory_used,
             true, // error occurred
+            self.weight,
         );
     }
 
@@ -126,15 +138,132 @@ impl PerformanceMeasurement {
     }
 }
 
+/// Units of [`OperationCounters`]' weighted counts per operation.
+///
+/// A sample's weight need not be whole (`1 / 0.3`), so weighted counts are
+/// kept in thousandths of an operation and only rounded when reported.
+const COUNT_SCALE: u64 = 1_000;
+
+/// `weight` operations in [`COUNT_SCALE`] units
+fn scaled_count(weight: f64) -> u64 {
+    (weight * COUNT_SCALE as f64).round() as u64
+}
+
+/// Whole operations in `units`, rounded to the nearest
+fn unscaled_count(units: u64) -> u64 {
+    units.saturating_add(COUNT_SCALE / 2) / COUNT_SCALE
+}
+
+/// `total / (units / COUNT_SCALE)`: the mean over the weighted operations
+fn per_operation(total: u64, units: u64) -> u64 {
+    (u128::from(total) * u128::from(COUNT_SCALE))
+        .checked_div(u128::from(units))
+        .map_or(0, |mean| u64::try_from(mean).unwrap_or(u64::MAX))
+}
+
+/// Lock-free accumulators behind the metrics of one operation type.
+///
+/// Recording only performs atomic adds and min/max updates, so threads
+/// recording the same operation type never wait on each other. Durations are
+/// kept in nanoseconds and timestamps as nanoseconds since the monitor started.
+/// Operation, allocation and error counts are in [`COUNT_SCALE`] units.
+#[derive(Debug)]
+struct OperationCounters {
+    operation_count: AtomicU64,
+    sampled_count: AtomicU64,
+    total_nanos: AtomicU64,
+    min_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+    peak_memory_bytes: AtomicU64,
+    allocation_count: AtomicU64,
+    total_allocated: AtomicU64,
+    error_count: AtomicU64,
+    last_updated_nanos: AtomicU64,
+}
+
+impl OperationCounters {
+    fn new() -> Self {
+        Self {
+            operation_count: AtomicU64::new(0),
+            sampled_count: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+            min_nanos: AtomicU64::new(u64::MAX),
+            max_nanos: AtomicU64::new(0),
+            peak_memory_bytes: AtomicU64::new(0),
+            allocation_count: AtomicU64::new(0),
+            total_allocated: AtomicU64::new(0),
+            error_count: AtomicU64::new(0),
+            last_updated_nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Adds one sample standing for `weight` operations
+    fn record(&self, duration: Duration, memory_used: u64, is_error: bool, weight: f64, now: u64) {
+        let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        let units = scaled_count(weight);
+
+        self.operation_count.fetch_add(units, Ordering::Relaxed);
+        self.sampled_count.fetch_add(1, Ordering::Relaxed);
+        self.total_nanos
+            .fetch_add((nanos as f64 * weight).round() as u64, Ordering::Relaxed);
+        self.min_nanos.fetch_min(nanos, Ordering::Relaxed);
+        self.max_nanos.fetch_max(nanos, Ordering::Relaxed);
+
+        self.allocation_count.fetch_add(units, Ordering::Relaxed);
+        self.total_allocated.fetch_add(
+            (memory_used as f64 * weight).round() as u64,
+            Ordering::Relaxed,
+        );
+        self.peak_memory_bytes
+            .fetch_max(memory_used, Ordering::Relaxed);
+
+        if is_error {
+            self.error_count.fetch_add(units, Ordering::Relaxed);
+        }
+        self.last_updated_nanos.fetch_max(now, Ordering::Relaxed);
+    }
+
+    /// Current values; fields may mix in samples recorded while reading
+    fn snapshot(&self, started: Instant) -> PerformanceMetrics {
+        let operation_units = self.operation_count.load(Ordering::Relaxed);
+        let allocation_units = self.allocation_count.load(Ordering::Relaxed);
+        let total_allocated = self.total_allocated.load(Ordering::Relaxed);
+
+        PerformanceMetrics {
+            operation_count: unscaled_count(operation_units),
+            sampled_count: self.sampled_count.load(Ordering::Relaxed),
+            avg_execution_time: Duration::from_nanos(per_operation(
+                self.total_nanos.load(Ordering::Relaxed),
+                operation_units,
+            )),
+            min_execution_time: Duration::from_nanos(self.min_nanos.load(Ordering::Relaxed)),
+            max_execution_time: Duration::from_nanos(self.max_nanos.load(Ordering::Relaxed)),
+            memory_stats: MemoryStats {
+                peak_memory_bytes: self.peak_memory_bytes.load(Ordering::Relaxed),
+                avg_memory_per_operation: per_operation(total_allocated, allocation_units),
+                allocation_count: unscaled_count(allocation_units),
+                total_allocated,
+            },
+            error_count: unscaled_count(self.error_count.load(Ordering::Relaxed)),
+            last_updated: started
+                + Duration::from_nanos(self.last_updated_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
 /// Main performance monitoring system
 #[derive(Debug)]
 pub struct PerformanceMonitor {
-    /// Metrics storage by operation type
-    metrics: RwLock<HashMap<OperationType, PerformanceMetrics>>,
+    /// Counters by operation type; the lock is only written when a new type first appears
+    metrics: RwLock<HashMap<OperationType, Arc<OperationCounters>>>,
+    /// Reference point of the counters' timestamps
+    started: Instant,
     /// Global configuration
     config: PerformanceConfig,
     /// Operation thresholds for alerting
     thresholds: RwLock<HashMap<OperationType, PerformanceThreshold>>,
+    /// Error responses per `ErrorTag`, indexed by discriminant
+    error_tag_counts: [AtomicU64; ErrorTag::ALL.len()],
 }
 
 /// Configuration for performance monitoring
@@ -150,6 +279,26 @@ pub struct PerformanceConfig {
     pub sampling_rate: f64,
 }
 
+impl PerformanceConfig {
+    /// Default configuration with the sampling rate taken from `PERF_SAMPLE_RATE`.
+    ///
+    /// Values outside `0.0..=1.0` are clamped; a missing or unparsable value keeps
+    /// the default of recording every operation.
+    pub fn from_env() -> Self {
+        let sampling_rate = std::env::var("PERF_SAMPLE_RATE")
+            .ok()
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|rate| rate.is_finite())
+            .map(|rate| rate.clamp(0.0, 1.0))
+            .unwrap_or(1.0);
+
+        Self {
+            sampling_rate,
+            ..Self::default()
+        }
+    }
+}
+
 impl Default for PerformanceConfig {
     fn default() -> Self {
         Self {
@@ -207,8 +356,10 @@ impl PerformanceMonitor {
     pub fn new() -> Arc<Self> {
         Arc::new(Self {
             metrics: RwLock::new(HashMap::new()),
+            started: Instant::now(),
             config: PerformanceConfig::default(),
             thresholds: RwLock::new(HashMap::new()),
+            error_tag_counts: Default::default(),
         })
     }
 
@@ -216,11 +367,19 @@ impl PerformanceMonitor {
     pub fn with_config(config: PerformanceConfig) -> Arc<Self> {
         Arc::new(Self {
             metrics: RwLock::new(HashMap::new()),
+            started: Instant::now(),
             config,
             thresholds: RwLock::new(HashMap::new()),
+            error_tag_counts: Default::default(),
         })
     }
 
+    /// Whether this monitor discards everything; always `false`
+    #[inline(always)]
+    pub fn is_noop(&self) -> bool {
+        false
+    }
+
     /// Start measuring a functional operation
     pub fn start_measurement(
         self: &Arc<Self>,
@@ -231,19 +390,37 @@ impl PerformanceMonitor {
         }
 
         // Apply sampling rate
-        if rand::random::<f64>() > self.config.sampling_rate {
-            return None;
-        }
+        let weight = self.sample_weight()?;
 
         Some(PerformanceMeasurement {
             operation_type,
             start_time: Instant::now(),
             initial_memory: self.get_current_memory_usage(),
+            weight,
             monitor: Arc::clone(self),
         })
     }
 
+    /// Decide whether the next operation is sampled.
+    ///
+    /// Returns the number of operations the sample represents, or `None` when
+    /// the operation should be skipped. At a rate of `1.0` every operation is
+    /// recorded with a weight of one.
+    fn sample_weight(&self) -> Option<f64> {
+        let rate = self.config.sampling_rate;
+        if rate >= 1.0 {
+            return Some(1.0);
+        }
+        if rate <= 0.0 || rand::random::<f64>() >= rate {
+            return None;
+        }
+        Some(1.0 / rate)
+    }
+
     /// Record a completed operation
+    ///
+    /// Subject to `PerformanceConfig::sampling_rate`: only a sample of calls is
+    /// recorded, and each recorded call counts for `1 / sampling_rate` operations.
     pub fn record_operation(
         &self,
         operation_type: OperationType,
@@ -251,80 +428,60 @@ impl PerformanceMonitor {
         memory_used: u64,
         is_error: bool,
     ) {
-        let mut metrics = self.metrics.write().unwrap();
-
-        let metric = metrics
-            .entry(operation_type.clone())
-            .or_insert_with(|| PerformanceMetrics {
-                operation_count: 0,
-                avg_execution_time: Duration::from_nanos(0),
-                min_execution_time: Duration::from_secs(u64::MAX),
-                max_execution_time: Duration::from_nanos(0),
-                memory_stats: MemoryStats {
-                    peak_memory_bytes: 0,
-                    avg_memory_per_operation: 0,
-                    allocation_count: 0,
-                    total_allocated: 0,
-                },
-                error_count: 0,
-                last_updated: Instant::now(),
-            });
-
-        // Get previous count before incrementing
-        let prev_count = metric.operation_count;
-
-        // Update operation count
-        metric.operation_count += 1;
-
-        // Update timing statistics
-        if prev_count == 0 {
-            // First sample - set avg, min, and max to duration
-            metric.avg_execution_time = duration;
-            metric.min_execution_time = duration;
-            metric.max_execution_time = duration;
-        } else {
-            // Rolling average: new_avg = (old_avg * prev_count + duration) / new_count
-            metric.avg_execution_time = (metric.avg_execution_time * prev_count as u32 + duration)
-                / metric.operation_count as u32;
-
-            if duration < metric.min_execution_time {
-                metric.min_execution_time = duration;
-            }
-
-            if duration > metric.max_execution_time {
-                metric.max_execution_time = duration;
-            }
+        if let Some(weight) = self.sample_weight() {
+            self.record_weighted(operation_type, duration, memory_used, is_error, weight);
         }
+    }
 
-        // Update memory statistics
-        metric.memory_stats.allocation_count += 1;
-        metric.memory_stats.total_allocated += memory_used;
-        metric.memory_stats.avg_memory_per_operation =
-            metric.memory_stats.total_allocated / metric.memory_stats.allocation_count;
+    /// Record a sampled operation standing for `weight` operations
+    fn record_weighted(
+        &self,
+        operation_type: OperationType,
+        duration: Duration,
+        memory_used: u64,
+        is_error: bool,
+        weight: f64,
+    ) {
+        let now = u64::try_from(self.started.elapsed().as_nanos()).unwrap_or(u64::MAX);
+        let counters = self.counters_for(&operation_type);
+        counters.record(duration, memory_used, is_error, weight, now);
 
-        if memory_used > metric.memory_stats.peak_memory_bytes {
-            metric.memory_stats.peak_memory_bytes = memory_used;
-        }
+        // Check thresholds and generate alerts if necessary
+        self.check_thresholds(&operation_type, &counters);
+    }
 
-        // Update error count
-        if is_error {
-            metric.error_count += 1;
+    /// Counters of `operation_type`, created on first use
+    fn counters_for(&self, operation_type: &OperationType) -> Arc<OperationCounters> {
+        if let Some(counters) = self.metrics.read().unwrap().get(operation_type) {
+            return Arc::clone(counters);
         }
-
-        metric.last_updated = Instant::now();
-
-        // Check thresholds and generate alerts if necessary
-        self.check_thresholds(&operation_type, metric);
+        let mut metrics = self.metrics.write().unwrap();
+        Arc::clone(
+            metrics
+                .entry(operation_type.clone())
+                .or_insert_with(|| Arc::new(OperationCounters::new())),
+        )
     }
 
     /// Get performance metrics for a specific operation type
     pub fn get_metrics(&self, operation_type: &OperationType) -> Option<PerformanceMetrics> {
-        self.metrics.read().unwrap().get(operation_type).cloned()
+        self.metrics
+            .read()
+            .unwrap()
+            .get(operation_type)
+            .map(|counters| counters.snapshot(self.started))
     }
 
     /// Get all performance metrics
     pub fn get_all_metrics(&self) -> HashMap<OperationType, PerformanceMetrics> {
-        self.metrics.read().unwrap().clone()
+        self.metrics
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(operation_type, counters)| {
+                (operation_type.clone(), counters.snapshot(self.started))
+            })
+            .collect()
     }
 
     /// Set performance threshold for an operation type
@@ -337,7 +494,7 @@ impl PerformanceMonitor {
 
     /// Get performance summary for health checks
     pub fn get_health_summary(&self) -> HealthSummary {
-        let metrics = self.metrics.read().unwrap();
+        let metrics = self.get_all_metrics();
         let mut total_operations = 0u64;
         let mut total_errors = 0u64;
         let mut slowest_operation = Duration::from_nanos(0);
@@ -373,10 +530,11 @@ impl PerformanceMonitor {
     }
 
     /// Check performance thresholds and generate alerts
-    fn check_thresholds(&self, operation_type: &OperationType, metric: &PerformanceMetrics) {
+    fn check_thresholds(&self, operation_type: &OperationType, counters: &OperationCounters) {
         let thresholds = self.thresholds.read().unwrap();
 
         if let Some(threshold) = thresholds.get(operation_type) {
+            let metric = counters.snapshot(self.started);
             let error_rate = metric.error_count as f64 / metric.operation_count as f64;
 
             // Check execution time threshold
@@ -418,6 +576,28 @@ impl PerformanceMonitor {
         0
     }
 
+    /// Count one error response carrying `tag`.
+    ///
+    /// Tag counters are monotonic: `reset_metrics` leaves them untouched.
+    pub fn record_error_tag(&self, tag: ErrorTag) {
+        if self.config.enabled {
+            self.error_tag_counts[tag as usize].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of error responses recorded for `tag`
+    pub fn error_count_for_tag(&self, tag: ErrorTag) -> u64 {
+        self.error_tag_counts[tag as usize].load(Ordering::Relaxed)
+    }
+
+    /// Error response counts of every tag, keyed by tag name
+    pub fn errors_by_tag(&self) -> BTreeMap<String, u64> {
+        ErrorTag::ALL
+            .into_iter()
+            .map(|tag| (tag.to_string(), self.error_count_for_tag(tag)))
+            .collect()
+    }
+
     /// Reset all metrics (useful for testing)
     pub fn reset_metrics(&self) {
         self.metrics.write().unwrap().clear();
@@ -441,19 +621,338 @@ pub struct HealthSummary {
     pub monitoring_enabled: bool,
 }
 
+/// Monitor that records nothing, used in place of [`PerformanceMonitor`] when the
+/// `performance_monitoring` feature is off.
+///
+/// It mirrors the monitor's API so call sites need no `cfg` branches; every method
+/// is an empty inline body the optimizer removes.
+#[derive(Debug, Default)]
+pub struct NoopMonitor;
+
+impl NoopMonitor {
+    /// Create a new no-op monitor
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self)
+    }
+
+    /// Create a no-op monitor, ignoring `config`
+    pub fn with_config(_config: PerformanceConfig) -> Arc<Self> {
+        Self::new()
+    }
+
+    /// Whether this monitor discards everything; always `true`
+    #[inline(always)]
+    pub fn is_noop(&self) -> bool {
+        true
+    }
+
+    /// Never starts a measurement
+    #[inline(always)]
+    pub fn start_measurement(
+        self: &Arc<Self>,
+        _operation_type: OperationType,
+    ) -> Option<PerformanceMeasurement> {
+        None
+    }
+
+    /// Discards the operation
+    #[inline(always)]
+    pub fn record_operation(
+        &self,
+        _operation_type: OperationType,
+        _duration: Duration,
+        _memory_used: u64,
+        _is_error: bool,
+    ) {
+    }
+
+    /// Always `None`
+    #[inline(always)]
+    pub fn get_metrics(&self, _operation_type: &OperationType) -> Option<PerformanceMetrics> {
+        None
+    }
+
+    /// Always empty
+    #[inline(always)]
+    pub fn get_all_metrics(&self) -> HashMap<OperationType, PerformanceMetrics> {
+        HashMap::new()
+    }
+
+    /// Discards the threshold
+    #[inline(always)]
+    pub fn set_threshold(&self, _operation_type: OperationType, _threshold: PerformanceThreshold) {}
+
+    /// Empty summary with `monitoring_enabled` unset
+    pub fn get_health_summary(&self) -> HealthSummary {
+        HealthSummary {
+            total_operations: 0,
+            error_rate: 0.0,
+            slowest_operation: Duration::from_nanos(0),
+            highest_memory_usage: 0,
+            operation_types_tracked: 0,
+            monitoring_enabled: false,
+        }
+    }
+
+    /// Discards the error response
+    #[inline(always)]
+    pub fn record_error_tag(&self, _tag: ErrorTag) {}
+
+    /// Always `0`
+    #[inline(always)]
+    pub fn error_count_for_tag(&self, _tag: ErrorTag) -> u64 {
+        0
+    }
+
+    /// Every tag with a count of `0`
+    pub fn errors_by_tag(&self) -> BTreeMap<String, u64> {
+        ErrorTag::ALL
+            .into_iter()
+            .map(|tag| (tag.to_string(), 0))
+            .collect()
+    }
+
+    /// Nothing to reset
+    #[inline(always)]
+    pub fn reset_metrics(&self) {}
+}
+
+/// Monitor type behind [`get_performance_monitor`]
+#[cfg(feature = "performance_monitoring")]
+pub type ActiveMonitor = PerformanceMonitor;
+
+/// Monitor type behind [`get_performance_monitor`]
+#[cfg(not(feature = "performance_monitoring"))]
+pub type ActiveMonitor = NoopMonitor;
+
 /// Global performance monitor instance
-static GLOBAL_MONITOR: std::sync::OnceLock<Arc<PerformanceMonitor>> = std::sync::OnceLock::new();
+static GLOBAL_MONITOR: std::sync::OnceLock<Arc<ActiveMonitor>> = std::sync::OnceLock::new();
 
 /// Initialize the global performance monitor
 pub fn init_performance_monitor(config: PerformanceConfig) {
     GLOBAL_MONITOR
-        .set(PerformanceMonitor::with_config(config))
+        .set(ActiveMonitor::with_config(config))
         .unwrap_or_else(|_| panic!("Performance monitor already initialized"));
 }
 
 /// Get the global performance monitor instance
-pub fn get_performance_monitor() -> &'static Arc<PerformanceMonitor> {
-    GLOBAL_MONITOR.get_or_init(|| PerformanceMonitor::new())
+///
+/// Without the `performance_monitoring` feature this is a [`NoopMonitor`], so
+/// callers can record unconditionally.
+pub fn get_performance_monitor() -> &'static Arc<ActiveMonitor> {
+    GLOBAL_MONITOR.get_or_init(|| ActiveMonitor::with_config(PerformanceConfig::from_env()))
+}
+
+/// Upper bounds, in seconds, of the HTTP request duration histogram buckets
+pub const HTTP_DURATION_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Request counts and durations of every HTTP request the service answered.
+///
+/// Requests are counted by method and status; durations go into one
+/// histogram over [`HTTP_DURATION_BUCKETS`], each bucket counting only its own
+/// range so recording is a single atomic add.
+#[cfg(feature = "performance_monitoring")]
+#[derive(Debug, Default)]
+pub struct HttpMetrics {
+    requests: std::sync::Mutex<BTreeMap<(String, u16), u64>>,
+    buckets: [AtomicU64; HTTP_DURATION_BUCKETS.len() + 1],
+    duration_nanos: AtomicU64,
+}
+
+#[cfg(feature = "performance_monitoring")]
+impl HttpMetrics {
+    pub fn record(&self, method: &str, status: u16, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        let bucket = HTTP_DURATION_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(HTTP_DURATION_BUCKETS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.duration_nanos.fetch_add(
+            u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX),
+            Ordering::Relaxed,
+        );
+
+        let mut requests = self.requests.lock().unwrap_or_else(|e| e.into_inner());
+        *requests.entry((method.to_string(), status)).or_default() += 1;
+    }
+
+    /// Request counts keyed by method and status
+    pub fn requests(&self) -> BTreeMap<(String, u16), u64> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// Process-wide HTTP metrics, recorded by the request log middleware
+#[cfg(feature = "performance_monitoring")]
+pub fn http_metrics() -> &'static HttpMetrics {
+    static METRICS: std::sync::OnceLock<HttpMetrics> = std::sync::OnceLock::new();
+    METRICS.get_or_init(HttpMetrics::default)
+}
+
+/// Metrics rendered in the Prometheus text exposition format (version 0.0.4).
+///
+/// Every metric family is written as its `# HELP` and `# TYPE` lines followed
+/// by its samples, as `GET /metrics` serves them.
+#[cfg(feature = "performance_monitoring")]
+#[derive(Debug, Default)]
+pub struct PrometheusRegistry {
+    output: String,
+}
+
+#[cfg(feature = "performance_monitoring")]
+impl PrometheusRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a family of `kind` (`counter`, `gauge`, ...) with one sample per labelled value.
+    pub fn family<'a>(
+        &mut self,
+        name: &str,
+        kind: &str,
+        help: &str,
+        samples: impl IntoIterator<Item = (Vec<(&'a str, String)>, f64)>,
+    ) -> &mut Self {
+        self.header(name, kind, help);
+        for (labels, value) in samples {
+            self.sample(name, &labels, value);
+        }
+        self
+    }
+
+    /// Adds the counters and timings of every operation type, labelled by operation.
+    pub fn operations(
+        &mut self,
+        metrics: &HashMap<OperationType, PerformanceMetrics>,
+    ) -> &mut Self {
+        let mut operations: Vec<(String, &PerformanceMetrics)> = metrics
+            .iter()
+            .map(|(operation, metrics)| (operation.to_string(), metrics))
+            .collect();
+        operations.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let family = |select: fn(&PerformanceMetrics) -> f64| {
+            operations
+                .iter()
+                .map(move |(operation, metrics)| {
+                    (vec![("operation", operation.clone())], select(metrics))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        self.family(
+            "functional_operations_total",
+            "counter",
+            "Functional operations performed",
+            family(|m| m.operation_count as f64),
+        )
+        .family(
+            "functional_operation_errors_total",
+            "counter",
+            "Functional operations that failed",
+            family(|m| m.error_count as f64),
+        )
+        .family(
+            "functional_operation_avg_duration_seconds",
+            "gauge",
+            "Average duration of a functional operation",
+            family(|m| m.avg_execution_time.as_secs_f64()),
+        )
+        .family(
+            "functional_operation_max_duration_seconds",
+            "gauge",
+            "Longest duration of a functional operation",
+            family(|m| m.max_execution_time.as_secs_f64()),
+        )
+        .family(
+            "functional_operation_peak_memory_bytes",
+            "gauge",
+            "Peak memory used by one functional operation",
+            family(|m| m.memory_stats.peak_memory_bytes as f64),
+        )
+    }
+
+    /// Adds the error response counts, labelled by error tag.
+    pub fn error_tags(&mut self, errors_by_tag: &BTreeMap<String, u64>) -> &mut Self {
+        self.family(
+            "service_error_responses_total",
+            "counter",
+            "Error responses by error tag",
+            errors_by_tag
+                .iter()
+                .map(|(tag, count)| (vec![("tag", tag.clone())], *count as f64)),
+        )
+    }
+
+    /// Adds the HTTP request counter and duration histogram.
+    pub fn http(&mut self, http: &HttpMetrics) -> &mut Self {
+        self.family(
+            "http_requests_total",
+            "counter",
+            "HTTP requests answered",
+            http.requests()
+                .into_iter()
+                .map(|((method, status), count)| {
+                    (
+                        vec![("method", method), ("status", status.to_string())],
+                        count as f64,
+                    )
+                }),
+        );
+
+        let name = "http_request_duration_seconds";
+        self.header(name, "histogram", "HTTP request duration");
+        let bucket_name = format!("{}_bucket", name);
+        let mut cumulative = 0;
+        for (index, counter) in http.buckets.iter().enumerate() {
+            cumulative += counter.load(Ordering::Relaxed);
+            let bound = HTTP_DURATION_BUCKETS
+                .get(index)
+                .map_or_else(|| "+Inf".to_string(), |bound| bound.to_string());
+            self.sample(&bucket_name, &[("le", bound)], cumulative as f64);
+        }
+        let sum = Duration::from_nanos(http.duration_nanos.load(Ordering::Relaxed));
+        self.sample(&format!("{}_sum", name), &[], sum.as_secs_f64());
+        self.sample(&format!("{}_count", name), &[], cumulative as f64);
+        self
+    }
+
+    /// The exposition text.
+    pub fn render(&self) -> &str {
+        &self.output
+    }
+
+    fn header(&mut self, name: &str, kind: &str, help: &str) {
+        self.output.push_str(&format!("# HELP {} {}\n", name, help));
+        self.output.push_str(&format!("# TYPE {} {}\n", name, kind));
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, String)], value: f64) {
+        self.output.push_str(name);
+        if !labels.is_empty() {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape_label_value(value)))
+                .collect();
+            self.output.push_str(&format!("{{{}}}", labels.join(",")));
+        }
+        self.output.push_str(&format!(" {}\n", value));
+    }
+}
+
+/// `value` with the backslashes, quotes and newlines of a label value escaped.
+#[cfg(feature = "performance_monitoring")]
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
 }
 
 /// Convenience macro for measuring functional operations
@@ -654,6 +1153,42 @@ mod tests {
         assert_eq!(metrics.avg_execution_time, expected_avg);
     }
 
+    #[test]
+    fn test_concurrent_recording_keeps_every_operation() {
+        const THREADS: u64 = 8;
+        const RECORDS_PER_THREAD: u64 = 1_000;
+
+        let monitor = PerformanceMonitor::new();
+        thread::scope(|scope| {
+            for t in 0..THREADS {
+                let monitor = &monitor;
+                scope.spawn(move || {
+                    for i in 0..RECORDS_PER_THREAD {
+                        monitor.record_operation(
+                            OperationType::PureFunctionCall,
+                            Duration::from_micros(1 + t),
+                            64,
+                            i % 10 == 0,
+                        );
+                    }
+                });
+            }
+        });
+
+        let metrics = monitor
+            .get_metrics(&OperationType::PureFunctionCall)
+            .unwrap();
+        assert_eq!(metrics.operation_count, THREADS * RECORDS_PER_THREAD);
+        assert_eq!(metrics.sampled_count, THREADS * RECORDS_PER_THREAD);
+        assert_eq!(metrics.error_count, THREADS * RECORDS_PER_THREAD / 10);
+        assert_eq!(
+            metrics.memory_stats.total_allocated,
+            64 * THREADS * RECORDS_PER_THREAD
+        );
+        assert_eq!(metrics.min_execution_time, Duration::from_micros(1));
+        assert_eq!(metrics.max_execution_time, Duration::from_micros(THREADS));
+    }
+
     #[test]
     fn test_sampling_rate() {
         let config = PerformanceConfig {
@@ -669,6 +1204,69 @@ mod tests {
         assert!(measurement.is_none());
     }
 
+    #[test]
+    fn test_low_sampling_rate_records_fewer_operations_with_scaled_counts() {
+        let config = PerformanceConfig {
+            sampling_rate: 0.1,
+            ..Default::default()
+        };
+        let monitor = PerformanceMonitor::with_config(config);
+
+        for _ in 0..10_000 {
+            monitor.record_operation(
+                OperationType::IteratorChain,
+                Duration::from_micros(5),
+                64,
+                true,
+            );
+        }
+
+        let metrics = monitor.get_metrics(&OperationType::IteratorChain).unwrap();
+        assert!(metrics.sampled_count > 0);
+        assert!(metrics.sampled_count < 2_000);
+        assert_eq!(metrics.operation_count, metrics.sampled_count * 10);
+        assert_eq!(metrics.error_count, metrics.operation_count);
+        assert_eq!(metrics.avg_execution_time, Duration::from_micros(5));
+        assert!((5_000..15_000).contains(&metrics.operation_count));
+    }
+
+    #[test]
+    fn test_fractional_sample_weight_is_not_rounded() {
+        let config = PerformanceConfig {
+            sampling_rate: 0.3,
+            ..Default::default()
+        };
+        let monitor = PerformanceMonitor::with_config(config);
+
+        for _ in 0..10_000 {
+            monitor.record_operation(
+                OperationType::IteratorChain,
+                Duration::from_micros(3),
+                0,
+                false,
+            );
+        }
+
+        // Each sample stands for 3.33 operations, not 3
+        let metrics = monitor.get_metrics(&OperationType::IteratorChain).unwrap();
+        let expected = (metrics.sampled_count as f64 / 0.3).round() as u64;
+        assert!(metrics.operation_count.abs_diff(expected) <= 1);
+        assert_eq!(metrics.avg_execution_time, Duration::from_micros(3));
+    }
+
+    #[test]
+    fn test_full_sampling_rate_records_every_operation() {
+        let monitor = PerformanceMonitor::new();
+
+        for _ in 0..100 {
+            monitor.record_operation(OperationType::IteratorChain, Duration::ZERO, 0, false);
+        }
+
+        let metrics = monitor.get_metrics(&OperationType::IteratorChain).unwrap();
+        assert_eq!(metrics.sampled_count, 100);
+        assert_eq!(metrics.operation_count, 100);
+    }
+
     #[test]
     fn test_disabled_monitoring() {
         let config = PerformanceConfig {
@@ -702,6 +1300,175 @@ mod tests {
         assert_eq!(monitor.get_all_metrics().len(), 0);
     }
 
+    #[test]
+    fn test_error_tag_counts() {
+        let monitor = PerformanceMonitor::new();
+        monitor.record_error_tag(ErrorTag::Tenant);
+        monitor.record_error_tag(ErrorTag::Tenant);
+        monitor.record_error_tag(ErrorTag::Db);
+        monitor.reset_metrics();
+
+        assert_eq!(monitor.error_count_for_tag(ErrorTag::Tenant), 2);
+        assert_eq!(monitor.error_count_for_tag(ErrorTag::Db), 1);
+        let by_tag = monitor.errors_by_tag();
+        assert_eq!(by_tag.len(), ErrorTag::ALL.len());
+        assert_eq!(by_tag["validation"], 0);
+        assert_eq!(by_tag["tenant"], 2);
+    }
+
+    #[test]
+    fn test_noop_monitor_records_nothing() {
+        let monitor = NoopMonitor::new();
+        assert!(monitor.is_noop());
+        assert!(monitor
+            .start_measurement(OperationType::IteratorChain)
+            .is_none());
+
+        monitor.record_operation(
+            OperationType::IteratorChain,
+            Duration::from_millis(100),
+            1024,
+            true,
+        );
+        monitor.record_error_tag(ErrorTag::Db);
+
+        assert!(monitor.get_metrics(&OperationType::IteratorChain).is_none());
+        assert!(monitor.get_all_metrics().is_empty());
+        assert_eq!(monitor.error_count_for_tag(ErrorTag::Db), 0);
+        assert_eq!(monitor.errors_by_tag().len(), ErrorTag::ALL.len());
+
+        let summary = monitor.get_health_summary();
+        assert_eq!(summary.total_operations, 0);
+        assert!(!summary.monitoring_enabled);
+    }
+
+    #[test]
+    fn test_global_monitor_matches_feature() {
+        let monitor = get_performance_monitor();
+        assert_eq!(monitor.is_noop(), !cfg!(feature = "performance_monitoring"));
+
+        let operation = OperationType::Custom("global_monitor_feature".to_string());
+        monitor.record_operation(operation.clone(), Duration::from_millis(1), 0, false);
+
+        // Sampling and concurrent resets make the real monitor's count unreliable here
+        if monitor.is_noop() {
+            assert!(monitor.get_metrics(&operation).is_none());
+        }
+        assert!(!PerformanceMonitor::new().is_noop());
+    }
+
+    /// Checks `text` against the Prometheus text exposition format: every
+    /// sample is well-formed and belongs to a family typed before it, and
+    /// histogram buckets are cumulative up to `+Inf`, which equals `_count`.
+    #[cfg(feature = "performance_monitoring")]
+    fn assert_valid_exposition(text: &str) {
+        let sample = regex::Regex::new(
+            r#"^([a-zA-Z_:][a-zA-Z0-9_:]*)(?:\{((?:[a-zA-Z_][a-zA-Z0-9_]*="(?:[^"\\\n]|\\.)*",?)*)\})? (\S+)$"#,
+        )
+        .unwrap();
+        let mut types: HashMap<String, String> = HashMap::new();
+        let mut last_bucket: HashMap<String, f64> = HashMap::new();
+
+        assert!(text.ends_with('\n'), "exposition must end with a newline");
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let mut parts = comment.splitn(3, ' ');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some("HELP"), Some(_), Some(_)) => {}
+                    (Some("TYPE"), Some(name), Some(kind)) => {
+                        assert!(
+                            ["counter", "gauge", "histogram", "summary", "untyped"].contains(&kind),
+                            "unknown type in {line}"
+                        );
+                        assert!(
+                            types.insert(name.to_string(), kind.to_string()).is_none(),
+                            "{name} typed twice"
+                        );
+                    }
+                    _ => panic!("malformed comment {line}"),
+                }
+                continue;
+            }
+
+            let captures = sample
+                .captures(line)
+                .unwrap_or_else(|| panic!("malformed sample {line}"));
+            let name = &captures[1];
+            let value: f64 = match &captures[3] {
+                "+Inf" => f64::INFINITY,
+                "-Inf" => f64::NEG_INFINITY,
+                value => value
+                    .parse()
+                    .unwrap_or_else(|_| panic!("bad value in {line}")),
+            };
+
+            let histogram = ["_bucket", "_sum", "_count"]
+                .iter()
+                .find_map(|suffix| name.strip_suffix(suffix))
+                .filter(|family| types.get(*family).map(String::as_str) == Some("histogram"));
+            match histogram {
+                Some(family) if name.ends_with("_bucket") => {
+                    let previous = last_bucket.insert(family.to_string(), value).unwrap_or(0.0);
+                    assert!(value >= previous, "buckets of {family} not cumulative");
+                }
+                Some(family) if name.ends_with("_count") => {
+                    assert_eq!(
+                        last_bucket.get(family),
+                        Some(&value),
+                        "+Inf bucket != count"
+                    );
+                }
+                Some(_) => {}
+                None => assert!(types.contains_key(name), "{name} sampled before its TYPE"),
+            }
+        }
+    }
+
+    #[cfg(feature = "performance_monitoring")]
+    #[test]
+    fn test_prometheus_exposition_is_valid() {
+        let monitor = PerformanceMonitor::new();
+        monitor.record_operation(
+            OperationType::IteratorChain,
+            Duration::from_millis(3),
+            64,
+            false,
+        );
+        monitor.record_operation(
+            OperationType::Custom("quoted \"name\"\\".to_string()),
+            Duration::from_millis(1),
+            0,
+            true,
+        );
+        let http = HttpMetrics::default();
+        http.record("GET", 200, Duration::from_millis(2));
+        http.record("GET", 200, Duration::from_millis(40));
+        http.record("POST", 422, Duration::from_secs(30));
+
+        let mut registry = PrometheusRegistry::new();
+        registry
+            .operations(&monitor.get_all_metrics())
+            .error_tags(&monitor.errors_by_tag())
+            .http(&http)
+            .family(
+                "db_pool_connections",
+                "gauge",
+                "Open connections of a database pool",
+                [(vec![("pool", "main".to_string())], 3.0)],
+            );
+        let text = registry.render();
+
+        assert_valid_exposition(text);
+        assert!(text.contains("functional_operations_total{operation=\"iterator_chain\"} 1\n"));
+        assert!(text.contains(r#"operation="custom_quoted \"name\"\\""#));
+        assert!(text.contains("http_requests_total{method=\"GET\",status=\"200\"} 2\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{le=\"10\"} 2\n"));
+        assert!(text.contains("http_request_duration_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("http_request_duration_seconds_count 3\n"));
+        assert!(text.contains("db_pool_connections{pool=\"main\"} 3\n"));
+    }
+
     #[test]
     fn test_operation_type_display() {
         assert_eq!(OperationType::IteratorChain.to_string(), "iterator_chain");
@@ -10,6 +10,8 @@ use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 
+use serde::Serialize;
+
 use super::function_traits::{FunctionCategory, FunctionContainer, FunctionWrapper, PureFunction};
 
 /// Information about a registered function (without the executable function)
@@ -21,6 +23,18 @@ pub struct FunctionInfo {
     pub output_type_id: std::any::TypeId,
 }
 
+/// Usage statistics of a registered function, as reported by the admin API.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionStats {
+    /// Signature the function was registered under
+    pub name: &'static str,
+    /// Input and output types, e.g. `i32 -> i32`
+    pub signature: String,
+    pub category: FunctionCategory,
+    /// Number of times the function was executed through the registry
+    pub call_count: u64,
+}
+
 /// Performance metrics for registry operations.
 #[derive(Debug, Clone)]
 pub struct RegistryMetrics {
@@ -260,6 +274,44 @@ impl PureFunctionRegistry {
             .unwrap_or_default())
     }
 
+    /// Usage statistics of every registered function, optionally restricted to one category.
+    ///
+    /// Functions are ordered by call count, busiest first, then by name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let registry = create_standard_registry().unwrap();
+    /// registry.execute::<i32, i32>(FunctionCategory::Mathematical, "double", 2).unwrap();
+    /// let stats = registry.function_stats(None).unwrap();
+    /// assert_eq!(stats[0].name, "double");
+    /// assert_eq!(stats[0].call_count, 1);
+    /// ```
+    pub fn function_stats(
+        &self,
+        category: Option<FunctionCategory>,
+    ) -> Result<Vec<FunctionStats>, RegistryError> {
+        let functions = self
+            .functions
+            .read()
+            .map_err(|_| RegistryError::LockPoisoned)?;
+
+        let mut stats: Vec<FunctionStats> = functions
+            .iter()
+            .filter(|(function_category, _)| category.map_or(true, |c| c == **function_category))
+            .flat_map(|(_, category_map)| category_map.values())
+            .map(|container| FunctionStats {
+                name: container.signature(),
+                signature: container.type_signature(),
+                category: container.category(),
+                call_count: container.call_count(),
+            })
+            .collect();
+        stats.sort_by(|a, b| b.call_count.cmp(&a.call_count).then(a.name.cmp(b.name)));
+
+        Ok(stats)
+    }
+
     /// Attempts to register a new function produced by composing two existing functions in the registry.
     ///
     /// Currently composition is not implemented and the function always returns `RegistryError::IncompatibleComposition`
@@ -15,6 +15,7 @@
 #![allow(dead_code)]
 
 use crate::functional::function_traits::{FunctionCategory, PureFunction};
+use crate::functional::query_composition::{ParameterSanitizer, SanitizationError};
 use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::query_builder::*;
@@ -78,6 +79,10 @@ pub enum Operator {
     IsNull,
     /// IS NOT NULL
     IsNotNull,
+    /// IN (list of values)
+    In,
+    /// BETWEEN low AND high (inclusive)
+    Between,
 }
 
 /// Type-safe predicate representation.
@@ -92,6 +97,8 @@ pub struct Predicate<T> {
     pub operator: Operator,
     /// Value to compare against (None for NULL checks)
     pub value: Option<T>,
+    /// Values of an IN list, or the low and high bounds of BETWEEN
+    pub values: Vec<T>,
     /// Field name for display (for error messages)
     pub field_name: String,
 }
@@ -121,11 +128,137 @@ where
             column,
             operator,
             value,
+            values: Vec::new(),
+            field_name,
+        }
+    }
+
+    /// Constructs a Predicate whose operator takes several values (`Operator::In` or `Operator::Between`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let col = Column::<i32, i32>::new("contacts".to_string(), "id".to_string());
+    /// let p = Predicate::with_values(col, Operator::In, vec![1, 2], "id".to_string());
+    /// assert_eq!(p.values, vec![1, 2]);
+    /// ```
+    pub fn with_values(
+        column: Column<T, T>,
+        operator: Operator,
+        values: Vec<T>,
+        field_name: String,
+    ) -> Self {
+        Self {
+            column,
+            operator,
+            value: None,
+            values,
             field_name,
         }
     }
 }
 
+impl<T> Predicate<T>
+where
+    T: Clone + ToString + Send + Sync + 'static,
+{
+    /// Compiles the predicate into a parameterized SQL fragment.
+    ///
+    /// Every value is bound through `sanitizer` and referenced by a `$n` placeholder,
+    /// numbered after the parameters already bound. An empty IN list compiles to the
+    /// always-false `1 = 0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut sanitizer = ParameterSanitizer::new();
+    /// let col = Column::<i32, i32>::new("contacts".to_string(), "id".to_string());
+    /// let sql = in_list(col, vec![1, 2], "id".to_string()).to_sql(&mut sanitizer).unwrap();
+    /// assert_eq!(sql, "contacts.id IN ($1, $2)");
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The SQL fragment, or a `SanitizationError` when a value is rejected or the
+    /// predicate lacks the values its operator needs.
+    pub fn to_sql(&self, sanitizer: &mut ParameterSanitizer) -> Result<String, SanitizationError> {
+        let column = format!("{}.{}", self.column.table, self.column.column);
+        let mut bind = |value: &T| {
+            let placeholder = format!("${}", sanitizer.bindings().len() + 1);
+            sanitizer
+                .bind_parameter(placeholder.clone(), value.to_string(), sql_type::<T>())
+                .map(|_| placeholder)
+        };
+        let single = || {
+            self.value.as_ref().ok_or_else(|| {
+                SanitizationError::Other(format!(
+                    "{:?} on '{}' requires a value",
+                    self.operator, self.field_name
+                ))
+            })
+        };
+
+        let fragment = match self.operator {
+            Operator::Equals => format!("{} = {}", column, bind(single()?)?),
+            Operator::NotEquals => format!("{} <> {}", column, bind(single()?)?),
+            Operator::GreaterThan => format!("{} > {}", column, bind(single()?)?),
+            Operator::LessThan => format!("{} < {}", column, bind(single()?)?),
+            Operator::GreaterThanEqual => format!("{} >= {}", column, bind(single()?)?),
+            Operator::LessThanEqual => format!("{} <= {}", column, bind(single()?)?),
+            Operator::Contains => format!("{} LIKE '%' || {} || '%'", column, bind(single()?)?),
+            Operator::NotContains => {
+                format!("{} NOT LIKE '%' || {} || '%'", column, bind(single()?)?)
+            }
+            Operator::IsNull => format!("{} IS NULL", column),
+            Operator::IsNotNull => format!("{} IS NOT NULL", column),
+            Operator::In if self.values.is_empty() => "1 = 0".to_string(),
+            Operator::In => {
+                let placeholders = self
+                    .values
+                    .iter()
+                    .map(&mut bind)
+                    .collect::<Result<Vec<_>, _>>()?;
+                format!("{} IN ({})", column, placeholders.join(", "))
+            }
+            Operator::Between => match self.values.as_slice() {
+                [low, high] => format!("{} BETWEEN {} AND {}", column, bind(low)?, bind(high)?),
+                _ => {
+                    return Err(SanitizationError::Other(format!(
+                        "BETWEEN on '{}' requires a low and a high bound",
+                        self.field_name
+                    )))
+                }
+            },
+        };
+        Ok(fragment)
+    }
+}
+
+/// SQL type recorded with a bound parameter of Rust type `T`.
+fn sql_type<T: 'static>() -> String {
+    use std::any::TypeId;
+
+    let id = TypeId::of::<T>();
+    let sql_type = if id == TypeId::of::<i16>() {
+        "SMALLINT"
+    } else if id == TypeId::of::<i32>() {
+        "INTEGER"
+    } else if id == TypeId::of::<i64>() {
+        "BIGINT"
+    } else if id == TypeId::of::<f64>() {
+        "DOUBLE PRECISION"
+    } else if id == TypeId::of::<bool>() {
+        "BOOLEAN"
+    } else if id == TypeId::of::<chrono::NaiveDate>() {
+        "DATE"
+    } else if id == TypeId::of::<chrono::NaiveDateTime>() {
+        "TIMESTAMP"
+    } else {
+        "VARCHAR"
+    };
+    sql_type.to_string()
+}
+
 /// Type representing a composable query filter.
 ///
 /// QueryFilter allows building complex queries through functional composition
@@ -758,6 +891,44 @@ where
     Predicate::new(column, operator, None, field_name)
 }
 
+/// Create an IN predicate matching rows whose column equals any of `values`.
+///
+/// An empty `values` list matches no rows.
+///
+/// # Examples
+///
+/// ```
+/// use crate::functional::query_builder::{Column, Operator, in_list};
+///
+/// let col = Column::<i32, i32>::new("contacts".to_string(), "id".to_string());
+/// let pred = in_list(col, vec![1, 2, 3], "id".to_string());
+/// assert!(matches!(pred.operator, Operator::In));
+/// ```
+pub fn in_list<T>(column: Column<T, T>, values: Vec<T>, field_name: String) -> Predicate<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Predicate::with_values(column, Operator::In, values, field_name)
+}
+
+/// Create a BETWEEN predicate matching rows whose column lies within `low..=high`.
+///
+/// # Examples
+///
+/// ```
+/// use crate::functional::query_builder::{Column, Operator, between};
+///
+/// let col = Column::<i32, i32>::new("contacts".to_string(), "age".to_string());
+/// let pred = between(col, 18, 65, "age".to_string());
+/// assert_eq!(pred.values, vec![18, 65]);
+/// ```
+pub fn between<T>(column: Column<T, T>, low: T, high: T, field_name: String) -> Predicate<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    Predicate::with_values(column, Operator::Between, vec![low, high], field_name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -858,4 +1029,62 @@ mod tests {
         );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_in_list_compiles_to_bound_placeholders() {
+        let mut sanitizer = ParameterSanitizer::new();
+        let ids = Column::<i32, i32>::new("people".to_string(), "id".to_string());
+
+        let sql = in_list(ids, vec![3, 5, 8], "id".to_string())
+            .to_sql(&mut sanitizer)
+            .unwrap();
+        assert_eq!(sql, "people.id IN ($1, $2, $3)");
+        assert_eq!(sanitizer.bindings()["$2"].value, "5");
+        assert_eq!(sanitizer.bindings()["$3"].sql_type, "INTEGER");
+
+        // Values are bound, never spliced into the fragment
+        let names = Column::<String, String>::new("people".to_string(), "name".to_string());
+        let injected = in_list(
+            names,
+            vec!["Ann".to_string(), "x') OR 1=1; --".to_string()],
+            "name".to_string(),
+        );
+        assert!(injected.to_sql(&mut sanitizer).is_err());
+    }
+
+    #[test]
+    fn test_empty_in_list_is_always_false() {
+        let mut sanitizer = ParameterSanitizer::new();
+        let ids = Column::<i32, i32>::new("people".to_string(), "id".to_string());
+
+        let sql = in_list(ids, Vec::new(), "id".to_string())
+            .to_sql(&mut sanitizer)
+            .unwrap();
+        assert_eq!(sql, "1 = 0");
+        assert!(sanitizer.bindings().is_empty());
+    }
+
+    #[test]
+    fn test_between_compiles_after_existing_parameters() {
+        use chrono::NaiveDate;
+
+        let mut sanitizer = ParameterSanitizer::new();
+        let name = Column::<String, String>::new("people".to_string(), "name".to_string());
+        let created =
+            Column::<NaiveDate, NaiveDate>::new("people".to_string(), "created_at".to_string());
+
+        let name_sql = equals(name, "Ann".to_string(), "name".to_string())
+            .to_sql(&mut sanitizer)
+            .unwrap();
+        let low = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+        let high = NaiveDate::from_ymd_opt(2026, 6, 30).unwrap();
+        let range_sql = between(created, low, high, "created_at".to_string())
+            .to_sql(&mut sanitizer)
+            .unwrap();
+
+        assert_eq!(name_sql, "people.name = $1");
+        assert_eq!(range_sql, "people.created_at BETWEEN $2 AND $3");
+        assert_eq!(sanitizer.bindings()["$2"].value, "2026-01-01");
+        assert_eq!(sanitizer.bindings()["$3"].sql_type, "DATE");
+    }
 }
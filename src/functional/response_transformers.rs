@@ -50,6 +50,7 @@ use serde_json::{self, json, Value as JsonValue};
 use thiserror::Error;
 
 use crate::constants;
+use crate::middleware::server_timing::time_phase;
 use crate::models::response::ResponseBody;
 
 /// Supported output formats handled by the response transformer.
@@ -531,7 +532,9 @@ where
             metadata: self.metadata,
         };
 
-        match render_response(builder, envelope, format) {
+        match time_phase(req, "serialization", || {
+            render_response(builder, envelope, format)
+        }) {
             Ok(response) => response,
             Err(err) => serialization_error(err),
         }
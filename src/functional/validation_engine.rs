@@ -8,40 +8,47 @@
 
 use std::collections::HashMap;
 
-use crate::functional::validation_rules::{ValidationError, ValidationResult, ValidationRule};
+use crate::functional::validation_rules::{
+    AsyncValidationRule, ValidationError, ValidationResult, ValidationRule,
+};
 
 /// Validation pipeline configuration
 #[derive(Debug, Clone)]
 pub struct ValidationConfig {
-    /// Stop on first validation error
+    /// Stop at the first failing rule; when false every rule runs and all errors are reported
     pub fail_fast: bool,
     /// Maximum number of validation errors to collect
     pub max_errors: Option<usize>,
     /// Enable parallel validation for large datasets
     pub parallel_validation: bool,
+    /// Run async rules even when a sync rule already failed; by default the
+    /// (possibly expensive) async rules are skipped for input that is invalid anyway
+    pub run_async_after_sync_errors: bool,
 }
 
 impl Default for ValidationConfig {
     /// Creates a ValidationConfig populated with sensible defaults.
     ///
     /// The defaults are:
-    /// - `fail_fast = true`
+    /// - `fail_fast = false`
     /// - `max_errors = Some(10)`
     /// - `parallel_validation = false`
+    /// - `run_async_after_sync_errors = false`
     ///
     /// # Examples
     ///
     /// ```
     /// let cfg = ValidationConfig::default();
-    /// assert!(cfg.fail_fast);
+    /// assert!(!cfg.fail_fast);
     /// assert_eq!(cfg.max_errors, Some(10));
     /// assert!(!cfg.parallel_validation);
     /// ```
     fn default() -> Self {
         Self {
-            fail_fast: true,
+            fail_fast: false,
             max_errors: Some(10),
             parallel_validation: false,
+            run_async_after_sync_errors: false,
         }
     }
 }
@@ -182,7 +189,7 @@ impl<T> ValidationOutcome<T> {
     /// ```
     /// // Construct a successful outcome, then add an error to it.
     /// let outcome = ValidationOutcome::success(42);
-    /// let err = ValidationError { code: "E001".into(), message: "Invalid value".into(), field: "age".into() };
+    /// let err = ValidationError::new("age", "E001", "Invalid value");
     /// let failed = outcome.add_error(err);
     /// assert!(!failed.is_valid);
     /// assert!(failed.value.is_none());
@@ -232,8 +239,8 @@ impl<T> ValidationEngine<T> {
     ///
     /// ```
     /// let engine = ValidationEngine::<i32>::new();
-    /// // default configuration is applied (fail_fast = true by default)
-    /// assert!(engine.config.fail_fast);
+    /// // default configuration is applied (fail_fast = false by default)
+    /// assert!(!engine.config.fail_fast);
     /// ```
     pub fn new() -> Self {
         Self {
@@ -248,12 +255,13 @@ impl<T> ValidationEngine<T> {
     ///
     /// ```
     /// let cfg = ValidationConfig {
-    ///     fail_fast: false,
+    ///     fail_fast: true,
     ///     max_errors: Some(5),
     ///     parallel_validation: true,
+    ///     run_async_after_sync_errors: false,
     /// };
     /// /// let engine: ValidationEngine<String> = ValidationEngine::with_config(cfg);
-    /// assert_eq!(engine.config.fail_fast, false);
+    /// assert!(engine.config.fail_fast);
     /// ```
     pub fn with_config(config: ValidationConfig) -> Self {
         Self {
@@ -266,7 +274,7 @@ impl<T> ValidationEngine<T> {
     ///
     /// This applies each provided rule to `value` using a context for `field_name`. Collected errors
     /// are returned if any rules fail; validation honors the engine configuration (stopping early if
-    /// `fail_fast` is true and respecting `max_errors` when set).
+    /// `fail_fast` is set and respecting `max_errors` when set).
     ///
     /// # Examples
     ///
@@ -328,7 +336,7 @@ impl<T> ValidationEngine<T> {
     ///
     /// Iterates the provided (field_name, value, rules) tuples, validating each field with the given rules.
     /// On success returns a map of field names to their validated references; on any failures returns all collected validation errors.
-    /// If the engine is configured with `fail_fast`, validation stops after the first failing field.
+    /// When the engine is configured with `fail_fast`, validation stops after the first failing field.
     ///
     /// # Examples
     ///
@@ -360,7 +368,7 @@ impl<T> ValidationEngine<T> {
                 all_errors.extend(field_result.errors);
             }
 
-            // Stop if fail_fast is enabled and we have errors
+            // Stop at the first failing field when failing fast
             if self.config.fail_fast && has_failures {
                 break;
             }
@@ -372,6 +380,85 @@ impl<T> ValidationEngine<T> {
             ValidationOutcome::success(results)
         }
     }
+
+    /// Run every rule of every field and return all errors together.
+    ///
+    /// Unlike [`validate_fields`](Self::validate_fields) this ignores `fail_fast` and
+    /// `max_errors`, so a form can highlight each invalid field in one round trip.
+    /// Errors keep field order, then rule order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let engine = validator::<String>();
+    /// let name = "".to_string();
+    /// let email = "nope".to_string();
+    /// let errors = engine
+    ///     .validate_all(vec![
+    ///         ("name".to_string(), &name, vec![Box::new(Required) as Box<dyn ValidationRule<String>>]),
+    ///         ("email".to_string(), &email, vec![Box::new(Email) as Box<dyn ValidationRule<String>>]),
+    ///     ])
+    ///     .unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    /// ```
+    pub fn validate_all<'a, I, R>(&self, field_validators: I) -> Result<(), Vec<ValidationError>>
+    where
+        T: 'a,
+        I: IntoIterator<Item = (String, &'a T, Vec<R>)>,
+        R: ValidationRule<T>,
+    {
+        let errors: Vec<ValidationError> = field_validators
+            .into_iter()
+            .flat_map(|(field_name, value, rules)| {
+                rules
+                    .into_iter()
+                    .filter_map(move |rule| rule.validate(value, &field_name).err())
+            })
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Like [`validate_all`](Self::validate_all), followed by the async rules of `async_fields`.
+    ///
+    /// Every sync rule runs first. The async rules, typically database
+    /// lookups, only run when the sync phase passed, unless
+    /// `run_async_after_sync_errors` is set. They run one after the other and
+    /// their errors follow the sync errors.
+    pub async fn validate_all_with_async<'a, I, R, A>(
+        &self,
+        sync_fields: I,
+        async_fields: Vec<(String, &'a T, Vec<A>)>,
+    ) -> Result<(), Vec<ValidationError>>
+    where
+        T: 'a,
+        I: IntoIterator<Item = (String, &'a T, Vec<R>)>,
+        R: ValidationRule<T>,
+        A: AsyncValidationRule<T>,
+    {
+        let mut errors = self.validate_all(sync_fields).err().unwrap_or_default();
+        if !errors.is_empty() && !self.config.run_async_after_sync_errors {
+            return Err(errors);
+        }
+
+        for (field_name, value, rules) in &async_fields {
+            for rule in rules {
+                if let Err(error) = rule.validate(value, field_name).await {
+                    errors.push(error);
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
 }
 
 /// Creates a validation rule that applies the provided rules only when a predicate is true.
@@ -532,13 +619,26 @@ where
     )
 }
 
+/// Boxed validator of the items of a pipeline or lazy iterator
+type Validator<T> = Box<dyn Fn(&T) -> ValidationResult<()>>;
+
+/// Step of a [`ValidationPipeline`], applied to every item in the order the steps were added
+enum PipelineStep<T> {
+    Validate(Validator<T>),
+    Normalize(Box<dyn Fn(&mut T)>),
+}
+
 /// Iterator-based validation pipeline for processing streams of data
+///
+/// Validators and normalizations run in the order they were added, so a
+/// validator sees the value as rewritten by the normalizations before it. The
+/// items in the result are the normalized ones, ready to be stored.
 pub struct ValidationPipeline<T, I>
 where
     I: Iterator<Item = T>,
 {
     iterator: I,
-    validators: Vec<Box<dyn Fn(&T) -> ValidationResult<()>>>,
+    steps: Vec<PipelineStep<T>>,
     config: ValidationConfig,
 }
 
@@ -560,7 +660,7 @@ where
     pub fn new(iterator: I) -> Self {
         Self {
             iterator,
-            validators: Vec::new(),
+            steps: Vec::new(),
             config: ValidationConfig::default(),
         }
     }
@@ -584,7 +684,30 @@ where
     where
         F: Fn(&T) -> ValidationResult<()> + 'static,
     {
-        self.validators.push(Box::new(validator));
+        self.steps.push(PipelineStep::Validate(Box::new(validator)));
+        self
+    }
+
+    /// Rewrites the string `field` of every item with `normalize` before the validators added after it run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let result = ValidationPipeline::new(vec![" 555-1234 ".to_string()].into_iter())
+    ///     .normalize(|phone: &mut String| phone, |v| v.replace([' ', '-'], ""))
+    ///     .add_validator(|phone: &String| Phone.validate(phone, "phone"))
+    ///     .validate();
+    /// assert_eq!(result.valid_items, vec!["5551234".to_string()]);
+    /// ```
+    pub fn normalize<F>(mut self, field: F, normalize: fn(&str) -> String) -> Self
+    where
+        F: Fn(&mut T) -> &mut String + 'static,
+    {
+        self.steps
+            .push(PipelineStep::Normalize(Box::new(move |item| {
+                let value = field(item);
+                *value = normalize(value);
+            })));
         self
     }
 
@@ -594,7 +717,7 @@ where
     ///
     /// ```
     /// let data = vec![1, 2, 3].into_iter();
-    /// /// let config = ValidationConfig { fail_fast: false, max_errors: Some(5), parallel_validation: false };
+    /// /// let config = ValidationConfig { fail_fast: true, max_errors: Some(5), ..ValidationConfig::default() };
     /// let pipeline = ValidationPipeline::new(data).with_config(config);
     /// ```
     pub fn with_config(mut self, config: ValidationConfig) -> Self {
@@ -623,11 +746,18 @@ where
         let mut invalid_items = Vec::new();
         let mut total_errors = 0;
 
-        for item in self.iterator {
+        for mut item in self.iterator {
             let mut item_errors = Vec::new();
 
-            // Apply all validators to this item
-            for validator in &self.validators {
+            // Apply all steps to this item
+            for step in &self.steps {
+                let validator = match step {
+                    PipelineStep::Normalize(normalize) => {
+                        normalize(&mut item);
+                        continue;
+                    }
+                    PipelineStep::Validate(validator) => validator,
+                };
                 match validator(&item) {
                     Ok(()) => {}
                     Err(error) => {
@@ -697,18 +827,24 @@ where
     where
         T: Clone + Eq + std::hash::Hash,
     {
-        // Take ownership of validators before moving self.iterator
-        let validators = self.validators;
+        // Take ownership of the steps before moving self.iterator
+        let steps = self.steps;
 
         // Use itertools for advanced validation patterns
         let mut valid_items = Vec::new();
         let mut invalid_items = Vec::new();
 
         // Group items by validation status using itertools
-        let grouped = self.iterator.map(|item| {
-            let errors: Vec<_> = validators
+        let grouped = self.iterator.map(|mut item| {
+            let errors: Vec<_> = steps
                 .iter()
-                .filter_map(|validator| validator(&item).err())
+                .filter_map(|step| match step {
+                    PipelineStep::Normalize(normalize) => {
+                        normalize(&mut item);
+                        None
+                    }
+                    PipelineStep::Validate(validator) => validator(&item).err(),
+                })
                 .collect();
 
             (item, errors)
@@ -825,15 +961,15 @@ impl<T> ValidationPipelineResult<T> {
     /// ```
     /// // given a `result: ValidationPipelineResult<_>` with collected errors:
     /// let grouped = result.errors_by_code();
-    /// // `grouped` maps error codes (String) to Vec<&ValidationError>
+    /// // `grouped` maps error codes to Vec<&ValidationError>
     /// assert!(grouped.is_empty() || grouped.values().next().is_some());
     /// ```
-    pub fn errors_by_code(&self) -> HashMap<String, Vec<&ValidationError>> {
+    pub fn errors_by_code(&self) -> HashMap<&'static str, Vec<&ValidationError>> {
         let mut grouped = HashMap::new();
 
         for error in self.all_errors() {
             grouped
-                .entry(error.code.clone())
+                .entry(error.code)
                 .or_insert_with(Vec::new)
                 .push(error);
         }
@@ -848,7 +984,7 @@ where
     I: Iterator<Item = T>,
 {
     iterator: I,
-    validators: Vec<Box<dyn Fn(&T) -> ValidationResult<()>>>,
+    validators: Vec<Validator<T>>,
 }
 
 impl<T, I> LazyValidationIterator<T, I>
@@ -953,9 +1089,9 @@ where
 
 /// /// let engine = validator::<i32>();
 
-/// // default configuration applies (fail_fast = true, max_errors = Some(10), parallel_validation = false)
+/// // default configuration applies (fail_fast = false, max_errors = Some(10), parallel_validation = false)
 
-/// assert!(engine.config.fail_fast);
+/// assert!(!engine.config.fail_fast);
 
 /// ```
 pub fn validator<T>() -> ValidationEngine<T> {
@@ -967,7 +1103,7 @@ pub fn validator<T>() -> ValidationEngine<T> {
 /// # Examples
 ///
 /// ```
-/// /// let config = ValidationConfig { fail_fast: false, max_errors: Some(5), parallel_validation: false };
+/// /// let config = ValidationConfig { fail_fast: true, max_errors: Some(5), ..ValidationConfig::default() };
 /// /// let engine: ValidationEngine<String> = validator_with_config(config);
 /// ```
 pub fn validator_with_config<T>(config: ValidationConfig) -> ValidationEngine<T> {
@@ -1005,8 +1141,13 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
     use super::*;
-    use crate::functional::validation_rules::{Email, Required};
+    use crate::error::ServiceError;
+    use crate::functional::validation_rules::{Email, Length, Phone, Required, UniqueInDb};
+    use crate::models::person::PersonDTO;
 
     // Tests using concrete types for validation rules
 
@@ -1073,6 +1214,44 @@ mod tests {
         assert_eq!(result.total_errors, 1);
     }
 
+    #[test]
+    fn test_validation_pipeline_normalizes_before_validating() {
+        let strip = |value: &str| value.replace([' ', '-'], "");
+        let data = vec![" 555 1234 ".to_string(), "555-12".to_string()];
+
+        let result = ValidationPipeline::new(data.clone().into_iter())
+            .normalize(|phone: &mut String| phone, strip)
+            .add_validator(|phone: &String| Phone.validate(phone, "phone"))
+            .validate();
+        assert_eq!(result.valid_items, vec!["5551234".to_string()]);
+        assert_eq!(result.invalid_items[0].0, "55512");
+
+        let result = ValidationPipeline::new(data.into_iter())
+            .normalize(|phone: &mut String| phone, strip)
+            .add_validator(|phone: &String| Phone.validate(phone, "phone"))
+            .validate_with_itertools();
+        assert_eq!(result.valid_items, vec!["5551234".to_string()]);
+    }
+
+    #[test]
+    fn test_validation_pipeline_normalizes_struct_field() {
+        let person = PersonDTO {
+            email: "jane@example.com".to_string(),
+            name: "Jane".to_string(),
+            gender: false,
+            age: 30,
+            address: "US".to_string(),
+            phone: " 555 1234 ".to_string(),
+        };
+
+        let result = ValidationPipeline::new(vec![person].into_iter())
+            .normalize(|p: &mut PersonDTO| &mut p.phone, |v| v.replace(' ', ""))
+            .add_validator(|p: &PersonDTO| Phone.validate(&p.phone, "phone"))
+            .validate();
+        // The valid items carry the normalized value, which is what gets stored
+        assert_eq!(result.valid_items[0].phone, "5551234");
+    }
+
     /// Demonstrates validating items lazily with `LazyValidationIterator`, producing a `ValidationOutcome` per element.
     ///
     /// # Examples
@@ -1102,4 +1281,168 @@ mod tests {
         assert!(!results[1].is_valid);
         assert!(results[2].is_valid);
     }
+
+    #[test]
+    fn test_fail_fast_stops_at_first_error() {
+        let value = "a".to_string();
+        let rules = || {
+            vec![
+                Length {
+                    min: Some(3),
+                    max: None,
+                },
+                Length {
+                    min: Some(5),
+                    max: None,
+                },
+            ]
+        };
+        let fail_fast = ValidationEngine::with_config(ValidationConfig {
+            fail_fast: true,
+            ..ValidationConfig::default()
+        });
+        let collect_all = ValidationEngine::with_config(ValidationConfig::default());
+
+        assert_eq!(
+            fail_fast
+                .validate_field(&value, "name", rules())
+                .errors
+                .len(),
+            1
+        );
+        assert_eq!(
+            collect_all
+                .validate_field(&value, "name", rules())
+                .errors
+                .len(),
+            2
+        );
+
+        let pipeline = |config: ValidationConfig| {
+            ValidationPipeline::new(vec!["".to_string()].into_iter())
+                .add_validator(|s: &String| Required.validate(s, "email"))
+                .add_validator(|s: &String| Email.validate(s, "email"))
+                .with_config(config)
+                .validate()
+        };
+        let result = pipeline(ValidationConfig {
+            fail_fast: true,
+            ..ValidationConfig::default()
+        });
+        assert_eq!(result.total_errors, 1);
+        assert_eq!(pipeline(ValidationConfig::default()).total_errors, 2);
+    }
+
+    #[actix_web::test]
+    async fn test_async_rules_are_skipped_when_sync_rules_fail() {
+        let lookups = Arc::new(AtomicUsize::new(0));
+        let unique = || {
+            let lookups = lookups.clone();
+            UniqueInDb::new(move |username: &String| {
+                lookups.fetch_add(1, Ordering::SeqCst);
+                Ok(username == "taken")
+            })
+        };
+        let min_length = || {
+            vec![Length {
+                min: Some(3),
+                max: None,
+            }]
+        };
+        let validate = |engine: ValidationEngine<String>, username: String| {
+            let unique = unique();
+            async move {
+                engine
+                    .validate_all_with_async(
+                        vec![("username".to_string(), &username, min_length())],
+                        vec![("username".to_string(), &username, vec![unique])],
+                    )
+                    .await
+                    .err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|error| error.code)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // The length rule fails, so the lookup never runs
+        let codes = validate(ValidationEngine::new(), "ab".to_string()).await;
+        assert_eq!(codes, vec!["length.min"]);
+        assert_eq!(lookups.load(Ordering::SeqCst), 0);
+
+        let codes = validate(ValidationEngine::new(), "taken".to_string()).await;
+        assert_eq!(codes, vec!["unique.taken"]);
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+
+        let run_all = ValidationEngine::with_config(ValidationConfig {
+            run_async_after_sync_errors: true,
+            ..ValidationConfig::default()
+        });
+        let codes = validate(run_all, "ab".to_string()).await;
+        assert_eq!(codes, vec!["length.min"]);
+        assert_eq!(lookups.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn test_unique_in_db_reports_failed_lookup() {
+        let rule = UniqueInDb::new(|_: &String| {
+            Err(ServiceError::service_unavailable("database is down", 5))
+        });
+
+        let error = rule
+            .validate(&"anyone".to_string(), "username")
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, "unique.unverified");
+    }
+
+    #[test]
+    fn test_validate_all_reports_every_field() {
+        let engine = ValidationEngine::with_config(ValidationConfig {
+            fail_fast: true,
+            max_errors: Some(1),
+            ..ValidationConfig::default()
+        });
+        let username = "ab".to_string();
+        let email = "not-an-email".to_string();
+        let phone = "12".to_string();
+        let rules = |min: usize| -> Vec<Box<dyn ValidationRule<String>>> {
+            vec![
+                Box::new(Length {
+                    min: Some(min),
+                    max: None,
+                }),
+                Box::new(Email),
+            ]
+        };
+
+        let errors = engine
+            .validate_all(vec![
+                ("username".to_string(), &username, rules(3)),
+                ("email".to_string(), &email, rules(1)),
+                ("phone".to_string(), &phone, rules(7)),
+            ])
+            .unwrap_err();
+
+        let reported: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|error| (error.field.as_str(), error.code))
+            .collect();
+        assert_eq!(
+            reported,
+            vec![
+                ("username", "length.min"),
+                ("username", "email.invalid"),
+                ("email", "email.invalid"),
+                ("phone", "length.min"),
+                ("phone", "email.invalid"),
+            ]
+        );
+
+        let valid = "someone@example.com".to_string();
+        assert!(engine
+            .validate_all(vec![("email".to_string(), &valid, rules(3))])
+            .is_ok());
+    }
 }
@@ -6,43 +6,136 @@
 
 #![allow(dead_code)]
 
+use futures::future::LocalBoxFuture;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Serialize;
 use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::config::db::{Connection, Pool};
+use crate::error::ServiceError;
 
 /// Cached regex patterns for validation
 static EMAIL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").unwrap());
-static PHONE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[\d\s\-\(\)\+]{7,20}$").unwrap());
+static PHONE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\+?\d{7,15}$").unwrap());
 
 /// Validation result type for composable validation chains
 pub type ValidationResult<T> = Result<T, ValidationError>;
 
+/// Stable machine-readable codes emitted by the built-in rules.
+///
+/// Clients key localized messages on these, so existing values must never change.
+pub mod codes {
+    /// [`Required`](super::Required): the value is empty/default
+    pub const REQUIRED: &str = "required";
+    /// [`Length`](super::Length): shorter than `min`
+    pub const LENGTH_MIN: &str = "length.min";
+    /// [`Length`](super::Length): longer than `max`
+    pub const LENGTH_MAX: &str = "length.max";
+    /// [`Email`](super::Email): not an email address
+    pub const EMAIL_INVALID: &str = "email.invalid";
+    /// [`Range`](super::Range): below `min`
+    pub const RANGE_MIN: &str = "range.min";
+    /// [`Range`](super::Range): above `max`
+    pub const RANGE_MAX: &str = "range.max";
+    /// [`Phone`](super::Phone): not a phone number
+    pub const PHONE_INVALID: &str = "phone.invalid";
+    /// [`OneOf`](super::OneOf): not one of the allowed values
+    pub const ONE_OF_INVALID: &str = "one_of.invalid";
+    /// [`Url`](super::Url): not an absolute URL
+    pub const URL_INVALID: &str = "url.invalid";
+    /// [`Url`](super::Url): the scheme is not on the allowlist
+    pub const URL_INVALID_SCHEME: &str = "url.invalid_scheme";
+    /// [`Url`](super::Url): a host is required but the URL has none
+    pub const URL_MISSING_HOST: &str = "url.missing_host";
+    /// [`UniqueInDb`](super::UniqueInDb): the value is already stored
+    pub const UNIQUE_TAKEN: &str = "unique.taken";
+    /// [`UniqueInDb`](super::UniqueInDb): the lookup failed, so uniqueness is unknown
+    pub const UNIQUE_UNVERIFIED: &str = "unique.unverified";
+    /// [`UniqueEmail`](super::UniqueEmail): another user already registered the email
+    pub const EMAIL_TAKEN: &str = "email.taken";
+    /// [`PasswordStrength`](super::PasswordStrength): shorter than the minimum length
+    pub const PASSWORD_TOO_SHORT: &str = "password.too_short";
+    /// [`PasswordStrength`](super::PasswordStrength): too few character classes, an uppercase letter is missing
+    pub const PASSWORD_NEEDS_UPPERCASE: &str = "password.needs_uppercase";
+    /// [`PasswordStrength`](super::PasswordStrength): too few character classes, a lowercase letter is missing
+    pub const PASSWORD_NEEDS_LOWERCASE: &str = "password.needs_lowercase";
+    /// [`PasswordStrength`](super::PasswordStrength): too few character classes, a digit is missing
+    pub const PASSWORD_NEEDS_DIGIT: &str = "password.needs_digit";
+    /// [`PasswordStrength`](super::PasswordStrength): too few character classes, a symbol is missing
+    pub const PASSWORD_NEEDS_SYMBOL: &str = "password.needs_symbol";
+    /// [`PasswordStrength`](super::PasswordStrength): on the common-password list
+    pub const PASSWORD_TOO_COMMON: &str = "password.too_common";
+}
+
 /// Validation error with detailed information
-#[derive(Debug, Clone, PartialEq)]
+///
+/// `path` locates the offending value inside a nested structure (for example
+/// `item[3].icms.vBC`). It is `None` for flat validations where `field` alone
+/// identifies the value, and is omitted from the serialized form in that case.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct ValidationError {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub field: String,
+    /// Stable machine-readable code, see [`codes`] for the built-in ones
+    pub code: &'static str,
+    /// Human-readable English message, kept for clients that don't translate `code`
+    pub message: String,
+}
+
+/// The `{field, code, message}` triple clients use to localize a validation error
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationErrorDetail {
     pub field: String,
-    pub code: String,
+    pub code: &'static str,
     pub message: String,
 }
 
+impl From<ValidationError> for ValidationErrorDetail {
+    fn from(error: ValidationError) -> Self {
+        Self {
+            field: error.field,
+            code: error.code,
+            message: error.message,
+        }
+    }
+}
+
 impl ValidationError {
     /// Creates a ValidationError with the provided field name, error code, and message.
     ///
     /// # Examples
     ///
     /// ```
-    /// let err = ValidationError::new("email", "INVALID_EMAIL", "Email format is invalid");
+    /// let err = ValidationError::new("email", codes::EMAIL_INVALID, "Email format is invalid");
     /// assert_eq!(err.field, "email");
-    /// assert_eq!(err.code, "INVALID_EMAIL");
+    /// assert_eq!(err.code, "email.invalid");
     /// assert_eq!(err.message, "Email format is invalid");
     /// ```
-    pub fn new(field: &str, code: &str, message: &str) -> Self {
+    pub fn new(field: &str, code: &'static str, message: &str) -> Self {
         Self {
+            path: None,
             field: field.to_string(),
-            code: code.to_string(),
+            code,
             message: message.to_string(),
         }
     }
+
+    /// Attaches a structured path pointing at the offending nested element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let err = ValidationError::new("vBC", "NEGATIVE_VALUE", "vBC must not be negative")
+    ///     .with_path("item[3].icms.vBC");
+    /// assert_eq!(err.path.as_deref(), Some("item[3].icms.vBC"));
+    /// ```
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
 }
 
 /// Core validation rule trait for composable validation
@@ -50,6 +143,150 @@ pub trait ValidationRule<T> {
     fn validate(&self, value: &T, field_name: &str) -> ValidationResult<()>;
 }
 
+/// Boxed rules validate like the rule they hold, so rules of different types can share a `Vec`
+impl<T, R: ValidationRule<T> + ?Sized> ValidationRule<T> for Box<R> {
+    fn validate(&self, value: &T, field_name: &str) -> ValidationResult<()> {
+        (**self).validate(value, field_name)
+    }
+}
+
+/// Validation rule that has to wait on I/O, such as a database lookup
+///
+/// Async rules are run by
+/// [`ValidationEngine::validate_all_with_async`](crate::functional::validation_engine::ValidationEngine::validate_all_with_async)
+/// once the cheap synchronous rules have passed.
+pub trait AsyncValidationRule<T> {
+    fn validate<'a>(
+        &'a self,
+        value: &'a T,
+        field_name: &'a str,
+    ) -> LocalBoxFuture<'a, ValidationResult<()>>;
+}
+
+impl<T, R: AsyncValidationRule<T> + ?Sized> AsyncValidationRule<T> for Box<R> {
+    fn validate<'a>(
+        &'a self,
+        value: &'a T,
+        field_name: &'a str,
+    ) -> LocalBoxFuture<'a, ValidationResult<()>> {
+        (**self).validate(value, field_name)
+    }
+}
+
+/// Uniqueness check against stored data - fails when `lookup` reports the value as taken
+///
+/// `lookup` runs on the blocking thread pool. A failed lookup is reported as
+/// [`codes::UNIQUE_UNVERIFIED`] rather than letting a possible duplicate through.
+pub struct UniqueInDb<T> {
+    lookup: Arc<UniqueLookup<T>>,
+}
+
+/// Reports whether a value is already stored
+type UniqueLookup<T> = dyn Fn(&T) -> Result<bool, ServiceError> + Send + Sync;
+
+impl<T> UniqueInDb<T> {
+    pub fn new(lookup: impl Fn(&T) -> Result<bool, ServiceError> + Send + Sync + 'static) -> Self {
+        UniqueInDb {
+            lookup: Arc::new(lookup),
+        }
+    }
+
+    /// Rule running the `exists` query on a connection from `pool`.
+    pub fn query(
+        pool: Pool,
+        exists: impl Fn(&T, &mut Connection) -> diesel::QueryResult<bool> + Send + Sync + 'static,
+    ) -> Self {
+        Self::new(move |value| {
+            let mut conn = pool
+                .get()
+                .map_err(|e| ServiceError::from_pool_error(e, &pool))?;
+            exists(value, &mut conn).map_err(|e| ServiceError::internal_server_error(e.to_string()))
+        })
+    }
+}
+
+impl<T: Clone + Send + 'static> AsyncValidationRule<T> for UniqueInDb<T> {
+    fn validate<'a>(
+        &'a self,
+        value: &'a T,
+        field_name: &'a str,
+    ) -> LocalBoxFuture<'a, ValidationResult<()>> {
+        let lookup = self.lookup.clone();
+        let value = value.clone();
+        Box::pin(async move {
+            let taken = crate::middleware::request_trace::block(move || lookup(&value))
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|result| result.map_err(|e| e.to_string()));
+            match taken {
+                Ok(false) => Ok(()),
+                Ok(true) => Err(ValidationError::new(
+                    field_name,
+                    codes::UNIQUE_TAKEN,
+                    &format!("{} is already taken", field_name),
+                )),
+                Err(e) => {
+                    log::error!("Uniqueness lookup for {} failed: {}", field_name, e);
+                    Err(ValidationError::new(
+                        field_name,
+                        codes::UNIQUE_UNVERIFIED,
+                        &format!("{} could not be checked for uniqueness", field_name),
+                    ))
+                }
+            }
+        })
+    }
+}
+
+/// Email not registered by any user yet - [`UniqueInDb`] reporting [`codes::EMAIL_TAKEN`]
+///
+/// Emails are compared case-insensitively. A failed lookup is reported as
+/// [`codes::UNIQUE_UNVERIFIED`].
+pub struct UniqueEmail {
+    unique: UniqueInDb<String>,
+}
+
+impl UniqueEmail {
+    pub fn new(
+        lookup: impl Fn(&String) -> Result<bool, ServiceError> + Send + Sync + 'static,
+    ) -> Self {
+        UniqueEmail {
+            unique: UniqueInDb::new(lookup),
+        }
+    }
+
+    /// Rule looking the email up among the users of the tenant behind `pool`.
+    pub fn query(pool: Pool) -> Self {
+        UniqueEmail {
+            unique: UniqueInDb::query(pool, |email: &String, conn| {
+                crate::models::user::operations::email_in_use(email, None, conn)
+            }),
+        }
+    }
+}
+
+impl AsyncValidationRule<String> for UniqueEmail {
+    fn validate<'a>(
+        &'a self,
+        value: &'a String,
+        field_name: &'a str,
+    ) -> LocalBoxFuture<'a, ValidationResult<()>> {
+        Box::pin(async move {
+            self.unique
+                .validate(value, field_name)
+                .await
+                .map_err(|error| match error.code {
+                    codes::UNIQUE_TAKEN => ValidationError::new(
+                        field_name,
+                        codes::EMAIL_TAKEN,
+                        &format!("Email '{}' is already registered", value),
+                    ),
+                    _ => error,
+                })
+        })
+    }
+}
+
 /// Required field validation - ensures value is not empty/default
 pub struct Required;
 
@@ -57,7 +294,7 @@ impl<T: Default + PartialEq> ValidationRule<T> for Required {
     /// Ensures the provided value is not equal to its type's default.
     ///
     /// If the value equals T::default(), validation fails and a `ValidationError` is returned
-    /// with code `"REQUIRED"` and a message of the form `"<field_name> is required"`.
+    /// with code `"required"` and a message of the form `"<field_name> is required"`.
     ///
     /// # Parameters
     ///
@@ -66,7 +303,7 @@ impl<T: Default + PartialEq> ValidationRule<T> for Required {
     ///
     /// # Returns
     ///
-    /// `Ok(())` if the value is not the default, `Err(ValidationError)` with code `"REQUIRED"` otherwise.
+    /// `Ok(())` if the value is not the default, `Err(ValidationError)` with code `"required"` otherwise.
     ///
     /// # Examples
     ///
@@ -77,14 +314,14 @@ impl<T: Default + PartialEq> ValidationRule<T> for Required {
     ///
     /// let empty: String = String::default();
     /// let err = rule.validate(&empty, "greeting").unwrap_err();
-    /// assert_eq!(err.code, "REQUIRED");
+    /// assert_eq!(err.code, "required");
     /// assert_eq!(err.message, "greeting is required");
     /// ```
     fn validate(&self, value: &T, field_name: &str) -> ValidationResult<()> {
         if *value == T::default() {
             return Err(ValidationError::new(
                 field_name,
-                "REQUIRED",
+                codes::REQUIRED,
                 &format!("{} is required", field_name),
             ));
         }
@@ -102,8 +339,8 @@ impl ValidationRule<String> for Length {
     /// Validates that a string's length falls within the rule's optional minimum and maximum bounds.
     ///
     /// If `min` is set and the string has fewer than `min` characters, validation fails with code
-    /// `TOO_SHORT`. If `max` is set and the string has more than `max` characters, validation fails
-    /// with code `TOO_LONG`. Error messages include `field_name`.
+    /// `length.min`. If `max` is set and the string has more than `max` characters, validation fails
+    /// with code `length.max`. Error messages include `field_name`.
     ///
     /// # Parameters
     ///
@@ -120,8 +357,8 @@ impl ValidationRule<String> for Length {
     ///
     /// let rule = Length { min: Some(2), max: Some(4) };
     /// assert!(rule.validate(&"hi".to_string(), "name").is_ok());
-    /// assert!(rule.validate(&"h".to_string(), "name").is_err()); // TOO_SHORT
-    /// assert!(rule.validate(&"hello".to_string(), "name").is_err()); // TOO_LONG
+    /// assert!(rule.validate(&"h".to_string(), "name").is_err()); // length.min
+    /// assert!(rule.validate(&"hello".to_string(), "name").is_err()); // length.max
     /// ```
     fn validate(&self, value: &String, field_name: &str) -> ValidationResult<()> {
         let len = value.len();
@@ -130,7 +367,7 @@ impl ValidationRule<String> for Length {
             if len < min {
                 return Err(ValidationError::new(
                     field_name,
-                    "TOO_SHORT",
+                    codes::LENGTH_MIN,
                     &format!("{} must be at least {} characters", field_name, min),
                 ));
             }
@@ -140,7 +377,7 @@ impl ValidationRule<String> for Length {
             if len > max {
                 return Err(ValidationError::new(
                     field_name,
-                    "TOO_LONG",
+                    codes::LENGTH_MAX,
                     &format!("{} must be at most {} characters", field_name, max),
                 ));
             }
@@ -158,7 +395,7 @@ impl ValidationRule<String> for Email {
     ///
     /// # Returns
     ///
-    /// `Ok(())` when the value matches a simple email pattern; `Err(ValidationError)` with code `INVALID_EMAIL` otherwise.
+    /// `Ok(())` when the value matches a simple email pattern; `Err(ValidationError)` with code `email.invalid` otherwise.
     ///
     /// # Examples
     ///
@@ -172,7 +409,7 @@ impl ValidationRule<String> for Email {
         if !EMAIL_REGEX.is_match(value) {
             return Err(ValidationError::new(
                 field_name,
-                "INVALID_EMAIL",
+                codes::EMAIL_INVALID,
                 &format!("{} must be a valid email address", field_name),
             ));
         }
@@ -192,7 +429,7 @@ impl ValidationRule<i32> for Range {
     ///
     /// Returns `Ok(())` if `value` is greater than or equal to `min` (when `min` is set)
     /// and less than or equal to `max` (when `max` is set). Returns `Err(ValidationError)`
-    /// with code `"TOO_SMALL"` when `value` is less than `min`, or `"TOO_LARGE"` when
+    /// with code `"range.min"` when `value` is less than `min`, or `"range.max"` when
     /// `value` is greater than `max`. The error message includes the `field_name` and the
     /// violated bound.
     ///
@@ -202,14 +439,14 @@ impl ValidationRule<i32> for Range {
     /// let range = Range { min: Some(0), max: Some(10) };
     /// assert!(range.validate(&5, "count").is_ok());
     /// let err = range.validate(&-1, "count").unwrap_err();
-    /// assert_eq!(err.code, "TOO_SMALL");
+    /// assert_eq!(err.code, "range.min");
     /// ```
     fn validate(&self, value: &i32, field_name: &str) -> ValidationResult<()> {
         if let Some(min) = self.min {
             if *value < min {
                 return Err(ValidationError::new(
                     field_name,
-                    "TOO_SMALL",
+                    codes::RANGE_MIN,
                     &format!("{} must be at least {}", field_name, min),
                 ));
             }
@@ -219,7 +456,7 @@ impl ValidationRule<i32> for Range {
             if *value > max {
                 return Err(ValidationError::new(
                     field_name,
-                    "TOO_LARGE",
+                    codes::RANGE_MAX,
                     &format!("{} must be at most {}", field_name, max),
                 ));
             }
@@ -229,27 +466,79 @@ impl ValidationRule<i32> for Range {
     }
 }
 
+/// Strips formatting from a phone number, optionally converting it to E.164.
+///
+/// Spaces, dashes, dots, slashes and parentheses are removed and an
+/// international `00` prefix becomes `+`. When `default_country_code` is given,
+/// a number without a country code gets `+<code>` prepended after dropping a
+/// national trunk `0`. Any other character is kept, so garbage stays invalid.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(normalize_phone("(012) 345-6789", None), "0123456789");
+/// assert_eq!(normalize_phone("012.345.6789", Some("84")), "+84123456789");
+/// assert_eq!(normalize_phone("0084 12 345 6789", Some("1")), "+84123456789");
+/// ```
+pub fn normalize_phone(value: &str, default_country_code: Option<&str>) -> String {
+    let stripped: String = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, '-' | '.' | '/' | '(' | ')'))
+        .collect();
+
+    if stripped.is_empty() || stripped.starts_with('+') {
+        return stripped;
+    }
+    if let Some(international) = stripped.strip_prefix("00") {
+        return format!("+{}", international);
+    }
+
+    match default_country_code {
+        Some(code) => {
+            let national = stripped.strip_prefix('0').unwrap_or(&stripped);
+            format!("+{}{}", code.trim_start_matches('+'), national)
+        }
+        None => stripped,
+    }
+}
+
+/// Canonical form of an email address: trimmed and lowercased.
+///
+/// Emails are stored and compared in this form so that addresses differing
+/// only in case are treated as the same account.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(normalize_email("  Alice@Example.COM "), "alice@example.com");
+/// ```
+pub fn normalize_email(value: &str) -> String {
+    value.trim().to_lowercase()
+}
+
 /// Phone number format validation (basic)
 pub struct Phone;
 
 impl ValidationRule<String> for Phone {
-    /// Validates that a string is a phone number containing only digits, spaces, dashes, parentheses, or `+`, with length between 7 and 20 characters.
+    /// Validates that a string is a phone number: once normalized with [`normalize_phone`],
+    /// 7 to 15 digits with an optional leading `+`.
     ///
-    /// Returns an `Err(ValidationError)` with code `"INVALID_PHONE"` when the value does not match the expected phone format.
+    /// Returns an `Err(ValidationError)` with code `"phone.invalid"` when the value does not match the expected phone format.
     ///
     /// # Examples
     ///
     /// ```
     /// let phone = Phone;
     /// assert!(phone.validate(&"123-456-7890".to_string(), "contact_phone").is_ok());
+    /// assert!(phone.validate(&"(012) 345.6789".to_string(), "contact_phone").is_ok());
     /// assert!(phone.validate(&"invalid_phone!".to_string(), "contact_phone").is_err());
     /// ```
     fn validate(&self, value: &String, field_name: &str) -> ValidationResult<()> {
-        // Basic phone regex - allows digits, spaces, dashes, parentheses, plus
-        if !PHONE_REGEX.is_match(value) {
+        // Formatting characters are ignored; what remains must be digits with an optional `+`
+        if !PHONE_REGEX.is_match(&normalize_phone(value, None)) {
             return Err(ValidationError::new(
                 field_name,
-                "INVALID_PHONE",
+                codes::PHONE_INVALID,
                 &format!("{} must be a valid phone number", field_name),
             ));
         }
@@ -261,7 +550,7 @@ impl ValidationRule<String> for Phone {
 /// Custom validation using a predicate function
 pub struct Custom<F> {
     predicate: F,
-    error_code: String,
+    error_code: &'static str,
     error_message: String,
 }
 
@@ -279,10 +568,10 @@ impl<F> Custom<F> {
     /// assert!(rule.validate(&5, "age").is_ok());
     /// assert!(rule.validate(&0, "age").is_err());
     /// ```
-    pub fn new(predicate: F, error_code: &str, error_message: &str) -> Self {
+    pub fn new(predicate: F, error_code: &'static str, error_message: &str) -> Self {
         Self {
             predicate,
-            error_code: error_code.to_string(),
+            error_code,
             error_message: error_message.to_string(),
         }
     }
@@ -312,7 +601,7 @@ where
         if !(self.predicate)(value) {
             return Err(ValidationError::new(
                 field_name,
-                &self.error_code,
+                self.error_code,
                 &self.error_message.replace("{}", field_name),
             ));
         }
@@ -321,8 +610,12 @@ where
 }
 
 /// One-of validation for enums or allowed values
+///
+/// The allowed values may be fixed in code ([`OneOf::new`]) or loaded at
+/// runtime, e.g. from configuration, with [`OneOf::from_iter`].
 pub struct OneOf<T: Clone + PartialEq> {
     allowed_values: Vec<T>,
+    matches: fn(&T, &T) -> bool,
 }
 
 impl<T: Clone + PartialEq> OneOf<T> {
@@ -338,15 +631,48 @@ impl<T: Clone + PartialEq> OneOf<T> {
     /// assert!(rule.validate(&"cherry".to_string(), "fruit").is_err());
     /// ```
     pub fn new(allowed_values: Vec<T>) -> Self {
-        Self { allowed_values }
+        Self {
+            allowed_values,
+            matches: |allowed, value| allowed == value,
+        }
+    }
+}
+
+impl OneOf<String> {
+    /// Compares values ignoring case, so `"Work"` matches an allowed `"work"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let rule = OneOf::from_iter(["work".to_string()]).case_insensitive();
+    /// assert!(rule.validate(&"Work".to_string(), "category").is_ok());
+    /// ```
+    pub fn case_insensitive(mut self) -> Self {
+        self.matches = |allowed, value| allowed.to_lowercase() == value.to_lowercase();
+        self
+    }
+}
+
+/// Builds the rule from values only known at runtime.
+///
+/// # Examples
+///
+/// ```
+/// let categories = vec!["family".to_string(), "work".to_string()];
+/// let rule = OneOf::from_iter(categories);
+/// assert!(rule.validate(&"work".to_string(), "category").is_ok());
+/// ```
+impl<T: Clone + PartialEq> FromIterator<T> for OneOf<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(values: I) -> Self {
+        Self::new(values.into_iter().collect())
     }
 }
 
-impl<T: Clone + PartialEq> ValidationRule<T> for OneOf<T> {
+impl<T: Clone + PartialEq + std::fmt::Display> ValidationRule<T> for OneOf<T> {
     /// Validates that the provided value is contained in the rule's allowed values.
     ///
-    /// Returns `Ok(())` if `value` is equal to one of the allowed values, `Err(ValidationError)` with code
-    /// `"INVALID_VALUE"` and a message indicating the field otherwise.
+    /// Returns `Ok(())` if `value` matches one of the allowed values, `Err(ValidationError)` with code
+    /// `"one_of.invalid"` and a message listing the allowed values otherwise.
     ///
     /// # Examples
     ///
@@ -355,14 +681,25 @@ impl<T: Clone + PartialEq> ValidationRule<T> for OneOf<T> {
     ///
     /// let rule = OneOf::new(vec!["red".to_string(), "green".to_string()]);
     /// assert!(rule.validate(&"red".to_string(), "color").is_ok());
-    /// assert!(rule.validate(&"blue".to_string(), "color").is_err());
+    /// let err = rule.validate(&"blue".to_string(), "color").unwrap_err();
+    /// assert_eq!(err.message, "color must be one of: red, green");
     /// ```
     fn validate(&self, value: &T, field_name: &str) -> ValidationResult<()> {
-        if !self.allowed_values.contains(value) {
+        if !self
+            .allowed_values
+            .iter()
+            .any(|allowed| (self.matches)(allowed, value))
+        {
+            let allowed = self
+                .allowed_values
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
             return Err(ValidationError::new(
                 field_name,
-                "INVALID_VALUE",
-                &format!("{} must be one of the allowed values", field_name),
+                codes::ONE_OF_INVALID,
+                &format!("{} must be one of: {}", field_name, allowed),
             ));
         }
         Ok(())
@@ -410,34 +747,103 @@ impl<T: Clone + Eq + std::hash::Hash> ValidationRule<Vec<T>> for Unique {
 }
 
 /// URL format validation
-pub struct Url;
+///
+/// Accepts absolute URLs whose scheme is on an allowlist, `http` and `https`
+/// by default, so `javascript:` and `file:` URLs are rejected. Schemes compare
+/// case-insensitively. A host can additionally be required, which rejects
+/// URLs such as `mailto:` ones when their scheme is allowed.
+pub struct Url {
+    allowed_schemes: Vec<String>,
+    require_host: bool,
+}
+
+impl Url {
+    /// Accepts `http` and `https` URLs, with or without a host.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let rule = Url::new().schemes(["https"]).require_host();
+    /// assert!(rule.validate(&"https://example.com".to_string(), "website").is_ok());
+    /// assert!(rule.validate(&"http://example.com".to_string(), "website").is_err());
+    /// ```
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the allowed schemes, e.g. `["https"]`.
+    pub fn schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_schemes = schemes
+            .into_iter()
+            .map(|scheme| scheme.as_ref().to_ascii_lowercase())
+            .collect();
+        self
+    }
+
+    /// Rejects URLs without a host.
+    pub fn require_host(mut self) -> Self {
+        self.require_host = true;
+        self
+    }
+}
+
+impl Default for Url {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: vec!["http".to_string(), "https".to_string()],
+            require_host: false,
+        }
+    }
+}
 
 impl ValidationRule<String> for Url {
-    /// Validates that a string is a well-formed URL.
+    /// Checks that the value parses as an absolute URL, then its scheme, then its host.
     ///
-    /// On failure returns a `ValidationError` with code `"INVALID_URL"` and message
-    /// `"<field_name> must be a valid URL"`.
+    /// Fails with `url.invalid`, `url.invalid_scheme` or `url.missing_host` respectively.
     ///
     /// # Examples
     ///
     /// ```
-    /// let rule = Url;
-    /// let ok = rule.validate(&"https://example.com".to_string(), "website");
-    /// assert!(ok.is_ok());
+    /// let rule = Url::new();
+    /// assert!(rule.validate(&"https://example.com".to_string(), "website").is_ok());
     ///
-    /// let err = rule.validate(&"not-a-url".to_string(), "website");
-    /// assert!(err.is_err());
-    /// let e = err.unwrap_err();
-    /// assert_eq!(e.code, "INVALID_URL");
-    /// assert_eq!(e.message, "website must be a valid URL");
+    /// let e = rule.validate(&"javascript:alert(1)".to_string(), "website").unwrap_err();
+    /// assert_eq!(e.code, "url.invalid_scheme");
+    /// assert_eq!(e.message, "website must use one of the schemes: http, https");
     /// ```
     fn validate(&self, value: &String, field_name: &str) -> ValidationResult<()> {
-        if url::Url::parse(value).is_err() {
+        let Ok(url) = url::Url::parse(value.trim()) else {
             return Err(ValidationError::new(
                 field_name,
-                "INVALID_URL",
+                codes::URL_INVALID,
                 &format!("{} must be a valid URL", field_name),
             ));
+        };
+        if !self
+            .allowed_schemes
+            .iter()
+            .any(|scheme| scheme == url.scheme())
+        {
+            return Err(ValidationError::new(
+                field_name,
+                codes::URL_INVALID_SCHEME,
+                &format!(
+                    "{} must use one of the schemes: {}",
+                    field_name,
+                    self.allowed_schemes.join(", ")
+                ),
+            ));
+        }
+        if self.require_host && url.host_str().map_or(true, str::is_empty) {
+            return Err(ValidationError::new(
+                field_name,
+                codes::URL_MISSING_HOST,
+                &format!("{} must include a host", field_name),
+            ));
         }
         Ok(())
     }
@@ -475,6 +881,175 @@ impl ValidationRule<bool> for MustBeTrue {
     }
 }
 
+/// Character class counted by [`PasswordStrength`]
+struct PasswordClass {
+    /// Reported when the class is the first one missing
+    code: &'static str,
+    name: &'static str,
+    contains: fn(char) -> bool,
+}
+
+/// Classes in the order missing ones are reported
+const PASSWORD_CLASSES: [PasswordClass; 4] = [
+    PasswordClass {
+        code: codes::PASSWORD_NEEDS_UPPERCASE,
+        name: "an uppercase letter",
+        contains: char::is_uppercase,
+    },
+    PasswordClass {
+        code: codes::PASSWORD_NEEDS_LOWERCASE,
+        name: "a lowercase letter",
+        contains: char::is_lowercase,
+    },
+    PasswordClass {
+        code: codes::PASSWORD_NEEDS_DIGIT,
+        name: "a digit",
+        contains: char::is_numeric,
+    },
+    PasswordClass {
+        code: codes::PASSWORD_NEEDS_SYMBOL,
+        name: "a symbol",
+        contains: |c| !c.is_alphanumeric(),
+    },
+];
+
+/// Frequently used passwords rejected by [`PasswordStrength::reject_common`], lowercased
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456789",
+    "12345678",
+    "1234567890",
+    "password",
+    "password1",
+    "password123",
+    "password!",
+    "p@ssw0rd",
+    "p@ssword1",
+    "passw0rd",
+    "passw0rd!",
+    "qwerty123",
+    "qwertyuiop",
+    "qwerty1!",
+    "iloveyou",
+    "iloveyou1",
+    "welcome1",
+    "welcome123",
+    "welcome1!",
+    "admin123",
+    "admin@123",
+    "letmein1",
+    "letmein!",
+    "abc12345",
+    "abcd1234",
+    "football1",
+    "baseball1",
+    "sunshine1",
+    "princess1",
+    "monkey123",
+    "dragon123",
+    "changeme",
+    "changeme1",
+    "trustno1",
+    "superman1",
+    "summer2024",
+    "winter2024",
+    "spring2024",
+    "autumn2024",
+    "1q2w3e4r",
+    "1qaz2wsx",
+    "zaq12wsx",
+];
+
+/// Password strength validation
+///
+/// Requires `min_length` characters and at least `min_classes` of the four
+/// character classes (uppercase, lowercase, digit, symbol), and optionally
+/// rejects passwords from a small embedded list of common ones. Only the first
+/// failing check is reported, each with its own `password.*` code.
+pub struct PasswordStrength {
+    min_length: usize,
+    min_classes: usize,
+    reject_common: bool,
+}
+
+impl PasswordStrength {
+    /// Requires `min_length` characters and no particular character classes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let rule = PasswordStrength::new(12).min_classes(3).reject_common();
+    /// assert!(rule.validate(&"correct-Horse-7".to_string(), "password").is_ok());
+    /// ```
+    pub fn new(min_length: usize) -> Self {
+        Self {
+            min_length,
+            min_classes: 0,
+            reject_common: false,
+        }
+    }
+
+    /// Requires at least `min_classes` of the four character classes.
+    pub fn min_classes(mut self, min_classes: usize) -> Self {
+        self.min_classes = min_classes.min(PASSWORD_CLASSES.len());
+        self
+    }
+
+    /// Rejects passwords on the embedded common-password list, ignoring case.
+    pub fn reject_common(mut self) -> Self {
+        self.reject_common = true;
+        self
+    }
+}
+
+impl ValidationRule<String> for PasswordStrength {
+    /// Checks the length first, then the character classes, then the common-password list.
+    ///
+    /// When too few classes are present the error names the first missing one,
+    /// e.g. `password.needs_symbol`.
+    fn validate(&self, value: &String, field_name: &str) -> ValidationResult<()> {
+        if value.chars().count() < self.min_length {
+            return Err(ValidationError::new(
+                field_name,
+                codes::PASSWORD_TOO_SHORT,
+                &format!(
+                    "{} must be at least {} characters",
+                    field_name, self.min_length
+                ),
+            ));
+        }
+
+        let missing: Vec<_> = PASSWORD_CLASSES
+            .iter()
+            .filter(|class| !value.chars().any(class.contains))
+            .collect();
+        if let Some(class) = missing.first() {
+            if PASSWORD_CLASSES.len() - missing.len() < self.min_classes {
+                return Err(ValidationError::new(
+                    field_name,
+                    class.code,
+                    &format!(
+                        "{} needs {}: use at least {} of uppercase letters, lowercase letters, digits and symbols",
+                        field_name, class.name, self.min_classes
+                    ),
+                ));
+            }
+        }
+
+        if self.reject_common && COMMON_PASSWORDS.contains(&value.to_lowercase().as_str()) {
+            return Err(ValidationError::new(
+                field_name,
+                codes::PASSWORD_TOO_COMMON,
+                &format!(
+                    "{} is too common, choose a less predictable one",
+                    field_name
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 /// Creates a composite validation rule that requires every provided rule to succeed.
 ///
 /// The returned rule applies all given rules to a value and fails if any single rule fails.
@@ -812,6 +1387,35 @@ mod tests {
             .is_err());
     }
 
+    #[test]
+    fn test_normalize_email_ignores_case_and_padding() {
+        assert_eq!(normalize_email("A@x.com"), normalize_email("a@X.COM "));
+        assert_eq!(normalize_email(" Bob@Example.org"), "bob@example.org");
+    }
+
+    #[test]
+    fn test_phone_validates_normalized_form() {
+        let rule = Phone;
+        assert!(rule
+            .validate(&"(012) 345-6789".to_string(), "phone")
+            .is_ok());
+        assert!(rule
+            .validate(&"+84 12.345.6789".to_string(), "phone")
+            .is_ok());
+        assert!(rule.validate(&"12-34".to_string(), "phone").is_err());
+        assert!(rule.validate(&"012 345 abc".to_string(), "phone").is_err());
+        assert!(rule.validate(&"+++1234567".to_string(), "phone").is_err());
+    }
+
+    #[test]
+    fn test_normalize_phone_formats() {
+        assert_eq!(normalize_phone(" 012-345 6789 ", None), "0123456789");
+        assert_eq!(normalize_phone("0084 (12) 345-6789", None), "+84123456789");
+        assert_eq!(normalize_phone("", Some("84")), "");
+        assert_eq!(normalize_phone("+1 555 0100", Some("84")), "+15550100");
+        assert_eq!(normalize_phone("0123456789", Some("+84")), "+84123456789");
+    }
+
     #[test]
     fn test_range_validation() {
         let rule = Range {
@@ -904,4 +1508,196 @@ mod tests {
         assert!(validator.validate(&5, "number").is_ok());
         assert!(!*called.borrow());
     }
+
+    #[test]
+    fn test_builtin_rules_emit_stable_codes() {
+        let code = |result: ValidationResult<()>| result.unwrap_err().code;
+        let text = |value: &str| value.to_string();
+
+        assert_eq!(code(Required.validate(&text(""), "name")), "required");
+        let length = Length {
+            min: Some(2),
+            max: Some(3),
+        };
+        assert_eq!(code(length.validate(&text("a"), "name")), "length.min");
+        assert_eq!(code(length.validate(&text("abcd"), "name")), "length.max");
+        assert_eq!(
+            code(Email.validate(&text("nope"), "email")),
+            "email.invalid"
+        );
+        let range = Range {
+            min: Some(0),
+            max: Some(150),
+        };
+        assert_eq!(code(range.validate(&-1, "age")), "range.min");
+        assert_eq!(code(range.validate(&151, "age")), "range.max");
+        assert_eq!(
+            code(Phone.validate(&text("call me"), "phone")),
+            "phone.invalid"
+        );
+    }
+
+    #[test]
+    fn test_password_strength_rejects_weak_password() {
+        let rule = PasswordStrength::new(8).min_classes(3);
+
+        let err = rule.validate(&"Ab1!".to_string(), "password").unwrap_err();
+        assert_eq!(err.code, codes::PASSWORD_TOO_SHORT);
+        assert_eq!(err.message, "password must be at least 8 characters");
+
+        let err = rule
+            .validate(&"lowercase1".to_string(), "password")
+            .unwrap_err();
+        assert_eq!(err.code, codes::PASSWORD_NEEDS_UPPERCASE);
+
+        let rule = PasswordStrength::new(8).min_classes(4);
+        let err = rule
+            .validate(&"Lowercase1".to_string(), "password")
+            .unwrap_err();
+        assert_eq!(err.code, codes::PASSWORD_NEEDS_SYMBOL);
+    }
+
+    #[test]
+    fn test_password_strength_accepts_strong_password() {
+        let rule = PasswordStrength::new(12).min_classes(4).reject_common();
+
+        assert!(rule
+            .validate(&"Tr0ub4dor&3-staple".to_string(), "password")
+            .is_ok());
+        // Three classes suffice when three are required
+        let rule = PasswordStrength::new(8).min_classes(3);
+        assert!(rule
+            .validate(&"NoSymbols42".to_string(), "password")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_password_strength_rejects_common_password() {
+        let rule = PasswordStrength::new(8).min_classes(3);
+        assert!(rule.validate(&"Password1".to_string(), "password").is_ok());
+
+        let err = rule
+            .reject_common()
+            .validate(&"Password1".to_string(), "password")
+            .unwrap_err();
+        assert_eq!(err.code, codes::PASSWORD_TOO_COMMON);
+    }
+
+    #[test]
+    fn test_url_accepts_allowed_scheme() {
+        let rule = Url::new().schemes(["https"]).require_host();
+        assert!(rule
+            .validate(&"https://example.com/contact?id=1".to_string(), "website")
+            .is_ok());
+        assert!(rule
+            .validate(&"HTTPS://Example.com".to_string(), "website")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_url_rejects_scheme_outside_allowlist() {
+        let rule = Url::new().schemes(["https"]);
+        let err = rule
+            .validate(&"http://example.com".to_string(), "website")
+            .unwrap_err();
+        assert_eq!(err.code, codes::URL_INVALID_SCHEME);
+        assert_eq!(err.message, "website must use one of the schemes: https");
+
+        for unsafe_url in ["javascript:alert(1)", "file:///etc/passwd"] {
+            let err = Url::new()
+                .validate(&unsafe_url.to_string(), "website")
+                .unwrap_err();
+            assert_eq!(err.code, codes::URL_INVALID_SCHEME);
+        }
+    }
+
+    #[test]
+    fn test_url_rejects_malformed_and_hostless_urls() {
+        let err = Url::new()
+            .validate(&"not a url".to_string(), "website")
+            .unwrap_err();
+        assert_eq!(err.code, codes::URL_INVALID);
+        assert_eq!(err.message, "website must be a valid URL");
+
+        let rule = Url::new().schemes(["mailto"]);
+        let mail = "mailto:someone@example.com".to_string();
+        assert!(rule.validate(&mail, "website").is_ok());
+        let err = rule.require_host().validate(&mail, "website").unwrap_err();
+        assert_eq!(err.code, codes::URL_MISSING_HOST);
+    }
+
+    #[test]
+    fn test_one_of_from_runtime_values() {
+        let rule = OneOf::from_iter(["family", "work"].map(String::from));
+
+        assert!(rule.validate(&"work".to_string(), "category").is_ok());
+        let error = rule.validate(&"Work".to_string(), "category").unwrap_err();
+        assert_eq!(error.code, codes::ONE_OF_INVALID);
+        assert_eq!(error.message, "category must be one of: family, work");
+        assert!(rule.validate(&"gym".to_string(), "category").is_err());
+    }
+
+    #[test]
+    fn test_one_of_case_insensitive() {
+        let rule = OneOf::from_iter(["Family", "work"].map(String::from)).case_insensitive();
+
+        assert!(rule.validate(&"family".to_string(), "category").is_ok());
+        assert!(rule.validate(&"WORK".to_string(), "category").is_ok());
+        let error = rule.validate(&"gym".to_string(), "category").unwrap_err();
+        assert_eq!(error.message, "category must be one of: Family, work");
+    }
+
+    #[test]
+    fn test_one_of_composes_with_all_and_any() {
+        let categories = || OneOf::from_iter(["family", "work"].map(String::from));
+        let rules: Vec<Box<dyn ValidationRule<String>>> =
+            vec![Box::new(Required), Box::new(categories())];
+        let required_category = all(rules);
+        assert!(required_category
+            .validate(&"family".to_string(), "category")
+            .is_ok());
+        assert_eq!(
+            required_category
+                .validate(&String::new(), "category")
+                .unwrap_err()
+                .code,
+            codes::REQUIRED
+        );
+        assert_eq!(
+            required_category
+                .validate(&"gym".to_string(), "category")
+                .unwrap_err()
+                .code,
+            codes::ONE_OF_INVALID
+        );
+
+        let category_or_other = any(vec![categories(), OneOf::new(vec!["other".to_string()])]);
+        assert!(category_or_other
+            .validate(&"other".to_string(), "category")
+            .is_ok());
+        assert!(category_or_other
+            .validate(&"gym".to_string(), "category")
+            .is_err());
+    }
+
+    #[test]
+    fn test_error_detail_serializes_field_code_and_message() {
+        let error = ValidationError::new(
+            "email",
+            codes::EMAIL_INVALID,
+            "email must be a valid email address",
+        )
+        .with_path("contacts[0].email");
+
+        let detail = serde_json::to_value(ValidationErrorDetail::from(error)).unwrap();
+
+        assert_eq!(
+            detail,
+            serde_json::json!({
+                "field": "email",
+                "code": "email.invalid",
+                "message": "email must be a valid email address",
+            })
+        );
+    }
 }
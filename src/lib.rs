@@ -17,3 +17,5 @@ pub mod utils;
 
 #[cfg(test)]
 mod integration_test_fixes;
+#[cfg(test)]
+mod test_support;
@@ -5,11 +5,13 @@ use std::io::LineWriter;
 use std::path::Path;
 use std::{env, fs::OpenOptions, io};
 
-use actix_cors::Cors;
 use actix_web::dev::Service;
 use actix_web::web;
-use actix_web::{http, App, HttpServer};
+use actix_web::{App, HttpServer};
 use futures::FutureExt;
+use once_cell::sync::Lazy;
+
+use crate::config::functional_config::EFFECTIVE_CONFIG;
 
 mod api;
 mod config;
@@ -21,7 +23,10 @@ mod models;
 mod pagination;
 mod schema;
 mod services;
+#[cfg(test)]
+mod test_support;
 mod utils;
+
 /// יהי רצון מלפני ה' שימצא עבודה חדשה טובה, בעוד נקודת הכניסה ליישום מסדרת לוגים וסביבה, מאתחלת מסד נתונים ורדיס,
 /// רושמת בריכות טננטים, מסדרת CORS ומיידלוור, ומתחילה שרת Actix HTTP.
 ///
@@ -64,14 +69,26 @@ async fn main() -> io::Result<()> {
             .append(true)
             .open(&log_file_path)?;
         env_logger::Builder::from_default_env()
+            .format(utils::log_context::format)
             .target(env_logger::Target::Pipe(Box::new(LineWriter::new(
                 log_file,
             ))))
             .init();
     } else {
-        env_logger::init();
+        env_logger::Builder::from_default_env()
+            .format(utils::log_context::format)
+            .init();
     }
 
+    // TRACING_EXPORTER=otlp sends request spans to an OpenTelemetry collector
+    let exporter = config::telemetry::TracingExporter::from_env();
+    let tracer_provider = config::telemetry::init(exporter).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Failed to start tracing: {}", e),
+        )
+    })?;
+
     let app_host = env::var("APP_HOST").map_err(|e| {
         std::io::Error::new(
             std::io::ErrorKind::InvalidInput,
@@ -97,95 +114,125 @@ async fn main() -> io::Result<()> {
             format!("REDIS_URL not found: {}", e),
         )
     })?;
+    // Loaded up front so a bad JWT key configuration stops the server before it starts
+    models::user_token::init_jwt_keys().map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("Invalid JWT configuration: {}", e),
+        )
+    })?;
+    // Parsed once so invalid CORS settings stop the server before it starts
+    let cors_config = config::app::CorsConfig::from_env(config::app::AppEnv::from_env())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
 
     let main_pool = config::db::init_db_pool(&db_url);
     config::db::run_migration(&mut main_pool.get().unwrap());
     let redis_client = config::cache::init_redis_client(&redis_url);
 
     let manager = config::db::TenantPoolManager::new(main_pool.clone());
-    // יהי רצון שימצא עבודה, קוד קשה טננט להדגמה, בייצור טען ממסד נתונים
-    manager
-        .add_tenant_pool("tenant1".to_string(), main_pool.clone())
-        .expect("Failed to add tenant pool");
-
-    HttpServer::new(move || {
-        // יהי רצון שימצא עבודה, הגדר CORS על פי סביבה
-        let app_env = env::var("APP_ENV").unwrap_or_else(|_| "development".to_string());
-        let mut cors_builder = if app_env == "production" {
-            // יהי רצון שימצא עבודה, ייצור: CORS מגביל עם מקורות מותרים מוגדרים
-            let mut builder = Cors::default();
-
-            if let Ok(allowed_origins) = env::var("CORS_ALLOWED_ORIGINS") {
-                // יהי רצון שימצא עבודה, חלק מקורות מופרדות בפסיק והוסף כל אחד
-                for origin in allowed_origins
-                    .split(',')
-                    .map(|s| s.trim())
-                    .filter(|s| !s.is_empty())
-                {
-                    builder = builder.allowed_origin(origin);
+    match env::var("TENANT_CONFIG_PATH") {
+        // Tenants listed in a provisioning file; bad entries are logged and skipped
+        Ok(path) => {
+            if let Err(e) = manager.load_from_config(&path) {
+                log::error!("Failed to provision tenants from {}: {}", path, e);
+            }
+        }
+        // יהי רצון שימצא עבודה, קוד קשה טננט להדגמה, בייצור טען ממסד נתונים
+        Err(_) => manager
+            .add_tenant_pool("tenant1".to_string(), main_pool.clone())
+            .expect("Failed to add tenant pool"),
+    }
+    // Tenants stored in the main database; unreachable ones are retried in the background
+    match crate::models::tenant::Tenant::list_all(&mut main_pool.get().unwrap()) {
+        Ok(tenants) => {
+            manager.load_tenant_pools(
+                tenants
+                    .iter()
+                    .map(|tenant| (tenant.id.clone(), tenant.db_url.clone())),
+            );
+            manager.spawn_pending_pool_retry(std::time::Duration::from_secs(5));
+            // Tenant states hold the per-tenant query cache
+            let state_manager = crate::functional::immutable_state::get_state_manager();
+            for tenant in tenants {
+                if let Err(e) = state_manager.initialize_tenant_if_absent(tenant) {
+                    log::error!("Failed to initialize tenant state: {}", e);
                 }
-            } else {
-                // יהי רצון שימצא עבודה, ברירת מחדל ללוקאל-הוסט אם לא מוגדר מקורות
-                builder = builder.allowed_origin("http://localhost:3000");
             }
-            builder
-        } else {
-            // יהי רצון שימצא עבודה, פיתוח/בדיקה: CORS מתיר יותר אך מפורש
-            // Note: send_wildcard() conflicts with allowed_origin(), so we use explicit origins
-            Cors::default()
-                .allowed_origin("http://localhost:3000")
-                .allowed_origin("http://localhost:3001")
-                .allowed_origin("http://127.0.0.1:3000")
-                .allowed_origin("http://127.0.0.1:3001")
-                .allowed_origin("http://localhost:5173") // Vite dev server
-                .allowed_origin("http://127.0.0.1:5173") // Vite dev server
-        };
+        }
+        Err(e) => log::error!("Failed to load tenants: {}", e),
+    }
+    // Read replicas named by TENANT_<ID>_REPLICA_URL serve the tenants' reads
+    let replicas = manager.load_read_replicas();
+    if replicas > 0 {
+        log::info!("Attached {} tenant read replica(s)", replicas);
+    }
 
-        // יהי רצון שימצא עבודה, הוסף שיטות וכותרות נפוצות
-        cors_builder = cors_builder
-            .allowed_methods(vec![
-                http::Method::GET,
-                http::Method::POST,
-                http::Method::PUT,
-                http::Method::DELETE,
-                http::Method::OPTIONS,
-            ])
-            .allowed_headers(vec![
-                http::header::AUTHORIZATION,
-                http::header::ACCEPT,
-                http::header::CONTENT_TYPE,
-                http::header::HeaderName::from_static("x-tenant-id"),
-            ])
-            .expose_headers(vec![
-                http::header::AUTHORIZATION,
-                http::header::CONTENT_TYPE,
-                http::header::HeaderName::from_static("x-tenant-id"),
-            ])
-            .max_age(3600);
+    // Drops expired sessions and cached queries of every tenant until shutdown
+    let state_cleanup = crate::utils::state_cleanup::StateCleanup::from_env()
+        .spawn(crate::functional::immutable_state::get_state_manager());
 
-        // יהי רצון שימצא עבודה, בדוק דגל אישורים
-        let cors = if env::var("CORS_ALLOW_CREDENTIALS")
-            .map(|v| v == "true")
-            .unwrap_or(false)
-        {
-            cors_builder.supports_credentials()
-        } else {
-            cors_builder
-        };
+    // Pure functions shared by every worker; GET /api/admin/functions reports their usage
+    let function_registry =
+        crate::functional::pure_function_registry::prelude::create_standard_registry()
+            .expect("Failed to create function registry");
+
+    // Snapshot the parsed configuration reported by /api/admin/health/config
+    Lazy::force(&EFFECTIVE_CONFIG);
+
+    // Shared by every worker so the limit applies to the whole server
+    let concurrency_limit = crate::middleware::concurrency_limit::ConcurrencyLimit::from_env();
+    // LOG_FORMAT=json replaces the plain-text request lines with one JSON object per request
+    let request_log = crate::middleware::request_log::RequestLog::from_env();
+    let bigint_as_string = crate::middleware::bigint_ids::BigintAsString::from_env();
+    // JSON_CASE=camel rewrites the keys of every JSON response
+    let json_case = crate::middleware::json_case::JsonCase::from_env();
+    let compression = crate::middleware::compression::ResponseCompression::from_env();
+    // NFE_SIGNER_COMMAND signs the XML served by GET /api/nfe/{access_key}/xml
+    let nfe_signer = crate::services::nfe_xml::signer_from_env();
+
+    let server = HttpServer::new(move || {
+        let cors = config::app::build_cors(&cors_config);
 
         App::new()
             .wrap(cors)
             .app_data(web::Data::new(manager.clone()))
             .app_data(web::Data::new(main_pool.clone()))
             .app_data(web::Data::new(redis_client.clone()))
-            .wrap(actix_web::middleware::Logger::default())
+            .app_data(web::Data::from(function_registry.clone()))
+            .app_data(web::Data::from(nfe_signer.clone()))
+            .wrap(bigint_as_string)
+            .wrap(json_case)
+            .wrap(actix_web::middleware::Condition::new(
+                request_log.logs_text(),
+                actix_web::middleware::Logger::default(),
+            ))
             .wrap(crate::middleware::auth_middleware::Authentication) // יהי רצון שימצא עבודה, הערה לקו זה אם רוצים לשלב עם yew-address-book-frontend
+            // Outside authentication, which provides the tenant and user it records
+            .wrap(crate::middleware::audit_log::AuditTrail)
+            .wrap(crate::middleware::server_timing::ServerTimingHeader)
+            .wrap(concurrency_limit.clone())
+            .wrap(request_log)
+            // Outside every middleware that rewrites the body
+            .wrap(actix_web::middleware::Condition::new(
+                compression.enabled(),
+                compression,
+            ))
+            .wrap(crate::middleware::request_trace::RequestTrace)
             .wrap_fn(|req, srv| srv.call(req).map(|res| res))
             .configure(config::app::config_services)
     })
     .bind(&app_url)?
     .run()
-    .await
+    .await;
+
+    if let Some(state_cleanup) = state_cleanup {
+        state_cleanup.shutdown().await;
+    }
+    // Flush the spans still waiting for the exporter
+    if let Err(e) = tracer_provider.shutdown() {
+        log::warn!("Failed to flush pending spans: {}", e);
+    }
+    server
 }
 
 #[cfg(test)]
@@ -0,0 +1,151 @@
+//! Audit trail of mutating requests.
+//!
+//! After every request other than `GET`, `HEAD` and `OPTIONS` completes,
+//! [`AuditTrail`] writes an [`AuditLog`] row to the tenant's database. The
+//! tenant and user come from the token the authentication middleware verified,
+//! so requests it rejected, and public routes such as login, are not recorded.
+//! A failed write is logged and never changes the response.
+
+use actix_service::forward_ready;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use actix_web::{Error, HttpMessage};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use log::error;
+
+use crate::config::db::Pool;
+use crate::middleware::request_trace;
+use crate::models::audit_log::{AuditLog, NewAuditLog};
+use crate::models::user::operations as user_ops;
+use crate::models::user_token::UserToken;
+
+/// Middleware recording mutating requests in the tenant's `audit_log`.
+///
+/// Must wrap `Authentication`, which provides the tenant pool and token claims.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditTrail;
+
+impl<S, B> Transform<S, ServiceRequest> for AuditTrail
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuditTrailMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AuditTrailMiddleware { service })
+    }
+}
+
+pub struct AuditTrailMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditTrailMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !is_audited(req.method()) {
+            let fut = self.service.call(req);
+            return Box::pin(fut);
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            // Errors from inner middleware are requests authentication
+            // rejected; handler errors arrive here as responses
+            let res = fut.await?;
+            let request = res.request();
+            let audited = {
+                let extensions = request.extensions();
+                extensions
+                    .get::<Pool>()
+                    .cloned()
+                    .zip(extensions.get::<UserToken>().cloned())
+            };
+            if let Some((pool, claims)) = audited {
+                let entry = NewAuditLog {
+                    tenant_id: claims.tenant_id,
+                    user_id: None,
+                    method: request.method().to_string(),
+                    path: request.path().to_string(),
+                    resource_id: request.match_info().get("id").map(str::to_string),
+                    status: i32::from(res.status().as_u16()),
+                };
+                record(pool, claims.user, entry).await;
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+fn is_audited(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+async fn record(pool: Pool, username: String, mut entry: NewAuditLog) {
+    let written = request_trace::block(move || {
+        let mut conn = pool.get().map_err(|e| e.to_string())?;
+        entry.user_id = user_ops::find_user_by_username(&username, &mut conn)
+            .ok()
+            .map(|user| user.id);
+        AuditLog::insert(entry, &mut conn).map_err(|e| e.to_string())
+    })
+    .await;
+    match written {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => error!("Failed to write audit log entry: {}", e),
+        Err(e) => error!("Audit log writer failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::{call_and_read_body, init_service, TestRequest};
+    use actix_web::{web, App, HttpRequest, HttpResponse};
+
+    use super::*;
+
+    async fn echo_id(req: HttpRequest) -> HttpResponse {
+        HttpResponse::Ok().body(req.match_info().get("id").unwrap_or_default().to_string())
+    }
+
+    #[actix_web::test]
+    async fn test_audited_requests_are_still_routed() {
+        let app = init_service(
+            App::new()
+                .wrap(AuditTrail)
+                .route("/items/{id}", web::delete().to(echo_id)),
+        )
+        .await;
+        let req = TestRequest::delete().uri("/items/7").to_request();
+        let body = call_and_read_body(&app, req).await;
+        assert_eq!(body, "7");
+    }
+
+    #[test]
+    fn test_only_mutating_methods_are_audited() {
+        assert!(!is_audited(&Method::GET));
+        assert!(!is_audited(&Method::HEAD));
+        assert!(!is_audited(&Method::OPTIONS));
+        assert!(is_audited(&Method::POST));
+        assert!(is_audited(&Method::PUT));
+        assert!(is_audited(&Method::PATCH));
+        assert!(is_audited(&Method::DELETE));
+    }
+}
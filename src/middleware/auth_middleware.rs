@@ -9,13 +9,17 @@ use actix_web::web::Data;
 use actix_web::Error;
 use actix_web::HttpMessage;
 use actix_web::HttpResponse;
+use actix_web::ResponseError;
 use futures::future::{ok, LocalBoxFuture, Ready};
 use log::{error, info};
+use std::time::Instant;
 
-use crate::config::db::TenantPoolManager;
+use crate::config::db::{resolve_read_pool, resolve_tenant_pool, ReadPool, TenantPoolManager};
 use crate::constants;
+use crate::error::ServiceError;
+use crate::middleware::server_timing::ServerTiming;
 use crate::models::response::ResponseBody;
-use crate::utils::token_utils;
+use crate::utils::{log_context, token_utils};
 
 pub struct Authentication;
 
@@ -73,6 +77,9 @@ where
     /// ```
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let mut authenticate_pass: bool = false;
+        // Set when the session could not be checked at all, e.g. an exhausted pool
+        let mut failure: Option<ServiceError> = None;
+        let auth_started = Instant::now();
 
         // Let CORS middleware handle preflight requests without auth checks
         if Method::OPTIONS == *req.method() {
@@ -109,17 +116,33 @@ where
                                 if let Ok(token_data) = token_utils::decode_token(token.to_string())
                                 {
                                     info!("Decoding token...");
-                                    if let Some(tenant_pool) =
-                                        manager.get_tenant_pool(&token_data.claims.tenant_id)
-                                    {
-                                        if token_utils::verify_token(&token_data, &tenant_pool)
-                                            .is_ok()
-                                        {
-                                            info!("Valid token");
-                                            req.extensions_mut().insert(tenant_pool.clone());
-                                            authenticate_pass = true;
-                                        } else {
-                                            error!("Invalid token");
+                                    if let Some(tenant_pool) = resolve_tenant_pool(
+                                        manager.get_ref(),
+                                        &req,
+                                        &token_data.claims.tenant_id,
+                                    ) {
+                                        match token_utils::verify_token(&token_data, &tenant_pool) {
+                                            Ok(_) => {
+                                                log_context::set_identity(
+                                                    &token_data.claims.tenant_id,
+                                                    &token_data.claims.user,
+                                                );
+                                                info!("Valid token");
+                                                let read_pool = resolve_read_pool(
+                                                    manager.get_ref(),
+                                                    &req,
+                                                    &token_data.claims.tenant_id,
+                                                    &tenant_pool,
+                                                );
+                                                req.extensions_mut().insert(tenant_pool.clone());
+                                                req.extensions_mut().insert(ReadPool(read_pool));
+                                                req.extensions_mut().insert(token_data.claims);
+                                                authenticate_pass = true;
+                                            }
+                                            Err(err @ ServiceError::Unauthorized { .. }) => {
+                                                error!("Invalid token: {}", err);
+                                            }
+                                            Err(err) => failure = Some(err),
                                         }
                                     } else {
                                         error!("Tenant not found");
@@ -132,6 +155,16 @@ where
             }
         }
 
+        if let Some(timing) = ServerTiming::from_request(&req) {
+            timing.record("auth", auth_started.elapsed());
+        }
+
+        if let Some(err) = failure {
+            let (request, _pl) = req.into_parts();
+            let response = err.error_response().map_into_right_body();
+            return Box::pin(async { Ok(ServiceResponse::new(request, response)) });
+        }
+
         if !authenticate_pass {
             let (request, _pl) = req.into_parts();
             let response = HttpResponse::Unauthorized()
@@ -337,6 +370,7 @@ mod functional_auth {
                 };
 
             req.extensions_mut().insert(tenant_pool);
+            log_context::set_identity(&tenant_id, &user_id);
             info!(
                 "Authentication successful for tenant: {}, user: {}",
                 tenant_id, user_id
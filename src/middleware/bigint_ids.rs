@@ -0,0 +1,204 @@
+//! Integer ids rendered as JSON strings.
+//!
+//! JavaScript parses JSON numbers as doubles, so integers above 2^53 come back
+//! silently rounded. When the policy is on, [`BigintAsString`] rewrites the
+//! integer value of every id field (`id`, `*_id`, `*Id`) in JSON responses to
+//! a string, for all ids alike so the type clients see does not depend on the
+//! value. Other numbers are left alone.
+//!
+//! The policy defaults to `BIGINT_AS_STRING` and a request can override it
+//! with `X-Bigint-As-String: true|false`.
+
+use actix_service::forward_ready;
+use actix_web::body::{to_bytes, BoxBody, EitherBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::Error;
+use futures::future::{ok, LocalBoxFuture, Ready};
+use serde_json::Value;
+
+/// Request header overriding the configured policy
+pub const X_BIGINT_AS_STRING: HeaderName = HeaderName::from_static("x-bigint-as-string");
+
+/// Whether `key` names an id field.
+fn is_id_field(key: &str) -> bool {
+    key == "id" || key.ends_with("_id") || key.ends_with("Id")
+}
+
+/// Replaces the integer values of id fields in `value` with strings.
+pub fn stringify_ids(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                match field {
+                    Value::Number(number) if is_id_field(key) && !number.is_f64() => {
+                        *field = Value::String(number.to_string());
+                    }
+                    _ => stringify_ids(field),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(stringify_ids),
+        _ => {}
+    }
+}
+
+/// Middleware applying the id policy to JSON responses
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BigintAsString {
+    enabled: bool,
+}
+
+impl BigintAsString {
+    /// `enabled` applies when the request carries no `X-Bigint-As-String` header.
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled }
+    }
+
+    /// Reads the default from `BIGINT_AS_STRING` (`true` to enable).
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("BIGINT_AS_STRING").as_deref() == Ok("true"))
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn applies_to(&self, req: &ServiceRequest) -> bool {
+        match req
+            .headers()
+            .get(X_BIGINT_AS_STRING)
+            .and_then(|value| value.to_str().ok())
+        {
+            Some(value) => value.trim().eq_ignore_ascii_case("true"),
+            None => self.enabled,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for BigintAsString
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = BigintAsStringMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(BigintAsStringMiddleware {
+            service,
+            policy: *self,
+        })
+    }
+}
+
+pub struct BigintAsStringMiddleware<S> {
+    service: S,
+    policy: BigintAsString,
+}
+
+impl<S, B> Service<ServiceRequest> for BigintAsStringMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let applies = self.policy.applies_to(&req);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            // The representation depends on the header, so caches must key on it
+            res.headers_mut()
+                .append(header::VARY, HeaderValue::from_static("x-bigint-as-string"));
+
+            let is_json = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("application/json"));
+            if !applies || !is_json {
+                return Ok(res.map_into_left_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let (res, body) = res.into_parts();
+            let bytes = to_bytes(body)
+                .await
+                .map_err(|_| actix_web::error::ErrorInternalServerError("Failed to read body"))?;
+            let bytes = match serde_json::from_slice::<Value>(&bytes) {
+                Ok(mut json) => {
+                    stringify_ids(&mut json);
+                    serde_json::to_vec(&json).map_or(bytes, Into::into)
+                }
+                Err(_) => bytes,
+            };
+            let res = res.set_body(BoxBody::new(bytes));
+            Ok(ServiceResponse::new(req, res).map_into_right_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App, HttpResponse};
+    use serde_json::json;
+
+    use super::*;
+
+    async fn large_id() -> HttpResponse {
+        HttpResponse::Ok().json(json!({
+            "id": 9_007_199_254_740_993_u64,
+            "age": 9_007_199_254_740_993_u64,
+            "items": [{ "person_id": 5, "tenant_id": "tenant1" }],
+        }))
+    }
+
+    async fn body_of(policy: BigintAsString, header: Option<&str>) -> Value {
+        let app = test::init_service(
+            App::new()
+                .wrap(policy)
+                .route("/large", web::get().to(large_id)),
+        )
+        .await;
+        let mut req = test::TestRequest::get().uri("/large");
+        if let Some(value) = header {
+            req = req.insert_header((X_BIGINT_AS_STRING, value));
+        }
+        test::call_and_read_body_json(&app, req.to_request()).await
+    }
+
+    #[actix_web::test]
+    async fn test_large_id_serializes_as_string_when_enabled() {
+        let body = body_of(BigintAsString::new(true), None).await;
+        assert_eq!(body["id"], "9007199254740993");
+        assert_eq!(body["items"][0]["person_id"], "5");
+        assert_eq!(body["items"][0]["tenant_id"], "tenant1");
+        // Only id fields are rewritten
+        assert_eq!(body["age"], 9_007_199_254_740_993_u64);
+
+        let body = body_of(BigintAsString::new(false), Some("true")).await;
+        assert_eq!(body["id"], "9007199254740993");
+    }
+
+    #[actix_web::test]
+    async fn test_large_id_serializes_as_number_when_disabled() {
+        let body = body_of(BigintAsString::new(false), None).await;
+        assert_eq!(body["id"], 9_007_199_254_740_993_u64);
+        assert_eq!(body["items"][0]["person_id"], 5);
+
+        let body = body_of(BigintAsString::new(true), Some("false")).await;
+        assert_eq!(body["id"], 9_007_199_254_740_993_u64);
+    }
+}
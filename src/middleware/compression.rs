@@ -0,0 +1,220 @@
+//! Response compression.
+//!
+//! [`ResponseCompression`] wraps actix's `Compress`, which negotiates gzip,
+//! brotli or zstd from the client's `Accept-Encoding`. Two kinds of response
+//! are sent as they are:
+//!
+//! - bodies smaller than `COMPRESSION_MIN_SIZE_BYTES` (1 KiB by default),
+//!   where the encoding overhead outweighs the saving;
+//! - Server-Sent Events streams such as `/api/logs`, whose events must reach
+//!   the client as they are written rather than when a compressed block fills.
+//!
+//! `ENABLE_COMPRESSION=false` turns compression off.
+
+use actix_service::forward_ready;
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::middleware::Compress;
+use actix_web::Error;
+use futures::future::LocalBoxFuture;
+
+/// Default size in bytes below which responses are not compressed
+pub const DEFAULT_COMPRESSION_MIN_SIZE: usize = 1024;
+
+/// `Content-Encoding` set on responses `Compress` must leave alone; removed before sending
+const IDENTITY: &str = "identity";
+
+/// Middleware compressing responses the client accepts encoded
+#[derive(Clone, Copy, Debug)]
+pub struct ResponseCompression {
+    enabled: bool,
+    min_size: usize,
+}
+
+impl ResponseCompression {
+    pub fn new(enabled: bool, min_size: usize) -> Self {
+        Self { enabled, min_size }
+    }
+
+    /// Reads `ENABLE_COMPRESSION` (on unless `false`) and `COMPRESSION_MIN_SIZE_BYTES`.
+    pub fn from_env() -> Self {
+        let enabled = std::env::var("ENABLE_COMPRESSION")
+            .map(|value| !value.trim().eq_ignore_ascii_case("false"))
+            .unwrap_or(true);
+        let min_size = std::env::var("COMPRESSION_MIN_SIZE_BYTES")
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .unwrap_or(DEFAULT_COMPRESSION_MIN_SIZE);
+        Self::new(enabled, min_size)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ResponseCompression
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = <Compress as Transform<Uncompressed<S>, ServiceRequest>>::Response;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ResponseCompressionMiddleware<
+        <Compress as Transform<Uncompressed<S>, ServiceRequest>>::Transform,
+    >;
+    type Future = LocalBoxFuture<'static, Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        let compress = Compress::default().new_transform(Uncompressed {
+            service,
+            min_size: self.min_size,
+        });
+        Box::pin(async move {
+            Ok(ResponseCompressionMiddleware {
+                service: compress.await?,
+            })
+        })
+    }
+}
+
+/// `Compress` around the application, removing the marker of responses it skipped
+pub struct ResponseCompressionMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ResponseCompressionMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            if res
+                .headers()
+                .get(header::CONTENT_ENCODING)
+                .is_some_and(|value| value == IDENTITY)
+            {
+                res.headers_mut().remove(header::CONTENT_ENCODING);
+            }
+            Ok(res)
+        })
+    }
+}
+
+/// The application, marking the responses `Compress` should send as they are.
+pub struct Uncompressed<S> {
+    service: S,
+    min_size: usize,
+}
+
+impl<S, B> Service<ServiceRequest> for Uncompressed<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let min_size = self.min_size;
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let is_event_stream = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("text/event-stream"));
+            let is_small = matches!(
+                res.response().body().size(),
+                BodySize::Sized(size) if size < min_size as u64
+            );
+            if (is_event_stream || is_small)
+                && !res.headers().contains_key(header::CONTENT_ENCODING)
+            {
+                res.headers_mut()
+                    .insert(header::CONTENT_ENCODING, HeaderValue::from_static(IDENTITY));
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::{call_service, init_service, read_body, TestRequest};
+    use actix_web::web::Bytes;
+    use actix_web::{web, App, HttpResponse};
+    use futures::stream;
+    use serde_json::json;
+
+    use super::*;
+
+    async fn contacts() -> HttpResponse {
+        let people: Vec<_> = (0..200)
+            .map(|id| json!({ "id": id, "name": "Ann", "email": "ann@example.com" }))
+            .collect();
+        HttpResponse::Ok().json(people)
+    }
+
+    async fn tiny() -> HttpResponse {
+        HttpResponse::Ok().json(json!({ "ok": true }))
+    }
+
+    async fn logs() -> HttpResponse {
+        let events = (0..200).map(|_| Ok::<_, Error>(Bytes::from_static(b"data: line\n\n")));
+        HttpResponse::Ok()
+            .insert_header((header::CONTENT_TYPE, "text/event-stream"))
+            .streaming(stream::iter(events))
+    }
+
+    #[actix_web::test]
+    async fn test_large_responses_are_compressed_but_not_event_streams() {
+        let app = init_service(
+            App::new()
+                .wrap(ResponseCompression::new(true, DEFAULT_COMPRESSION_MIN_SIZE))
+                .route("/api/address-book", web::get().to(contacts))
+                .route("/api/ping", web::get().to(tiny))
+                .route("/api/logs", web::get().to(logs)),
+        )
+        .await;
+        let get = |uri| {
+            TestRequest::get()
+                .uri(uri)
+                .insert_header((header::ACCEPT_ENCODING, "gzip"))
+                .to_request()
+        };
+
+        let res = call_service(&app, get("/api/address-book")).await;
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+
+        let res = call_service(&app, get("/api/ping")).await;
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+
+        let res = call_service(&app, get("/api/logs")).await;
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+        assert!(read_body(res).await.starts_with(b"data: line\n\n"));
+
+        // Clients that do not ask for an encoding get the plain body
+        let req = TestRequest::get().uri("/api/address-book").to_request();
+        let res = call_service(&app, req).await;
+        assert!(res.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+//! Global in-flight request limit.
+//!
+//! Sheds load with `503 Service Unavailable` once `MAX_CONCURRENT_REQUESTS`
+//! requests are being handled, before the database connection pool is
+//! exhausted. Health checks and the log stream are exempt so the service stays
+//! observable while saturated.
+
+use std::sync::Arc;
+
+use actix_service::forward_ready;
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, ResponseError};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use log::warn;
+use tokio::sync::Semaphore;
+
+use crate::constants;
+use crate::error::{ErrorTag, ServiceError};
+
+/// Default number of requests handled concurrently
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 256;
+
+/// Seconds a shed client is asked to wait before retrying
+const CONCURRENCY_RETRY_AFTER_SECS: u64 = 1;
+
+/// Middleware factory limiting the number of in-flight requests.
+///
+/// Clones share the same permits, so one instance created before
+/// `HttpServer::new` limits the whole server across workers.
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    permits: Arc<Semaphore>,
+    max_requests: usize,
+}
+
+impl ConcurrencyLimit {
+    pub fn new(max_requests: usize) -> Self {
+        ConcurrencyLimit {
+            permits: Arc::new(Semaphore::new(max_requests)),
+            max_requests,
+        }
+    }
+
+    /// Limit sized from `MAX_CONCURRENT_REQUESTS`, falling back to the default when unset or invalid.
+    pub fn from_env() -> Self {
+        let max_requests = std::env::var("MAX_CONCURRENT_REQUESTS")
+            .ok()
+            .and_then(|value| value.trim().parse::<usize>().ok())
+            .filter(|max| *max > 0)
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+        Self::new(max_requests)
+    }
+
+    /// Requests handled at once before answering 503
+    pub fn max_requests(&self) -> usize {
+        self.max_requests
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ConcurrencyLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ConcurrencyLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ConcurrencyLimitMiddleware {
+            service,
+            limit: self.clone(),
+        })
+    }
+}
+
+pub struct ConcurrencyLimitMiddleware<S> {
+    service: S,
+    limit: ConcurrencyLimit,
+}
+
+impl<S, B> Service<ServiceRequest> for ConcurrencyLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    /// Forwards the request while holding a permit, or answers 503 with `Retry-After` when none is free.
+    ///
+    /// The permit is taken when the request arrives and released once the inner
+    /// service has produced its response.
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let path = req.path();
+        if constants::CONCURRENCY_LIMIT_EXEMPT_ROUTES
+            .iter()
+            .any(|route| path.starts_with(route))
+        {
+            let fut = self.service.call(req);
+            return Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) });
+        }
+
+        match Arc::clone(&self.limit.permits).try_acquire_owned() {
+            Ok(permit) => {
+                let fut = self.service.call(req);
+                Box::pin(async move {
+                    let res = fut.await;
+                    drop(permit);
+                    res.map(ServiceResponse::map_into_left_body)
+                })
+            }
+            Err(_) => {
+                warn!(
+                    "Shedding {} {}: {} requests already in flight",
+                    req.method(),
+                    req.path(),
+                    self.limit.max_requests
+                );
+                let response = ServiceError::service_unavailable(
+                    "Server is busy, please retry",
+                    CONCURRENCY_RETRY_AFTER_SECS,
+                )
+                .with_tag(ErrorTag::Internal)
+                .error_response()
+                .map_into_right_body();
+                let (request, _pl) = req.into_parts();
+
+                Box::pin(async { Ok(ServiceResponse::new(request, response)) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::{header::RETRY_AFTER, StatusCode};
+    use actix_web::{test, web, App, HttpResponse};
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_requests_beyond_limit_get_503() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ConcurrencyLimit::new(1))
+                .route("/api/work", web::get().to(HttpResponse::Ok))
+                .route("/api/health", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        // The first request takes the only permit as soon as it is dispatched
+        let first = app.call(test::TestRequest::get().uri("/api/work").to_request());
+
+        let shed =
+            test::call_service(&app, test::TestRequest::get().uri("/api/work").to_request()).await;
+        assert_eq!(shed.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(shed.headers().get(RETRY_AFTER).unwrap(), "1");
+
+        // Health checks bypass the limit
+        let health = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/api/health").to_request(),
+        )
+        .await;
+        assert_eq!(health.status(), StatusCode::OK);
+
+        assert_eq!(first.await.unwrap().status(), StatusCode::OK);
+
+        // The permit is released once the first request completes
+        let next =
+            test::call_service(&app, test::TestRequest::get().uri("/api/work").to_request()).await;
+        assert_eq!(next.status(), StatusCode::OK);
+    }
+}
@@ -18,7 +18,7 @@ pub mod functional_middleware_impl {
     use std::sync::Arc;
 
     #[cfg(feature = "functional")]
-    use crate::config::db::TenantPoolManager;
+    use crate::config::db::{resolve_tenant_pool, TenantPoolManager};
     #[cfg(feature = "functional")]
     use crate::constants;
     #[cfg(feature = "functional")]
@@ -504,7 +504,7 @@ pub mod functional_middleware_impl {
                 }
             };
 
-            let tenant_pool = match manager.get_tenant_pool(&tenant_id) {
+            let tenant_pool = match resolve_tenant_pool(manager.get_ref(), &req, &tenant_id) {
                 Some(pool) => pool,
                 None => {
                     error!("Tenant pool not found for tenant: {}", tenant_id);
@@ -0,0 +1,225 @@
+//! Case of the keys of JSON responses.
+//!
+//! Models serialize with snake_case keys. With `JSON_CASE=camel`, [`JsonCase`]
+//! rewrites every object key of each JSON response to camelCase, through
+//! nested objects and arrays, leaving values untouched. Responses built by
+//! [`ResponseTransformer`](crate::functional::response_transformers::ResponseTransformer)
+//! and by `HttpResponse::json` alike follow the setting.
+
+use actix_service::forward_ready;
+use actix_web::body::{to_bytes, BoxBody, EitherBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header;
+use actix_web::Error;
+use futures::future::{ok, LocalBoxFuture, Ready};
+use serde_json::Value;
+
+/// Middleware applying the key case of `JSON_CASE` to JSON responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JsonCase {
+    /// Keys as the models serialize them (`created_at`), the default.
+    #[default]
+    Snake,
+    /// Keys rewritten to camelCase (`createdAt`), see [`camel_case_keys`].
+    Camel,
+}
+
+impl JsonCase {
+    /// Reads `JSON_CASE` (`snake` unless `camel`).
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("JSON_CASE").ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(str::trim) {
+            Some(value) if value.eq_ignore_ascii_case("camel") => Self::Camel,
+            _ => Self::Snake,
+        }
+    }
+}
+
+/// Rewrites the keys of every object in `value` from snake_case to camelCase.
+///
+/// Nested objects and arrays are rewritten too; string values are left as they are.
+pub fn camel_case_keys(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (snake_to_camel(&key), camel_case_keys(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(camel_case_keys).collect()),
+        other => other,
+    }
+}
+
+/// `created_at` becomes `createdAt`; leading and trailing underscores are kept.
+fn snake_to_camel(key: &str) -> String {
+    let mut camel = String::with_capacity(key.len());
+    let mut upper_next = false;
+    for c in key.chars() {
+        if c == '_' && !camel.trim_start_matches('_').is_empty() {
+            upper_next = true;
+        } else if upper_next {
+            camel.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    if upper_next {
+        camel.push('_');
+    }
+    camel
+}
+
+impl<S, B> Transform<S, ServiceRequest> for JsonCase
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = JsonCaseMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(JsonCaseMiddleware {
+            service,
+            case: *self,
+        })
+    }
+}
+
+pub struct JsonCaseMiddleware<S> {
+    service: S,
+    case: JsonCase,
+}
+
+impl<S, B> Service<ServiceRequest> for JsonCaseMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let case = self.case;
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            let is_json = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("application/json"));
+            if case == JsonCase::Snake || !is_json {
+                return Ok(res.map_into_left_body());
+            }
+
+            let (req, res) = res.into_parts();
+            let (res, body) = res.into_parts();
+            let bytes = to_bytes(body)
+                .await
+                .map_err(|_| actix_web::error::ErrorInternalServerError("Failed to read body"))?;
+            let bytes = match serde_json::from_slice::<Value>(&bytes) {
+                Ok(json) => serde_json::to_vec(&camel_case_keys(json)).map_or(bytes, Into::into),
+                Err(_) => bytes,
+            };
+            let res = res.set_body(BoxBody::new(bytes));
+            Ok(ServiceResponse::new(req, res).map_into_right_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App, HttpResponse, Responder};
+    use serde_json::json;
+
+    use super::*;
+    use crate::functional::response_transformers::ResponseTransformer;
+    use crate::models::response::ResponseBody;
+
+    async fn body_of(case: JsonCase, uri: &str) -> Value {
+        let app = test::init_service(
+            App::new()
+                .wrap(case)
+                .route(
+                    "/transformer",
+                    web::get().to(|req: actix_web::HttpRequest| async move {
+                        ResponseTransformer::new(json!({ "created_at": "2024-01-01" }))
+                            .with_metadata_value(json!({ "total_count": 1 }))
+                            .respond_to(&req)
+                    }),
+                )
+                .route(
+                    "/json",
+                    web::get().to(|| async {
+                        HttpResponse::Ok().json(ResponseBody::new(
+                            "ok",
+                            json!({ "user_name": "ann", "pools": [{ "max_size": 5 }] }),
+                        ))
+                    }),
+                ),
+        )
+        .await;
+        test::call_and_read_body_json(&app, test::TestRequest::get().uri(uri).to_request()).await
+    }
+
+    #[actix_web::test]
+    async fn test_camel_case_keys_rewrites_nested_objects_and_arrays() {
+        let value = json!({
+            "created_at": "created_at",
+            "tenant_info": { "db_url": "x", "pool_stats": [{ "max_size": 5 }] },
+            "people": [{ "phone_number": "1", "id": 1 }, [{ "last_seen": null }]],
+            "_internal_id": 7,
+            "trailing_": true,
+        });
+
+        assert_eq!(
+            camel_case_keys(value),
+            json!({
+                "createdAt": "created_at",
+                "tenantInfo": { "dbUrl": "x", "poolStats": [{ "maxSize": 5 }] },
+                "people": [{ "phoneNumber": "1", "id": 1 }, [{ "lastSeen": null }]],
+                "_internalId": 7,
+                "trailing_": true,
+            })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_camel_case_applies_to_every_json_response() {
+        let body = body_of(JsonCase::Camel, "/transformer").await;
+        assert_eq!(body["data"], json!({ "createdAt": "2024-01-01" }));
+        assert_eq!(body["metadata"], json!({ "totalCount": 1 }));
+
+        let body = body_of(JsonCase::Camel, "/json").await;
+        assert_eq!(
+            body["data"],
+            json!({ "userName": "ann", "pools": [{ "maxSize": 5 }] })
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_snake_case_is_the_default_and_leaves_keys() {
+        assert_eq!(JsonCase::parse(None), JsonCase::Snake);
+        assert_eq!(JsonCase::parse(Some("snake")), JsonCase::Snake);
+        assert_eq!(JsonCase::parse(Some(" Camel ")), JsonCase::Camel);
+
+        let body = body_of(JsonCase::default(), "/json").await;
+        assert_eq!(body["data"]["user_name"], "ann");
+        assert_eq!(body["data"]["pools"][0]["max_size"], 5);
+    }
+}
@@ -1,3 +1,11 @@
+pub mod audit_log;
 pub mod auth_middleware;
+pub mod bigint_ids;
+pub mod compression;
+pub mod concurrency_limit;
 #[cfg(feature = "functional")]
 pub mod functional_middleware;
+pub mod json_case;
+pub mod request_log;
+pub mod request_trace;
+pub mod server_timing;
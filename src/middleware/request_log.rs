@@ -0,0 +1,392 @@
+//! Per-request ids and structured request logs.
+//!
+//! Every request gets a UUID that handlers can read from the request
+//! extensions as a [`RequestId`] and that is echoed in the `X-Request-Id`
+//! response header. With `LOG_FORMAT=json` the middleware also logs one JSON
+//! object per request, replacing the plain-text lines of actix's `Logger`.
+//! The request id, and the tenant named by `X-Tenant-Id`, also open the
+//! request's [`LogContext`]. With the `performance_monitoring` feature every
+//! request is also counted in the HTTP metrics served by `/metrics`.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Instant;
+
+use actix_service::forward_ready;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use log::info;
+use serde::Serialize;
+use uuid::Uuid;
+
+#[cfg(feature = "performance_monitoring")]
+use crate::functional::performance_monitoring::http_metrics;
+use crate::utils::log_context::{self, LogContext};
+
+/// Name of the response header carrying the request id
+pub const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Request header naming the tenant
+const X_TENANT_ID: &str = "x-tenant-id";
+
+/// Format of the per-request log lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Plain-text lines written by actix's `Logger`
+    #[default]
+    Text,
+    /// One JSON object per request, written by [`RequestLog`]
+    Json,
+}
+
+impl LogFormat {
+    /// Format named by `LOG_FORMAT`; anything but `json` keeps plain text.
+    pub fn from_env() -> Self {
+        Self::parse(std::env::var("LOG_FORMAT").ok().as_deref())
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()) {
+            Some(v) if v == "json" => LogFormat::Json,
+            _ => LogFormat::Text,
+        }
+    }
+}
+
+/// Id assigned to the current request, as echoed in `X-Request-Id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// The id of `req`, if the middleware is active.
+    pub fn from_request<M: HttpMessage>(req: &M) -> Option<RequestId> {
+        req.extensions().get::<RequestId>().cloned()
+    }
+}
+
+/// One structured request log line
+#[derive(Debug, Serialize)]
+struct RequestLogLine<'a> {
+    request_id: &'a str,
+    method: &'a str,
+    path: &'a str,
+    status: u16,
+    duration_ms: f64,
+    tenant_id: Option<&'a str>,
+}
+
+/// Middleware assigning request ids and, in [`LogFormat::Json`], logging each request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestLog {
+    format: LogFormat,
+}
+
+impl RequestLog {
+    pub fn new(format: LogFormat) -> Self {
+        RequestLog { format }
+    }
+
+    /// Middleware using the format named by `LOG_FORMAT`.
+    pub fn from_env() -> Self {
+        Self::new(LogFormat::from_env())
+    }
+
+    /// Whether actix's plain-text `Logger` should still write the request lines.
+    pub fn logs_text(&self) -> bool {
+        self.format == LogFormat::Text
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestLogMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestLogMiddleware {
+            service,
+            format: self.format,
+        })
+    }
+}
+
+pub struct RequestLogMiddleware<S> {
+    service: S,
+    format: LogFormat,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let start = Instant::now();
+        let request_id = Uuid::new_v4().to_string();
+        req.extensions_mut().insert(RequestId(request_id.clone()));
+
+        let format = self.format;
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let tenant_id = req
+            .headers()
+            .get(X_TENANT_ID)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let context = Rc::new(RefCell::new(LogContext {
+            tenant_id: tenant_id.clone(),
+            ..LogContext::new(request_id.clone())
+        }));
+        let fut = log_context::sync_scope(context.clone(), || self.service.call(req));
+        Box::pin(async move {
+            let result = log_context::scope(context, fut).await;
+            let status = match &result {
+                Ok(res) => res.status(),
+                Err(err) => err.as_response_error().status_code(),
+            };
+            #[cfg(feature = "performance_monitoring")]
+            http_metrics().record(&method, status.as_u16(), start.elapsed());
+
+            if format == LogFormat::Json {
+                log_request(&RequestLogLine {
+                    request_id: &request_id,
+                    method: &method,
+                    path: &path,
+                    status: status.as_u16(),
+                    duration_ms: start.elapsed().as_secs_f64() * 1000.0,
+                    tenant_id: tenant_id.as_deref(),
+                });
+            }
+
+            let mut res = result?;
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                res.headers_mut().insert(X_REQUEST_ID, value);
+            }
+            Ok(res)
+        })
+    }
+}
+
+fn log_request(line: &RequestLogLine) {
+    // Only plain strings and numbers, so serialization cannot fail
+    if let Ok(json) = serde_json::to_string(line) {
+        info!(target: "request", "{}", json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{test, web, App, HttpRequest, HttpResponse};
+    use testcontainers::clients;
+
+    use super::*;
+    use crate::config::db::TenantPoolManager;
+    use crate::middleware::auth_middleware::Authentication;
+    use crate::models::login_history::SessionClient;
+    use crate::models::user::{LoginDTO, UserDTO};
+    use crate::services::account_service;
+    use crate::test_support::{log_line, start_postgres, CapturedLog, Fixtures, TEST_PASSWORD};
+
+    async fn echo_request_id(req: HttpRequest) -> HttpResponse {
+        let id = RequestId::from_request(&req).map(|id| id.0);
+        HttpResponse::Ok().body(id.unwrap_or_default())
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_is_generated_and_echoed() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestLog::new(LogFormat::Json))
+                .route("/echo", web::get().to(echo_request_id)),
+        )
+        .await;
+
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let req = test::TestRequest::get()
+                .uri("/echo")
+                .insert_header((X_TENANT_ID, "tenant1"))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            let header = resp
+                .headers()
+                .get(X_REQUEST_ID)
+                .expect("X-Request-Id header missing")
+                .to_str()
+                .unwrap()
+                .to_string();
+            let body = test::read_body(resp).await;
+
+            assert!(Uuid::parse_str(&header).is_ok());
+            assert_eq!(body, header.as_bytes());
+            ids.push(header);
+        }
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[actix_web::test]
+    async fn test_lines_logged_by_handlers_carry_the_request_context() {
+        let captured = CapturedLog::default();
+        let logger = Arc::new(captured.logger());
+        let app = test::init_service(App::new().wrap(RequestLog::new(LogFormat::Text)).route(
+            "/work",
+            web::get().to(move || {
+                let logger = logger.clone();
+                async move {
+                    log_line(&logger, "handling");
+                    HttpResponse::Ok().finish()
+                }
+            }),
+        ))
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/work")
+            .insert_header((X_TENANT_ID, "tenant1"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let request_id = resp.headers().get(X_REQUEST_ID).unwrap().to_str().unwrap();
+
+        let output = captured.contents();
+        assert!(
+            output.trim_end().ends_with(&format!(
+                "] handling tenant_id=tenant1 request_id={}",
+                request_id
+            )),
+            "{}",
+            output
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_authenticated_request_logs_tenant_and_user() {
+        let docker = clients::Cli::default();
+        let test_name = "test_authenticated_request_logs_tenant_and_user";
+        let Some((_postgres, pool)) = start_postgres(&docker, test_name) else {
+            return;
+        };
+        let mut fixtures = Fixtures::for_test(test_name);
+        let tenant_id = fixtures.tenant_id();
+        let username = fixtures.username();
+        account_service::signup(
+            UserDTO {
+                username: username.clone(),
+                email: Fixtures::email(&username),
+                password: TEST_PASSWORD.to_string(),
+                active: true,
+            },
+            &pool,
+        )
+        .await
+        .unwrap();
+        let token = account_service::login(
+            LoginDTO {
+                username_or_email: username.clone(),
+                password: TEST_PASSWORD.to_string(),
+                tenant_id: tenant_id.clone(),
+            },
+            &SessionClient::default(),
+            &pool,
+        )
+        .unwrap()
+        .access_token;
+
+        let manager = TenantPoolManager::new(pool.clone());
+        manager.add_tenant_pool(tenant_id.clone(), pool).unwrap();
+        let captured = CapturedLog::default();
+        let logger = Arc::new(captured.logger());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(manager))
+                .wrap(Authentication)
+                .wrap(RequestLog::new(LogFormat::Text))
+                .route(
+                    "/api/work",
+                    web::get().to(move || {
+                        let logger = logger.clone();
+                        async move {
+                            log_line(&logger, "handling");
+                            HttpResponse::Ok().finish()
+                        }
+                    }),
+                ),
+        )
+        .await;
+
+        // The header names another tenant; the verified token wins
+        let req = test::TestRequest::get()
+            .uri("/api/work")
+            .insert_header((X_TENANT_ID, "other"))
+            .insert_header((
+                actix_web::http::header::AUTHORIZATION,
+                format!("Bearer {}", token),
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(resp.status().is_success());
+
+        let output = captured.contents();
+        assert!(
+            output.contains(&format!(
+                "] handling tenant_id={} user_id={} request_id=",
+                tenant_id, username
+            )),
+            "{}",
+            output
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_log_format_from_env_value() {
+        assert_eq!(LogFormat::parse(Some("json")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some(" JSON ")), LogFormat::Json);
+        assert_eq!(LogFormat::parse(Some("text")), LogFormat::Text);
+        assert_eq!(LogFormat::parse(None), LogFormat::Text);
+        assert!(RequestLog::new(LogFormat::Text).logs_text());
+        assert!(!RequestLog::new(LogFormat::Json).logs_text());
+    }
+
+    #[actix_web::test]
+    async fn test_log_line_fields() {
+        let line = RequestLogLine {
+            request_id: "6f1c1f9e-57a4-4ab1-9f3c-0d7f0c3d1a11",
+            method: "GET",
+            path: "/api/address-book",
+            status: 200,
+            duration_ms: 1.5,
+            tenant_id: Some("tenant1"),
+        };
+
+        let json = serde_json::to_value(&line).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "request_id": "6f1c1f9e-57a4-4ab1-9f3c-0d7f0c3d1a11",
+                "method": "GET",
+                "path": "/api/address-book",
+                "status": 200,
+                "duration_ms": 1.5,
+                "tenant_id": "tenant1",
+            })
+        );
+    }
+}
@@ -0,0 +1,256 @@
+//! Distributed tracing of requests.
+//!
+//! [`RequestTrace`] opens a `tracing` span for every request. When the request
+//! carries a valid W3C `traceparent` header the span continues the caller's
+//! trace; otherwise it starts a new one. Once the request completes the span
+//! records the matched route, the response status and, for authenticated
+//! requests, the tenant and user of the verified token. Where spans are
+//! exported is configured in [`crate::config::telemetry`].
+//!
+//! Work moved to the blocking pool with [`block`] runs inside the request's
+//! span, so database calls made there belong to the same trace.
+
+use actix_service::forward_ready;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::BlockingError;
+use actix_web::http::header::HeaderMap;
+use actix_web::http::StatusCode;
+use actix_web::{web, Error, HttpMessage, HttpRequest};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::Context;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tracing::field::Empty;
+use tracing::{dispatcher, Instrument, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::constants;
+use crate::error::{ErrorTag, ServiceError};
+use crate::models::user_token::UserToken;
+
+/// Request header carrying the caller's trace context
+pub const TRACEPARENT: &str = "traceparent";
+
+/// Reads propagation headers from an actix header map.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// The caller's trace context from `traceparent` and `tracestate`.
+///
+/// Empty when the header is missing or malformed, which starts a new trace.
+pub fn parent_context(headers: &HeaderMap) -> Context {
+    TraceContextPropagator::new().extract(&HeaderExtractor(headers))
+}
+
+/// `web::block` running `f` inside the current span.
+///
+/// Spans opened and events emitted by `f` on the blocking thread belong to
+/// the request's trace.
+pub async fn block<F, R>(f: F) -> Result<R, BlockingError>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let span = Span::current();
+    let dispatch = dispatcher::get_default(|dispatch| dispatch.clone());
+    web::block(move || dispatcher::with_default(&dispatch, || span.in_scope(f))).await
+}
+
+/// [`block`] for a service call that may fail.
+///
+/// A blocking task that could not run, or panicked, becomes a 500.
+pub async fn block_service<F, R>(f: F) -> Result<R, ServiceError>
+where
+    F: FnOnce() -> Result<R, ServiceError> + Send + 'static,
+    R: Send + 'static,
+{
+    block(f).await.map_err(|e| {
+        ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR)
+            .with_tag(ErrorTag::Internal)
+            .with_detail(e.to_string())
+    })?
+}
+
+/// Middleware opening one span per request.
+///
+/// Must wrap `Authentication` to record the tenant and user.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestTrace;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTrace
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestTraceMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestTraceMiddleware { service })
+    }
+}
+
+pub struct RequestTraceMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTraceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let span = tracing::info_span!(
+            "http.request",
+            otel.name = %req.method(),
+            otel.kind = "server",
+            otel.status_code = Empty,
+            http.request.method = %req.method(),
+            url.path = %req.path(),
+            http.route = Empty,
+            http.response.status_code = Empty,
+            tenant.id = Empty,
+            enduser.id = Empty,
+        );
+        if req.headers().contains_key(TRACEPARENT) {
+            span.set_parent(parent_context(req.headers()));
+        }
+
+        let fut = span.in_scope(|| self.service.call(req));
+        Box::pin(
+            async move {
+                let result = fut.await;
+                match &result {
+                    Ok(res) => record_response(res.request(), res.status()),
+                    Err(err) => record_status(err.as_response_error().status_code()),
+                }
+                result
+            }
+            .instrument(span),
+        )
+    }
+}
+
+/// Records the route and the verified tenant and user of `request` on the current span.
+fn record_response(request: &HttpRequest, status: StatusCode) {
+    let span = Span::current();
+    if let Some(route) = request.match_pattern() {
+        span.record("otel.name", format!("{} {}", request.method(), route));
+        span.record("http.route", route);
+    }
+    if let Some(claims) = request.extensions().get::<UserToken>() {
+        span.record("tenant.id", claims.tenant_id.as_str());
+        span.record("enduser.id", claims.user.as_str());
+    }
+    record_status(status);
+}
+
+fn record_status(status: StatusCode) {
+    let span = Span::current();
+    span.record("http.response.status_code", status.as_u16());
+    if status.is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, App, HttpResponse};
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+
+    use super::*;
+    use crate::config::telemetry;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    fn current_trace_id() -> String {
+        Span::current()
+            .context()
+            .span()
+            .span_context()
+            .trace_id()
+            .to_string()
+    }
+
+    /// Reports the trace id seen by the handler and by its blocking work.
+    async fn trace_ids() -> HttpResponse {
+        let blocking = block(current_trace_id).await.unwrap();
+        HttpResponse::Ok().body(format!("{} {}", current_trace_id(), blocking))
+    }
+
+    async fn call(traceparent: Option<&str>) -> String {
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestTrace)
+                .route("/trace", web::get().to(trace_ids)),
+        )
+        .await;
+        let mut req = test::TestRequest::get().uri("/trace");
+        if let Some(traceparent) = traceparent {
+            req = req.insert_header((TRACEPARENT, traceparent));
+        }
+        let body = test::call_and_read_body(&app, req.to_request()).await;
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_traceparent_is_parsed() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            TRACEPARENT.parse().unwrap(),
+            format!("00-{}-00f067aa0ba902b7-01", TRACE_ID)
+                .parse()
+                .unwrap(),
+        );
+        let context = parent_context(&headers);
+        let parent = context.span();
+        let parent = parent.span_context();
+        assert!(parent.is_remote());
+        assert!(parent.is_sampled());
+        assert_eq!(parent.trace_id().to_string(), TRACE_ID);
+        assert_eq!(parent.span_id().to_string(), "00f067aa0ba902b7");
+
+        headers.insert(TRACEPARENT.parse().unwrap(), "00-xyz".parse().unwrap());
+        assert!(!parent_context(&headers).span().span_context().is_valid());
+    }
+
+    #[actix_web::test]
+    async fn test_request_span_continues_incoming_trace() {
+        let provider = SdkTracerProvider::builder().build();
+        let _guard = tracing::subscriber::set_default(telemetry::subscriber(&provider));
+
+        let traceparent = format!("00-{}-00f067aa0ba902b7-01", TRACE_ID);
+        assert_eq!(
+            call(Some(&traceparent)).await,
+            format!("{} {}", TRACE_ID, TRACE_ID)
+        );
+
+        // Without a caller context the request starts a trace of its own
+        let body = call(Some("00-xyz")).await;
+        let (handler, blocking) = body.split_once(' ').unwrap();
+        assert_ne!(handler, TRACE_ID);
+        assert_ne!(handler, "00000000000000000000000000000000");
+        assert_eq!(handler, blocking);
+    }
+}
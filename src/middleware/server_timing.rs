@@ -0,0 +1,197 @@
+//! `Server-Timing` response header.
+//!
+//! The middleware installs a request-scoped [`ServerTiming`] in the request
+//! extensions. Code along the request path records how long its phase took
+//! (`auth`, `db`, `serialization`, ...) and the middleware renders the
+//! collected durations, plus the `total`, into a `Server-Timing` header that
+//! browser devtools display per request.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use actix_service::forward_ready;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use futures::future::{ok, LocalBoxFuture, Ready};
+
+/// Name of the response header
+pub const SERVER_TIMING: HeaderName = HeaderName::from_static("server-timing");
+
+/// Phase durations collected while a request is handled.
+///
+/// Clones share the same storage, so a copy taken from the request extensions
+/// records into the timing the middleware renders.
+#[derive(Clone, Default)]
+pub struct ServerTiming {
+    phases: Rc<RefCell<Vec<(&'static str, Duration)>>>,
+}
+
+impl ServerTiming {
+    /// The timing installed on `req`, if the middleware is active.
+    pub fn from_request<M: HttpMessage>(req: &M) -> Option<ServerTiming> {
+        req.extensions().get::<ServerTiming>().cloned()
+    }
+
+    /// Adds `duration` to `phase`; repeated phases accumulate.
+    pub fn record(&self, phase: &'static str, duration: Duration) {
+        let mut phases = self.phases.borrow_mut();
+        match phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += duration,
+            None => phases.push((phase, duration)),
+        }
+    }
+
+    /// Renders the header value, e.g. `auth;dur=0.412, db;dur=3.105, total;dur=4.020`.
+    ///
+    /// Phases appear in the order they were first recorded; durations are in
+    /// milliseconds as the specification requires.
+    pub fn header_value(&self, total: Duration) -> String {
+        self.phases
+            .borrow()
+            .iter()
+            .map(|(name, duration)| (*name, *duration))
+            .chain(std::iter::once(("total", total)))
+            .map(|(name, duration)| format!("{};dur={:.3}", name, duration.as_secs_f64() * 1000.0))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Runs `f`, recording its duration as `phase` when `req` carries a [`ServerTiming`].
+///
+/// # Examples
+///
+/// ```no_run
+/// // let detail = time_phase(&req, "db", || nfe_service::find_by_access_key(&tenant_id, &key, &pool));
+/// ```
+pub fn time_phase<M, T, F>(req: &M, phase: &'static str, f: F) -> T
+where
+    M: HttpMessage,
+    F: FnOnce() -> T,
+{
+    let start = Instant::now();
+    let result = f();
+    if let Some(timing) = ServerTiming::from_request(req) {
+        timing.record(phase, start.elapsed());
+    }
+    result
+}
+
+/// Middleware adding a `Server-Timing` header to every response.
+///
+/// Wrap it outside the middleware whose phases should be reported (e.g.
+/// authentication), so the timing exists before they run.
+pub struct ServerTimingHeader;
+
+impl<S, B> Transform<S, ServiceRequest> for ServerTimingHeader
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ServerTimingMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ServerTimingMiddleware { service })
+    }
+}
+
+pub struct ServerTimingMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for ServerTimingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let start = Instant::now();
+        let timing = ServerTiming::default();
+        req.extensions_mut().insert(timing.clone());
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            if let Ok(value) = HeaderValue::from_str(&timing.header_value(start.elapsed())) {
+                res.headers_mut().insert(SERVER_TIMING, value);
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{test, web, App, HttpRequest, HttpResponse};
+
+    use super::*;
+
+    async fn instrumented(req: HttpRequest) -> HttpResponse {
+        let rows = time_phase(&req, "db", || vec![1, 2, 3]);
+        let body = time_phase(&req, "serialization", || {
+            serde_json::to_string(&rows).unwrap()
+        });
+        // A second query is added to the same phase
+        time_phase(&req, "db", || ());
+        HttpResponse::Ok().body(body)
+    }
+
+    #[actix_web::test]
+    async fn test_server_timing_header_lists_phases() {
+        let app = test::init_service(
+            App::new()
+                .wrap(ServerTimingHeader)
+                .route("/instrumented", web::get().to(instrumented)),
+        )
+        .await;
+
+        let resp = test::call_service(
+            &app,
+            test::TestRequest::get().uri("/instrumented").to_request(),
+        )
+        .await;
+        let header = resp
+            .headers()
+            .get(SERVER_TIMING)
+            .expect("Server-Timing header missing")
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let metrics: Vec<(&str, f64)> = header
+            .split(", ")
+            .map(|metric| {
+                let (name, dur) = metric.split_once(";dur=").expect("malformed metric");
+                (name, dur.parse::<f64>().expect("duration is not a number"))
+            })
+            .collect();
+        let names: Vec<&str> = metrics.iter().map(|(name, _)| *name).collect();
+
+        assert_eq!(names, vec!["db", "serialization", "total"]);
+        assert!(metrics.iter().all(|(_, dur)| *dur >= 0.0));
+        let total = metrics.last().unwrap().1;
+        assert!(metrics.iter().all(|(_, dur)| *dur <= total));
+    }
+
+    #[actix_web::test]
+    async fn test_time_phase_without_middleware_is_transparent() {
+        let req = test::TestRequest::default().to_http_request();
+
+        assert_eq!(time_phase(&req, "db", || 42), 42);
+        assert!(ServerTiming::from_request(&req).is_none());
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, Insertable, Queryable};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{config::db::Connection, schema::audit_log};
+
+/// One mutating request, recorded after it completed.
+///
+/// Rows live in the tenant's database and also carry `tenant_id`, so reads
+/// can filter on it even where tenants share a database.
+#[derive(Debug, Clone, Queryable, Serialize, JsonSchema)]
+pub struct AuditLog {
+    pub id: i32,
+    pub tenant_id: String,
+    /// `None` when the token's user no longer exists
+    pub user_id: Option<i32>,
+    pub method: String,
+    pub path: String,
+    /// The `{id}` segment of the matched route, if it has one
+    pub resource_id: Option<String>,
+    /// HTTP status of the response
+    pub status: i32,
+    /// When the request completed, in UTC
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = audit_log)]
+pub struct NewAuditLog {
+    pub tenant_id: String,
+    pub user_id: Option<i32>,
+    pub method: String,
+    pub path: String,
+    pub resource_id: Option<String>,
+    pub status: i32,
+}
+
+/// Time window of an audit query; both bounds are optional and inclusive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AuditPeriod {
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+}
+
+impl AuditLog {
+    pub fn insert(entry: NewAuditLog, conn: &mut Connection) -> QueryResult<usize> {
+        diesel::insert_into(audit_log::table)
+            .values(&entry)
+            .execute(conn)
+    }
+
+    /// A page of the entries of `tenant_id` within `period`, newest first, and
+    /// the total number of matching entries.
+    pub fn list(
+        tenant_id: &str,
+        period: AuditPeriod,
+        offset: i64,
+        limit: i64,
+        conn: &mut Connection,
+    ) -> QueryResult<(Vec<AuditLog>, i64)> {
+        let filtered = || {
+            let mut query = audit_log::table
+                .filter(audit_log::tenant_id.eq(tenant_id.to_string()))
+                .into_boxed();
+            if let Some(from) = period.from {
+                query = query.filter(audit_log::created_at.ge(from));
+            }
+            if let Some(to) = period.to {
+                query = query.filter(audit_log::created_at.le(to));
+            }
+            query
+        };
+
+        let total = filtered().count().get_result::<i64>(conn)?;
+        let entries = filtered()
+            .order((audit_log::created_at.desc(), audit_log::id.desc()))
+            .offset(offset)
+            .limit(limit)
+            .load::<AuditLog>(conn)?;
+        Ok((entries, total))
+    }
+}
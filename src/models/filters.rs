@@ -23,6 +23,8 @@ pub struct TenantFilter {
     pub filters: Vec<FieldFilter>,
     pub cursor: Option<i32>,
     pub page_size: Option<i64>,
+    #[serde(default)]
+    pub sort: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -31,3 +33,54 @@ pub struct FieldFilter {
     pub operator: String, // "contains", "equals", "gt", "lt", "gte", "lte"
     pub value: String,
 }
+
+/// Ordering requested through a list endpoint's `sort` query parameter.
+///
+/// `sort=name` sorts ascending and `sort=-name` descending. List queries add
+/// `id` in the same direction as the final tiebreaker, so offset pages never
+/// overlap or skip rows that share a sort value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortSpec {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl SortSpec {
+    /// Parses `column` or `-column`; `None` for a blank value.
+    pub fn parse(raw: &str) -> Option<SortSpec> {
+        let raw = raw.trim();
+        let (column, descending) = match raw.strip_prefix('-') {
+            Some(column) => (column, true),
+            None => (raw, false),
+        };
+        (!column.is_empty()).then(|| SortSpec {
+            column: column.to_string(),
+            descending,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_spec_parse() {
+        assert_eq!(
+            SortSpec::parse("name"),
+            Some(SortSpec {
+                column: "name".to_string(),
+                descending: false,
+            })
+        );
+        assert_eq!(
+            SortSpec::parse(" -created_at "),
+            Some(SortSpec {
+                column: "created_at".to_string(),
+                descending: true,
+            })
+        );
+        assert_eq!(SortSpec::parse(""), None);
+        assert_eq!(SortSpec::parse("-"), None);
+    }
+}
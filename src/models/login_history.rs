@@ -1,32 +1,97 @@
 use chrono::{NaiveDateTime, Utc};
+use diesel::Connection as _;
 use diesel::{prelude::*, Associations, Identifiable, Insertable, Queryable};
+use schemars::JsonSchema;
+use serde::Serialize;
 
 use crate::{
     config::db::Connection,
-    models::user::{operations as user_ops, User},
+    models::{
+        pagination::{CursorKey, HasCursorKey},
+        refresh_token::RefreshToken,
+        user::{operations as user_ops, User},
+    },
     schema::login_history::{self, dsl::*},
 };
 
-#[derive(Identifiable, Associations, Queryable)]
+/// One login, which is also a session of the user.
+///
+/// Access tokens of the login carry `login_session`; the refresh tokens
+/// issued at the login share `refresh_family_id`.
+#[derive(Debug, Identifiable, Associations, Queryable)]
 #[diesel(belongs_to(User))]
 #[diesel(table_name = login_history)]
 pub struct LoginHistory {
     pub id: i32,
     pub user_id: i32,
     pub login_timestamp: NaiveDateTime,
+    /// Session id carried by the access tokens of this login
+    pub login_session: Option<String>,
+    /// Family of the refresh tokens issued at this login
+    pub refresh_family_id: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Last login or token refresh of the session
+    pub last_seen_at: Option<NaiveDateTime>,
+    /// Set when the session is logged out or revoked
+    pub revoked_at: Option<NaiveDateTime>,
 }
+
+/// Client a login request came from
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+/// An active session as listed to its user
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct SessionDTO {
+    pub id: i32,
+    pub created_at: NaiveDateTime,
+    pub last_seen_at: NaiveDateTime,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Whether this is the session of the requesting token
+    pub current: bool,
+}
+
+impl SessionDTO {
+    pub fn new(session: LoginHistory, current_session: &str) -> Self {
+        SessionDTO {
+            current: session.login_session.as_deref() == Some(current_session),
+            id: session.id,
+            created_at: session.login_timestamp,
+            last_seen_at: session.last_seen_at.unwrap_or(session.login_timestamp),
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+        }
+    }
+}
+
+/// Paged by `login_timestamp`; pair with `CursorPaginator::sort_column("login_timestamp")`.
+impl HasCursorKey for LoginHistory {
+    fn cursor_key(&self) -> CursorKey {
+        CursorKey::new(self.login_timestamp.and_utc(), self.id)
+    }
+}
+
 #[derive(Insertable)]
 #[diesel(table_name = login_history)]
 pub struct LoginHistoryInsertableDTO {
     pub user_id: i32,
     pub login_timestamp: NaiveDateTime,
+    pub login_session: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
 }
 
 impl LoginHistory {
     /// Constructs an insertable login-history record for the given username if the user exists.
     ///
     /// Looks up the user by username and, on success, returns a `LoginHistoryInsertableDTO` populated
-    /// with the user's `id` and the current UTC timestamp. Returns `None` if no matching user is found.
+    /// with the user's `id`, the current UTC timestamp, the new `session` id and the `client` details.
+    /// Returns `None` if no matching user is found.
     ///
     /// # Returns
     ///
@@ -36,18 +101,26 @@ impl LoginHistory {
     ///
     /// ```
     /// // assume `conn` is a valid &mut Connection and a user "alice" exists
-    /// let dto = create("alice", &mut conn);
+    /// let dto = create("alice", "session-uuid", &SessionClient::default(), &mut conn);
     /// assert!(dto.is_some());
     /// if let Some(record) = dto {
     ///     assert!(record.user_id > 0);
     /// }
     /// ```
-    pub fn create(un: &str, conn: &mut Connection) -> Option<LoginHistoryInsertableDTO> {
+    pub fn create(
+        un: &str,
+        session: &str,
+        client: &SessionClient,
+        conn: &mut Connection,
+    ) -> Option<LoginHistoryInsertableDTO> {
         user_ops::find_user_by_username(un, conn).ok().map(|user| {
             let now = Utc::now();
             LoginHistoryInsertableDTO {
                 user_id: user.id,
                 login_timestamp: now.naive_utc(),
+                login_session: Some(session.to_string()),
+                user_agent: client.user_agent.clone(),
+                ip_address: client.ip_address.clone(),
             }
         })
     }
@@ -66,6 +139,9 @@ impl LoginHistory {
     /// let record = LoginHistoryInsertableDTO {
     ///     user_id: 1,
     ///     login_timestamp: chrono::Utc::now().naive_utc(),
+    ///     login_session: None,
+    ///     user_agent: None,
+    ///     ip_address: None,
     /// };
     ///
     /// let affected = save_login_history(record, &mut conn).unwrap();
@@ -79,4 +155,143 @@ impl LoginHistory {
             .values(&insert_record)
             .execute(conn)
     }
+
+    /// The session of `uid` whose access tokens carry `session`, unless revoked.
+    pub fn find_active_session(
+        uid: i32,
+        session: &str,
+        conn: &mut Connection,
+    ) -> QueryResult<LoginHistory> {
+        login_history
+            .filter(user_id.eq(uid))
+            .filter(login_session.eq(session))
+            .filter(revoked_at.is_null())
+            .first(conn)
+    }
+
+    /// The session that started the refresh token family `family_id`.
+    pub fn find_by_refresh_family(
+        family_id: &str,
+        conn: &mut Connection,
+    ) -> QueryResult<LoginHistory> {
+        login_history
+            .filter(refresh_family_id.eq(family_id))
+            .first(conn)
+    }
+
+    /// Links the session `session` to the refresh tokens issued with it.
+    pub fn attach_refresh_family(
+        session: &str,
+        family_id: &str,
+        conn: &mut Connection,
+    ) -> QueryResult<usize> {
+        diesel::update(login_history.filter(login_session.eq(session)))
+            .set(refresh_family_id.eq(family_id))
+            .execute(conn)
+    }
+
+    /// Records that the session was just used.
+    pub fn touch(session_id: i32, conn: &mut Connection) -> QueryResult<usize> {
+        diesel::update(login_history.find(session_id))
+            .set(last_seen_at.eq(Utc::now().naive_utc()))
+            .execute(conn)
+    }
+
+    /// Sessions of `uid` that can still be used, newest first.
+    ///
+    /// A session is active until revoked, as long as its refresh tokens have
+    /// not all expired or been revoked; `current_session` is always included.
+    pub fn find_active_for_user(
+        uid: i32,
+        current_session: &str,
+        conn: &mut Connection,
+    ) -> QueryResult<Vec<LoginHistory>> {
+        use crate::schema::refresh_tokens;
+
+        let live_refresh_token = refresh_tokens::table
+            .filter(refresh_tokens::family_id.nullable().eq(refresh_family_id))
+            .filter(
+                refresh_tokens::revoked
+                    .is_null()
+                    .or(refresh_tokens::revoked.eq(false)),
+            )
+            .filter(refresh_tokens::used_at.is_null())
+            .filter(refresh_tokens::expires_at.gt(Utc::now().naive_utc()));
+
+        login_history
+            .filter(user_id.eq(uid))
+            .filter(login_session.is_not_null())
+            .filter(revoked_at.is_null())
+            .filter(
+                login_session
+                    .eq(current_session)
+                    .or(diesel::dsl::exists(live_refresh_token)),
+            )
+            .order((login_timestamp.desc(), id.desc()))
+            .load(conn)
+    }
+
+    /// Revokes the sessions of `uid` selected by `only` (all when `None`),
+    /// except `keep_session`, together with their refresh tokens.
+    ///
+    /// Returns the number of sessions revoked.
+    fn revoke_where(
+        uid: i32,
+        only: Option<i32>,
+        keep_session: Option<&str>,
+        conn: &mut Connection,
+    ) -> QueryResult<usize> {
+        conn.transaction(|conn| {
+            let mut query = login_history
+                .filter(user_id.eq(uid))
+                .filter(revoked_at.is_null())
+                .into_boxed();
+            if let Some(session_id) = only {
+                query = query.filter(id.eq(session_id));
+            }
+            if let Some(keep) = keep_session {
+                query = query.filter(login_session.is_null().or(login_session.ne(keep)));
+            }
+            let sessions: Vec<LoginHistory> = query.load(conn)?;
+
+            for session in &sessions {
+                if let Some(family_id) = &session.refresh_family_id {
+                    RefreshToken::revoke_family(family_id, conn)?;
+                }
+            }
+            let ids: Vec<i32> = sessions.iter().map(|session| session.id).collect();
+            diesel::update(login_history.filter(id.eq_any(&ids)))
+                .set(revoked_at.eq(Utc::now().naive_utc()))
+                .execute(conn)
+        })
+    }
+
+    /// Revokes the session `session_id` of `uid` and its refresh tokens.
+    ///
+    /// Returns `NotFound` when `uid` has no such active session.
+    pub fn revoke_session(uid: i32, session_id: i32, conn: &mut Connection) -> QueryResult<()> {
+        match Self::revoke_where(uid, Some(session_id), None, conn)? {
+            0 => Err(diesel::result::Error::NotFound),
+            _ => Ok(()),
+        }
+    }
+
+    /// Revokes the session whose access tokens carry `session`, e.g. on logout.
+    pub fn revoke_by_login_session(
+        uid: i32,
+        session: &str,
+        conn: &mut Connection,
+    ) -> QueryResult<()> {
+        let current = Self::find_active_session(uid, session, conn)?;
+        Self::revoke_session(uid, current.id, conn)
+    }
+
+    /// Revokes every session of `uid` except the one carrying `keep_session`.
+    pub fn revoke_other_sessions(
+        uid: i32,
+        keep_session: &str,
+        conn: &mut Connection,
+    ) -> QueryResult<usize> {
+        Self::revoke_where(uid, None, Some(keep_session), conn)
+    }
 }
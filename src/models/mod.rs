@@ -11,6 +11,7 @@
 //! - Pure function registries for data transformations
 //! - Performance monitoring for database operations
 
+pub mod audit_log;
 pub mod filters;
 pub mod login_history;
 pub mod nfe_cofins;
@@ -34,7 +35,9 @@ pub mod user_token;
 pub use crate::functional::{
     query_builder::Column,
     validation_engine::{ValidationConfig, ValidationEngine},
-    validation_rules::{Custom, Email, Length, Phone, Range, ValidationError},
+    validation_rules::{
+        Custom, Email, Length, Phone, Range, ValidationError, ValidationErrorDetail,
+    },
 };
 
 // Re-export commonly used functional traits
@@ -102,4 +105,24 @@ pub mod functional_utils {
     pub fn to_error_messages(errors: Vec<ValidationError>) -> Vec<String> {
         errors.into_iter().map(|error| error.message).collect()
     }
+
+    /// Return the `{field, code, message}` details of the validation errors, in the same order.
+    ///
+    /// Unlike [`to_error_messages`], the stable `code` lets clients show localized messages.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use crate::models::functional_utils::{to_error_details, ValidationError};
+    ///
+    /// let errors = vec![ValidationError::new("email", "email.invalid", "email must be a valid email address")];
+    /// let details = to_error_details(errors);
+    /// assert_eq!(details[0].code, "email.invalid");
+    /// ```
+    pub fn to_error_details(errors: Vec<ValidationError>) -> Vec<ValidationErrorDetail> {
+        errors
+            .into_iter()
+            .map(ValidationErrorDetail::from)
+            .collect()
+    }
 }
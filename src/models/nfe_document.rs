@@ -1,7 +1,25 @@
-use crate::schema::nfe_documents;
+use crate::{
+    config::db::Connection,
+    models::{
+        nfe_cofins::{NewNfeCofins, NfeCofins},
+        nfe_emitter::NewNfeEmitter,
+        nfe_icms::{NewNfeIcms, NfeIcms},
+        nfe_ipi::{NewNfeIpi, NfeIpi},
+        nfe_item::{NewNfeItem, NfeItem},
+        nfe_pis::{NewNfePis, NfePis},
+        nfe_recipient::NewNfeRecipient,
+    },
+    schema::{
+        nfe_cofins, nfe_documents, nfe_emitters, nfe_icms, nfe_ipi, nfe_items, nfe_pis,
+        nfe_recipients,
+    },
+};
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
+use diesel::upsert::excluded;
+use diesel::Connection as _;
 use rust_decimal::Decimal;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 #[derive(Queryable, Identifiable, Serialize, Deserialize, Debug)]
@@ -39,6 +57,8 @@ pub struct NfeDocument {
     pub justificativa_contingencia: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub emitter_id: Option<i32>,
+    pub recipient_id: Option<i32>,
 }
 
 #[derive(Insertable, Serialize, Deserialize, Debug)]
@@ -68,6 +88,8 @@ pub struct NewNfeDocument {
     pub contrato: Option<String>,
     pub informacoes_adicionais: Option<String>,
     pub informacoes_fisco: Option<String>,
+    pub emitter_id: Option<i32>,
+    pub recipient_id: Option<i32>,
 }
 
 #[derive(AsChangeset, Serialize, Deserialize, Debug)]
@@ -83,3 +105,545 @@ pub struct UpdateNfeDocument {
     pub informacoes_fisco: Option<String>,
     pub updated_at: Option<NaiveDateTime>,
 }
+
+/// Party (emitter or recipient) identification carried by an incoming NFe.
+///
+/// Exactly one of `cnpj` or `cpf` is expected; digits only.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct NfePartyPayload {
+    pub cnpj: Option<String>,
+    pub cpf: Option<String>,
+    pub razao_social: String,
+}
+
+/// Tax group (ICMS, IPI, PIS or COFINS) attached to an incoming NFe item.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct NfeTaxPayload {
+    pub cst: String,
+    pub valor_bc: Option<Decimal>,
+    pub aliquota: Option<Decimal>,
+    pub valor: Option<Decimal>,
+}
+
+/// Item line of an incoming NFe together with its tax groups.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct NfeItemPayload {
+    pub codigo: String,
+    pub ean: Option<String>,
+    pub descricao: String,
+    pub ncm: Option<String>,
+    pub cfop: String,
+    pub unidade: String,
+    pub quantidade: Decimal,
+    pub valor_unitario: Decimal,
+    pub valor_total: Decimal,
+    pub valor_desconto: Option<Decimal>,
+    pub icms: Option<NfeTaxPayload>,
+    pub ipi: Option<NfeTaxPayload>,
+    pub pis: Option<NfeTaxPayload>,
+    pub cofins: Option<NfeTaxPayload>,
+}
+
+/// Complete NFe as submitted by a client: header, parties, items and taxes.
+///
+/// `access_key` is the 44-digit chave de acesso and is stored as `nfe_id`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
+pub struct NfeDocumentPayload {
+    pub access_key: String,
+    pub serie: String,
+    pub numero: String,
+    pub modelo: Option<String>,
+    pub data_emissao: Option<NaiveDateTime>,
+    pub emitter: NfePartyPayload,
+    pub recipient: Option<NfePartyPayload>,
+    pub items: Vec<NfeItemPayload>,
+    pub valor_produtos: Decimal,
+    pub valor_total: Decimal,
+    pub valor_impostos: Decimal,
+    pub valor_desconto: Option<Decimal>,
+    pub valor_frete: Option<Decimal>,
+    pub valor_seguro: Option<Decimal>,
+    pub valor_outras_despesas: Option<Decimal>,
+    pub informacoes_adicionais: Option<String>,
+}
+
+/// Item of a stored NFe with its tax groups attached.
+#[derive(Serialize, Debug)]
+pub struct NfeItemDetail {
+    #[serde(flatten)]
+    pub item: NfeItem,
+    pub icms: Option<NfeIcms>,
+    pub ipi: Option<NfeIpi>,
+    pub pis: Option<NfePis>,
+    pub cofins: Option<NfeCofins>,
+}
+
+/// Stored NFe assembled with its items (ordered by `numero_item`) and taxes.
+#[derive(Serialize, Debug)]
+pub struct NfeDocumentDetail {
+    #[serde(flatten)]
+    pub document: NfeDocument,
+    pub items: Vec<NfeItemDetail>,
+}
+
+impl NfeDocumentDetail {
+    /// Rebuilds the payload the document was imported from, for rendering.
+    ///
+    /// Item tax groups keep their CST, base, rate and value; PIS and COFINS
+    /// use their percentage rate.
+    pub fn into_payload(
+        self,
+        emitter: NfePartyPayload,
+        recipient: Option<NfePartyPayload>,
+    ) -> NfeDocumentPayload {
+        let document = self.document;
+        let items = self
+            .items
+            .into_iter()
+            .map(|detail| NfeItemPayload {
+                codigo: detail.item.codigo,
+                ean: detail.item.ean,
+                descricao: detail.item.descricao,
+                ncm: detail.item.ncm,
+                cfop: detail.item.cfop,
+                unidade: detail.item.unidade,
+                quantidade: detail.item.quantidade,
+                valor_unitario: detail.item.valor_unitario,
+                valor_total: detail.item.valor_total,
+                valor_desconto: detail.item.valor_desconto,
+                icms: detail.icms.map(|t| NfeTaxPayload {
+                    cst: t.cst,
+                    valor_bc: t.valor_bc,
+                    aliquota: t.aliquota,
+                    valor: t.valor,
+                }),
+                ipi: detail.ipi.map(|t| NfeTaxPayload {
+                    cst: t.cst,
+                    valor_bc: t.valor_bc,
+                    aliquota: t.aliquota,
+                    valor: t.valor,
+                }),
+                pis: detail.pis.map(|t| NfeTaxPayload {
+                    cst: t.cst,
+                    valor_bc: t.valor_bc,
+                    aliquota: t.aliquota_percentual,
+                    valor: t.valor,
+                }),
+                cofins: detail.cofins.map(|t| NfeTaxPayload {
+                    cst: t.cst,
+                    valor_bc: t.valor_bc,
+                    aliquota: t.aliquota_percentual,
+                    valor: t.valor,
+                }),
+            })
+            .collect();
+
+        NfeDocumentPayload {
+            access_key: document.nfe_id,
+            serie: document.serie,
+            numero: document.numero,
+            modelo: Some(document.modelo),
+            data_emissao: Some(document.data_emissao),
+            emitter,
+            recipient,
+            items,
+            valor_produtos: document.valor_produtos,
+            valor_total: document.valor_total,
+            valor_impostos: document.valor_impostos,
+            valor_desconto: document.valor_desconto,
+            valor_frete: document.valor_frete,
+            valor_seguro: document.valor_seguro,
+            valor_outras_despesas: document.valor_outras_despesas,
+            informacoes_adicionais: document.informacoes_adicionais,
+        }
+    }
+}
+
+impl NfeDocument {
+    /// Finds a tenant's document by its access key (`nfe_id`).
+    pub fn find_by_access_key(
+        tenant_id: &str,
+        access_key: &str,
+        conn: &mut Connection,
+    ) -> QueryResult<NfeDocument> {
+        nfe_documents::table
+            .filter(nfe_documents::tenant_id.eq(tenant_id))
+            .filter(nfe_documents::nfe_id.eq(access_key))
+            .first::<NfeDocument>(conn)
+    }
+
+    /// Loads the document's items and their tax groups.
+    ///
+    /// Each child table is fetched with a single query and grouped in memory,
+    /// so the cost is five queries regardless of the number of items.
+    pub fn into_detail(self, conn: &mut Connection) -> QueryResult<NfeDocumentDetail> {
+        let items = NfeItem::belonging_to(&self)
+            .order(nfe_items::numero_item.asc())
+            .load::<NfeItem>(conn)?;
+
+        let icms = NfeIcms::belonging_to(&items)
+            .load::<NfeIcms>(conn)?
+            .grouped_by(&items);
+        let ipi = NfeIpi::belonging_to(&items)
+            .load::<NfeIpi>(conn)?
+            .grouped_by(&items);
+        let pis = NfePis::belonging_to(&items)
+            .load::<NfePis>(conn)?
+            .grouped_by(&items);
+        let cofins = NfeCofins::belonging_to(&items)
+            .load::<NfeCofins>(conn)?
+            .grouped_by(&items);
+
+        let items = items
+            .into_iter()
+            .zip(icms)
+            .zip(ipi)
+            .zip(pis)
+            .zip(cofins)
+            .map(|((((item, icms), ipi), pis), cofins)| NfeItemDetail {
+                item,
+                icms: icms.into_iter().next(),
+                ipi: ipi.into_iter().next(),
+                pis: pis.into_iter().next(),
+                cofins: cofins.into_iter().next(),
+            })
+            .collect();
+
+        Ok(NfeDocumentDetail {
+            document: self,
+            items,
+        })
+    }
+
+    /// Loads the emitter and recipient linked to the document.
+    ///
+    /// Documents imported before parties were recorded have neither.
+    pub fn parties(
+        &self,
+        conn: &mut Connection,
+    ) -> QueryResult<(Option<NfePartyPayload>, Option<NfePartyPayload>)> {
+        let emitter = match self.emitter_id {
+            Some(id) => nfe_emitters::table
+                .find(id)
+                .select((
+                    nfe_emitters::cnpj,
+                    nfe_emitters::cpf,
+                    nfe_emitters::razao_social,
+                ))
+                .first::<(Option<String>, Option<String>, String)>(conn)
+                .optional()?,
+            None => None,
+        };
+        let recipient = match self.recipient_id {
+            Some(id) => nfe_recipients::table
+                .find(id)
+                .select((
+                    nfe_recipients::cnpj,
+                    nfe_recipients::cpf,
+                    nfe_recipients::razao_social,
+                ))
+                .first::<(Option<String>, Option<String>, String)>(conn)
+                .optional()?,
+            None => None,
+        };
+
+        let party = |(cnpj, cpf, razao_social)| NfePartyPayload {
+            cnpj,
+            cpf,
+            razao_social,
+        };
+        Ok((emitter.map(party), recipient.map(party)))
+    }
+
+    /// Inserts a payload with its parties, items and tax groups in a single
+    /// transaction.
+    ///
+    /// The emitter and recipient are upserted into the tenant's `nfe_emitters`
+    /// and `nfe_recipients` by CNPJ or CPF, refreshing their `razao_social`,
+    /// and linked to the document. The payload is expected to have been
+    /// validated beforehand; database constraint violations (e.g. a duplicate
+    /// access key) roll back the whole document, parties included.
+    ///
+    /// # Returns
+    /// The id of the inserted `nfe_documents` row.
+    pub fn insert_payload(
+        tenant_id: &str,
+        payload: &NfeDocumentPayload,
+        conn: &mut Connection,
+    ) -> QueryResult<i32> {
+        conn.transaction(|conn| {
+            let emitter_id = upsert_emitter(tenant_id, &payload.emitter, conn)?;
+            let recipient_id = payload
+                .recipient
+                .as_ref()
+                .map(|recipient| upsert_recipient(tenant_id, recipient, conn))
+                .transpose()?;
+
+            let document = NewNfeDocument {
+                tenant_id: tenant_id.to_string(),
+                nfe_id: payload.access_key.clone(),
+                serie: payload.serie.clone(),
+                numero: payload.numero.clone(),
+                modelo: payload.modelo.clone(),
+                versao: None,
+                status: None,
+                tipo_operacao: None,
+                tipo_emissao: None,
+                finalidade: None,
+                indicador_presencial: None,
+                data_emissao: payload.data_emissao,
+                data_saida_entrada: None,
+                valor_total: payload.valor_total,
+                valor_desconto: payload.valor_desconto,
+                valor_frete: payload.valor_frete,
+                valor_seguro: payload.valor_seguro,
+                valor_outras_despesas: payload.valor_outras_despesas,
+                valor_produtos: payload.valor_produtos,
+                valor_impostos: payload.valor_impostos,
+                pedido_compra: None,
+                contrato: None,
+                informacoes_adicionais: payload.informacoes_adicionais.clone(),
+                informacoes_fisco: None,
+                emitter_id: Some(emitter_id),
+                recipient_id,
+            };
+
+            let document_id = diesel::insert_into(nfe_documents::table)
+                .values(&document)
+                .returning(nfe_documents::id)
+                .get_result::<i32>(conn)?;
+
+            for (index, item) in payload.items.iter().enumerate() {
+                insert_item(document_id, index as i32 + 1, item, conn)?;
+            }
+
+            Ok(document_id)
+        })
+    }
+}
+
+fn upsert_emitter(
+    tenant_id: &str,
+    party: &NfePartyPayload,
+    conn: &mut Connection,
+) -> QueryResult<i32> {
+    let emitter = NewNfeEmitter {
+        tenant_id: tenant_id.to_string(),
+        cnpj: party.cnpj.clone(),
+        cpf: party.cpf.clone(),
+        razao_social: party.razao_social.clone(),
+        nome_fantasia: None,
+        inscricao_estadual: None,
+        inscricao_estadual_subst_tributario: None,
+        inscricao_municipal: None,
+        cnae: None,
+        regime_tributario: None,
+        logradouro: None,
+        numero: None,
+        complemento: None,
+        bairro: None,
+        codigo_municipio: None,
+        municipio: None,
+        uf: None,
+        cep: None,
+        codigo_pais: None,
+        pais: None,
+        telefone: None,
+    };
+    let refresh = (
+        nfe_emitters::razao_social.eq(excluded(nfe_emitters::razao_social)),
+        nfe_emitters::updated_at.eq(diesel::dsl::now),
+    );
+    let insert = diesel::insert_into(nfe_emitters::table).values(&emitter);
+
+    if party.cnpj.is_some() {
+        insert
+            .on_conflict((nfe_emitters::tenant_id, nfe_emitters::cnpj))
+            .do_update()
+            .set(refresh)
+            .returning(nfe_emitters::id)
+            .get_result(conn)
+    } else {
+        insert
+            .on_conflict((nfe_emitters::tenant_id, nfe_emitters::cpf))
+            .do_update()
+            .set(refresh)
+            .returning(nfe_emitters::id)
+            .get_result(conn)
+    }
+}
+
+fn upsert_recipient(
+    tenant_id: &str,
+    party: &NfePartyPayload,
+    conn: &mut Connection,
+) -> QueryResult<i32> {
+    let recipient = NewNfeRecipient {
+        tenant_id: tenant_id.to_string(),
+        tipo_pessoa: if party.cnpj.is_some() { "J" } else { "F" }.to_string(),
+        cnpj: party.cnpj.clone(),
+        cpf: party.cpf.clone(),
+        id_estrangeiro: None,
+        razao_social: party.razao_social.clone(),
+        nome_fantasia: None,
+        inscricao_estadual: None,
+        inscricao_municipal: None,
+        inscricao_suframa: None,
+        email: None,
+        logradouro: None,
+        numero: None,
+        complemento: None,
+        bairro: None,
+        codigo_municipio: None,
+        municipio: None,
+        uf: None,
+        cep: None,
+        codigo_pais: None,
+        pais: None,
+        telefone: None,
+    };
+    let refresh = (
+        nfe_recipients::razao_social.eq(excluded(nfe_recipients::razao_social)),
+        nfe_recipients::updated_at.eq(diesel::dsl::now),
+    );
+    let insert = diesel::insert_into(nfe_recipients::table).values(&recipient);
+
+    if party.cnpj.is_some() {
+        insert
+            .on_conflict((nfe_recipients::tenant_id, nfe_recipients::cnpj))
+            .do_update()
+            .set(refresh)
+            .returning(nfe_recipients::id)
+            .get_result(conn)
+    } else {
+        insert
+            .on_conflict((nfe_recipients::tenant_id, nfe_recipients::cpf))
+            .do_update()
+            .set(refresh)
+            .returning(nfe_recipients::id)
+            .get_result(conn)
+    }
+}
+
+fn insert_item(
+    document_id: i32,
+    numero_item: i32,
+    item: &NfeItemPayload,
+    conn: &mut Connection,
+) -> QueryResult<()> {
+    let tax_value = |tax: &Option<NfeTaxPayload>| tax.as_ref().and_then(|t| t.valor);
+    let tax_base = |tax: &Option<NfeTaxPayload>| tax.as_ref().and_then(|t| t.valor_bc);
+
+    let new_item = NewNfeItem {
+        nfe_document_id: document_id,
+        numero_item,
+        product_id: None,
+        codigo: item.codigo.clone(),
+        ean: item.ean.clone(),
+        descricao: item.descricao.clone(),
+        ncm: item.ncm.clone(),
+        cfop: item.cfop.clone(),
+        unidade: item.unidade.clone(),
+        quantidade: item.quantidade,
+        valor_unitario: item.valor_unitario,
+        valor_total: item.valor_total,
+        valor_desconto: item.valor_desconto,
+        valor_frete: None,
+        valor_seguro: None,
+        valor_outras_despesas: None,
+        valor_bc_icms: tax_base(&item.icms),
+        valor_icms: tax_value(&item.icms),
+        valor_bc_icms_st: None,
+        valor_icms_st: None,
+        valor_bc_ipi: tax_base(&item.ipi),
+        valor_ipi: tax_value(&item.ipi),
+        valor_bc_pis: tax_base(&item.pis),
+        valor_pis: tax_value(&item.pis),
+        valor_bc_cofins: tax_base(&item.cofins),
+        valor_cofins: tax_value(&item.cofins),
+        informacoes_adicionais: None,
+        numero_pedido_compra: None,
+        item_pedido_compra: None,
+    };
+
+    let item_id = diesel::insert_into(nfe_items::table)
+        .values(&new_item)
+        .returning(nfe_items::id)
+        .get_result::<i32>(conn)?;
+
+    if let Some(icms) = &item.icms {
+        diesel::insert_into(nfe_icms::table)
+            .values(&NewNfeIcms {
+                nfe_item_id: item_id,
+                cst: icms.cst.clone(),
+                modalidade_bc: None,
+                valor_bc: icms.valor_bc,
+                aliquota: icms.aliquota,
+                valor: icms.valor,
+                modalidade_bc_st: None,
+                percentual_mva_st: None,
+                percentual_reducao_bc_st: None,
+                valor_bc_st: None,
+                aliquota_st: None,
+                valor_st: None,
+                percentual_reducao_bc_efetiva: None,
+                valor_bc_efetiva: None,
+                aliquota_efetiva: None,
+                valor_efetivo: None,
+                codigo_beneficio_fiscal: None,
+                percentual_diferimento: None,
+            })
+            .execute(conn)?;
+    }
+
+    if let Some(ipi) = &item.ipi {
+        diesel::insert_into(nfe_ipi::table)
+            .values(&NewNfeIpi {
+                nfe_item_id: item_id,
+                cst: ipi.cst.clone(),
+                classe_enquadramento: None,
+                cnpj_produtor: None,
+                codigo_selo_controle: None,
+                quantidade_selo: None,
+                modalidade_bc: None,
+                valor_bc: ipi.valor_bc,
+                aliquota: ipi.aliquota,
+                quantidade_unidade: None,
+                valor_unidade: None,
+                valor: ipi.valor,
+            })
+            .execute(conn)?;
+    }
+
+    if let Some(pis) = &item.pis {
+        diesel::insert_into(nfe_pis::table)
+            .values(&NewNfePis {
+                nfe_item_id: item_id,
+                cst: pis.cst.clone(),
+                modalidade_bc: None,
+                valor_bc: pis.valor_bc,
+                aliquota_percentual: pis.aliquota,
+                aliquota_valor: None,
+                quantidade_vendida: None,
+                valor: pis.valor,
+            })
+            .execute(conn)?;
+    }
+
+    if let Some(cofins) = &item.cofins {
+        diesel::insert_into(nfe_cofins::table)
+            .values(&NewNfeCofins {
+                nfe_item_id: item_id,
+                cst: cofins.cst.clone(),
+                modalidade_bc: None,
+                valor_bc: cofins.valor_bc,
+                aliquota_percentual: cofins.aliquota,
+                aliquota_valor: None,
+                quantidade_vendida: None,
+                valor: cofins.valor,
+            })
+            .execute(conn)?;
+    }
+
+    Ok(())
+}
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 pub struct NfeEmitter {
     pub id: i32,
     pub tenant_id: String,
-    pub cnpj: String,
+    pub cnpj: Option<String>,
     pub cpf: Option<String>,
     pub razao_social: String,
     pub nome_fantasia: Option<String>,
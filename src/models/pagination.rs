@@ -1,14 +1,18 @@
 // Simplified pagination for actix-web-rest-api-with-jwt
 
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
 use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::query_builder::*;
 use diesel::query_dsl::methods::LoadQuery;
-use diesel::sql_types::BigInt;
+use diesel::sql_types::{BigInt, Integer, Timestamptz};
+use serde::Serialize;
 
 use crate::constants::MESSAGE_OK;
+use crate::error::{ErrorTag, ServiceError};
 
-use super::response::Page;
+use super::{filters::SortSpec, response::Page};
 
 // Trait to extract id from model types
 pub trait HasId {
@@ -107,6 +111,205 @@ where
     }
 }
 
+/// Column `CursorPaginator` sorts on unless told otherwise
+pub const DEFAULT_CURSOR_SORT_COLUMN: &str = "created_at";
+
+/// Last-seen sort key of a cursor page: the sort timestamp, with `id` as tiebreaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorKey {
+    pub created_at: DateTime<Utc>,
+    pub id: i32,
+}
+
+impl CursorKey {
+    pub fn new(created_at: DateTime<Utc>, id: i32) -> Self {
+        CursorKey { created_at, id }
+    }
+
+    /// Opaque, URL-safe form of the key handed to clients as `next_cursor`.
+    ///
+    /// Postgres stores timestamps to the microsecond, so that is the precision kept.
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!(
+            "{}:{}",
+            self.created_at.timestamp_micros(),
+            self.id
+        ))
+    }
+
+    /// Parses a cursor produced by [`CursorKey::encode`].
+    pub fn decode(cursor: &str) -> Result<Self, ServiceError> {
+        let malformed = || ServiceError::bad_request(format!("Malformed cursor '{}'", cursor));
+
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| malformed())?;
+        let raw = String::from_utf8(bytes).map_err(|_| malformed())?;
+        let (micros, id) = raw.split_once(':').ok_or_else(malformed)?;
+        let micros = micros.parse::<i64>().map_err(|_| malformed())?;
+        let id = id.parse::<i32>().map_err(|_| malformed())?;
+        let created_at = DateTime::from_timestamp_micros(micros).ok_or_else(malformed)?;
+
+        Ok(CursorKey::new(created_at, id))
+    }
+}
+
+/// Rows that can be paged by [`paginate_by_cursor`]
+pub trait HasCursorKey {
+    fn cursor_key(&self) -> CursorKey;
+}
+
+/// One page of rows in sort-key order plus the cursor of the following page.
+#[derive(Debug, Clone, Serialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// Keyset pagination over `(sort column, id)`.
+///
+/// Unlike offset paging, rows inserted while a client is paging never shift
+/// the pages it has yet to read: each page starts strictly after the last key seen.
+#[derive(Debug, Clone)]
+pub struct CursorPaginator<T> {
+    query: T,
+    after: Option<CursorKey>,
+    per_page: i64,
+    sort_column: &'static str,
+}
+
+impl<T> CursorPaginator<T> {
+    pub fn new(query: T) -> Self {
+        CursorPaginator {
+            query,
+            after: None,
+            per_page: crate::constants::DEFAULT_PER_PAGE,
+            sort_column: DEFAULT_CURSOR_SORT_COLUMN,
+        }
+    }
+
+    /// Only rows sorting after `key` are returned.
+    pub fn after(mut self, key: Option<CursorKey>) -> Self {
+        self.after = key;
+        self
+    }
+
+    pub fn per_page(mut self, per_page: i64) -> Self {
+        self.per_page = per_page.max(1);
+        self
+    }
+
+    /// Timestamp column of the subquery to sort on instead of `created_at`.
+    pub fn sort_column(mut self, column: &'static str) -> Self {
+        self.sort_column = column;
+        self
+    }
+
+    /// Loads the page, fetching one extra row to learn whether another page follows.
+    pub fn load_page<'a, U>(mut self, conn: &mut PgConnection) -> QueryResult<CursorPage<U>>
+    where
+        Self: LoadQuery<'a, PgConnection, U>,
+        U: HasCursorKey,
+    {
+        let per_page = self.per_page as usize;
+        self.per_page += 1;
+        let mut items = self.load::<U>(conn)?;
+
+        let next_cursor = if items.len() > per_page {
+            items.truncate(per_page);
+            items.last().map(|item| item.cursor_key().encode())
+        } else {
+            None
+        };
+
+        Ok(CursorPage { items, next_cursor })
+    }
+}
+
+impl<T> QueryId for CursorPaginator<T>
+where
+    T: QueryId,
+{
+    type QueryId = ();
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl<T: Query> Query for CursorPaginator<T> {
+    type SqlType = T::SqlType;
+}
+
+impl<T> RunQueryDsl<PgConnection> for CursorPaginator<T> {}
+
+impl<T> QueryFragment<Pg> for CursorPaginator<T>
+where
+    T: QueryFragment<Pg>,
+{
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        out.push_sql("SELECT * FROM (");
+        self.query.walk_ast(out.reborrow())?;
+        out.push_sql(") t");
+        if let Some(after) = &self.after {
+            out.push_sql(" WHERE (t.");
+            out.push_identifier(self.sort_column)?;
+            out.push_sql(", t.id) > (");
+            out.push_bind_param::<Timestamptz, _>(&after.created_at)?;
+            out.push_sql(", ");
+            out.push_bind_param::<Integer, _>(&after.id)?;
+            out.push_sql(")");
+        }
+        out.push_sql(" ORDER BY t.");
+        out.push_identifier(self.sort_column)?;
+        out.push_sql(", t.id LIMIT ");
+        out.push_bind_param::<BigInt, _>(&self.per_page)?;
+        Ok(())
+    }
+}
+
+/// Loads the page of `query` following `cursor`, `page_size` rows at a time.
+///
+/// `query` must select a `created_at` timestamp and an `id` column; use
+/// [`CursorPaginator::sort_column`] directly for other timestamp columns.
+/// A cursor that was not produced by [`CursorKey::encode`] is a bad request.
+pub fn paginate_by_cursor<'a, Q, U>(
+    query: Q,
+    cursor: Option<&str>,
+    page_size: i64,
+    conn: &mut PgConnection,
+) -> Result<CursorPage<U>, ServiceError>
+where
+    CursorPaginator<Q>: LoadQuery<'a, PgConnection, U>,
+    U: HasCursorKey,
+{
+    let after = cursor.map(CursorKey::decode).transpose()?;
+
+    CursorPaginator::new(query)
+        .after(after)
+        .per_page(page_size)
+        .load_page(conn)
+        .map_err(|e| ServiceError::internal_server_error(format!("Failed to load page: {}", e)))
+}
+
+/// Parses a list endpoint's comma-separated `sort` parameter, e.g. `name,-age`,
+/// into one [`SortSpec`] per column in priority order.
+///
+/// Only the `sortable` columns are accepted, so the result can be matched onto
+/// query columns without ever reaching the SQL as text; any other column is a
+/// bad request. A blank parameter yields no columns.
+pub fn parse_sort(sort: &str, sortable: &[&str]) -> Result<Vec<SortSpec>, ServiceError> {
+    let specs: Vec<SortSpec> = sort.split(',').filter_map(SortSpec::parse).collect();
+    let unknown: Vec<&str> = specs
+        .iter()
+        .map(|spec| spec.column.as_str())
+        .filter(|column| !sortable.contains(column))
+        .collect();
+    if !unknown.is_empty() {
+        return Err(
+            ServiceError::bad_request(format!("Cannot sort by: {}", unknown.join(", ")))
+                .with_tag(ErrorTag::Validation)
+                .with_metadata("sortable_columns", sortable.join(",")),
+        );
+    }
+    Ok(specs)
+}
+
 // Iterator-based pagination utilities.
 //
 // FP-012: iterator-driven pagination with bounded memory usage. The helpers here
@@ -340,6 +543,8 @@ pub fn total_pages(total_count: usize, per_page: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use actix_web::ResponseError;
+
     use super::*;
 
     #[test]
@@ -399,4 +604,162 @@ mod tests {
         assert_eq!(mapped.items, vec![0, 2, 4, 6, 8]);
         assert_eq!(mapped.summary.has_more, false);
     }
+
+    #[test]
+    fn cursor_key_round_trips_through_encoding() {
+        let created_at = DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap();
+        let key = CursorKey::new(created_at, 42);
+
+        let cursor = key.encode();
+        assert!(!cursor.contains(':'));
+        assert_eq!(CursorKey::decode(&cursor).unwrap(), key);
+    }
+
+    #[test]
+    fn malformed_cursor_is_a_bad_request() {
+        let not_a_key = URL_SAFE_NO_PAD.encode("no-separator");
+        let bad_id = URL_SAFE_NO_PAD.encode("1700000000:abc");
+
+        for cursor in ["%%%", not_a_key.as_str(), bad_id.as_str()] {
+            assert!(matches!(
+                CursorKey::decode(cursor),
+                Err(ServiceError::BadRequest { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn parse_sort_reads_a_single_column() {
+        assert_eq!(
+            parse_sort("-age", &["name", "age"]).unwrap(),
+            vec![SortSpec {
+                column: "age".to_string(),
+                descending: true,
+            }]
+        );
+        assert!(parse_sort(" ", &["name", "age"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_sort_keeps_columns_in_priority_order() {
+        let specs = parse_sort("name, -age,id", &["id", "name", "age"]).unwrap();
+        let columns: Vec<(&str, bool)> = specs
+            .iter()
+            .map(|spec| (spec.column.as_str(), spec.descending))
+            .collect();
+        assert_eq!(columns, vec![("name", false), ("age", true), ("id", false)]);
+    }
+
+    #[test]
+    fn parse_sort_rejects_unknown_columns() {
+        let err = parse_sort("name,-password;drop table people", &["name"]).unwrap_err();
+        assert_eq!(err.status_code(), actix_web::http::StatusCode::BAD_REQUEST);
+        assert!(err.to_string().contains("password;drop table people"));
+    }
+
+    #[test]
+    fn cursor_paginator_filters_after_the_key() {
+        use crate::schema::login_history;
+
+        let first = CursorPaginator::new(login_history::table).sort_column("login_timestamp");
+        let sql = diesel::debug_query::<Pg, _>(&first).to_string();
+        assert!(!sql.contains("WHERE"));
+        assert!(sql.contains(r#"ORDER BY t."login_timestamp", t.id LIMIT"#));
+
+        let key = CursorKey::new(Utc::now(), 7);
+        let next = first.after(Some(key));
+        let sql = diesel::debug_query::<Pg, _>(&next).to_string();
+        assert!(sql.contains(r#"WHERE (t."login_timestamp", t.id) > ($1, $2)"#));
+    }
+
+    #[actix_web::test]
+    async fn paginate_by_cursor_is_stable_across_inserts() {
+        use crate::models::login_history::LoginHistory;
+        use crate::models::user::{operations as user_ops, password::Argon2id, UserDTO};
+        use crate::schema::login_history;
+        use crate::test_support::{start_postgres, Fixtures, TEST_PASSWORD};
+
+        let docker = testcontainers::clients::Cli::default();
+        let Some((_postgres, pool)) =
+            start_postgres(&docker, "paginate_by_cursor_is_stable_across_inserts")
+        else {
+            return;
+        };
+        let mut conn = pool.get().unwrap();
+        let username = Fixtures::for_test("paginate_by_cursor_is_stable_across_inserts").username();
+        user_ops::signup_user(
+            UserDTO {
+                email: Fixtures::email(&username),
+                username: username.clone(),
+                password: TEST_PASSWORD.to_string(),
+                active: true,
+            },
+            &Argon2id,
+            &mut conn,
+        )
+        .unwrap();
+        let user_id = user_ops::find_user_by_username(&username, &mut conn)
+            .unwrap()
+            .id;
+
+        // Pairs of rows share a timestamp so the id tiebreaker is exercised
+        let base = chrono::NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let insert = |minutes: i64, conn: &mut PgConnection| {
+            diesel::insert_into(login_history::table)
+                .values((
+                    login_history::user_id.eq(user_id),
+                    login_history::login_timestamp.eq(base + chrono::Duration::minutes(minutes)),
+                ))
+                .execute(conn)
+                .unwrap();
+        };
+        for minutes in [0, 0, 1, 1, 2] {
+            insert(minutes, &mut conn);
+        }
+
+        let load = |cursor: Option<&str>, conn: &mut PgConnection| {
+            CursorPaginator::new(login_history::table.select(login_history::all_columns))
+                .sort_column("login_timestamp")
+                .after(cursor.map(CursorKey::decode).transpose().unwrap())
+                .per_page(2)
+                .load_page::<LoginHistory>(conn)
+                .unwrap()
+        };
+
+        let first = load(None, &mut conn);
+        assert_eq!(first.items.len(), 2);
+
+        // Rows landing before and after the cursor while the client is paging
+        insert(-1, &mut conn);
+        insert(3, &mut conn);
+
+        let mut seen: Vec<i32> = first.items.iter().map(|row| row.id).collect();
+        let mut cursor = first.next_cursor;
+        while let Some(next) = cursor {
+            let page = load(Some(&next), &mut conn);
+            seen.extend(page.items.iter().map(|row| row.id));
+            cursor = page.next_cursor;
+        }
+
+        // Every original row exactly once plus the later insert, never the earlier one
+        let expected: Vec<i32> = login_history::table
+            .filter(login_history::login_timestamp.ge(base))
+            .order((login_history::login_timestamp, login_history::id))
+            .select(login_history::id)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(expected.len(), 6);
+        assert_eq!(seen, expected);
+
+        let malformed = paginate_by_cursor::<_, LoginHistory>(
+            login_history::table.select(login_history::all_columns),
+            Some("not a cursor"),
+            2,
+            &mut conn,
+        );
+        assert!(matches!(malformed, Err(ServiceError::BadRequest { .. })));
+    }
 }
@@ -1,21 +1,36 @@
-use diesel::{prelude::*, AsChangeset, BoxableExpression, Insertable, Queryable};
+use chrono::NaiveDateTime;
+use diesel::{dsl::now, pg::Pg, prelude::*, AsChangeset, BoxableExpression, Insertable, Queryable};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     config::db::Connection, constants::MESSAGE_OK, error::ServiceError,
-    models::pagination::SortingAndPaging, schema::people,
+    models::pagination::SortingAndPaging, schema::people, utils::csv,
 };
 
 use super::{
-    filters::PersonFilter, functional_utils, pagination::HasId, response::Page, Custom, Email,
-    Length, Phone, Range,
+    filters::{PersonFilter, SortSpec},
+    functional_utils,
+    pagination::HasId,
+    response::Page,
+    Custom, Email, Length, Phone, Range,
 };
 
-use crate::functional::{validation_engine::ValidationOutcome, validation_rules::ValidationRule};
+use crate::functional::{
+    iterator_engine::HeapSize,
+    validation_rules::{codes, ValidationError, ValidationRule},
+};
 
 // Re-export functional utilities for person operations
 
-#[derive(Clone, Queryable, Serialize, Deserialize)]
+/// A contact of the address book.
+///
+/// Loaded with `Person::as_select()`: `people` also has the generated
+/// `search_vector` column, which only full-text search reads, and the owning
+/// `tenant_id`, which only writes check.
+#[derive(Clone, Queryable, Selectable, Serialize, Deserialize, JsonSchema)]
+#[diesel(table_name = people)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Person {
     pub id: i32,
     pub name: String,
@@ -24,9 +39,14 @@ pub struct Person {
     pub address: String,
     pub phone: String,
     pub email: String,
+    /// Set when the contact is soft-deleted; such contacts are hidden until restored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
+    /// Incremented by every update; a full update must send the version it replaces
+    pub version: i32,
 }
 
-#[derive(Insertable, AsChangeset, Serialize, Deserialize)]
+#[derive(Insertable, AsChangeset, Serialize, Deserialize, Debug, Clone, JsonSchema)]
 #[diesel(table_name = people)]
 pub struct PersonDTO {
     pub name: String,
@@ -37,6 +57,27 @@ pub struct PersonDTO {
     pub email: String,
 }
 
+/// Body of a full update: every field of the person and the `version` the client read
+#[derive(Serialize, Deserialize, Debug, Clone, JsonSchema)]
+pub struct PersonUpdate {
+    #[serde(flatten)]
+    pub person: PersonDTO,
+    pub version: i32,
+}
+
+impl From<&Person> for PersonDTO {
+    fn from(person: &Person) -> Self {
+        PersonDTO {
+            name: person.name.clone(),
+            gender: person.gender,
+            age: person.age,
+            address: person.address.clone(),
+            phone: person.phone.clone(),
+            email: person.email.clone(),
+        }
+    }
+}
+
 impl PersonDTO {
     /// Check whether a string contains any non-whitespace characters.
     ///
@@ -54,7 +95,7 @@ impl PersonDTO {
         !value.trim().is_empty()
     }
 
-    /// Validate the DTO's fields and collect any validation error messages.
+    /// Collect every validation error of the DTO, field by field.
     ///
     /// Performs the following checks:
     /// - name: required (non-blank) and maximum length 100.
@@ -63,6 +104,64 @@ impl PersonDTO {
     /// - address: required and maximum length 500.
     /// - age: must be between 0 and 150.
     ///
+    /// Unlike a fail-fast check, every violated rule is reported so a client
+    /// can fix all of its input in one round trip.
+    pub fn validation_errors(&self) -> Vec<ValidationError> {
+        let required = || -> Box<dyn ValidationRule<String>> {
+            Box::new(Custom::new(
+                Self::is_not_blank,
+                codes::REQUIRED,
+                "{} is required",
+            ))
+        };
+        let length =
+            |min, max| -> Box<dyn ValidationRule<String>> { Box::new(Length { min, max }) };
+
+        let string_errors = functional_utils::validation_engine::<String>()
+            .validate_all(vec![
+                (
+                    "name".to_string(),
+                    &self.name,
+                    vec![required(), length(None, Some(100))],
+                ),
+                (
+                    "email".to_string(),
+                    &self.email,
+                    vec![required(), Box::new(Email), length(None, Some(255))],
+                ),
+                (
+                    "phone".to_string(),
+                    &self.phone,
+                    vec![required(), length(Some(10), Some(20)), Box::new(Phone)],
+                ),
+                (
+                    "address".to_string(),
+                    &self.address,
+                    vec![required(), length(None, Some(500))],
+                ),
+            ])
+            .err()
+            .unwrap_or_default();
+
+        let age_errors = functional_utils::validation_engine::<i32>()
+            .validate_all(vec![(
+                "age".to_string(),
+                &self.age,
+                vec![Range {
+                    min: Some(0),
+                    max: Some(150),
+                }],
+            )])
+            .err()
+            .unwrap_or_default();
+
+        string_errors.into_iter().chain(age_errors).collect()
+    }
+
+    /// Validate the DTO's fields and collect any validation error messages.
+    ///
+    /// See [`PersonDTO::validation_errors`] for the checks performed.
+    ///
     /// # Returns
     ///
     /// `Ok(())` if all validations pass, `Err(Vec<String>)` containing one or more human-readable error messages otherwise.
@@ -85,113 +184,173 @@ impl PersonDTO {
     /// assert!(errors.iter().any(|e| e.contains("name")));
     /// ```
     pub fn validate(&self) -> Result<(), Vec<String>> {
-        let string_engine = functional_utils::validation_engine::<String>();
-        let range_engine = functional_utils::validation_engine::<i32>();
-
-        let string_validations = [
-            string_engine.validate_field(
-                &self.name,
-                "name",
-                vec![Custom::new(
-                    Self::is_not_blank,
-                    "REQUIRED",
-                    "{} is required",
-                )],
-            ),
-            string_engine.validate_field(
-                &self.name,
-                "name",
-                vec![Length {
-                    min: None,
-                    max: Some(100),
-                }],
-            ),
-            string_engine.validate_field(
-                &self.email,
-                "email",
-                vec![Custom::new(
-                    Self::is_not_blank,
-                    "REQUIRED",
-                    "{} is required",
-                )],
-            ),
-            string_engine.validate_field(&self.email, "email", vec![Email]),
-            string_engine.validate_field(
-                &self.email,
-                "email",
-                vec![Length {
-                    min: None,
-                    max: Some(255),
-                }],
-            ),
-            string_engine.validate_field(
-                &self.phone,
-                "phone",
-                vec![Custom::new(
-                    Self::is_not_blank,
-                    "REQUIRED",
-                    "{} is required",
-                )],
-            ),
-            string_engine.validate_field(
-                &self.phone,
-                "phone",
-                vec![Length {
-                    min: Some(10),
-                    max: Some(20),
-                }],
-            ),
-            string_engine.validate_field(&self.phone, "phone", vec![Phone]),
-            {
-                let rules: Vec<Box<dyn ValidationRule<String>>> = vec![
-                    Box::new(Custom::new(
-                        Self::is_not_blank,
-                        "REQUIRED",
-                        "{} is required",
-                    )),
-                    Box::new(Length {
-                        min: None,
-                        max: Some(500),
-                    }),
-                ];
-                let mut errors = Vec::new();
-                for rule in &rules {
-                    if let Err(e) = rule.validate(&self.address, "address") {
-                        errors.push(e);
-                    }
-                }
-                if errors.is_empty() {
-                    ValidationOutcome::success(&self.address)
-                } else {
-                    ValidationOutcome::failure(errors)
-                }
-            },
-        ];
-
-        let age_validations = [range_engine.validate_field(
-            &self.age,
-            "age",
-            vec![Range {
-                min: Some(0),
-                max: Some(150),
-            }],
-        )];
-
-        let mut errors: Vec<String> = string_validations
-            .into_iter()
-            .flat_map(|outcome| functional_utils::to_error_messages(outcome.errors))
-            .collect();
-
-        errors.extend(
-            age_validations
-                .into_iter()
-                .flat_map(|outcome| functional_utils::to_error_messages(outcome.errors)),
-        );
-
+        let errors = self.validation_errors();
         if errors.is_empty() {
             Ok(())
         } else {
-            Err(errors)
+            Err(functional_utils::to_error_messages(errors))
+        }
+    }
+}
+
+/// Partial update of a person; only the fields present in the body change.
+///
+/// Each field tells an omitted key (`None`) apart from an explicit `null`
+/// (`Some(None)`). Every person field is required, so `null` is rejected by
+/// [`PersonPatch::apply`] rather than clearing the stored value.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PersonPatch {
+    #[serde(default, deserialize_with = "present")]
+    pub name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub gender: Option<Option<bool>>,
+    #[serde(default, deserialize_with = "present")]
+    pub age: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "present")]
+    pub address: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub phone: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub email: Option<Option<String>>,
+}
+
+/// Shortest query [`Person::search`] runs as a full-text search
+pub const SEARCH_MIN_LENGTH: usize = 3;
+
+/// `to_tsquery` text requiring every word of `query` as a prefix, or `None`
+/// when it has no words.
+///
+/// Anything but letters and digits separates words, so the query syntax of
+/// `to_tsquery` never reaches it.
+fn tsquery(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(|term| format!("{}:*", term))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" & "))
+}
+
+/// `text` with the `LIKE` wildcards escaped, to match it literally.
+fn escape_like(text: &str) -> String {
+    text.chars()
+        .flat_map(|c| match c {
+            '\\' | '%' | '_' => vec!['\\', c],
+            _ => vec![c],
+        })
+        .collect()
+}
+
+/// Orders `query` by each column of `sort` in turn, by name when `sort` is empty.
+///
+/// `id` breaks the remaining ties in the direction of the last column, so offset
+/// pages are stable. Columns must be [`Person::SORTABLE_COLUMNS`]; any other
+/// sorts by `id`.
+fn order_people<'a>(
+    query: people::BoxedQuery<'a, Pg>,
+    sort: &[SortSpec],
+) -> people::BoxedQuery<'a, Pg> {
+    let Some(last) = sort.last() else {
+        return query.order((people::name.asc(), people::id.asc()));
+    };
+    let query = sort.iter().fold(query, |query, spec| {
+        match (spec.column.as_str(), spec.descending) {
+            ("name", false) => query.then_order_by(people::name.asc()),
+            ("name", true) => query.then_order_by(people::name.desc()),
+            ("gender", false) => query.then_order_by(people::gender.asc()),
+            ("gender", true) => query.then_order_by(people::gender.desc()),
+            ("age", false) => query.then_order_by(people::age.asc()),
+            ("age", true) => query.then_order_by(people::age.desc()),
+            ("address", false) => query.then_order_by(people::address.asc()),
+            ("address", true) => query.then_order_by(people::address.desc()),
+            ("phone", false) => query.then_order_by(people::phone.asc()),
+            ("phone", true) => query.then_order_by(people::phone.desc()),
+            ("email", false) => query.then_order_by(people::email.asc()),
+            ("email", true) => query.then_order_by(people::email.desc()),
+            (_, false) => query.then_order_by(people::id.asc()),
+            (_, true) => query.then_order_by(people::id.desc()),
+        }
+    });
+    if last.descending {
+        query.then_order_by(people::id.desc())
+    } else {
+        query.then_order_by(people::id.asc())
+    }
+}
+
+/// Deserializes a key that is present in the body, so `null` becomes `Some(None)`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+impl PersonPatch {
+    /// Names of the fields present in the body, in declaration order.
+    pub fn fields(&self) -> Vec<&'static str> {
+        [
+            ("name", self.name.is_some()),
+            ("gender", self.gender.is_some()),
+            ("age", self.age.is_some()),
+            ("address", self.address.is_some()),
+            ("phone", self.phone.is_some()),
+            ("email", self.email.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, present)| present.then_some(field))
+        .collect()
+    }
+
+    /// `person` with the present fields replaced.
+    ///
+    /// Fails with one `required` error per field explicitly set to `null`.
+    /// The merged values are not validated here; see
+    /// [`PersonDTO::validation_errors`].
+    pub fn apply(self, person: &Person) -> Result<PersonDTO, Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        let name = merge_field("name", self.name, &person.name, &mut errors);
+        let gender = merge_field("gender", self.gender, &person.gender, &mut errors);
+        let age = merge_field("age", self.age, &person.age, &mut errors);
+        let address = merge_field("address", self.address, &person.address, &mut errors);
+        let phone = merge_field("phone", self.phone, &person.phone, &mut errors);
+        let email = merge_field("email", self.email, &person.email, &mut errors);
+
+        match (name, gender, age, address, phone, email) {
+            (Some(name), Some(gender), Some(age), Some(address), Some(phone), Some(email)) => {
+                Ok(PersonDTO {
+                    name,
+                    gender,
+                    age,
+                    address,
+                    phone,
+                    email,
+                })
+            }
+            _ => Err(errors),
+        }
+    }
+}
+
+/// The patched value of `field`, or `None` after recording an error for an explicit `null`.
+fn merge_field<T: Clone>(
+    field: &str,
+    value: Option<Option<T>>,
+    current: &T,
+    errors: &mut Vec<ValidationError>,
+) -> Option<T> {
+    match value {
+        None => Some(current.clone()),
+        Some(Some(value)) => Some(value),
+        Some(None) => {
+            errors.push(ValidationError::new(
+                field,
+                codes::REQUIRED,
+                &format!("{} is required", field),
+            ));
+            None
         }
     }
 }
@@ -202,17 +361,163 @@ impl HasId for Person {
     }
 }
 
+impl HeapSize for Person {
+    fn heap_size(&self) -> usize {
+        self.name.heap_size()
+            + self.address.heap_size()
+            + self.phone.heap_size()
+            + self.email.heap_size()
+    }
+}
+
 impl Person {
+    /// Keys of the serialized person, selectable with a `fields=` parameter
+    pub const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "gender",
+        "age",
+        "address",
+        "phone",
+        "email",
+        "deleted_at",
+        "version",
+    ];
+
+    /// Columns a contact list can be sorted by with `sort=`
+    pub const SORTABLE_COLUMNS: &'static [&'static str] =
+        &["id", "name", "gender", "age", "address", "phone", "email"];
+
+    /// Header row of the CSV export, matching [`Person::write_csv`]
+    pub const CSV_HEADER: &'static [&'static str] =
+        &["id", "name", "gender", "age", "address", "phone", "email"];
+
+    /// Appends the person to `out` as one CSV record.
+    pub fn write_csv(&self, out: &mut String) {
+        csv::write_record(
+            out,
+            [
+                self.id.to_string().as_str(),
+                self.name.as_str(),
+                if self.gender { "true" } else { "false" },
+                self.age.to_string().as_str(),
+                self.address.as_str(),
+                self.phone.as_str(),
+                self.email.as_str(),
+            ],
+        );
+    }
+
     pub fn find_all(conn: &mut Connection) -> QueryResult<Vec<Person>> {
-        people::table.order(people::id.asc()).load::<Person>(conn)
+        people::table
+            .filter(people::deleted_at.is_null())
+            .order(people::id.asc())
+            .select(Person::as_select())
+            .load::<Person>(conn)
+    }
+
+    /// `limit` people from `offset` ordered by `sort` (by name when empty), and
+    /// the number of people in total.
+    pub fn list_page(
+        offset: i64,
+        limit: i64,
+        sort: &[SortSpec],
+        conn: &mut Connection,
+    ) -> QueryResult<(Vec<Person>, i64)> {
+        let active = || people::table.filter(people::deleted_at.is_null());
+        let total = active().count().get_result::<i64>(conn)?;
+        let records = order_people(active().into_boxed(), sort)
+            .offset(offset)
+            .limit(limit)
+            .select(Person::as_select())
+            .load::<Person>(conn)?;
+        Ok((records, total))
+    }
+
+    /// Up to `limit` people with an id greater than `after`, in id order.
+    ///
+    /// Keyset pagination for reading the whole table in batches: pass the id
+    /// of the last person of the previous batch as `after`.
+    pub fn find_after(after: i32, limit: i64, conn: &mut Connection) -> QueryResult<Vec<Person>> {
+        people::table
+            .filter(people::id.gt(after))
+            .filter(people::deleted_at.is_null())
+            .order(people::id.asc())
+            .limit(limit)
+            .select(Person::as_select())
+            .load::<Person>(conn)
     }
 
     pub fn find_by_id(i: i32, conn: &mut Connection) -> QueryResult<Person> {
-        people::table.find(i).get_result::<Person>(conn)
+        people::table
+            .find(i)
+            .filter(people::deleted_at.is_null())
+            .select(Person::as_select())
+            .get_result::<Person>(conn)
+    }
+
+    /// Finds the person with the given id even if it was soft-deleted, for admin recovery.
+    pub fn find_including_deleted(i: i32, conn: &mut Connection) -> QueryResult<Person> {
+        people::table
+            .find(i)
+            .select(Person::as_select())
+            .get_result::<Person>(conn)
+    }
+
+    /// Up to `limit` people matching `query`, most relevant first.
+    ///
+    /// Queries of at least [`SEARCH_MIN_LENGTH`] characters run a full-text
+    /// search over name, email and phone: every word must match as a prefix,
+    /// and results are ranked with `ts_rank`, name matches ahead of email and
+    /// phone matches. Shorter queries fall back to a case-insensitive substring
+    /// match, in id order. The connection belongs to the tenant's database, so
+    /// only that tenant's contacts are searched. Soft-deleted people are never
+    /// included.
+    pub fn search(query: &str, limit: i64, conn: &mut Connection) -> QueryResult<Vec<Person>> {
+        use diesel::dsl::sql;
+        use diesel::sql_types::{Bool, Float, Text};
+
+        let query = query.trim();
+        let Some(tsquery) = tsquery(query).filter(|_| query.chars().count() >= SEARCH_MIN_LENGTH)
+        else {
+            let pattern = format!("%{}%", escape_like(query));
+            return people::table
+                .filter(people::deleted_at.is_null())
+                .filter(
+                    people::name
+                        .ilike(pattern.clone())
+                        .or(people::email.ilike(pattern.clone()))
+                        .or(people::phone.ilike(pattern)),
+                )
+                .order(people::id.asc())
+                .limit(limit)
+                .select(Person::as_select())
+                .load::<Person>(conn);
+        };
+
+        people::table
+            .filter(people::deleted_at.is_null())
+            .filter(
+                sql::<Bool>("search_vector @@ to_tsquery('simple', ")
+                    .bind::<Text, _>(tsquery.clone())
+                    .sql(")"),
+            )
+            .order((
+                sql::<Float>("ts_rank(search_vector, to_tsquery('simple', ")
+                    .bind::<Text, _>(tsquery)
+                    .sql("))")
+                    .desc(),
+                people::id.asc(),
+            ))
+            .limit(limit)
+            .select(Person::as_select())
+            .load::<Person>(conn)
     }
 
     /// Get a paginated Page of people matching the provided filter criteria.
     ///
+    /// Soft-deleted people are never included.
+    ///
     /// Applies the following optional filters from `PersonFilter`:
     /// - `age`: exact match.
     /// - `email`, `name`, `phone`: partial match using SQL `LIKE` with surrounding `%` wildcards (case-sensitive).
@@ -241,7 +546,9 @@ impl Person {
     /// ```
     pub fn filter(filter: PersonFilter, conn: &mut Connection) -> QueryResult<Page<Person>> {
         // Use functional query building with iterator-based predicate composition
-        let mut query = people::table.into_boxed();
+        let mut query = people::table
+            .filter(people::deleted_at.is_null())
+            .into_boxed();
 
         // Build query using functional composition with fold
         let predicates: Vec<
@@ -340,6 +647,7 @@ impl Person {
         // Handle sorting through pagination - don't add ORDER BY to the base query
         // The pagination system will handle ordering by the cursor column
         let records = query
+            .select(Person::as_select())
             .paginate(cursor)
             .per_page(page_size)
             .load_items::<Person>(conn)?;
@@ -353,7 +661,7 @@ impl Person {
         ))
     }
 
-    /// Insert a new person record into the `people` table.
+    /// Insert a new person record, owned by `tenant_id`, into the `people` table.
     ///
     /// Inserts the provided `PersonDTO` and returns the number of rows inserted.
     ///
@@ -370,25 +678,57 @@ impl Person {
     ///     phone: "555-1234".into(),
     ///     email: "alice@example.com".into(),
     /// };
-    /// let rows_inserted = insert(new_person, &mut conn).unwrap();
+    /// let rows_inserted = insert("tenant1", new_person, &mut conn).unwrap();
     /// assert_eq!(rows_inserted, 1);
     /// ```
-    pub fn insert(new_person: PersonDTO, conn: &mut Connection) -> Result<usize, ServiceError> {
+    pub fn insert(
+        tenant_id: &str,
+        new_person: PersonDTO,
+        conn: &mut Connection,
+    ) -> Result<usize, ServiceError> {
         // Validate using functional validation patterns
-        new_person
-            .validate()
-            .map_err(|errors| ServiceError::bad_request(errors.join("; ")))?;
+        let errors = new_person.validation_errors();
+        if !errors.is_empty() {
+            return Err(ServiceError::validation(errors));
+        }
 
         // Insert using functional composition
         diesel::insert_into(people::table)
-            .values(&new_person)
+            .values((&new_person, people::tenant_id.eq(tenant_id)))
             .execute(conn)
             .map_err(|e| {
                 ServiceError::internal_server_error(format!("Failed to insert person: {}", e))
             })
     }
 
-    /// Updates the person record with the specified id using values from `updated_person`.
+    /// Inserts `new_people`, owned by `tenant_id`, with one multi-row `INSERT`
+    /// inside a transaction.
+    ///
+    /// Rows are expected to be validated already; if any row is rejected by the database,
+    /// none are inserted.
+    ///
+    /// # Returns
+    ///
+    /// Number of rows inserted on success.
+    pub fn insert_many(
+        tenant_id: &str,
+        new_people: &[PersonDTO],
+        conn: &mut Connection,
+    ) -> QueryResult<usize> {
+        let rows: Vec<_> = new_people
+            .iter()
+            .map(|person| (person, people::tenant_id.eq(tenant_id)))
+            .collect();
+        // `Connection` here is the crate's connection type, shadowing diesel's trait
+        diesel::Connection::transaction(conn, |conn| {
+            diesel::insert_into(people::table)
+                .values(rows)
+                .execute(conn)
+        })
+    }
+
+    /// Updates the person record with the specified id using values from `updated_person`,
+    /// incrementing its version.
     ///
     /// # Examples
     ///
@@ -411,16 +751,40 @@ impl Person {
     ///
     /// Number of rows updated on success.
     pub fn update(i: i32, updated_person: PersonDTO, conn: &mut Connection) -> QueryResult<usize> {
-        diesel::update(people::table.find(i))
-            .set(&updated_person)
+        diesel::update(people::table.find(i).filter(people::deleted_at.is_null()))
+            .set((&updated_person, people::version.eq(people::version + 1)))
             .execute(conn)
     }
 
-    /// Deletes the person with the given id from the people table.
+    /// Updates the person only while it is still at `version`, incrementing it.
+    ///
+    /// # Returns
+    ///
+    /// `usize` number of rows updated; `0` if the person is missing, deleted or
+    /// was updated since `version` was read.
+    pub fn update_if_version(
+        i: i32,
+        version: i32,
+        updated_person: PersonDTO,
+        conn: &mut Connection,
+    ) -> QueryResult<usize> {
+        diesel::update(
+            people::table
+                .find(i)
+                .filter(people::deleted_at.is_null())
+                .filter(people::version.eq(version)),
+        )
+        .set((&updated_person, people::version.eq(people::version + 1)))
+        .execute(conn)
+    }
+
+    /// Soft-deletes the person with the given id by setting `deleted_at` to the current time.
+    ///
+    /// The row is kept so it can be brought back with [`Person::restore`].
     ///
     /// # Returns
     ///
-    /// `usize` number of rows deleted.
+    /// `usize` number of rows deleted; `0` if the person is missing or already deleted.
     ///
     /// # Examples
     ///
@@ -434,6 +798,107 @@ impl Person {
     /// assert_eq!(deleted, 1);
     /// ```
     pub fn delete(i: i32, conn: &mut Connection) -> QueryResult<usize> {
-        diesel::delete(people::table.find(i)).execute(conn)
+        diesel::update(people::table.find(i).filter(people::deleted_at.is_null()))
+            .set(people::deleted_at.eq(now.nullable()))
+            .execute(conn)
+    }
+
+    /// The listed ids whose person is owned by a tenant other than `tenant_id`,
+    /// deleted or not, in ascending order.
+    ///
+    /// People created before contacts had an owner belong to no tenant and are
+    /// never reported.
+    pub fn foreign_ids(
+        tenant_id: &str,
+        ids: &[i32],
+        conn: &mut Connection,
+    ) -> QueryResult<Vec<i32>> {
+        people::table
+            .filter(people::id.eq_any(ids))
+            .filter(people::tenant_id.ne(tenant_id))
+            .select(people::id)
+            .order(people::id.asc())
+            .load(conn)
+    }
+
+    /// Soft-deletes every listed person of `tenant_id` that is not deleted yet.
+    ///
+    /// People owned by another tenant are left alone; see [`Person::foreign_ids`].
+    ///
+    /// # Returns
+    ///
+    /// The ids that were deleted; missing, foreign and already deleted people are left out.
+    pub fn delete_many(
+        tenant_id: &str,
+        ids: &[i32],
+        conn: &mut Connection,
+    ) -> QueryResult<Vec<i32>> {
+        diesel::update(
+            people::table
+                .filter(people::id.eq_any(ids))
+                .filter(
+                    people::tenant_id
+                        .is_null()
+                        .or(people::tenant_id.eq(tenant_id)),
+                )
+                .filter(people::deleted_at.is_null()),
+        )
+        .set(people::deleted_at.eq(now.nullable()))
+        .returning(people::id)
+        .get_results(conn)
+    }
+
+    /// Restores a soft-deleted person so it shows up in listings again.
+    ///
+    /// # Returns
+    ///
+    /// `usize` number of rows restored; `0` if the person is missing or not deleted.
+    pub fn restore(i: i32, conn: &mut Connection) -> QueryResult<usize> {
+        diesel::update(
+            people::table
+                .find(i)
+                .filter(people::deleted_at.is_not_null()),
+        )
+        .set(people::deleted_at.eq(None::<NaiveDateTime>))
+        .execute(conn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tsquery_requires_every_word_as_prefix() {
+        assert_eq!(tsquery("Jane  smith").as_deref(), Some("Jane:* & smith:*"));
+        assert_eq!(
+            tsquery("o'brien | !x").as_deref(),
+            Some("o:* & brien:* & x:*")
+        );
+        assert_eq!(tsquery(" &|! "), None);
+    }
+
+    #[test]
+    fn test_escape_like_matches_wildcards_literally() {
+        assert_eq!(escape_like("50%_a\\b"), "50\\%\\_a\\\\b");
+    }
+
+    #[test]
+    fn test_person_update_requires_version() {
+        let body = serde_json::json!({
+            "name": "Alice",
+            "gender": true,
+            "age": 30,
+            "address": "123 Main St",
+            "phone": "0123456789",
+            "email": "alice@example.com",
+        });
+        assert!(serde_json::from_value::<PersonUpdate>(body.clone()).is_err());
+
+        let mut body = body;
+        body["version"] = serde_json::json!(4);
+        let update: PersonUpdate = serde_json::from_value(body).unwrap();
+        assert_eq!(update.version, 4);
+        assert_eq!(update.person.name, "Alice");
     }
 }
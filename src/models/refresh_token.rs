@@ -1,8 +1,13 @@
 use chrono::{NaiveDateTime, Utc};
+use diesel::Connection as _;
 use diesel::{prelude::*, Associations, Identifiable, Insertable, Queryable};
 use uuid::Uuid;
 
-use crate::{config::db::Connection, models::user::User, schema::refresh_tokens};
+use crate::{
+    config::{db::Connection, functional_config::TOKEN_LIFETIMES},
+    models::user::User,
+    schema::refresh_tokens,
+};
 
 #[derive(Debug, Identifiable, Associations, Queryable)]
 #[diesel(belongs_to(User))]
@@ -14,6 +19,12 @@ pub struct RefreshToken {
     pub expires_at: NaiveDateTime,
     pub created_at: Option<NaiveDateTime>,
     pub revoked: Option<bool>,
+    /// Shared by every token rotated from the same login
+    pub family_id: String,
+    /// Set once the token has been exchanged for its successor
+    pub used_at: Option<NaiveDateTime>,
+    /// Id of the token issued in exchange for this one
+    pub replaced_by: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -22,12 +33,25 @@ pub struct NewRefreshToken {
     pub user_id: i32,
     pub token: String,
     pub expires_at: NaiveDateTime,
+    pub family_id: String,
+}
+
+/// Why a presented refresh token could not be rotated.
+#[derive(Debug, thiserror::Error)]
+pub enum RotationError {
+    #[error("refresh token is unknown, expired or revoked")]
+    Invalid,
+    /// The token had already been exchanged; its whole family has been revoked
+    #[error("refresh token was already used; token family {family_id} revoked")]
+    Reused { user_id: i32, family_id: String },
+    #[error(transparent)]
+    Database(#[from] diesel::result::Error),
 }
 
 impl RefreshToken {
     /// Generates, stores, and returns a new refresh token for the specified user.
     ///
-    /// Creates a new UUID-based token, sets its expiry to 30 days from now, inserts a
+    /// Creates a new UUID-based token, sets its expiry `REFRESH_TOKEN_TTL_SECS` (7 days by default) from now, inserts a
     /// corresponding refresh token row into the database, and returns the token string.
     ///
     /// # Returns
@@ -46,22 +70,30 @@ impl RefreshToken {
         user_id_val: i32,
         conn: &mut Connection,
     ) -> Result<String, diesel::result::Error> {
-        let token_val = Uuid::new_v4().to_string();
-        let expires_at_val = (Utc::now() + chrono::Duration::days(30)).naive_utc();
+        Self::issue(user_id_val, conn).map(|record| record.token)
+    }
+
+    /// Stores a new refresh token for `user_id_val` starting a new token family.
+    pub fn issue(user_id_val: i32, conn: &mut Connection) -> QueryResult<Self> {
+        Self::insert(user_id_val, Uuid::new_v4().to_string(), conn)
+    }
 
+    /// Inserts a fresh token for `user_id_val` in the token family `family_id_val`.
+    fn insert(user_id_val: i32, family_id_val: String, conn: &mut Connection) -> QueryResult<Self> {
         let new_token = NewRefreshToken {
             user_id: user_id_val,
-            token: token_val.clone(),
-            expires_at: expires_at_val,
+            token: Uuid::new_v4().to_string(),
+            expires_at: (Utc::now() + chrono::Duration::seconds(TOKEN_LIFETIMES.refresh_secs))
+                .naive_utc(),
+            family_id: family_id_val,
         };
 
         diesel::insert_into(refresh_tokens::table)
             .values(&new_token)
-            .execute(conn)?;
-
-        Ok(token_val)
+            .get_result(conn)
     }
 
+    /// Finds a token that can still be exchanged: not revoked, used or expired.
     pub fn find_by_token(token_val: &str, conn: &mut Connection) -> QueryResult<Self> {
         refresh_tokens::table
             .filter(refresh_tokens::token.eq(token_val))
@@ -70,10 +102,60 @@ impl RefreshToken {
                     .is_null()
                     .or(refresh_tokens::revoked.eq(false)),
             )
+            .filter(refresh_tokens::used_at.is_null())
             .filter(refresh_tokens::expires_at.gt(Utc::now().naive_utc()))
             .get_result(conn)
     }
 
+    /// Exchanges the `presented` token for a new one in the same family.
+    ///
+    /// The presented token is marked used and points at its successor through
+    /// `replaced_by`. Presenting a token that was already used means a copy of it
+    /// leaked, so the whole family is revoked and [`RotationError::Reused`] is
+    /// returned; the legitimate holder has to log in again.
+    pub fn rotate(presented: &str, conn: &mut Connection) -> Result<Self, RotationError> {
+        // A refused exchange is returned as `Ok(Err(..))` so the family revocation commits
+        conn.transaction(
+            |conn| -> Result<Result<Self, RotationError>, RotationError> {
+                // Locked so two concurrent exchanges of one token cannot both succeed
+                let current: Self = refresh_tokens::table
+                    .filter(refresh_tokens::token.eq(presented))
+                    .for_update()
+                    .get_result(conn)
+                    .optional()?
+                    .ok_or(RotationError::Invalid)?;
+
+                if current.used_at.is_some() {
+                    Self::revoke_family(&current.family_id, conn)?;
+                    return Ok(Err(RotationError::Reused {
+                        user_id: current.user_id,
+                        family_id: current.family_id,
+                    }));
+                }
+                if current.revoked == Some(true) || current.expires_at <= Utc::now().naive_utc() {
+                    return Err(RotationError::Invalid);
+                }
+
+                let successor = Self::insert(current.user_id, current.family_id.clone(), conn)?;
+                diesel::update(refresh_tokens::table.find(current.id))
+                    .set((
+                        refresh_tokens::used_at.eq(Utc::now().naive_utc()),
+                        refresh_tokens::replaced_by.eq(successor.id),
+                    ))
+                    .execute(conn)?;
+
+                Ok(Ok(successor))
+            },
+        )?
+    }
+
+    /// Revokes every token descending from the same login as `family_id_val`.
+    pub fn revoke_family(family_id_val: &str, conn: &mut Connection) -> QueryResult<usize> {
+        diesel::update(refresh_tokens::table.filter(refresh_tokens::family_id.eq(family_id_val)))
+            .set(refresh_tokens::revoked.eq(true))
+            .execute(conn)
+    }
+
     pub fn revoke(token_val: &str, conn: &mut Connection) -> QueryResult<usize> {
         diesel::update(refresh_tokens::table.filter(refresh_tokens::token.eq(token_val)))
             .set(refresh_tokens::revoked.eq(true))
@@ -86,3 +168,94 @@ impl RefreshToken {
             .execute(conn)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use testcontainers::clients;
+
+    use super::*;
+    use crate::models::user::{operations as user_ops, password::Argon2id, UserDTO};
+    use crate::test_support::{start_postgres, Fixtures, TEST_PASSWORD};
+
+    fn create_user(test_name: &str, conn: &mut Connection) -> i32 {
+        let username = Fixtures::for_test(test_name).username();
+        user_ops::signup_user(
+            UserDTO {
+                email: Fixtures::email(&username),
+                username: username.clone(),
+                password: TEST_PASSWORD.to_string(),
+                active: true,
+            },
+            &Argon2id,
+            conn,
+        )
+        .unwrap();
+        user_ops::find_user_by_username(&username, conn).unwrap().id
+    }
+
+    fn load(token_val: &str, conn: &mut Connection) -> RefreshToken {
+        refresh_tokens::table
+            .filter(refresh_tokens::token.eq(token_val))
+            .get_result(conn)
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_rotate_issues_successor_in_same_family() {
+        let test_name = "test_rotate_issues_successor_in_same_family";
+        let docker = clients::Cli::default();
+        let Some((_postgres, pool)) = start_postgres(&docker, test_name) else {
+            return;
+        };
+        let mut conn = pool.get().unwrap();
+        let user_id = create_user(test_name, &mut conn);
+
+        let first = RefreshToken::create(user_id, &mut conn).unwrap();
+        let second = RefreshToken::rotate(&first, &mut conn).unwrap();
+        let third = RefreshToken::rotate(&second.token, &mut conn).unwrap();
+
+        let used = load(&first, &mut conn);
+        assert!(used.used_at.is_some());
+        assert_eq!(used.replaced_by, Some(second.id));
+        assert_eq!(second.family_id, used.family_id);
+        assert_eq!(third.family_id, used.family_id);
+        assert_eq!(third.user_id, user_id);
+        assert!(RefreshToken::find_by_token(&first, &mut conn).is_err());
+        assert!(RefreshToken::find_by_token(&third.token, &mut conn).is_ok());
+
+        assert!(matches!(
+            RefreshToken::rotate("unknown-token", &mut conn),
+            Err(RotationError::Invalid)
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_rotate_reused_token_revokes_family() {
+        let test_name = "test_rotate_reused_token_revokes_family";
+        let docker = clients::Cli::default();
+        let Some((_postgres, pool)) = start_postgres(&docker, test_name) else {
+            return;
+        };
+        let mut conn = pool.get().unwrap();
+        let user_id = create_user(test_name, &mut conn);
+
+        let stolen = RefreshToken::create(user_id, &mut conn).unwrap();
+        let other_login = RefreshToken::create(user_id, &mut conn).unwrap();
+        let successor = RefreshToken::rotate(&stolen, &mut conn).unwrap();
+
+        let reused = RefreshToken::rotate(&stolen, &mut conn);
+        assert!(matches!(
+            reused,
+            Err(RotationError::Reused { user_id: id, .. }) if id == user_id
+        ));
+
+        // The revocation survives the refused exchange
+        assert_eq!(load(&successor.token, &mut conn).revoked, Some(true));
+        assert!(matches!(
+            RefreshToken::rotate(&successor.token, &mut conn),
+            Err(RotationError::Invalid)
+        ));
+        // Tokens from other logins are a different family
+        assert!(RefreshToken::find_by_token(&other_login, &mut conn).is_ok());
+    }
+}
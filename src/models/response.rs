@@ -1,9 +1,22 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::http::header::{self, ETag, EntityTag, Header, IfNoneMatch, TryIntoHeaderValue};
+use actix_web::{HttpRequest, HttpResponse};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::error::{ErrorTag, ServiceError};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ResponseBody<T> {
     pub message: String,
     pub data: T,
+    /// Set for page-numbered lists, see [`ResponseBody::paginated`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PageMetadata>,
+    /// Set for page-numbered lists, see [`ResponseBody::paginated`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<PageMeta>,
 }
 
 impl<T> ResponseBody<T> {
@@ -11,11 +24,87 @@ impl<T> ResponseBody<T> {
         ResponseBody {
             message: message.to_string(),
             data,
+            metadata: None,
+            pagination: None,
+        }
+    }
+
+    /// Envelope of one page of a list: `{message, data, metadata, pagination}`.
+    ///
+    /// `metadata` is the block cursor pages carry as well (see [`PageMetadata`]),
+    /// kept for clients that read it.
+    pub fn paginated(message: &str, data: T, pagination: PageMeta) -> ResponseBody<T> {
+        ResponseBody {
+            metadata: Some(pagination.metadata()),
+            pagination: Some(pagination),
+            ..ResponseBody::new(message, data)
         }
     }
 }
 
-#[derive(Serialize)]
+/// Position of a page within a page-numbered list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PageMeta {
+    /// 1-based page number
+    pub page: i64,
+    pub per_page: i64,
+    /// Number of items across all pages
+    pub total: i64,
+    /// `total / per_page`, rounded up; 0 for an empty list
+    pub total_pages: i64,
+}
+
+impl PageMeta {
+    /// Metadata of page `page` (1-based) of `per_page` items out of `total`.
+    pub fn new(page: i64, per_page: i64, total: i64) -> Self {
+        let per_page = per_page.max(1);
+        let total = total.max(0);
+        PageMeta {
+            page,
+            per_page,
+            total,
+            total_pages: (total + per_page - 1) / per_page,
+        }
+    }
+
+    /// Number of items on this page.
+    pub fn count(&self) -> i64 {
+        let before = (self.page.max(1) - 1).saturating_mul(self.per_page);
+        (self.total - before).clamp(0, self.per_page)
+    }
+
+    /// The `metadata` block of this page; page-numbered lists have no cursors.
+    pub fn metadata(&self) -> PageMetadata {
+        PageMetadata {
+            current_cursor: None,
+            page_size: self.per_page,
+            total_elements: Some(self.total),
+            next_cursor: None,
+            count: self.count(),
+            has_more: self.page < self.total_pages,
+        }
+    }
+}
+
+/// The `metadata` block of list responses.
+///
+/// Cursor pages ([`Page::metadata`]) and page-numbered ones
+/// ([`PageMeta::metadata`]) share this shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PageMetadata {
+    /// Cursor of this page; `None` for page-numbered lists
+    pub current_cursor: Option<i32>,
+    pub page_size: i64,
+    /// Number of items across all pages, when counted
+    pub total_elements: Option<i64>,
+    /// Cursor of the next page; `None` on the last page and for page-numbered lists
+    pub next_cursor: Option<i32>,
+    /// Number of items on this page
+    pub count: i64,
+    pub has_more: bool,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct Page<T> {
     pub message: String,
     pub data: Vec<T>,
@@ -55,4 +144,280 @@ impl<T> Page<T> {
             next_cursor,
         }
     }
+
+    /// The `metadata` block of this page.
+    pub fn metadata(&self) -> PageMetadata {
+        PageMetadata {
+            current_cursor: Some(self.current_cursor),
+            page_size: self.page_size,
+            total_elements: self.total_elements,
+            next_cursor: self.next_cursor,
+            count: self.data.len() as i64,
+            has_more: self.next_cursor.is_some(),
+        }
+    }
+}
+
+/// Weak entity tag over a serialized response body.
+///
+/// Uses 64-bit FNV-1a so the tag stays stable across builds and restarts.
+pub fn weak_etag(body: &[u8]) -> EntityTag {
+    let hash = body.iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    EntityTag::new_weak(format!("{:016x}", hash))
+}
+
+/// Whether the request's `If-None-Match` header matches `etag` (weak comparison).
+pub fn etag_matches(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        Err(_) => false,
+    }
+}
+
+/// Attach a weak `ETag` to a successful response, answering `304 Not Modified`
+/// with an empty body when the client already holds the same representation.
+///
+/// Non-2xx responses and streaming bodies are returned unchanged.
+pub fn with_etag(req: &HttpRequest, response: HttpResponse) -> HttpResponse {
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (mut response, body) = response.into_parts();
+    let bytes = match body.try_into_bytes() {
+        Ok(bytes) => bytes,
+        Err(body) => return response.set_body(body),
+    };
+
+    let etag = weak_etag(&bytes);
+    if etag_matches(req, &etag) {
+        return HttpResponse::NotModified()
+            .insert_header(ETag(etag))
+            .finish();
+    }
+
+    response.headers_mut().insert(
+        header::ETAG,
+        ETag(etag).try_into_value().expect("valid etag"),
+    );
+    response.set_body(BoxBody::new(bytes))
+}
+
+/// Keys kept by a `fields=name,email` sparse-fieldset query parameter.
+///
+/// Projection happens on the serialized JSON, so it works for any resource
+/// whose serializable keys are listed in `known`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSelection {
+    fields: Vec<String>,
+}
+
+impl FieldSelection {
+    /// Parses the comma-separated `fields` parameter against the `known` keys.
+    ///
+    /// Returns `Ok(None)` when `fields` is absent or blank, meaning the full
+    /// object is returned. Unknown names are ignored, or rejected with a 400
+    /// when `strict` is set.
+    pub fn parse(
+        fields: Option<&str>,
+        strict: bool,
+        known: &[&str],
+    ) -> Result<Option<Self>, ServiceError> {
+        let requested: Vec<&str> = fields
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .collect();
+        if requested.is_empty() {
+            return Ok(None);
+        }
+
+        let (fields, unknown): (Vec<&str>, Vec<&str>) = requested
+            .into_iter()
+            .partition(|field| known.contains(field));
+        if strict && !unknown.is_empty() {
+            return Err(ServiceError::bad_request(format!(
+                "Unknown fields: {}",
+                unknown.join(", ")
+            ))
+            .with_tag(ErrorTag::Validation)
+            .with_metadata("allowed_fields", known.join(",")));
+        }
+
+        Ok(Some(Self {
+            fields: fields.into_iter().map(str::to_string).collect(),
+        }))
+    }
+
+    /// Keeps only the selected keys of an object, or of every object in an array.
+    pub fn project(&self, value: JsonValue) -> JsonValue {
+        match value {
+            JsonValue::Array(items) => {
+                JsonValue::Array(items.into_iter().map(|item| self.project(item)).collect())
+            }
+            JsonValue::Object(mut object) => {
+                object.retain(|key, _| self.fields.iter().any(|field| field == key));
+                JsonValue::Object(object)
+            }
+            other => other,
+        }
+    }
+
+    /// Serializes `data` and projects it, see [`FieldSelection::project`].
+    pub fn apply<T: Serialize>(&self, data: &T) -> Result<JsonValue, serde_json::Error> {
+        serde_json::to_value(data).map(|value| self.project(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::body::to_bytes;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use actix_web::ResponseError;
+
+    use super::*;
+
+    fn json_response(body: &'static str) -> HttpResponse {
+        HttpResponse::Ok().body(body)
+    }
+
+    #[test]
+    fn test_paginated_body_carries_page_meta() {
+        let body = ResponseBody::paginated("ok", vec![1], PageMeta::new(3, 50, 101));
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            serde_json::json!({
+                "message": "ok",
+                "data": [1],
+                "metadata": {
+                    "current_cursor": null,
+                    "page_size": 50,
+                    "total_elements": 101,
+                    "next_cursor": null,
+                    "count": 1,
+                    "has_more": false,
+                },
+                "pagination": { "page": 3, "per_page": 50, "total": 101, "total_pages": 3 },
+            })
+        );
+
+        // Flat envelopes are unchanged
+        assert_eq!(
+            serde_json::to_value(ResponseBody::new("ok", 1)).unwrap(),
+            serde_json::json!({ "message": "ok", "data": 1 })
+        );
+    }
+
+    #[test]
+    fn test_total_pages_rounds_up() {
+        assert_eq!(PageMeta::new(1, 50, 0).total_pages, 0);
+        assert_eq!(PageMeta::new(1, 50, 1).total_pages, 1);
+        assert_eq!(PageMeta::new(1, 50, 50).total_pages, 1);
+        assert_eq!(PageMeta::new(1, 50, 51).total_pages, 2);
+        assert_eq!(PageMeta::new(1, 50, 100).total_pages, 2);
+        assert_eq!(PageMeta::new(1, 7, 23).total_pages, 4);
+    }
+
+    #[test]
+    fn test_page_meta_counts_items_of_its_page() {
+        assert_eq!(PageMeta::new(1, 50, 101).count(), 50);
+        assert_eq!(PageMeta::new(3, 50, 101).count(), 1);
+        assert_eq!(PageMeta::new(4, 50, 101).count(), 0);
+        assert_eq!(PageMeta::new(1, 50, 0).count(), 0);
+        assert!(PageMeta::new(2, 50, 101).metadata().has_more);
+    }
+
+    #[actix_web::test]
+    async fn test_with_etag_sets_weak_tag_and_keeps_body() {
+        let req = TestRequest::default().to_http_request();
+        let response = with_etag(&req, json_response(r#"{"data":[1]}"#));
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(etag.starts_with("W/\""));
+        let body = to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body.as_ref(), br#"{"data":[1]}"#);
+    }
+
+    #[actix_web::test]
+    async fn test_with_etag_returns_not_modified_for_matching_tag() {
+        let etag = weak_etag(br#"{"data":[1]}"#);
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, etag.to_string()))
+            .to_http_request();
+        let response = with_etag(&req, json_response(r#"{"data":[1]}"#));
+
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(
+            response.headers().get(header::ETAG).unwrap(),
+            etag.to_string().as_str()
+        );
+        assert!(to_bytes(response.into_body()).await.unwrap().is_empty());
+    }
+
+    #[actix_web::test]
+    async fn test_with_etag_ignores_stale_tag() {
+        let stale = weak_etag(br#"{"data":[1]}"#);
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, stale.to_string()))
+            .to_http_request();
+        let response = with_etag(&req, json_response(r#"{"data":[2]}"#));
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(
+            response.headers().get(header::ETAG).unwrap(),
+            stale.to_string().as_str()
+        );
+    }
+
+    #[test]
+    fn test_with_etag_skips_error_responses() {
+        let req = TestRequest::default().to_http_request();
+        let response = with_etag(&req, HttpResponse::NotFound().body("missing"));
+        assert!(response.headers().get(header::ETAG).is_none());
+    }
+
+    const KNOWN: &[&str] = &["id", "name", "email", "phone"];
+
+    #[test]
+    fn test_field_selection_projects_objects_and_arrays() {
+        let selection = FieldSelection::parse(Some(" name, email ,bogus"), false, KNOWN)
+            .unwrap()
+            .unwrap();
+        let people = serde_json::json!([
+            {"id": 1, "name": "A", "email": "a@example.com", "phone": "1"},
+            {"id": 2, "name": "B", "email": "b@example.com", "phone": "2"},
+        ]);
+
+        assert_eq!(
+            selection.project(people),
+            serde_json::json!([
+                {"name": "A", "email": "a@example.com"},
+                {"name": "B", "email": "b@example.com"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_field_selection_absent_or_strict() {
+        assert_eq!(FieldSelection::parse(None, true, KNOWN).unwrap(), None);
+        assert_eq!(
+            FieldSelection::parse(Some(" , "), true, KNOWN).unwrap(),
+            None
+        );
+
+        let error = FieldSelection::parse(Some("name,bogus"), true, KNOWN).unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+        assert!(error.to_string().contains("bogus"));
+    }
 }
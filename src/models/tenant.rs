@@ -1,25 +1,73 @@
 use chrono::NaiveDateTime;
-use diesel::{prelude::*, result, AsChangeset, Identifiable, Insertable, Queryable};
+use diesel::{pg::Pg, prelude::*, result, AsChangeset, Identifiable, Insertable, Queryable};
 use log;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::{
     constants::{self, MESSAGE_OK},
-    models::{filters::TenantFilter, response::Page},
+    functional::iterator_engine::HeapSize,
+    models::{
+        filters::{SortSpec, TenantFilter},
+        response::Page,
+    },
     pagination::{PaginatedPage, Pagination as IteratorPagination},
     schema::tenants::{self, dsl::*},
 };
 
 use super::{functional_utils, Custom};
 
-use crate::models::ValidationError;
+use crate::{functional::validation_rules::codes, models::ValidationError};
 
 // Re-export functional utilities for tenant operations
 
 const MAX_PAGE_SIZE: i64 = 10_000;
 
-#[derive(Clone, Identifiable, Queryable, Serialize, Deserialize)]
+/// Order of tenant lists whose request names no `sort`: newest first.
+pub const DEFAULT_TENANT_SORT: &str = "-created_at";
+
+/// Orders `query` by `sort` (see [`SortSpec`]), falling back to [`DEFAULT_TENANT_SORT`].
+///
+/// Handlers reject columns outside [`Tenant::SORTABLE_COLUMNS`] with a 400;
+/// should one reach here anyway it sorts like the default.
+/// `id` always breaks ties, so offset pages are stable even when many tenants
+/// share a timestamp or name. Tenants without timestamps sort last.
+fn order_tenants<'a>(
+    query: tenants::BoxedQuery<'a, Pg>,
+    sort: Option<&str>,
+) -> tenants::BoxedQuery<'a, Pg> {
+    let sort = sort
+        .and_then(SortSpec::parse)
+        .filter(|sort| Tenant::SORTABLE_COLUMNS.contains(&sort.column.as_str()))
+        .or_else(|| SortSpec::parse(DEFAULT_TENANT_SORT))
+        .expect("default tenant sort is valid");
+
+    match (sort.column.as_str(), sort.descending) {
+        ("id", false) => query.order(id.asc()),
+        ("id", true) => query.order(id.desc()),
+        ("name", false) => query.order((name.asc(), id.asc())),
+        ("name", true) => query.order((name.desc(), id.desc())),
+        ("updated_at", false) => query.order((updated_at.asc().nulls_last(), id.asc())),
+        ("updated_at", true) => query.order((updated_at.desc().nulls_last(), id.desc())),
+        (_, false) => query.order((created_at.asc().nulls_last(), id.asc())),
+        (_, true) => query.order((created_at.desc().nulls_last(), id.desc())),
+    }
+}
+
+/// Escapes the `LIKE` wildcards (and the escape character itself) in `term`.
+fn escape_like_pattern(term: &str) -> String {
+    term.chars()
+        .fold(String::with_capacity(term.len()), |mut escaped, c| {
+            if matches!(c, '\\' | '%' | '_') {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+            escaped
+        })
+}
+
+#[derive(Clone, Identifiable, Queryable, Serialize, Deserialize, JsonSchema)]
 #[diesel(table_name = tenants)]
 pub struct Tenant {
     pub id: String,
@@ -29,7 +77,13 @@ pub struct Tenant {
     pub updated_at: Option<NaiveDateTime>,
 }
 
-#[derive(Insertable, Serialize, Deserialize)]
+impl HeapSize for Tenant {
+    fn heap_size(&self) -> usize {
+        self.id.heap_size() + self.name.heap_size() + self.db_url.heap_size()
+    }
+}
+
+#[derive(Insertable, Serialize, Deserialize, JsonSchema)]
 #[diesel(table_name = tenants)]
 pub struct TenantDTO {
     pub id: String,
@@ -37,7 +91,7 @@ pub struct TenantDTO {
     pub db_url: String,
 }
 
-#[derive(AsChangeset, Serialize, Deserialize)]
+#[derive(AsChangeset, Serialize, Deserialize, JsonSchema)]
 #[diesel(table_name = tenants)]
 pub struct UpdateTenant {
     pub name: Option<String>,
@@ -45,6 +99,10 @@ pub struct UpdateTenant {
 }
 
 impl Tenant {
+    /// Columns a tenant list can be sorted by with `sort=`
+    pub const SORTABLE_COLUMNS: &'static [&'static str] =
+        &["id", "name", "created_at", "updated_at"];
+
     /// Checks whether a string contains any non-whitespace characters.
     ///
     /// # Examples
@@ -261,6 +319,7 @@ impl Tenant {
     ///
     /// The `offset` and `limit` parameters control the page window applied at the database level:
     /// `offset` is the number of records to skip and `limit` is the maximum number of records to return.
+    /// Tenants come in [`DEFAULT_TENANT_SORT`] order.
     ///
     /// # Returns
     ///
@@ -279,7 +338,53 @@ impl Tenant {
         conn: &mut crate::config::db::Connection,
     ) -> QueryResult<(Vec<Tenant>, i64)> {
         let total = tenants.count().get_result::<i64>(conn)?;
-        let results = tenants.offset(offset).limit(limit).load::<Tenant>(conn)?;
+        let results = order_tenants(tenants.into_boxed(), None)
+            .offset(offset)
+            .limit(limit)
+            .load::<Tenant>(conn)?;
+        Ok((results, total))
+    }
+
+    /// Fetches a page of the tenants whose `name` or `id` contains `search`, ignoring case,
+    /// and the total number of matching tenants.
+    ///
+    /// `%` and `_` in `search` match literally. `None` or a blank search matches every tenant.
+    /// Results are ordered by `sort` (see [`SortSpec`]), [`DEFAULT_TENANT_SORT`] when absent,
+    /// with `id` as tiebreaker so consecutive pages never overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// // assume `conn` is a mutable database connection: &mut crate::config::db::Connection
+    /// let (page, total) = Tenant::search_paginated(Some("acme"), Some("name"), 0, 10, &mut conn)?;
+    /// assert!(page.iter().all(|t| t.name.to_lowercase().contains("acme")
+    ///     || t.id.to_lowercase().contains("acme")));
+    /// ```
+    pub fn search_paginated(
+        search: Option<&str>,
+        sort: Option<&str>,
+        offset: i64,
+        limit: i64,
+        conn: &mut crate::config::db::Connection,
+    ) -> QueryResult<(Vec<Tenant>, i64)> {
+        let pattern = search
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+            .map(|term| format!("%{}%", escape_like_pattern(term)));
+
+        let mut count_query = tenants.count().into_boxed();
+        let mut page_query = order_tenants(tenants.into_boxed(), sort);
+        if let Some(pattern) = pattern {
+            count_query =
+                count_query.filter(name.ilike(pattern.clone()).or(id.ilike(pattern.clone())));
+            page_query = page_query.filter(name.ilike(pattern.clone()).or(id.ilike(pattern)));
+        }
+
+        let total = count_query.get_result::<i64>(conn)?;
+        let results = page_query
+            .offset(offset)
+            .limit(limit)
+            .load::<Tenant>(conn)?;
         Ok((results, total))
     }
 
@@ -332,7 +437,7 @@ impl Tenant {
                 vec![
                     Custom::new(
                         Self::is_not_blank as fn(&String) -> bool,
-                        "REQUIRED",
+                        codes::REQUIRED,
                         "{} cannot be empty",
                     ),
                     Custom::new(
@@ -347,7 +452,7 @@ impl Tenant {
                 "name",
                 vec![Custom::new(
                     Self::is_not_blank as fn(&String) -> bool,
-                    "REQUIRED",
+                    codes::REQUIRED,
                     "{} cannot be empty",
                 )],
             ),
@@ -418,6 +523,8 @@ impl Tenant {
     ///   - `contains` is implemented with SQL `LIKE`; `%` and `_` in the value act as wildcards.
     /// - `created_at`, `updated_at`: `gt`, `gte`, `lt`, `lte`, `equals`. Date/time values must use ISO-like format `YYYY-MM-DDTHH:MM:SS.sssZ` (for example `2023-12-25T10:00:00.000Z`).
     ///
+    /// Rows are ordered by `filter.sort`, [`DEFAULT_TENANT_SORT`] when absent, with `id` as tiebreaker.
+    ///
     /// Unknown fields or unsupported operators are ignored. Pagination parameters are normalized via the iterator-based pagination helper; an extra record is fetched per page to detect whether more data exists without materializing the full result set.
     ///
     /// Returns a `DatabaseError` when a date value cannot be parsed or when pagination parameters overflow supported bounds.
@@ -432,6 +539,7 @@ impl Tenant {
     /// //     filters: vec![FieldFilter { field: "name".into(), operator: "contains".into(), value: "acme".into() }],
     /// //     page_size: Some(100),
     /// //     cursor: Some(0),
+    /// //     sort: Some("-created_at".into()),
     /// // };
     /// // let tenant_page = Tenant::filter(filter, &mut conn).expect("query failed");
     /// // assert!(tenant_page.data.len() <= 100);
@@ -549,8 +657,9 @@ impl Tenant {
             )
         })?;
 
-        let mut query = query;
-        query = query.limit(limit).offset(offset);
+        let query = order_tenants(query, filter.sort.as_deref())
+            .limit(limit)
+            .offset(offset);
 
         let mut results = query.load::<Tenant>(conn)?;
 
@@ -1,12 +1,14 @@
 #![cfg_attr(test, allow(dead_code))]
 
 use diesel::{Identifiable, Insertable, Queryable, Selectable};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::users;
+use crate::{functional::iterator_engine::HeapSize, schema::users};
 
 // Include pure functional operations for User
 pub mod operations;
+pub mod password;
 
 #[derive(Identifiable, Queryable, Selectable, Serialize, Deserialize)]
 #[diesel(table_name = users)]
@@ -20,6 +22,20 @@ pub struct User {
     pub active: bool,
 }
 
+impl User {
+    /// Columns the user list can be sorted by through its `sort` parameter.
+    pub const SORTABLE_COLUMNS: &'static [&'static str] = &["id", "username", "email", "active"];
+}
+
+impl HeapSize for User {
+    fn heap_size(&self) -> usize {
+        self.username.heap_size()
+            + self.email.heap_size()
+            + self.password.heap_size()
+            + self.login_session.heap_size()
+    }
+}
+
 #[derive(Insertable, Serialize, Deserialize)]
 #[diesel(table_name = users)]
 pub struct UserDTO {
@@ -44,7 +60,7 @@ pub struct UserResponseDTO {
     pub active: bool,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct SignupDTO {
     pub username: String,
     pub email: String,
@@ -52,14 +68,14 @@ pub struct SignupDTO {
     pub tenant_id: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct LoginDTO {
     pub username_or_email: String,
     pub password: String,
     pub tenant_id: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct LoginInfoDTO {
     pub username: String,
     pub login_session: String,
@@ -3,84 +3,30 @@
 //! This module contains all database and business logic operations for Users,
 //! implemented as pure functions with functional composition patterns.
 
-use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
-    Argon2,
-};
-use diesel::{prelude::*, result::DatabaseErrorKind, result::QueryResult};
+use diesel::{pg::Pg, prelude::*, result::DatabaseErrorKind, result::QueryResult};
 use uuid::Uuid;
 
 use crate::{
     config::db::Connection,
     constants,
     error::ServiceError,
+    functional::validation_rules::normalize_email,
     models::{
-        login_history::LoginHistory,
-        user::{LoginDTO, LoginInfoDTO, User, UserDTO},
+        filters::SortSpec,
+        login_history::{LoginHistory, SessionClient},
+        user::{
+            password::{verify_password, PasswordHasher},
+            LoginDTO, LoginInfoDTO, User, UserDTO,
+        },
         user_token::UserToken,
     },
     schema::users::{self, dsl::*},
+    services::db_retry::{with_retry, RetryPolicy},
 };
 
-/// Hash a plain password using Argon2 with a randomly generated salt.
-///
-/// Returns the encoded Argon2 password hash on success or an error message on failure.
-///
-/// # Examples
-///
-/// ```
-/// let res = hash_password_argon2("s3cr3t");
-/// assert!(res.is_ok());
-/// let hash = res.unwrap();
-/// assert!(hash.starts_with("$argon2"));
-/// ```
-pub fn hash_password_argon2(plain_password: &str) -> Result<String, String> {
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    argon2
-        .hash_password(plain_password.as_bytes(), &salt)
-        .map_err(|e| format!("Password hashing failed: {}", e))
-        .map(|hash| hash.to_string())
-}
-
-/// Verify a user-supplied password against a stored password hash that may be Argon2 or bcrypt.
-///
-/// Attempts bcrypt verification when the stored hash has a bcrypt prefix (`$2`); otherwise attempts Argon2 verification. Returns `true` on successful verification, `false` on any mismatch or parsing/verification error.
-///
-/// # Examples
-///
-/// ```
-/// use rand::thread_rng;
-/// use argon2::Argon2;
-/// // bcrypt example
-/// let password = "s3cr3t";
-/// let bcrypt_hash = bcrypt::hash(password, bcrypt::DEFAULT_COST).unwrap();
-/// assert!(verify_password_hybrid(&bcrypt_hash, password));
-///
-/// // argon2 example
-/// use argon2::password_hash::SaltString;
-/// let salt = SaltString::generate(&mut thread_rng());
-/// let argon2 = Argon2::default();
-/// let argon2_hash = argon2.hash_password(password.as_bytes(), &salt).unwrap().to_string();
-/// assert!(verify_password_hybrid(&argon2_hash, password));
-/// ```
-pub fn verify_password_hybrid(stored_hash: &str, provided_password: &str) -> bool {
-    let is_bcrypt = stored_hash.starts_with("$2");
-
-    if is_bcrypt {
-        // Hash looks like bcrypt, use bcrypt verification
-        bcrypt::verify(provided_password, stored_hash).unwrap_or(false)
-    } else {
-        // Default to Argon2 verification
-        PasswordHash::new(stored_hash)
-            .map(|parsed_hash| {
-                let argon2 = Argon2::default();
-                argon2
-                    .verify_password(provided_password.as_bytes(), &parsed_hash)
-                    .is_ok()
-            })
-            .unwrap_or(false)
-    }
+define_sql_function! {
+    /// SQL `LOWER()`, used to compare emails case-insensitively
+    fn lower(value: diesel::sql_types::Text) -> diesel::sql_types::Text;
 }
 
 /// Generates a new login session identifier as a UUID v4 string.
@@ -98,14 +44,15 @@ pub fn generate_login_session() -> String {
 
 /// Creates and persistently records a new login session for a user.
 ///
-/// Attempts to save a login history entry for `user_name`, generates a new session identifier,
-/// updates the user's `login_session` in the database, and returns the corresponding `LoginInfoDTO`
-/// on success; returns `None` if any operation fails.
+/// Generates a new session identifier, saves it with the `client` details as a login history entry
+/// for `user_name`, updates the user's `login_session` in the database, and returns the corresponding
+/// `LoginInfoDTO` on success; returns `None` if any operation fails.
 ///
 /// # Parameters
 ///
 /// - `user_name`: username to create the session for.
 /// - `tenant_id`: tenant identifier to include in the returned `LoginInfoDTO`.
+/// - `client`: user agent and IP address the login came from.
 /// - `conn`: mutable database connection used for persistence operations.
 ///
 /// # Returns
@@ -118,16 +65,19 @@ pub fn generate_login_session() -> String {
 /// ```
 /// // assumes a helper `establish_connection()` exists in test context
 /// let mut conn = establish_connection();
-/// let result = create_login_session("alice", "tenant-1".to_string(), &mut conn);
+/// let result = create_login_session("alice", "tenant-1".to_string(), &SessionClient::default(), &mut conn);
 /// assert!(result.is_some());
 /// ```
 pub fn create_login_session(
     user_name: &str,
     tenant_id: String,
+    client: &SessionClient,
     conn: &mut Connection,
 ) -> Option<LoginInfoDTO> {
+    let login_session_str = generate_login_session();
+
     // Create and save login history
-    let login_history = LoginHistory::create(user_name, conn)?;
+    let login_history = LoginHistory::create(user_name, &login_session_str, client, conn)?;
     if let Err(e) = LoginHistory::save_login_history(login_history, conn) {
         log::error!(
             "Failed to save login history for user '{}': {}",
@@ -137,8 +87,7 @@ pub fn create_login_session(
         return None;
     }
 
-    // Generate session and update database
-    let login_session_str = generate_login_session();
+    // Record the session as the user's latest
     match update_login_session_to_db(user_name, &login_session_str, conn) {
         Ok(()) => Some(LoginInfoDTO {
             username: user_name.to_string(),
@@ -201,8 +150,9 @@ pub fn update_login_session_to_db(
 
 /// Registers a new user by hashing their password and inserting the user record into the database.
 ///
-/// Hashes the provided plaintext password with Argon2, constructs a new UserDTO containing the hash,
-/// and attempts to insert it into the users table. If the username (or other unique constraint) already
+/// Hashes the provided plaintext password with `hasher`, constructs a new UserDTO containing the hash
+/// and the lowercased email, and attempts to insert it into the users table. An email already used
+/// by another user, in any letter case, is rejected before inserting. If the username (or other unique constraint) already
 /// exists, returns a `bad_request` ServiceError identifying the duplicate; on hashing failures or other
 /// database errors returns an `internal_server_error`.
 ///
@@ -224,32 +174,51 @@ pub fn update_login_session_to_db(
 ///     ..Default::default()
 /// };
 /// let mut conn = establish_connection();
-/// let result = signup_user(user, &mut conn);
+/// let result = signup_user(user, &Argon2id, &mut conn);
 /// ```
-pub fn signup_user(user: UserDTO, conn: &mut Connection) -> Result<String, ServiceError> {
+pub fn signup_user(
+    user: UserDTO,
+    hasher: &dyn PasswordHasher,
+    conn: &mut Connection,
+) -> Result<String, ServiceError> {
+    let user_email = normalize_email(&user.email);
+    let email_taken = email_in_use(&user_email, None, conn).map_err(|err| {
+        log::error!("Signup failed: {}", err);
+        ServiceError::internal_server_error("Internal server error".to_string())
+    })?;
+    if email_taken {
+        return Err(ServiceError::bad_request(format!(
+            "Email '{}' is already registered",
+            user_email
+        )));
+    }
+
     // Hash password using functional composition
-    let password_hash = hash_password_argon2(&user.password)
+    let password_hash = hasher
+        .hash(&user.password)
         .map_err(|_| ServiceError::internal_server_error("Failed to hash password".to_string()))?;
 
     let user_name = user.username.clone();
     let new_user = UserDTO {
         password: password_hash,
+        email: user_email,
         ..user
     };
 
-    // Insert with functional error handling
-    diesel::insert_into(users)
-        .values(new_user)
-        .execute(conn)
-        .map_err(|err| match err {
-            diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
-                ServiceError::bad_request(format!("User '{}' is already registered", user_name))
-            }
-            _ => {
-                log::error!("Signup failed: {}", err);
-                ServiceError::internal_server_error("Internal server error".to_string())
-            }
-        })?;
+    // Insert with functional error handling; a racing signup for the same
+    // username or email surfaces as a unique violation, which is not retried
+    with_retry(&RetryPolicy::from_env(), || {
+        diesel::insert_into(users).values(&new_user).execute(conn)
+    })
+    .map_err(|err| match err {
+        diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            ServiceError::bad_request(format!("User '{}' is already registered", user_name))
+        }
+        _ => {
+            log::error!("Signup failed: {}", err);
+            ServiceError::internal_server_error("Internal server error".to_string())
+        }
+    })?;
 
     Ok(constants::MESSAGE_SIGNUP_SUCCESS.to_string())
 }
@@ -257,11 +226,14 @@ pub fn signup_user(user: UserDTO, conn: &mut Connection) -> Result<String, Servi
 /// Authenticate credentials and create a login session for a user.
 ///
 /// Attempts to find an active user matching the provided username or email, verifies the provided password
-/// (supports bcrypt and Argon2 formats), and creates a new login session on success.
+/// (supports bcrypt and Argon2 formats), and creates a new login session on success. A hash `hasher`
+/// would not produce is recomputed with it and stored, see [`PasswordHasher::needs_rehash`].
 ///
 /// # Parameters
 ///
 /// - `login`: credentials containing `username_or_email`, `password`, and `tenant_id`.
+/// - `client`: user agent and IP address recorded with the new session.
+/// - `hasher`: scheme passwords are moved to.
 ///
 /// # Returns
 ///
@@ -275,22 +247,53 @@ pub fn signup_user(user: UserDTO, conn: &mut Connection) -> Result<String, Servi
 ///     password: "s3cret".into(),
 ///     tenant_id: "tenant_1".into(),
 /// };
-/// let result = login_user(login, &mut conn);
+/// let result = login_user(login, &SessionClient::default(), &Argon2id, &mut conn);
 /// if let Some(info) = result {
 ///     assert_eq!(info.username, "alice");
 /// }
 /// ```
-pub fn login_user(login: LoginDTO, conn: &mut Connection) -> Option<LoginInfoDTO> {
-    // Functional composition: lookup -> validate -> verify -> create session
+pub fn login_user(
+    login: LoginDTO,
+    client: &SessionClient,
+    hasher: &dyn PasswordHasher,
+    conn: &mut Connection,
+) -> Option<LoginInfoDTO> {
+    // Functional composition: lookup -> validate -> verify -> rehash -> create session
     find_user_by_credentials(&login.username_or_email, conn)
         .filter(|user| user.active && !user.password.is_empty())
-        .filter(|user| verify_password_hybrid(&user.password, &login.password))
-        .and_then(|user| create_login_session(&user.username, login.tenant_id, conn))
+        .filter(|user| verify_password(&user.password, &login.password))
+        .inspect(|user| rehash_password(user, &login.password, hasher, conn))
+        .and_then(|user| create_login_session(&user.username, login.tenant_id, client, conn))
+}
+
+/// Stores `plain_password` hashed by `hasher` when the user's hash used another scheme or weaker parameters.
+///
+/// The password was just verified, so this is the only time it can be rehashed.
+/// A failure is logged and leaves the old hash, which keeps working, in place.
+fn rehash_password(
+    user: &User,
+    plain_password: &str,
+    hasher: &dyn PasswordHasher,
+    conn: &mut Connection,
+) {
+    if !hasher.needs_rehash(&user.password) {
+        return;
+    }
+    let result = hasher.hash(plain_password).and_then(|hash| {
+        diesel::update(users.find(user.id))
+            .set(password.eq(hash))
+            .execute(conn)
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to rehash password of user {}: {}", user.id, e);
+    }
 }
 
 /// Retrieves a user whose username or email matches the given identifier.
 ///
-/// Searches the users table for a row where `username` or `email` equals `identifier` and returns that user if found.
+/// Searches the users table for a row where `username` equals `identifier` or `email` equals it
+/// ignoring case, and returns that user if found.
 ///
 /// # Examples
 ///
@@ -307,7 +310,7 @@ pub fn login_user(login: LoginDTO, conn: &mut Connection) -> Option<LoginInfoDTO
 pub fn find_user_by_credentials(identifier: &str, conn: &mut Connection) -> Option<User> {
     users
         .filter(username.eq(identifier))
-        .or_filter(email.eq(identifier))
+        .or_filter(lower(email).eq(normalize_email(identifier)))
         .get_result::<User>(conn)
         .ok()
 }
@@ -319,9 +322,10 @@ pub fn logout_user(user_id: i32, conn: &mut Connection) -> Result<(), diesel::re
     Ok(())
 }
 
-/// Validates that a UserToken matches an existing user's login session in the database.
+/// Validates that a UserToken belongs to an active session of an existing user.
 ///
-/// Returns `true` if a user with matching `username` and `login_session` exists, `false` otherwise.
+/// Returns `true` if the user named in the token has a login whose `login_session` matches the
+/// token and that was not logged out or revoked, `false` otherwise.
 ///
 /// # Examples
 ///
@@ -339,18 +343,30 @@ pub fn is_valid_login_session(user_token: &UserToken, conn: &mut Connection) ->
         return false;
     }
 
+    find_user_by_session(username_trimmed, session_trimmed, conn).is_ok()
+}
+
+/// The user named `username_str` if `session` is one of their active sessions.
+fn find_user_by_session(
+    username_str: &str,
+    session: &str,
+    conn: &mut Connection,
+) -> QueryResult<User> {
+    use crate::schema::login_history;
+
     users
-        .filter(username.eq(username_trimmed))
-        .filter(login_session.eq(session_trimmed))
-        .filter(login_session.ne(""))
-        .get_result::<User>(conn)
-        .is_ok()
+        .inner_join(login_history::table)
+        .filter(username.eq(username_str))
+        .filter(login_history::login_session.eq(session))
+        .filter(login_history::revoked_at.is_null())
+        .select(users::all_columns)
+        .first::<User>(conn)
 }
 
 /// Retrieve login information that corresponds to a user token.
 ///
-/// Looks up a user whose `username` and `login_session` match the supplied `UserToken` and returns a `LoginInfoDTO`
-/// containing the username, stored login session, and the token's tenant id. If no matching user is found this
+/// Looks up the user of the active session named by the supplied `UserToken` and returns a `LoginInfoDTO`
+/// containing the username, the token's login session, and the token's tenant id. If no matching user is found this
 /// returns a `ServiceError::not_found`; unexpected database errors are mapped to `ServiceError::internal_server_error`.
 ///
 /// # Examples
@@ -383,16 +399,10 @@ pub fn find_login_info_by_token(
         return Err(ServiceError::bad_request("Username cannot be empty"));
     }
 
-    let user_result = users
-        .filter(username.eq(username_trimmed))
-        .filter(login_session.eq(session_trimmed))
-        .filter(login_session.ne(""))
-        .get_result::<User>(conn);
-
-    match user_result {
+    match find_user_by_session(username_trimmed, session_trimmed, conn) {
         Ok(user) => Ok(LoginInfoDTO {
             username: user.username,
-            login_session: user.login_session,
+            login_session: session_trimmed.to_string(),
             tenant_id: user_token.tenant_id.clone(),
         }),
         Err(diesel::result::Error::NotFound) => Err(ServiceError::not_found("User not found")),
@@ -419,7 +429,7 @@ pub fn find_user_by_username(username_str: &str, conn: &mut Connection) -> Query
         .get_result::<User>(conn)
 }
 
-/// Retrieve a user record that matches the given email.
+/// Retrieve a user record that matches the given email, ignoring case.
 ///
 /// # Parameters
 ///
@@ -442,7 +452,37 @@ pub fn find_user_by_username(username_str: &str, conn: &mut Connection) -> Query
 /// # }
 /// ```
 pub fn find_user_by_email(email_str: &str, conn: &mut Connection) -> QueryResult<User> {
-    users.filter(email.eq(email_str)).get_result::<User>(conn)
+    users
+        .filter(lower(email).eq(normalize_email(email_str)))
+        .get_result::<User>(conn)
+}
+
+/// Checks whether `email_str` is already used by a user, ignoring case.
+///
+/// Users live in their tenant's database, so the check is scoped to the tenant
+/// owning `conn`. `except_user_id` excludes a user from the check, so a user
+/// can keep their own address when updating their profile.
+///
+/// # Returns
+///
+/// `Ok(true)` when another user has the same email in any letter case.
+pub fn email_in_use(
+    email_str: &str,
+    except_user_id: Option<i32>,
+    conn: &mut Connection,
+) -> QueryResult<bool> {
+    let mut query = users
+        .filter(lower(email).eq(normalize_email(email_str)))
+        .select(id)
+        .into_boxed();
+    if let Some(user_id) = except_user_id {
+        query = query.filter(id.ne(user_id));
+    }
+
+    query
+        .first::<i32>(conn)
+        .optional()
+        .map(|found| found.is_some())
 }
 
 /// Retrieves the user with the specified ID from the database.
@@ -477,9 +517,40 @@ pub fn count_all_users(conn: &mut Connection) -> QueryResult<i64> {
     users.count().get_result(conn)
 }
 
-/// Retrieves a paginated list of users ordered by `id`.
+/// Orders `query` by each column of `sort` in turn, by `id` when `sort` is empty.
+///
+/// `id` breaks the remaining ties in the direction of the last column, so offset
+/// pages are stable. Columns must be [`User::SORTABLE_COLUMNS`]; any other
+/// sorts by `id`.
+fn order_users<'a>(
+    query: users::BoxedQuery<'a, Pg>,
+    sort: &[SortSpec],
+) -> users::BoxedQuery<'a, Pg> {
+    let Some(last) = sort.last() else {
+        return query.order(id.asc());
+    };
+    let query = sort.iter().fold(query, |query, spec| {
+        match (spec.column.as_str(), spec.descending) {
+            ("username", false) => query.then_order_by(username.asc()),
+            ("username", true) => query.then_order_by(username.desc()),
+            ("email", false) => query.then_order_by(email.asc()),
+            ("email", true) => query.then_order_by(email.desc()),
+            ("active", false) => query.then_order_by(active.asc()),
+            ("active", true) => query.then_order_by(active.desc()),
+            (_, false) => query.then_order_by(id.asc()),
+            (_, true) => query.then_order_by(id.desc()),
+        }
+    });
+    if last.descending {
+        query.then_order_by(id.desc())
+    } else {
+        query.then_order_by(id.asc())
+    }
+}
+
+/// Retrieves a paginated list of users ordered by `sort`, by `id` when empty.
 ///
-/// Uses `limit` and `offset` to control pagination; results are ordered ascending by `id`.
+/// Uses `limit` and `offset` to control pagination; `id` always breaks ties.
 ///
 /// # Examples
 ///
@@ -488,12 +559,16 @@ pub fn count_all_users(conn: &mut Connection) -> QueryResult<i64> {
 /// # use diesel::r2d2::ConnectionManager;
 /// # use diesel::PgConnection;
 /// // `conn` must be a valid `&mut PgConnection`.
-/// let users = find_all_users(10, 0, conn).expect("query failed");
+/// let users = find_all_users(10, 0, &[], conn).expect("query failed");
 /// assert!(users.len() <= 10);
 /// ```
-pub fn find_all_users(limit: i64, offset: i64, conn: &mut Connection) -> QueryResult<Vec<User>> {
-    users
-        .order(id)
+pub fn find_all_users(
+    limit: i64,
+    offset: i64,
+    sort: &[SortSpec],
+    conn: &mut Connection,
+) -> QueryResult<Vec<User>> {
+    order_users(users.into_boxed(), sort)
         .limit(limit)
         .offset(offset)
         .load::<User>(conn)
@@ -529,7 +604,7 @@ pub fn update_user(
     diesel::update(users.filter(id.eq(user_id)))
         .set((
             username.eq(updated_user.username),
-            email.eq(updated_user.email),
+            email.eq(normalize_email(&updated_user.email)),
             active.eq(updated_user.active),
         ))
         .execute(conn)
@@ -0,0 +1,184 @@
+//! Password hashing schemes.
+//!
+//! Stored hashes are self-describing: bcrypt hashes start with `$2` and carry
+//! their cost, Argon2 hashes are PHC strings (`$argon2id$v=19$m=...`) carrying
+//! their parameters. [`verify_password`] therefore accepts a hash of either
+//! scheme whatever the configured [`PasswordHasher`], and users move to the
+//! configured one as they log in.
+
+use argon2::password_hash::{
+    rand_core::OsRng, PasswordHash, PasswordHasher as _, PasswordVerifier, SaltString,
+};
+
+/// Hashes new passwords and verifies them against hashes of its own scheme
+pub trait PasswordHasher: Send + Sync {
+    fn hash(&self, plain_password: &str) -> Result<String, String>;
+
+    fn verify(&self, stored_hash: &str, plain_password: &str) -> bool;
+
+    /// Whether `stored_hash` should be replaced by a hash of this hasher.
+    ///
+    /// True for hashes of another scheme and for weaker parameters of this one.
+    fn needs_rehash(&self, stored_hash: &str) -> bool;
+}
+
+/// bcrypt at a fixed cost
+#[derive(Debug, Clone, Copy)]
+pub struct Bcrypt {
+    cost: u32,
+}
+
+impl Bcrypt {
+    pub fn new(cost: u32) -> Self {
+        Self { cost }
+    }
+
+    /// Whether `hash` looks like a bcrypt hash
+    fn matches(hash: &str) -> bool {
+        hash.starts_with("$2")
+    }
+}
+
+impl PasswordHasher for Bcrypt {
+    fn hash(&self, plain_password: &str) -> Result<String, String> {
+        bcrypt::hash(plain_password, self.cost)
+            .map_err(|e| format!("Password hashing failed: {}", e))
+    }
+
+    fn verify(&self, stored_hash: &str, plain_password: &str) -> bool {
+        bcrypt::verify(plain_password, stored_hash).unwrap_or(false)
+    }
+
+    fn needs_rehash(&self, stored_hash: &str) -> bool {
+        stored_hash
+            .parse::<bcrypt::HashParts>()
+            .map_or(true, |parts| parts.get_cost() < self.cost)
+    }
+}
+
+/// Argon2id with the default parameters of the `argon2` crate
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Argon2id;
+
+impl PasswordHasher for Argon2id {
+    fn hash(&self, plain_password: &str) -> Result<String, String> {
+        let salt = SaltString::generate(&mut OsRng);
+        argon2::Argon2::default()
+            .hash_password(plain_password.as_bytes(), &salt)
+            .map(|hash| hash.to_string())
+            .map_err(|e| format!("Password hashing failed: {}", e))
+    }
+
+    /// Verifies with the parameters stored in the hash, so older parameters still verify.
+    fn verify(&self, stored_hash: &str, plain_password: &str) -> bool {
+        PasswordHash::new(stored_hash)
+            .map(|parsed_hash| {
+                argon2::Argon2::default()
+                    .verify_password(plain_password.as_bytes(), &parsed_hash)
+                    .is_ok()
+            })
+            .unwrap_or(false)
+    }
+
+    fn needs_rehash(&self, stored_hash: &str) -> bool {
+        PasswordHash::new(stored_hash).map_or(true, |parsed_hash| {
+            parsed_hash.algorithm != argon2::ARGON2ID_IDENT
+        })
+    }
+}
+
+/// Scheme new passwords are hashed with, from `PASSWORD_HASH_ALGO`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordHashAlgo {
+    Bcrypt,
+    Argon2,
+}
+
+impl PasswordHashAlgo {
+    /// Parses `bcrypt` or `argon2` (also `argon2id`), ignoring case.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "bcrypt" => Some(Self::Bcrypt),
+            "argon2" | "argon2id" => Some(Self::Argon2),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Bcrypt => "bcrypt",
+            Self::Argon2 => "argon2",
+        }
+    }
+
+    /// The hasher of this scheme; bcrypt hashes at `bcrypt_cost`.
+    pub fn hasher(self, bcrypt_cost: u32) -> Box<dyn PasswordHasher> {
+        match self {
+            Self::Bcrypt => Box::new(Bcrypt::new(bcrypt_cost)),
+            Self::Argon2 => Box::new(Argon2id),
+        }
+    }
+}
+
+/// Verifies `plain_password` against a bcrypt or Argon2 `stored_hash`.
+///
+/// The scheme is read from the hash itself. Returns `false` on a mismatch and
+/// on a hash of neither scheme.
+pub fn verify_password(stored_hash: &str, plain_password: &str) -> bool {
+    if Bcrypt::matches(stored_hash) {
+        // Only the scheme matters for verifying: the cost is read from the hash
+        Bcrypt::new(bcrypt::DEFAULT_COST).verify(stored_hash, plain_password)
+    } else {
+        Argon2id.verify(stored_hash, plain_password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "Password123";
+
+    #[test]
+    fn test_argon2_hash_verifies() {
+        let hash = Argon2id.hash(PASSWORD).unwrap();
+
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_password(&hash, PASSWORD));
+        assert!(!verify_password(&hash, "password123"));
+        assert!(!Argon2id.needs_rehash(&hash));
+    }
+
+    #[test]
+    fn test_legacy_bcrypt_hash_verifies_under_argon2() {
+        let legacy = Bcrypt::new(4).hash(PASSWORD).unwrap();
+        let configured = PasswordHashAlgo::Argon2.hasher(12);
+
+        assert!(verify_password(&legacy, PASSWORD));
+        assert!(!verify_password(&legacy, "password123"));
+        // Verified once, the password moves to the configured scheme
+        assert!(configured.needs_rehash(&legacy));
+    }
+
+    #[test]
+    fn test_bcrypt_rehashes_below_its_cost_and_other_schemes() {
+        let bcrypt = Bcrypt::new(5);
+
+        assert!(bcrypt.needs_rehash(&Bcrypt::new(4).hash(PASSWORD).unwrap()));
+        assert!(!bcrypt.needs_rehash(&Bcrypt::new(5).hash(PASSWORD).unwrap()));
+        assert!(bcrypt.needs_rehash(&Argon2id.hash(PASSWORD).unwrap()));
+    }
+
+    #[test]
+    fn test_password_hash_algo_parses_names() {
+        assert_eq!(
+            PasswordHashAlgo::parse("Argon2id"),
+            Some(PasswordHashAlgo::Argon2)
+        );
+        assert_eq!(
+            PasswordHashAlgo::parse(" bcrypt "),
+            Some(PasswordHashAlgo::Bcrypt)
+        );
+        assert_eq!(PasswordHashAlgo::parse("md5"), None);
+    }
+}
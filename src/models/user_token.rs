@@ -1,29 +1,153 @@
 use std::{env, fs};
 
 use chrono::Utc;
-use jsonwebtoken::{EncodingKey, Header};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header};
 use log::debug;
-use once_cell::sync::Lazy;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 
-use crate::models::user::LoginInfoDTO;
+use crate::{config::functional_config::TOKEN_LIFETIMES, models::user::LoginInfoDTO};
 
-/// Lazily loads the JWT secret from `JWT_SECRET` env var or `src/secret.key` fallback.
-pub static SECRET_KEY: Lazy<Vec<u8>> = Lazy::new(|| {
-    // dotenv is idempotent; allows local development without explicit env loading elsewhere.
-    let _ = dotenv::dotenv();
+/// Why the JWT key configuration was rejected.
+#[derive(Debug, thiserror::Error)]
+pub enum JwtKeyError {
+    #[error("unsupported JWT_ALGORITHM {0:?}, expected HS256 or RS256")]
+    UnsupportedAlgorithm(String),
+    #[error("JWT secret not configured; provide JWT_SECRET or src/secret.key")]
+    MissingSecret,
+    #[error("{0} must be set when JWT_ALGORITHM=RS256")]
+    MissingKeyPath(&'static str),
+    #[error("failed to read {var} {path:?}: {source}")]
+    UnreadableKey {
+        var: &'static str,
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("invalid RS256 key pair: {0}")]
+    InvalidKeyPair(#[from] jsonwebtoken::errors::Error),
+}
 
+/// The HS256 secret from `JWT_SECRET`, falling back to `src/secret.key`.
+fn read_secret() -> Result<Vec<u8>, JwtKeyError> {
     if let Ok(secret) = env::var("JWT_SECRET") {
-        return secret.into_bytes();
+        return Ok(secret.into_bytes());
     }
 
     fs::read("src/secret.key")
         .or_else(|_| fs::read("secret.key"))
-        .expect("JWT secret not configured. Provide JWT_SECRET or src/secret.key")
-});
-static ONE_WEEK: i64 = 60 * 60 * 24 * 7; // in seconds
+        .map_err(|_| JwtKeyError::MissingSecret)
+}
+
+/// Signing algorithm of issued tokens, selected with `JWT_ALGORITHM` (default `HS256`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+    /// HMAC with the shared `JWT_SECRET`.
+    HS256,
+    /// RSA signature; tokens can be verified with the public key alone.
+    RS256,
+}
+
+impl JwtAlgorithm {
+    /// Reads `JWT_ALGORITHM`, defaulting to `HS256` when unset.
+    ///
+    /// An unsupported algorithm is an error rather than a fallback, so a typo
+    /// cannot silently downgrade the server to the shared secret.
+    pub fn from_env() -> Result<Self, JwtKeyError> {
+        match env::var("JWT_ALGORITHM") {
+            Err(_) => Ok(JwtAlgorithm::HS256),
+            Ok(value) => match value.trim().to_ascii_uppercase().as_str() {
+                "HS256" => Ok(JwtAlgorithm::HS256),
+                "RS256" => Ok(JwtAlgorithm::RS256),
+                _ => Err(JwtKeyError::UnsupportedAlgorithm(value)),
+            },
+        }
+    }
+}
+
+impl From<JwtAlgorithm> for Algorithm {
+    fn from(algorithm: JwtAlgorithm) -> Self {
+        match algorithm {
+            JwtAlgorithm::HS256 => Algorithm::HS256,
+            JwtAlgorithm::RS256 => Algorithm::RS256,
+        }
+    }
+}
+
+/// Keys used to sign and verify tokens with the configured algorithm.
+pub struct JwtKeys {
+    pub algorithm: JwtAlgorithm,
+    pub encoding: EncodingKey,
+    pub decoding: DecodingKey,
+}
+
+impl JwtKeys {
+    /// HS256 keys sharing one secret for signing and verification.
+    pub fn hs256(secret: &[u8]) -> Self {
+        JwtKeys {
+            algorithm: JwtAlgorithm::HS256,
+            encoding: EncodingKey::from_secret(secret),
+            decoding: DecodingKey::from_secret(secret),
+        }
+    }
+
+    /// RS256 keys from a PEM private key (signing) and PEM public key (verification).
+    pub fn rs256(private_pem: &[u8], public_pem: &[u8]) -> jsonwebtoken::errors::Result<Self> {
+        Ok(JwtKeys {
+            algorithm: JwtAlgorithm::RS256,
+            encoding: EncodingKey::from_rsa_pem(private_pem)?,
+            decoding: DecodingKey::from_rsa_pem(public_pem)?,
+        })
+    }
+
+    /// Builds the keys for [`JwtAlgorithm::from_env`].
+    ///
+    /// HS256 uses `JWT_SECRET` or `src/secret.key`; RS256 reads the PEM files
+    /// named by `JWT_PRIVATE_KEY_PATH` and `JWT_PUBLIC_KEY_PATH`.
+    pub fn from_env() -> Result<Self, JwtKeyError> {
+        // dotenv is idempotent; allows local development without explicit env loading elsewhere.
+        let _ = dotenv::dotenv();
+
+        match JwtAlgorithm::from_env()? {
+            JwtAlgorithm::HS256 => Ok(Self::hs256(&read_secret()?)),
+            JwtAlgorithm::RS256 => {
+                let read_pem = |var: &'static str| {
+                    let path = env::var(var).map_err(|_| JwtKeyError::MissingKeyPath(var))?;
+                    fs::read(&path).map_err(|source| JwtKeyError::UnreadableKey {
+                        var,
+                        path,
+                        source,
+                    })
+                };
+                Ok(Self::rs256(
+                    &read_pem("JWT_PRIVATE_KEY_PATH")?,
+                    &read_pem("JWT_PUBLIC_KEY_PATH")?,
+                )?)
+            }
+        }
+    }
+}
+
+static JWT_KEYS: OnceCell<JwtKeys> = OnceCell::new();
 
-#[derive(Serialize, Deserialize)]
+/// Loads the keys of the configured algorithm.
+///
+/// `main` calls this before starting the server so a bad key configuration
+/// stops it at startup; later calls return the loaded keys.
+pub fn init_jwt_keys() -> Result<&'static JwtKeys, JwtKeyError> {
+    JWT_KEYS.get_or_try_init(JwtKeys::from_env)
+}
+
+/// Keys of the configured algorithm, loaded on first use outside the server.
+///
+/// # Panics
+///
+/// Panics when the keys were not loaded by [`init_jwt_keys`] and the
+/// configuration is invalid.
+pub fn jwt_keys() -> &'static JwtKeys {
+    init_jwt_keys().unwrap_or_else(|e| panic!("Invalid JWT configuration: {}", e))
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub struct UserToken {
     // issued at
     pub iat: i64,
@@ -38,7 +162,10 @@ pub struct UserToken {
 impl UserToken {
     /// Generates a signed JWT containing issued-at, expiration, and the provided login information.
     ///
-    /// Token lifetime is taken from the `MAX_AGE` environment variable (seconds); if `MAX_AGE` is missing or cannot be parsed as an integer, `ONE_WEEK` is used.
+    /// The token is signed with the configured [`jwt_keys`].
+    ///
+    /// The token expires after the access lifetime of [`TOKEN_LIFETIMES`]
+    /// (`ACCESS_TOKEN_TTL_SECS`, 15 minutes by default).
     ///
     /// # Returns
     ///
@@ -59,27 +186,27 @@ impl UserToken {
     /// assert!(!token.is_empty());
     /// ```
     pub fn generate_token(login: &LoginInfoDTO) -> String {
-        let _ = dotenv::dotenv();
-        let max_age: i64 = match env::var("MAX_AGE") {
-            Ok(val) => val.parse::<i64>().unwrap_or(ONE_WEEK),
-            Err(_) => ONE_WEEK,
-        };
+        Self::generate_token_with(login, jwt_keys(), TOKEN_LIFETIMES.access_secs)
+    }
 
-        debug!("Token Max Age: {}", max_age);
+    /// Same as [`generate_token`](Self::generate_token), signing with `keys` a
+    /// token that expires after `lifetime_secs`.
+    pub fn generate_token_with(login: &LoginInfoDTO, keys: &JwtKeys, lifetime_secs: i64) -> String {
+        debug!("Token lifetime: {}s", lifetime_secs);
 
         let now = Utc::now().timestamp(); // in seconds
         let payload = UserToken {
             iat: now,
-            exp: now + max_age,
+            exp: now + lifetime_secs,
             user: login.username.clone(),
             login_session: login.login_session.clone(),
             tenant_id: login.tenant_id.clone(),
         };
 
         jsonwebtoken::encode(
-            &Header::default(),
+            &Header::new(keys.algorithm.into()),
             &payload,
-            &EncodingKey::from_secret(SECRET_KEY.as_slice()),
+            &keys.encoding,
         )
         .unwrap()
     }
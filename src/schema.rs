@@ -1,5 +1,24 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tsvector", schema = "pg_catalog"))]
+    pub struct Tsvector;
+}
+
+diesel::table! {
+    audit_log (id) {
+        id -> Int4,
+        tenant_id -> Varchar,
+        user_id -> Nullable<Int4>,
+        method -> Varchar,
+        path -> Varchar,
+        resource_id -> Nullable<Varchar>,
+        status -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     configuration (key) {
         #[max_length = 255]
@@ -17,6 +36,12 @@ diesel::table! {
         id -> Int4,
         user_id -> Int4,
         login_timestamp -> Timestamptz,
+        login_session -> Nullable<Varchar>,
+        refresh_family_id -> Nullable<Varchar>,
+        user_agent -> Nullable<Varchar>,
+        ip_address -> Nullable<Varchar>,
+        last_seen_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -85,6 +110,8 @@ diesel::table! {
         justificativa_contingencia -> Nullable<Text>,
         created_at -> Timestamptz,
         updated_at -> Timestamptz,
+        emitter_id -> Nullable<Int4>,
+        recipient_id -> Nullable<Int4>,
     }
 }
 
@@ -94,7 +121,7 @@ diesel::table! {
         #[max_length = 36]
         tenant_id -> Varchar,
         #[max_length = 14]
-        cnpj -> Varchar,
+        cnpj -> Nullable<Varchar>,
         #[max_length = 11]
         cpf -> Nullable<Varchar>,
         #[max_length = 120]
@@ -458,15 +485,22 @@ diesel::table! {
 }
 
 diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::Tsvector;
+
     people (id) {
         id -> Int4,
         name -> Varchar,
         gender -> Bool,
         age -> Int4,
         address -> Varchar,
-        #[max_length = 11]
+        #[max_length = 16]
         phone -> Varchar,
         email -> Varchar,
+        deleted_at -> Nullable<Timestamp>,
+        version -> Int4,
+        search_vector -> Nullable<Tsvector>,
+        tenant_id -> Nullable<Varchar>,
     }
 }
 
@@ -478,6 +512,9 @@ diesel::table! {
         expires_at -> Timestamptz,
         created_at -> Nullable<Timestamptz>,
         revoked -> Nullable<Bool>,
+        family_id -> Varchar,
+        used_at -> Nullable<Timestamptz>,
+        replaced_by -> Nullable<Int4>,
     }
 }
 
@@ -516,6 +553,8 @@ diesel::table! {
 
 diesel::joinable!(login_history -> users (user_id));
 diesel::joinable!(nfe_cofins -> nfe_items (nfe_item_id));
+diesel::joinable!(nfe_documents -> nfe_emitters (emitter_id));
+diesel::joinable!(nfe_documents -> nfe_recipients (recipient_id));
 diesel::joinable!(nfe_fiscal_info -> nfe_documents (nfe_document_id));
 diesel::joinable!(nfe_icms -> nfe_items (nfe_item_id));
 diesel::joinable!(nfe_ipi -> nfe_items (nfe_item_id));
@@ -529,6 +568,7 @@ diesel::joinable!(nfe_transport_volumes -> nfe_transport (nfe_transport_id));
 diesel::joinable!(refresh_tokens -> users (user_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    audit_log,
     configuration,
     login_history,
     nfe_cofins,
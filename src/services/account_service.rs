@@ -15,16 +15,27 @@
 use actix_web::http::header::HeaderValue;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     config::db::Pool,
     constants,
     error::ServiceError,
+    functional::{
+        validation_engine::validator,
+        validation_rules::{codes, Custom, PasswordStrength, UniqueEmail, ValidationRule},
+    },
+    middleware::request_trace,
     models::user::operations as user_ops,
     models::{
-        refresh_token::RefreshToken,
-        user::{LoginDTO, LoginInfoDTO, UserDTO, UserResponseDTO, UserUpdateDTO},
+        filters::SortSpec,
+        login_history::{LoginHistory, SessionClient, SessionDTO},
+        refresh_token::{RefreshToken, RotationError},
+        user::{
+            password::{PasswordHashAlgo, PasswordHasher},
+            LoginDTO, LoginInfoDTO, User, UserDTO, UserResponseDTO, UserUpdateDTO,
+        },
         user_token::UserToken,
     },
     services::functional_patterns::Validator,
@@ -37,63 +48,113 @@ use diesel::result::{DatabaseErrorKind, Error as DieselError};
 static EMAIL_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^[^@\s]+@[^@\s]+\.[^@\s]+$").expect("Invalid email regex"));
 
-/// Iterator-based validation using functional combinator pattern for UserDTO
-fn create_user_validator() -> Validator<UserDTO> {
-    Validator::new()
-        .rule(|dto: &UserDTO| {
-            if dto.username.trim().is_empty() {
-                Err(ServiceError::bad_request("Username cannot be empty"))
-            } else if dto.username.len() < 3 {
-                Err(ServiceError::bad_request(
-                    "Username too short (min 3 characters)",
-                ))
-            } else if dto.username.len() > 50 {
-                Err(ServiceError::bad_request(
-                    "Username too long (max 50 characters)",
-                ))
-            } else {
-                Ok(())
-            }
-        })
-        .rule(|dto: &UserDTO| {
-            let char_count = dto.password.chars().count();
-            if char_count < 8 {
-                Err(ServiceError::bad_request(
-                    "Password too short (min 8 characters)",
-                ))
-            } else if char_count > 64 {
-                Err(ServiceError::bad_request(
-                    "Password too long (max 64 characters)",
-                ))
-            } else if !dto.password.chars().any(|c| c.is_uppercase()) {
-                Err(ServiceError::bad_request(
-                    "Password must contain at least one uppercase letter",
-                ))
-            } else if !dto.password.chars().any(|c| c.is_lowercase()) {
-                Err(ServiceError::bad_request(
-                    "Password must contain at least one lowercase letter",
-                ))
-            } else if !dto.password.chars().any(|c| c.is_numeric()) {
-                Err(ServiceError::bad_request(
-                    "Password must contain at least one number",
-                ))
-            } else {
-                Ok(())
-            }
-        })
-        .rule(|dto: &UserDTO| {
-            if dto.email.trim().is_empty() {
-                Err(ServiceError::bad_request("Email cannot be empty"))
-            } else if !EMAIL_REGEX.is_match(&dto.email) {
-                Err(ServiceError::bad_request("Invalid email format"))
-            } else if dto.email.len() > 255 {
-                Err(ServiceError::bad_request(
-                    "Email too long (max 255 characters)",
-                ))
-            } else {
-                Ok(())
-            }
-        })
+/// bcrypt cost used when `BCRYPT_COST` is unset or out of [`BCRYPT_COST_RANGE`]
+const DEFAULT_BCRYPT_COST: u32 = 12;
+
+/// Costs accepted from `BCRYPT_COST`: cheaper hashes are too easy to crack, dearer
+/// ones make every login of a bcrypt user take seconds
+const BCRYPT_COST_RANGE: std::ops::RangeInclusive<u32> = 10..=16;
+
+/// Cost of bcrypt hashes, from `BCRYPT_COST` (default 12); with `PASSWORD_HASH_ALGO=bcrypt`
+/// lower-cost hashes are brought up to it on login
+pub static BCRYPT_COST: Lazy<u32> =
+    Lazy::new(|| bcrypt_cost(std::env::var("BCRYPT_COST").ok().as_deref()));
+
+fn bcrypt_cost(value: Option<&str>) -> u32 {
+    match value.map(|value| value.trim().parse::<u32>()) {
+        None => DEFAULT_BCRYPT_COST,
+        Some(Ok(cost)) if BCRYPT_COST_RANGE.contains(&cost) => cost,
+        Some(_) => {
+            log::warn!(
+                "BCRYPT_COST must be a number in {:?}, using {}",
+                BCRYPT_COST_RANGE,
+                DEFAULT_BCRYPT_COST
+            );
+            DEFAULT_BCRYPT_COST
+        }
+    }
+}
+
+/// Scheme of new password hashes, from `PASSWORD_HASH_ALGO` (default `argon2`)
+pub static PASSWORD_HASH_ALGO: Lazy<PasswordHashAlgo> =
+    Lazy::new(|| password_hash_algo(std::env::var("PASSWORD_HASH_ALGO").ok().as_deref()));
+
+/// Hasher of [`PASSWORD_HASH_ALGO`]; users hashed otherwise are moved to it on login
+static PASSWORD_HASHER: Lazy<Box<dyn PasswordHasher>> =
+    Lazy::new(|| PASSWORD_HASH_ALGO.hasher(*BCRYPT_COST));
+
+fn password_hash_algo(value: Option<&str>) -> PasswordHashAlgo {
+    match value.map(|value| (value, PasswordHashAlgo::parse(value))) {
+        None => PasswordHashAlgo::Argon2,
+        Some((_, Some(algo))) => algo,
+        Some((value, None)) => {
+            log::warn!(
+                "PASSWORD_HASH_ALGO must be bcrypt or argon2, got '{}', using argon2",
+                value
+            );
+            PasswordHashAlgo::Argon2
+        }
+    }
+}
+
+/// Rule over a string field of an account payload
+type StringRule = Box<dyn ValidationRule<String>>;
+
+/// Predicate rule reported with a fixed message
+fn predicate(check: fn(&String) -> bool, code: &'static str, message: &str) -> StringRule {
+    Box::new(Custom::new(check, code, message))
+}
+
+fn username_rules() -> Vec<StringRule> {
+    vec![
+        predicate(
+            |v: &String| !v.trim().is_empty(),
+            codes::REQUIRED,
+            "Username cannot be empty",
+        ),
+        predicate(
+            |v: &String| v.trim().is_empty() || v.len() >= 3,
+            codes::LENGTH_MIN,
+            "Username too short (min 3 characters)",
+        ),
+        predicate(
+            |v: &String| v.len() <= 50,
+            codes::LENGTH_MAX,
+            "Username too long (max 50 characters)",
+        ),
+    ]
+}
+
+/// Signup passwords need 8 characters, three character classes and must not be a common one
+fn password_rules() -> Vec<StringRule> {
+    vec![
+        Box::new(PasswordStrength::new(8).min_classes(3).reject_common()),
+        predicate(
+            |v: &String| v.chars().count() <= 64,
+            codes::LENGTH_MAX,
+            "Password too long (max 64 characters)",
+        ),
+    ]
+}
+
+fn email_rules() -> Vec<StringRule> {
+    vec![
+        predicate(
+            |v: &String| !v.trim().is_empty(),
+            codes::REQUIRED,
+            "Email cannot be empty",
+        ),
+        predicate(
+            |v: &String| v.trim().is_empty() || EMAIL_REGEX.is_match(v),
+            codes::EMAIL_INVALID,
+            "Invalid email format",
+        ),
+        predicate(
+            |v: &String| v.len() <= 255,
+            codes::LENGTH_MAX,
+            "Email too long (max 255 characters)",
+        ),
+    ]
 }
 
 /// Iterator-based validation using functional combinator pattern for LoginDTO
@@ -125,9 +186,22 @@ fn create_login_validator() -> Validator<LoginDTO> {
         })
 }
 
-/// Legacy validation for backward compatibility - uses new functional validator
-fn validate_user_dto(dto: &UserDTO) -> Result<(), ServiceError> {
-    create_user_validator().validate(dto)
+/// Validates a signup payload, reporting every failing rule of every field at once.
+///
+/// Once those rules pass, `unique_email` rejects an email another user already
+/// registered with [`codes::EMAIL_TAKEN`].
+async fn validate_user_dto(dto: &UserDTO, unique_email: UniqueEmail) -> Result<(), ServiceError> {
+    validator::<String>()
+        .validate_all_with_async(
+            vec![
+                ("username".to_string(), &dto.username, username_rules()),
+                ("password".to_string(), &dto.password, password_rules()),
+                ("email".to_string(), &dto.email, email_rules()),
+            ],
+            vec![("email".to_string(), &dto.email, vec![unique_email])],
+        )
+        .await
+        .map_err(ServiceError::validation)
 }
 
 /// Legacy validation for backward compatibility - uses new functional validator
@@ -135,14 +209,14 @@ fn validate_login_dto(dto: &LoginDTO) -> Result<(), ServiceError> {
     create_login_validator().validate(dto)
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct TokenBodyResponse {
     pub access_token: String,
     pub refresh_token: String,
     pub token_type: String,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct RefreshTokenRequest {
     pub refresh_token: String,
     pub tenant_id: String,
@@ -152,6 +226,9 @@ pub struct RefreshTokenRequest {
 ///
 /// Validation is performed using the module's iterator-based validators; on success the function
 /// executes a functional pipeline that persists the user via the database and returns a signup message.
+/// An email already registered with the tenant is rejected as `email.taken` before the insert.
+/// Transient database failures during the insert are retried per `RetryPolicy::from_env`; a username or
+/// email taken by a concurrent signup fails at once.
 ///
 /// # Returns
 ///
@@ -166,24 +243,29 @@ pub struct RefreshTokenRequest {
 /// // Construct a valid UserDTO and obtain a `Pool` from your application context.
 /// let user = UserDTO { username: "alice".into(), password: "Password1".into(), email: "alice@example.com".into() };
 /// let pool: Pool = /* obtain pool from app context */;
-/// let result = signup(user, &pool);
+/// let result = signup(user, &pool).await;
 /// // `result` will be Ok(...) on success or Err(...) on failure.
 /// ```
-pub fn signup(user: UserDTO, pool: &Pool) -> Result<String, ServiceError> {
+pub async fn signup(user: UserDTO, pool: &Pool) -> Result<String, ServiceError> {
     // Use iterator-based validation pipeline
-    validate_user_dto(&user)?;
+    validate_user_dto(&user, UniqueEmail::query(pool.clone())).await?;
 
-    // Use functional pipeline with validated data
-    crate::services::functional_service_base::ServicePipeline::new(pool.clone())
-        .with_data(user)
-        .execute(|user, conn| user_ops::signup_user(user, conn))
-        .log_error("signup operation")
+    // The insert retries with a sleeping backoff, so it runs off the worker thread
+    let pool = pool.clone();
+    request_trace::block_service(move || {
+        crate::services::functional_service_base::ServicePipeline::new(pool)
+            .with_data(user)
+            .execute(|user, conn| user_ops::signup_user(user, PASSWORD_HASHER.as_ref(), conn))
+    })
+    .await
+    .log_error("signup operation")
 }
 
 /// Authenticate login credentials and return access and refresh tokens.
 ///
 /// Validates the provided credentials, verifies the login session, generates an access token,
-/// creates a new refresh token, and returns both tokens in a `TokenBodyResponse`.
+/// creates a new refresh token, and returns both tokens in a `TokenBodyResponse`. The user
+/// agent and IP address of `client` are recorded with the new session.
 ///
 /// # Returns
 ///
@@ -198,15 +280,19 @@ pub fn signup(user: UserDTO, pool: &Pool) -> Result<String, ServiceError> {
 ///     password: "s3cr3tPass".to_string(),
 /// };
 /// let pool = get_test_pool();
-/// let token_body = login(login, &pool).unwrap();
+/// let token_body = login(login, &SessionClient::default(), &pool).unwrap();
 /// assert_eq!(token_body.token_type, "bearer");
 /// ```
-pub fn login(login: LoginDTO, pool: &Pool) -> Result<TokenBodyResponse, ServiceError> {
+pub fn login(
+    login: LoginDTO,
+    client: &SessionClient,
+    pool: &Pool,
+) -> Result<TokenBodyResponse, ServiceError> {
     let query_service = FunctionalQueryService::new(pool.clone());
 
     query_service
         .query(|conn| {
-            user_ops::login_user(login, conn).ok_or_else(|| {
+            user_ops::login_user(login, client, PASSWORD_HASHER.as_ref(), conn).ok_or_else(|| {
                 ServiceError::unauthorized(constants::MESSAGE_LOGIN_FAILED.to_string())
             })
         })
@@ -222,7 +308,15 @@ pub fn login(login: LoginDTO, pool: &Pool) -> Result<TokenBodyResponse, ServiceE
                         })
                         .and_then(|user| {
                             let access_token = UserToken::generate_token(&logged_user);
-                            RefreshToken::create(user.id, conn)
+                            RefreshToken::issue(user.id, conn)
+                                .and_then(|refresh_token| {
+                                    LoginHistory::attach_refresh_family(
+                                        &logged_user.login_session,
+                                        &refresh_token.family_id,
+                                        conn,
+                                    )
+                                    .map(|_| refresh_token.token)
+                                })
                                 .map_err(|e| {
                                     ServiceError::internal_server_error(format!(
                                         "Failed to create refresh token: {}",
@@ -244,7 +338,8 @@ pub fn login(login: LoginDTO, pool: &Pool) -> Result<TokenBodyResponse, ServiceE
 /// Invalidate the user's session represented by a bearer Authorization header.
 ///
 /// Attempts to extract and validate a bearer token from `authen_header`, verifies the token,
-/// looks up the corresponding user, and invalidates that user's session in the database.
+/// looks up the corresponding user, and revokes the token's session and its refresh tokens.
+/// The user's other sessions stay logged in.
 ///
 /// # Returns
 ///
@@ -286,29 +381,38 @@ pub fn logout(authen_header: &HeaderValue, pool: &Pool) -> Result<(), ServiceErr
             })
         })
         .and_then(|token_data| {
-            token_utils::verify_token(&token_data, pool).map_err(|_| {
-                ServiceError::unauthorized(constants::MESSAGE_PROCESS_TOKEN_ERROR.to_string())
-            })
+            token_utils::verify_token(&token_data, pool)
+                .map(|username| (username, token_data.claims.login_session))
+                .map_err(|e| match e {
+                    ServiceError::Unauthorized { .. } => ServiceError::unauthorized(
+                        constants::MESSAGE_PROCESS_TOKEN_ERROR.to_string(),
+                    ),
+                    e => e,
+                })
         })
-        .and_then(|username| {
+        .and_then(|(username, session)| {
             query_service
                 .query(|conn| {
                     user_ops::find_user_by_username(&username, conn).map_err(|_| {
                         ServiceError::internal_server_error("Database error".to_string())
                     })
                 })
-                .map(|user| (user, username))
+                .map(|user| (user, session))
         })
-        .and_then(|(user, _)| {
+        .and_then(|(user, session)| {
             query_service.query(|conn| {
-                user_ops::logout_user(user.id, conn).map_err(|e| {
-                    log::error!(
-                        "Failed to clear login session for user {}: {}",
-                        user.username,
-                        e
-                    );
-                    ServiceError::internal_server_error("Failed to clear login session".to_string())
-                })
+                LoginHistory::revoke_by_login_session(user.id, &session, conn)
+                    .and_then(|_| user_ops::logout_user(user.id, conn))
+                    .map_err(|e| {
+                        log::error!(
+                            "Failed to clear login session for user {}: {}",
+                            user.username,
+                            e
+                        );
+                        ServiceError::internal_server_error(
+                            "Failed to clear login session".to_string(),
+                        )
+                    })
             })
         })
         .log_error("logout operation")
@@ -376,7 +480,8 @@ pub fn refresh(
 
 /// Refreshes the access and refresh tokens for a valid refresh token and tenant.
 ///
-/// Validates the provided refresh token, retrieves the associated user, revokes the old refresh token, creates a new refresh token, and generates a new access token.
+/// Rotates the provided refresh token (see `RefreshToken::rotate`), retrieves the associated user, and generates a new access token.
+/// Presenting a refresh token that was already exchanged revokes every token of its family and is rejected as unauthorized.
 ///
 /// # Arguments
 /// - `refresh_token`: the refresh token string to validate and rotate.
@@ -402,57 +507,62 @@ pub fn refresh_with_token(
     log::debug!("refresh_with_token called for tenant: {}", tenant_id);
     let query_service = FunctionalQueryService::new(pool.clone());
 
-    // Find and validate refresh token
+    // Exchange the presented refresh token for its successor
     query_service
         .query(|conn| {
-            RefreshToken::find_by_token(refresh_token, conn)
-                .map_err(|_| ServiceError::unauthorized("Invalid refresh token".to_string()))
+            RefreshToken::rotate(refresh_token, conn).map_err(|e| match e {
+                RotationError::Invalid => {
+                    ServiceError::unauthorized("Invalid refresh token".to_string())
+                }
+                RotationError::Reused { user_id, family_id } => {
+                    log::warn!(
+                        "Refresh token reuse detected for user_id: {}, revoked token family {}",
+                        user_id,
+                        family_id
+                    );
+                    ServiceError::unauthorized("Invalid refresh token".to_string())
+                        .with_detail("Refresh token reuse detected; all sessions revoked")
+                }
+                RotationError::Database(e) => ServiceError::internal_server_error(format!(
+                    "Failed to rotate refresh token: {}",
+                    e
+                )),
+            })
         })
-        .and_then(|refresh_token_record| {
+        .and_then(|new_refresh_token| {
             log::debug!(
-                "Found refresh token for user_id: {}, expires_at: {}",
-                refresh_token_record.user_id,
-                refresh_token_record.expires_at
+                "Rotated refresh token for user_id: {}, expires_at: {}",
+                new_refresh_token.user_id,
+                new_refresh_token.expires_at
             );
             // Get user info for new token generation
             query_service
                 .query(|conn| {
-                    user_ops::find_user_by_id(refresh_token_record.user_id, conn).map_err(|_| {
-                        ServiceError::internal_server_error("Failed to find user".to_string())
-                    })
+                    let user = user_ops::find_user_by_id(new_refresh_token.user_id, conn).map_err(
+                        |_| ServiceError::internal_server_error("Failed to find user".to_string()),
+                    )?;
+                    // The new access token belongs to the session that started the token
+                    // family; families issued before sessions were tracked use the latest login
+                    let session = match LoginHistory::find_by_refresh_family(
+                        &new_refresh_token.family_id,
+                        conn,
+                    ) {
+                        Ok(session) => {
+                            let _ = LoginHistory::touch(session.id, conn);
+                            session.login_session.unwrap_or_default()
+                        }
+                        Err(_) => user.login_session.clone(),
+                    };
+                    Ok((user, session))
                 })
-                .and_then(|user| {
-                    // Generate new tokens
-                    let access_token = UserToken::generate_token(&LoginInfoDTO {
-                        username: user.username.clone(),
-                        login_session: user.login_session.clone(),
+                .map(|(user, session)| TokenBodyResponse {
+                    access_token: UserToken::generate_token(&LoginInfoDTO {
+                        username: user.username,
+                        login_session: session,
                         tenant_id: tenant_id.to_string(),
-                    });
-
-                    // Revoke old refresh token and create new one
-                    query_service
-                        .query(|conn| {
-                            // Revoke old token
-                            RefreshToken::revoke(refresh_token, conn).map_err(|e| {
-                                ServiceError::internal_server_error(format!(
-                                    "Failed to revoke old token: {}",
-                                    e
-                                ))
-                            })?;
-
-                            // Create new refresh token
-                            RefreshToken::create(user.id, conn).map_err(|e| {
-                                ServiceError::internal_server_error(format!(
-                                    "Failed to create refresh token: {}",
-                                    e
-                                ))
-                            })
-                        })
-                        .map(|new_refresh_token| TokenBodyResponse {
-                            access_token,
-                            refresh_token: new_refresh_token,
-                            token_type: "bearer".to_string(),
-                        })
+                    }),
+                    refresh_token: new_refresh_token.token,
+                    token_type: "bearer".to_string(),
                 })
         })
         .log_error("refresh_with_token operation")
@@ -506,6 +616,101 @@ pub fn me(authen_header: &HeaderValue, pool: &Pool) -> Result<LoginInfoDTO, Serv
         .log_error("me operation")
 }
 
+/// The user and session of the bearer token in `authen_header`, provided the session is active.
+fn authenticated_session(
+    authen_header: &HeaderValue,
+    pool: &Pool,
+) -> Result<(User, String), ServiceError> {
+    let unauthorized =
+        || ServiceError::unauthorized(constants::MESSAGE_PROCESS_TOKEN_ERROR.to_string());
+
+    let authen_str = authen_header.to_str().map_err(|_| unauthorized())?;
+    if !token_utils::is_auth_header_valid(authen_header) {
+        return Err(unauthorized());
+    }
+    let token_data = token_utils::decode_token(authen_str[6..].trim().to_string())
+        .map_err(|_| unauthorized())?;
+    let username = token_utils::verify_token(&token_data, pool).map_err(|e| match e {
+        ServiceError::Unauthorized { .. } => unauthorized(),
+        e => e,
+    })?;
+
+    FunctionalQueryService::new(pool.clone())
+        .query(|conn| {
+            user_ops::find_user_by_username(&username, conn)
+                .map_err(|_| ServiceError::internal_server_error("Database error".to_string()))
+        })
+        .map(|user| (user, token_data.claims.login_session))
+}
+
+/// Lists the active sessions of the user authenticated by `authen_header`, newest first.
+///
+/// The session of the presented token is flagged `current`.
+pub fn list_sessions(
+    authen_header: &HeaderValue,
+    pool: &Pool,
+) -> Result<Vec<SessionDTO>, ServiceError> {
+    let (user, current) = authenticated_session(authen_header, pool)?;
+
+    FunctionalQueryService::new(pool.clone())
+        .query(|conn| {
+            LoginHistory::find_active_for_user(user.id, &current, conn).map_err(|e| {
+                ServiceError::internal_server_error("Failed to list sessions".to_string())
+                    .with_detail(e.to_string())
+            })
+        })
+        .map(|sessions| {
+            sessions
+                .into_iter()
+                .map(|session| SessionDTO::new(session, &current))
+                .collect()
+        })
+        .log_error("list_sessions operation")
+}
+
+/// Revokes the session `session_id` of the user authenticated by `authen_header`.
+///
+/// The session's access tokens stop validating and its refresh tokens are revoked.
+/// Returns `NotFound` when the user has no such active session.
+pub fn revoke_session(
+    authen_header: &HeaderValue,
+    session_id: i32,
+    pool: &Pool,
+) -> Result<(), ServiceError> {
+    let (user, _) = authenticated_session(authen_header, pool)?;
+
+    FunctionalQueryService::new(pool.clone())
+        .query(|conn| {
+            LoginHistory::revoke_session(user.id, session_id, conn).map_err(|e| match e {
+                diesel::result::Error::NotFound => {
+                    ServiceError::not_found(format!("Session with id {} not found", session_id))
+                }
+                e => ServiceError::internal_server_error("Failed to revoke session".to_string())
+                    .with_detail(e.to_string()),
+            })
+        })
+        .log_error("revoke_session operation")
+}
+
+/// Revokes every session of the user authenticated by `authen_header` except its own.
+///
+/// Returns the number of sessions revoked.
+pub fn revoke_other_sessions(
+    authen_header: &HeaderValue,
+    pool: &Pool,
+) -> Result<usize, ServiceError> {
+    let (user, current) = authenticated_session(authen_header, pool)?;
+
+    FunctionalQueryService::new(pool.clone())
+        .query(|conn| {
+            LoginHistory::revoke_other_sessions(user.id, &current, conn).map_err(|e| {
+                ServiceError::internal_server_error("Failed to revoke sessions".to_string())
+                    .with_detail(e.to_string())
+            })
+        })
+        .log_error("revoke_other_sessions operation")
+}
+
 /// Retrieve users with pagination and return them as response DTOs.
 ///
 /// Maps the paginated database user records into `UserResponseDTO` values and converts
@@ -515,6 +720,7 @@ pub fn me(authen_header: &HeaderValue, pool: &Pool) -> Result<LoginInfoDTO, Serv
 ///
 /// - `limit`: Maximum number of users to return for this page.
 /// - `offset`: Number of users to skip before collecting the page.
+/// - `sort`: Columns to order by, `id` when empty; `id` always breaks ties.
 ///
 /// # Returns
 ///
@@ -524,19 +730,20 @@ pub fn me(authen_header: &HeaderValue, pool: &Pool) -> Result<LoginInfoDTO, Serv
 ///
 /// ```
 /// // Assume `pool` is a valid database connection pool available in scope.
-/// let users = find_all_users(25, 0, &pool).expect("query failed");
+/// let users = find_all_users(25, 0, &[], &pool).expect("query failed");
 /// assert!(users.len() <= 25);
 /// ```
 pub fn find_all_users(
     limit: i64,
     offset: i64,
+    sort: &[SortSpec],
     pool: &Pool,
 ) -> Result<Vec<UserResponseDTO>, ServiceError> {
     let query_service = FunctionalQueryService::new(pool.clone());
 
     query_service
         .query(|conn| {
-            user_ops::find_all_users(limit, offset, conn)
+            user_ops::find_all_users(limit, offset, sort, conn)
                 .map_err(|e| ServiceError::internal_server_error(format!("Database error: {}", e)))
         })
         .map(|users| {
@@ -627,6 +834,21 @@ pub fn update_user(
         })
     })?;
 
+    // Emails are unique per tenant regardless of letter case
+    query_service.query(|conn| {
+        match user_ops::email_in_use(&updated_user.email, Some(user_id), conn) {
+            Ok(false) => Ok(()),
+            Ok(true) => Err(ServiceError::bad_request(format!(
+                "Email '{}' is already registered",
+                updated_user.email
+            ))),
+            Err(e) => Err(ServiceError::internal_server_error(format!(
+                "Database error: {}",
+                e
+            ))),
+        }
+    })?;
+
     // Perform update
     query_service
         .query(|conn| {
@@ -683,26 +905,205 @@ pub fn delete_user(user_id: i32, pool: &Pool) -> Result<(), ServiceError> {
         .log_error("delete_user operation")
 }
 
-/// Iterator-based validation for UserUpdateDTO
+/// Iterator-based validation for UserUpdateDTO, reporting every failing rule at once
 fn validate_user_update_dto(user_update: &UserUpdateDTO) -> Result<(), ServiceError> {
-    if user_update.username.trim().is_empty() {
-        return Err(ServiceError::bad_request("Username cannot be empty"));
+    validator::<String>()
+        .validate_all(vec![
+            (
+                "username".to_string(),
+                &user_update.username,
+                username_rules(),
+            ),
+            ("email".to_string(), &user_update.email, email_rules()),
+        ])
+        .map_err(ServiceError::validation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rule treating only `registered` (in any letter case) as taken
+    fn unique_email(registered: &'static str) -> UniqueEmail {
+        UniqueEmail::new(move |email: &String| Ok(email.eq_ignore_ascii_case(registered)))
     }
-    if user_update.username.len() < 3 {
-        return Err(ServiceError::bad_request(
-            "Username too short (min 3 characters)",
-        ));
+
+    #[actix_web::test]
+    async fn test_signup_validation_collects_all_violations() {
+        let dto = UserDTO {
+            username: "ab".to_string(),
+            email: "nope".to_string(),
+            password: "short".to_string(),
+            active: true,
+        };
+
+        let error = validate_user_dto(&dto, unique_email("nope"))
+            .await
+            .unwrap_err();
+        let errors = &error.context().errors;
+        let codes: Vec<(&str, &str)> = errors.iter().map(|e| (e.field.as_str(), e.code)).collect();
+
+        // The email lookup only runs once every other rule passed
+        assert_eq!(
+            codes,
+            vec![
+                ("username", "length.min"),
+                ("password", "password.too_short"),
+                ("email", "email.invalid"),
+            ]
+        );
+        assert!(error.to_string().contains("Invalid email format"));
     }
-    if user_update.username.len() > 50 {
-        return Err(ServiceError::bad_request(
-            "Username too long (max 50 characters)",
-        ));
+
+    #[actix_web::test]
+    async fn test_signup_validation_is_unprocessable_with_every_field_error() {
+        use actix_web::body::to_bytes;
+        use actix_web::http::StatusCode;
+        use actix_web::ResponseError;
+
+        let dto = UserDTO {
+            username: "ab".to_string(),
+            email: "nope".to_string(),
+            password: "short".to_string(),
+            active: true,
+        };
+
+        let response = validate_user_dto(&dto, unique_email("taken@example.com"))
+            .await
+            .unwrap_err()
+            .error_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let errors = body["data"]["errors"].as_array().unwrap();
+        let fields: Vec<&str> = errors
+            .iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, vec!["username", "password", "email"]);
+        assert!(errors
+            .iter()
+            .all(|e| e["code"].is_string() && e["message"].is_string()));
+        assert!(body["message"]
+            .as_str()
+            .unwrap()
+            .contains("Invalid email format"));
+    }
+
+    #[test]
+    fn test_bcrypt_cost_is_validated() {
+        assert_eq!(bcrypt_cost(None), 12);
+        assert_eq!(bcrypt_cost(Some("14")), 14);
+        assert_eq!(bcrypt_cost(Some("4")), 12);
+        assert_eq!(bcrypt_cost(Some("31")), 12);
+        assert_eq!(bcrypt_cost(Some("cheap")), 12);
+    }
+
+    #[test]
+    fn test_password_hash_algo_defaults_to_argon2() {
+        assert_eq!(password_hash_algo(None), PasswordHashAlgo::Argon2);
+        assert_eq!(password_hash_algo(Some("bcrypt")), PasswordHashAlgo::Bcrypt);
+        assert_eq!(password_hash_algo(Some("scrypt")), PasswordHashAlgo::Argon2);
     }
-    if user_update.email.trim().is_empty() {
-        return Err(ServiceError::bad_request("Email cannot be empty"));
+
+    #[actix_web::test]
+    async fn test_signup_validation_rejects_common_password() {
+        let dto = UserDTO {
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            password: "Welcome123".to_string(),
+            active: true,
+        };
+
+        let error = validate_user_dto(&dto, unique_email("bob@example.com"))
+            .await
+            .unwrap_err();
+        let codes: Vec<&str> = error.context().errors.iter().map(|e| e.code).collect();
+        assert_eq!(codes, vec!["password.too_common"]);
+    }
+
+    #[actix_web::test]
+    async fn test_signup_validation_rejects_a_registered_email() {
+        let signup = |email: &str| UserDTO {
+            username: "alice".to_string(),
+            email: email.to_string(),
+            password: "Str0ng!Passphrase".to_string(),
+            active: true,
+        };
+
+        let error = validate_user_dto(
+            &signup("Alice@Example.com"),
+            unique_email("alice@example.com"),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.http_status(),
+            actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+        );
+        let codes: Vec<(&str, &str)> = error
+            .context()
+            .errors
+            .iter()
+            .map(|e| (e.field.as_str(), e.code))
+            .collect();
+        assert_eq!(codes, vec![("email", "email.taken")]);
+
+        assert!(validate_user_dto(
+            &signup("new@example.com"),
+            unique_email("alice@example.com")
+        )
+        .await
+        .is_ok());
     }
-    if !EMAIL_REGEX.is_match(&user_update.email) {
-        return Err(ServiceError::bad_request("Invalid email format"));
+
+    #[actix_web::test]
+    async fn test_user_pages_do_not_overlap_when_sort_values_tie() {
+        use diesel::prelude::*;
+        use testcontainers::clients;
+
+        use crate::schema::users;
+        use crate::test_support::{start_postgres, Fixtures};
+
+        let docker = clients::Cli::default();
+        let Some((_postgres, pool)) = start_postgres(
+            &docker,
+            "test_user_pages_do_not_overlap_when_sort_values_tie",
+        ) else {
+            return;
+        };
+        let mut fixtures =
+            Fixtures::for_test("test_user_pages_do_not_overlap_when_sort_values_tie");
+
+        let rows: Vec<_> = (0..7)
+            .map(|_| {
+                let username = fixtures.username();
+                (
+                    users::email.eq(Fixtures::email(&username)),
+                    users::username.eq(username),
+                    users::password.eq("unused"),
+                    users::login_session.eq(""),
+                    users::active.eq(true),
+                )
+            })
+            .collect();
+        let mut ids: Vec<i32> = diesel::insert_into(users::table)
+            .values(rows)
+            .returning(users::id)
+            .get_results(&mut pool.get().unwrap())
+            .unwrap();
+
+        // Every user shares `active`, so only the `id` tiebreaker orders them
+        let sort =
+            crate::models::pagination::parse_sort("-active", User::SORTABLE_COLUMNS).unwrap();
+        let mut paged = Vec::new();
+        for offset in (0..ids.len() as i64).step_by(3) {
+            let page = find_all_users(3, offset, &sort, &pool).unwrap();
+            paged.extend(page.into_iter().map(|user| user.id));
+        }
+
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(paged, ids);
     }
-    Ok(())
 }
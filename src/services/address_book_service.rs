@@ -11,51 +11,125 @@
 //! - **Immutable data transformations**: All operations preserve immutability
 //! - **Error handling monads**: Comprehensive Result/Option chaining
 
+use std::collections::HashSet;
+
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    config::db::Pool,
+    config::db::{timed_query, Pool},
     constants,
-    error::ServiceError,
+    error::{ErrorTag, ServiceError},
+    functional::{immutable_state::get_state_manager, validation_rules::normalize_phone},
     models::{
-        filters::PersonFilter,
-        person::{Person, PersonDTO},
-        response::Page,
+        filters::{PersonFilter, SortSpec},
+        person::{Person, PersonDTO, PersonPatch, PersonUpdate},
+        response::{Page, PageMeta},
+    },
+    services::{
+        contact_events::{contact_events, ContactEvent},
+        functional_service_base::{FunctionalErrorHandling, FunctionalQueryService},
+        transaction::{with_transaction, TransactionError},
     },
-    services::functional_patterns::Validator,
-    services::functional_service_base::{FunctionalErrorHandling, FunctionalQueryService},
+    utils::json_patch::{self, PatchOperation},
 };
 
-/// Iterator-based validation using functional combinator pattern
-fn create_person_validator() -> Validator<PersonDTO> {
-    Validator::new()
-        .rule(|dto: &PersonDTO| {
-            if dto.name.trim().is_empty() {
-                Err(ServiceError::bad_request("Name cannot be empty"))
-            } else if dto.name.len() > 100 {
-                Err(ServiceError::bad_request(
-                    "Name too long (max 100 characters)",
-                ))
-            } else {
-                Ok(())
-            }
-        })
-        .rule(|dto: &PersonDTO| {
-            if dto.email.trim().is_empty() {
-                Err(ServiceError::bad_request("Email cannot be empty"))
-            } else if !dto.email.contains('@') {
-                Err(ServiceError::bad_request("Invalid email format"))
-            } else if dto.email.len() > 255 {
-                Err(ServiceError::bad_request(
-                    "Email too long (max 255 characters)",
-                ))
-            } else {
-                Ok(())
-            }
-        })
+/// Maximum number of rows accepted by `validate_batch` and `insert_many`
+pub const MAX_BATCH_SIZE: usize = 1000;
+
+/// Drops the tenant's cached queries and notifies its `/ws` subscribers of a write.
+///
+/// Every write of this module calls it once the write is saved.
+pub fn contacts_changed(tenant_id: &str, event: ContactEvent) {
+    if let Err(e) = get_state_manager().clear_query_cache(tenant_id) {
+        log::warn!("Failed to clear query cache of tenant {}: {}", tenant_id, e);
+    }
+    contact_events().publish(tenant_id, event);
 }
 
-/// Legacy validation for backward compatibility - uses new functional validator
+/// A batch row as decoded from the request body; `Err` carries the decode error.
+pub type PersonBatchEntry = Result<PersonDTO, String>;
+
+/// Validation outcome of a single row of a batch
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PersonRowValidation {
+    pub index: usize,
+    pub valid: bool,
+    pub errors: Vec<String>,
+}
+
+/// Per-row validation results of a batch with aggregate counts
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PersonBatchValidation {
+    pub total: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    pub rows: Vec<PersonRowValidation>,
+}
+
+/// Insert outcome of a single row of a batch
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PersonRowInsert {
+    pub index: usize,
+    pub inserted: bool,
+    pub errors: Vec<String>,
+}
+
+/// Per-row insert results of a batch with aggregate counts
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PersonBatchInsert {
+    pub total: usize,
+    pub inserted: usize,
+    pub failed: usize,
+    pub rows: Vec<PersonRowInsert>,
+}
+
+/// Body of `POST api/address-book/bulk-delete`
+#[derive(Deserialize, Debug, Clone, JsonSchema)]
+pub struct PersonBulkDeleteRequest {
+    pub ids: Vec<i32>,
+}
+
+/// Outcome of `delete_many`
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct PersonBulkDelete {
+    /// Number of people deleted
+    pub deleted: usize,
+    /// Requested ids with no person to delete, in request order
+    pub not_found: Vec<i32>,
+}
+
+/// Country calling code used to complete phone numbers given without one.
+///
+/// Read from `PHONE_DEFAULT_COUNTRY_CODE` (digits, optional leading `+`); when
+/// unset or invalid, phone numbers are only stripped of formatting.
+fn default_phone_country_code() -> Option<String> {
+    std::env::var("PHONE_DEFAULT_COUNTRY_CODE")
+        .ok()
+        .map(|code| code.trim().trim_start_matches('+').to_string())
+        .filter(|code| !code.is_empty() && code.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Normalizes the phone number of `dto` so it is validated and stored in one canonical form.
+fn normalize_person_phone(mut dto: PersonDTO, default_country_code: Option<&str>) -> PersonDTO {
+    dto.phone = normalize_phone(&dto.phone, default_country_code);
+    dto
+}
+
+/// Applies the configured normalization to an incoming person.
+fn normalize_person_dto(dto: PersonDTO) -> PersonDTO {
+    normalize_person_phone(dto, default_phone_country_code().as_deref())
+}
+
+/// Validates every field of `dto`, reporting all violations in a single error.
 fn validate_person_dto(dto: &PersonDTO) -> Result<(), ServiceError> {
-    create_person_validator().validate(dto)
+    let errors = dto.validation_errors();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(ServiceError::validation(errors))
+    }
 }
 
 /// Fetches all Person records with iterator-based processing and lazy evaluation.
@@ -94,6 +168,54 @@ pub fn find_by_id(id: i32, pool: &Pool) -> Result<Person, ServiceError> {
     })
 }
 
+/// Reads page `page` (1-based) of `per_page` people ordered by `sort`, by name when empty.
+///
+/// # Returns
+/// The people of the page with their [`PageMeta`], or `Err(ServiceError)` on
+/// database errors.
+pub fn list_page(
+    page: i64,
+    per_page: i64,
+    sort: &[SortSpec],
+    pool: &Pool,
+) -> Result<(Vec<Person>, PageMeta), ServiceError> {
+    let query_service = FunctionalQueryService::new(pool.clone());
+    let offset = (page.max(1) - 1).saturating_mul(per_page);
+
+    query_service
+        .query(|conn| {
+            timed_query("Person::list_page", || {
+                Person::list_page(offset, per_page, sort, conn)
+            })
+            .map_err(|e| {
+                ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR)
+                    .with_tag(ErrorTag::Db)
+                    .with_detail(e.to_string())
+            })
+        })
+        .map(|(people, total)| (people, PageMeta::new(page, per_page, total)))
+        .log_error("list_page operation")
+}
+
+/// Reads the next batch of people for an export: up to `limit` people with an
+/// id greater than `after`, in id order.
+pub fn export_batch(after: i32, limit: i64, pool: &Pool) -> Result<Vec<Person>, ServiceError> {
+    let query_service = FunctionalQueryService::new(pool.clone());
+
+    query_service
+        .query(|conn| {
+            timed_query("Person::find_after", || {
+                Person::find_after(after, limit, conn)
+            })
+            .map_err(|e| {
+                ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR)
+                    .with_tag(ErrorTag::Db)
+                    .with_detail(e.to_string())
+            })
+        })
+        .log_error("export_batch operation")
+}
+
 /// Retrieves a paginated page of people using lazy iterator evaluation.
 ///
 /// Applies filtering through iterator chains without immediate collection,
@@ -109,20 +231,37 @@ pub fn filter(filter: PersonFilter, pool: &Pool) -> Result<Page<Person>, Service
 
     query_service.query(|conn| {
         debug!("Executing Person::filter with database connection");
-        Person::filter(filter, conn).map_err(|e| {
+        timed_query("Person::filter", || Person::filter(filter, conn)).map_err(|e| {
             error!("Database error in Person::filter: {}", e);
             ServiceError::internal_server_error(format!("Database error: {}", e))
         })
     })
 }
 
+/// Searches contacts for `query`, most relevant first; see [`Person::search`].
+///
+/// # Returns
+/// `Ok(Page<Person>)` with up to `limit` matches and no next cursor.
+pub fn search(query: &str, limit: i64, pool: &Pool) -> Result<Page<Person>, ServiceError> {
+    FunctionalQueryService::new(pool.clone()).query(|conn| {
+        timed_query("Person::search", || Person::search(query, limit, conn))
+            .map(|people| {
+                let total = people.len() as i64;
+                Page::new(constants::MESSAGE_OK, people, 0, limit, Some(total), None)
+            })
+            .map_err(|e| ServiceError::internal_server_error(format!("Database error: {}", e)))
+    })
+}
+
 /// Inserts a new person using iterator-based validation and functional pipelines.
 ///
 /// Uses iterator chains for validation and composes database operations through functional pipelines.
 ///
 /// # Returns
 /// `Ok(())` on successful insertion, `Err(ServiceError)` on validation or database errors.
-pub fn insert(new_person: PersonDTO, pool: &Pool) -> Result<(), ServiceError> {
+pub fn insert(tenant_id: &str, new_person: PersonDTO, pool: &Pool) -> Result<(), ServiceError> {
+    let new_person = normalize_person_dto(new_person);
+
     // Use iterator-based validation pipeline
     validate_person_dto(&new_person)?;
 
@@ -130,7 +269,7 @@ pub fn insert(new_person: PersonDTO, pool: &Pool) -> Result<(), ServiceError> {
     crate::services::functional_service_base::ServicePipeline::new(pool.clone())
         .with_data(new_person)
         .execute(|person, conn| {
-            Person::insert(person, conn)
+            Person::insert(tenant_id, person, conn)
                 .map_err(|_| {
                     ServiceError::internal_server_error(
                         constants::MESSAGE_CAN_NOT_INSERT_DATA.to_string(),
@@ -138,43 +277,185 @@ pub fn insert(new_person: PersonDTO, pool: &Pool) -> Result<(), ServiceError> {
                 })
                 .map(|_| ())
         })
+        .inspect(|_| contacts_changed(tenant_id, ContactEvent::created()))
 }
 
 /// Updates a person using iterator-based validation and functional pipelines.
 ///
-/// Validates input data using iterator chains, verifies existence, then performs update in a functional pipeline.
+/// Validates input data using iterator chains, then verifies existence and updates in one transaction.
+/// The update only applies while the person is still at `update.version`, so
+/// of two concurrent updates from the same version only the first is saved.
 ///
 /// # Returns
-/// `Ok(())` on successful update, `Err(ServiceError)` on validation or database errors.
-pub fn update(id: i32, updated_person: PersonDTO, pool: &Pool) -> Result<(), ServiceError> {
+/// `Ok(())` on successful update, `Conflict` when the person was updated since
+/// `update.version`, `Err(ServiceError)` on validation or database errors.
+pub fn update(
+    tenant_id: &str,
+    id: i32,
+    update: PersonUpdate,
+    pool: &Pool,
+) -> Result<(), ServiceError> {
+    let PersonUpdate { person, version } = update;
+    let updated_person = normalize_person_dto(person);
+
     // Use iterator-based validation pipeline
     validate_person_dto(&updated_person)?;
 
-    // Use functional pipeline with validated data
-    crate::services::functional_service_base::ServicePipeline::new(pool.clone())
-        .with_data((id, updated_person))
-        .execute(move |(person_id, person), conn| {
-            Person::find_by_id(person_id, conn).map_err(|_| {
-                ServiceError::not_found(format!("Person with id {} not found", person_id))
-            })?;
-            Person::update(person_id, person, conn)
-                .map_err(|_| {
-                    ServiceError::internal_server_error(
-                        constants::MESSAGE_CAN_NOT_UPDATE_DATA.to_string(),
-                    )
-                })
-                .map(|_| ())
-        })
+    with_transaction(pool, |conn| {
+        let current = Person::find_by_id(id, conn)
+            .map_err(|_| ServiceError::not_found(format!("Person with id {} not found", id)))?;
+        let updated = Person::update_if_version(id, version, updated_person.clone(), conn)
+            .map_err(|e| TransactionError::query_or(e, can_not_update))?;
+        if updated == 0 {
+            return Err(stale_version(id, version, &current).into());
+        }
+        Ok(())
+    })
+    .inspect(|_| contacts_changed(tenant_id, ContactEvent::updated(id)))
+}
+
+/// `Conflict` for an update of `id` from `version` after `current` was saved.
+///
+/// `current` may itself be outdated by the time the update runs; a client
+/// should read the person again before retrying.
+fn stale_version(id: i32, version: i32, current: &Person) -> ServiceError {
+    ServiceError::conflict(format!(
+        "Person with id {} was updated since version {}; read it again and retry",
+        id, version
+    ))
+    .with_metadata("current_version", current.version.to_string())
+}
+
+fn can_not_update() -> ServiceError {
+    ServiceError::internal_server_error(constants::MESSAGE_CAN_NOT_UPDATE_DATA.to_string())
+}
+
+/// Applies `patch` to `current`, validating only the fields present in the patch.
+///
+/// Fields left out keep their stored value even if it would no longer pass
+/// validation, so a partial update never fails because of them.
+fn apply_person_patch(current: &Person, patch: PersonPatch) -> Result<PersonDTO, ServiceError> {
+    let fields = patch.fields();
+    let person = patch.apply(current).map_err(ServiceError::validation)?;
+    validate_changed_fields(person, &fields)
+}
+
+/// Normalizes and validates the `fields` of `person` that a partial update changed.
+fn validate_changed_fields(
+    mut person: PersonDTO,
+    fields: &[&str],
+) -> Result<PersonDTO, ServiceError> {
+    if fields.contains(&"phone") {
+        person = normalize_person_dto(person);
+    }
+
+    let errors: Vec<_> = person
+        .validation_errors()
+        .into_iter()
+        .filter(|error| fields.contains(&error.field.as_str()))
+        .collect();
+    if errors.is_empty() {
+        Ok(person)
+    } else {
+        Err(ServiceError::validation(errors))
+    }
+}
+
+/// Applies the JSON Patch `operations` to the editable fields of `current`.
+///
+/// The patched document is the person as sent to `PUT`, so `/id` and other
+/// stored-only fields do not exist. Operations that cannot be applied, and
+/// results that are no longer a person, are `UnprocessableEntity`; as with
+/// [`apply_person_patch`], only the fields that changed are validated.
+fn apply_json_patch(
+    current: &Person,
+    operations: &[PatchOperation],
+) -> Result<PersonDTO, ServiceError> {
+    let unprocessable = |message: String| {
+        ServiceError::unprocessable_entity(message).with_tag(ErrorTag::Validation)
+    };
+
+    let original = serde_json::to_value(PersonDTO::from(current)).map_err(|e| {
+        ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR)
+            .with_tag(ErrorTag::Internal)
+            .with_detail(e.to_string())
+    })?;
+    let mut document = original.clone();
+    json_patch::apply(&mut document, operations)
+        .map_err(|e| unprocessable(format!("Invalid JSON patch: {}", e)))?;
+
+    if let Some(unknown) = document.as_object().and_then(|fields| {
+        fields
+            .keys()
+            .find(|key| original.get(key.as_str()).is_none())
+    }) {
+        return Err(unprocessable(format!("Unknown field '{}'", unknown)));
+    }
+    let changed: Vec<&str> = original
+        .as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(key, value)| document.get(key.as_str()) != Some(*value))
+        .map(|(key, _)| key.as_str())
+        .collect();
+    let person = serde_json::from_value(document.clone())
+        .map_err(|e| unprocessable(format!("Patched contact is not valid: {}", e)))?;
+    validate_changed_fields(person, &changed)
+}
+
+/// Partially updates a person: loads the row, applies the present fields of
+/// `patch` and saves the result.
+///
+/// # Returns
+/// The updated person, `NotFound` for an unknown or deleted id, or a
+/// validation error listing every invalid present field.
+pub fn patch(
+    tenant_id: &str,
+    id: i32,
+    patch: PersonPatch,
+    pool: &Pool,
+) -> Result<Person, ServiceError> {
+    with_transaction(pool, |conn| {
+        let current = Person::find_by_id(id, conn)
+            .map_err(|_| ServiceError::not_found(format!("Person with id {} not found", id)))?;
+        Person::update(id, apply_person_patch(&current, patch.clone())?, conn)
+            .map_err(|e| TransactionError::query_or(e, can_not_update))?;
+        Person::find_by_id(id, conn).map_err(|e| TransactionError::query_or(e, can_not_update))
+    })
+    .inspect(|_| contacts_changed(tenant_id, ContactEvent::updated(id)))
+}
+
+/// Applies a JSON Patch (RFC 6902) to a person: loads the row, applies
+/// `operations` to its fields and saves the result.
+///
+/// # Returns
+/// The updated person, `NotFound` for an unknown or deleted id,
+/// `UnprocessableEntity` when the patch cannot be applied, or a validation
+/// error listing every invalid changed field.
+pub fn json_patch(
+    tenant_id: &str,
+    id: i32,
+    operations: &[PatchOperation],
+    pool: &Pool,
+) -> Result<Person, ServiceError> {
+    with_transaction(pool, |conn| {
+        let current = Person::find_by_id(id, conn)
+            .map_err(|_| ServiceError::not_found(format!("Person with id {} not found", id)))?;
+        Person::update(id, apply_json_patch(&current, operations)?, conn)
+            .map_err(|e| TransactionError::query_or(e, can_not_update))?;
+        Person::find_by_id(id, conn).map_err(|e| TransactionError::query_or(e, can_not_update))
+    })
+    .inspect(|_| contacts_changed(tenant_id, ContactEvent::updated(id)))
 }
 
 /// Deletes a person using pure functional composition.
 ///
 /// Verifies existence through lazy evaluation, then performs deletion
-/// in a functional pipeline.
+/// in a functional pipeline. The deletion is soft: see [`Person::delete`].
 ///
 /// # Returns
 /// `Ok(())` on successful deletion, `Err(ServiceError)` on database errors.
-pub fn delete(id: i32, pool: &Pool) -> Result<(), ServiceError> {
+pub fn delete(tenant_id: &str, id: i32, pool: &Pool) -> Result<(), ServiceError> {
     let query_service = FunctionalQueryService::new(pool.clone());
 
     query_service
@@ -193,4 +474,463 @@ pub fn delete(id: i32, pool: &Pool) -> Result<(), ServiceError> {
                     .map(|_| ())
             })
         })
+        .inspect(|_| contacts_changed(tenant_id, ContactEvent::deleted(id)))
+}
+
+/// Soft-deletes the people of `tenant_id` with the given ids in one transaction.
+///
+/// Tenants may share a database, so every id is checked against the owner
+/// of its contact first: when any belongs to another tenant the whole batch
+/// is rejected and nothing is deleted. Ids without a contact are reported in
+/// `not_found` without failing the request. Repeated ids count once, and
+/// `deleted` events are published in request order.
+///
+/// # Returns
+/// `Ok(PersonBulkDelete)` with the number deleted and the ids not found,
+/// `Err(ServiceError::Forbidden)` when an id is another tenant's contact, or
+/// `Err(ServiceError)` when `ids` is empty, exceeds `MAX_BATCH_SIZE` or the
+/// update fails.
+pub fn delete_many(
+    tenant_id: &str,
+    ids: &[i32],
+    pool: &Pool,
+) -> Result<PersonBulkDelete, ServiceError> {
+    if ids.is_empty() {
+        return Err(ServiceError::bad_request("ids cannot be empty").with_tag(ErrorTag::Validation));
+    }
+    if ids.len() > MAX_BATCH_SIZE {
+        return Err(
+            ServiceError::bad_request(format!("Too many ids (max {})", MAX_BATCH_SIZE))
+                .with_tag(ErrorTag::Validation)
+                .with_detail(format!("Received {} ids", ids.len())),
+        );
+    }
+
+    let can_not_delete = || {
+        ServiceError::internal_server_error(constants::MESSAGE_CAN_NOT_DELETE_DATA.to_string())
+            .with_tag(ErrorTag::Db)
+    };
+    let ids: Vec<i32> = ids.iter().copied().unique().collect();
+    let deleted = with_transaction(pool, |conn| {
+        let foreign = timed_query("Person::foreign_ids", || {
+            Person::foreign_ids(tenant_id, &ids, conn)
+        })
+        .map_err(|e| TransactionError::query_or(e, can_not_delete))?;
+        if !foreign.is_empty() {
+            return Err(
+                ServiceError::forbidden("Cannot delete contacts of another tenant")
+                    .with_tag(ErrorTag::Tenant)
+                    .with_metadata("foreign_ids", foreign.iter().join(","))
+                    .into(),
+            );
+        }
+        timed_query("Person::delete_many", || {
+            Person::delete_many(tenant_id, &ids, conn)
+        })
+        .map_err(|e| TransactionError::query_or(e, can_not_delete))
+    })?;
+
+    let deleted: HashSet<i32> = deleted.into_iter().collect();
+    ids.iter()
+        .filter(|id| deleted.contains(id))
+        .for_each(|id| contacts_changed(tenant_id, ContactEvent::deleted(*id)));
+    let not_found = ids.into_iter().filter(|id| !deleted.contains(id)).collect();
+    Ok(PersonBulkDelete {
+        deleted: deleted.len(),
+        not_found,
+    })
+}
+
+/// Validates a batch of people without writing anything.
+///
+/// Each row goes through the same checks as `insert`; rows that could not be
+/// decoded are reported as invalid with their decode error.
+///
+/// # Returns
+/// `Ok(PersonBatchValidation)` with one result per row in input order, or
+/// `Err(ServiceError)` when the batch is empty or exceeds `MAX_BATCH_SIZE`.
+pub fn validate_batch(
+    entries: Vec<PersonBatchEntry>,
+) -> Result<PersonBatchValidation, ServiceError> {
+    let rows: Vec<PersonRowValidation> = validate_batch_entries(entries)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let errors = row.err().unwrap_or_default();
+            PersonRowValidation {
+                index,
+                valid: errors.is_empty(),
+                errors,
+            }
+        })
+        .collect();
+
+    let valid = rows.iter().filter(|row| row.valid).count();
+    Ok(PersonBatchValidation {
+        total: rows.len(),
+        valid,
+        invalid: rows.len() - valid,
+        rows,
+    })
+}
+
+/// Checks the batch size, then normalizes and validates every row, keeping input order.
+fn validate_batch_entries(
+    entries: Vec<PersonBatchEntry>,
+) -> Result<Vec<Result<PersonDTO, Vec<String>>>, ServiceError> {
+    if entries.is_empty() {
+        return Err(
+            ServiceError::bad_request("Batch cannot be empty").with_tag(ErrorTag::Validation)
+        );
+    }
+
+    if entries.len() > MAX_BATCH_SIZE {
+        return Err(ServiceError::bad_request(format!(
+            "Batch too large (max {} rows)",
+            MAX_BATCH_SIZE
+        ))
+        .with_tag(ErrorTag::Validation)
+        .with_detail(format!("Received {} rows", entries.len())));
+    }
+
+    let default_country_code = default_phone_country_code();
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let dto = normalize_person_phone(
+                entry.map_err(|e| vec![e])?,
+                default_country_code.as_deref(),
+            );
+            dto.validate().map(|_| dto)
+        })
+        .collect())
+}
+
+/// Inserts a batch of people with a single multi-row insert.
+///
+/// Every row is validated like `validate_batch` first. When any row is invalid, nothing is
+/// inserted unless `partial` is set, in which case the valid rows are still inserted. A
+/// database failure rolls back the whole batch.
+///
+/// # Returns
+/// `Ok(PersonBatchInsert)` with one result per row in input order, or `Err(ServiceError)`
+/// when the batch is empty, exceeds `MAX_BATCH_SIZE` or the insert fails.
+pub fn insert_many(
+    tenant_id: &str,
+    entries: Vec<PersonBatchEntry>,
+    partial: bool,
+    pool: &Pool,
+) -> Result<PersonBatchInsert, ServiceError> {
+    let validated = validate_batch_entries(entries)?;
+    let insert_valid_rows = partial || validated.iter().all(|row| row.is_ok());
+
+    let mut new_people = Vec::new();
+    let mut rows = Vec::with_capacity(validated.len());
+    for (index, row) in validated.into_iter().enumerate() {
+        let errors = match row {
+            Ok(dto) => {
+                if insert_valid_rows {
+                    new_people.push(dto);
+                }
+                Vec::new()
+            }
+            Err(errors) => errors,
+        };
+        rows.push(PersonRowInsert {
+            index,
+            inserted: insert_valid_rows && errors.is_empty(),
+            errors,
+        });
+    }
+
+    let inserted = if new_people.is_empty() {
+        0
+    } else {
+        with_transaction(pool, |conn| {
+            timed_query("Person::insert_many", || {
+                Person::insert_many(tenant_id, &new_people, conn)
+            })
+            .map_err(|e| {
+                TransactionError::query_or(e, || {
+                    ServiceError::internal_server_error(
+                        constants::MESSAGE_CAN_NOT_INSERT_DATA.to_string(),
+                    )
+                    .with_tag(ErrorTag::Db)
+                })
+            })
+        })?
+    };
+    if inserted > 0 {
+        contacts_changed(tenant_id, ContactEvent::created());
+    }
+
+    Ok(PersonBatchInsert {
+        total: rows.len(),
+        inserted,
+        failed: rows.len() - inserted,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person_with_phone(phone: &str) -> PersonDTO {
+        PersonDTO {
+            name: "Nguyen Van A".to_string(),
+            gender: true,
+            age: 30,
+            address: "Ha Noi".to_string(),
+            phone: phone.to_string(),
+            email: "a@example.com".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_phone_formats_normalize_to_same_stored_value() {
+        let formats = [
+            "0123456789",
+            "012 345 6789",
+            "012-345-6789",
+            "(012) 345.6789",
+            "+84 12 345 6789",
+            "0084-12-345-6789",
+        ];
+
+        for format in formats {
+            let dto = normalize_person_phone(person_with_phone(format), Some("84"));
+            assert_eq!(dto.phone, "+84123456789", "input {:?}", format);
+            assert!(dto.validate().is_ok(), "input {:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_validation_reports_every_invalid_field() {
+        let dto = PersonDTO {
+            name: " ".to_string(),
+            gender: false,
+            age: 200,
+            address: "Ha Noi".to_string(),
+            phone: "0123456789".to_string(),
+            email: "not-an-email".to_string(),
+        };
+
+        let error = validate_person_dto(&dto).unwrap_err();
+        assert_eq!(
+            error.http_status(),
+            actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+        );
+        let body = serde_json::to_value(crate::error::ErrorEnvelope::from_error(&error)).unwrap();
+
+        let fields: Vec<&str> = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, vec!["name", "email", "age"]);
+        assert_eq!(
+            body["errors"][2],
+            serde_json::json!({
+                "field": "age",
+                "code": "range.max",
+                "message": body["errors"][2]["message"],
+            })
+        );
+        assert!(body["tags"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|tag| tag == "validation"));
+    }
+
+    fn stored_person() -> Person {
+        Person {
+            id: 1,
+            name: "Nguyen Van A".to_string(),
+            gender: true,
+            age: 30,
+            address: "Ha Noi".to_string(),
+            phone: "0123456789".to_string(),
+            email: "a@example.com".to_string(),
+            deleted_at: None,
+            version: 1,
+        }
+    }
+
+    fn parse_patch(body: serde_json::Value) -> PersonPatch {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_patch_keeps_omitted_fields() {
+        let patch = parse_patch(serde_json::json!({ "age": 31 }));
+        assert_eq!(patch.fields(), vec!["age"]);
+
+        let person = apply_person_patch(&stored_person(), patch).unwrap();
+
+        assert_eq!(person.age, 31);
+        assert_eq!(person.name, "Nguyen Van A");
+        assert_eq!(person.email, "a@example.com");
+        assert_eq!(person.phone, "0123456789");
+    }
+
+    #[test]
+    fn test_patch_validates_only_present_fields() {
+        // The stored email no longer passes validation but is not part of the patch
+        let mut stored = stored_person();
+        stored.email = "legacy".to_string();
+
+        let person =
+            apply_person_patch(&stored, parse_patch(serde_json::json!({ "name": "B" }))).unwrap();
+        assert_eq!(person.name, "B");
+        assert_eq!(person.email, "legacy");
+
+        let error = apply_person_patch(
+            &stored,
+            parse_patch(serde_json::json!({ "name": " ", "age": 200 })),
+        )
+        .unwrap_err();
+        let body = serde_json::to_value(crate::error::ErrorEnvelope::from_error(&error)).unwrap();
+        let fields: Vec<&str> = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, vec!["name", "age"]);
+    }
+
+    #[test]
+    fn test_patch_rejects_clearing_required_fields() {
+        let patch = parse_patch(serde_json::json!({ "address": null, "phone": null }));
+        assert_eq!(patch.address, Some(None));
+        assert_eq!(patch.name, None);
+
+        let error = apply_person_patch(&stored_person(), patch).unwrap_err();
+        let body = serde_json::to_value(crate::error::ErrorEnvelope::from_error(&error)).unwrap();
+        let codes: Vec<(&str, &str)> = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| (e["field"].as_str().unwrap(), e["code"].as_str().unwrap()))
+            .collect();
+        assert_eq!(codes, vec![("address", "required"), ("phone", "required")]);
+    }
+
+    #[test]
+    fn test_patch_rejects_unknown_fields() {
+        assert!(serde_json::from_value::<PersonPatch>(serde_json::json!({ "nmae": "B" })).is_err());
+    }
+
+    fn parse_operations(body: serde_json::Value) -> Vec<PatchOperation> {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_json_patch_replace_validates_changed_fields() {
+        let mut stored = stored_person();
+        stored.email = "legacy".to_string();
+
+        let person = apply_json_patch(
+            &stored,
+            &parse_operations(serde_json::json!([
+                { "op": "test", "path": "/name", "value": "Nguyen Van A" },
+                { "op": "replace", "path": "/name", "value": "B" },
+                { "op": "replace", "path": "/age", "value": 31 },
+            ])),
+        )
+        .unwrap();
+        assert_eq!(person.name, "B");
+        assert_eq!(person.age, 31);
+        assert_eq!(person.email, "legacy");
+
+        let error = apply_json_patch(
+            &stored,
+            &parse_operations(serde_json::json!([
+                { "op": "replace", "path": "/age", "value": 200 },
+            ])),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.http_status(),
+            actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[test]
+    fn test_json_patch_remove_must_leave_a_person() {
+        let person = apply_json_patch(
+            &stored_person(),
+            &parse_operations(serde_json::json!([
+                { "op": "remove", "path": "/address" },
+                { "op": "add", "path": "/address", "value": "Hue" },
+            ])),
+        )
+        .unwrap();
+        assert_eq!(person.address, "Hue");
+
+        let error = apply_json_patch(
+            &stored_person(),
+            &parse_operations(serde_json::json!([{ "op": "remove", "path": "/address" }])),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.http_status(),
+            actix_web::http::StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[test]
+    fn test_json_patch_rejects_invalid_paths_and_ops() {
+        let bodies = [
+            serde_json::json!([{ "op": "replace", "path": "/id", "value": 2 }]),
+            serde_json::json!([{ "op": "replace", "path": "/name/first", "value": "B" }]),
+            serde_json::json!([{ "op": "add", "path": "/nickname", "value": "B" }]),
+            serde_json::json!([{ "op": "merge", "path": "/name", "value": "B" }]),
+        ];
+        for body in bodies {
+            let error =
+                apply_json_patch(&stored_person(), &parse_operations(body.clone())).unwrap_err();
+            assert_eq!(
+                error.http_status(),
+                actix_web::http::StatusCode::UNPROCESSABLE_ENTITY,
+                "body {}",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn test_phone_without_default_country_is_only_stripped() {
+        let dto = normalize_person_phone(person_with_phone("(012) 345-6789"), None);
+
+        assert_eq!(dto.phone, "0123456789");
+    }
+
+    #[actix_web::test]
+    async fn test_normalized_phone_round_trips_through_the_database() {
+        let docker = testcontainers::clients::Cli::default();
+        let Some((_postgres, pool)) = crate::test_support::start_postgres(
+            &docker,
+            "test_normalized_phone_round_trips_through_the_database",
+        ) else {
+            return;
+        };
+
+        // 12 characters, and the longest E.164 number: + and 15 digits
+        for (input, stored) in [
+            ("0084 12 345 6789", "+84123456789"),
+            ("+123 456 789 012 345", "+123456789012345"),
+        ] {
+            insert("tenant1", person_with_phone(input), &pool).unwrap();
+            let person = find_all(&pool)
+                .unwrap()
+                .into_iter()
+                .max_by_key(|person| person.id)
+                .unwrap();
+            assert_eq!(person.phone, stored, "input {:?}", input);
+        }
+    }
 }
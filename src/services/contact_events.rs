@@ -0,0 +1,134 @@
+//! Live contact change notifications.
+//!
+//! Every write to the address book publishes a [`ContactEvent`] on the
+//! tenant's broadcast channel, and `/api/address-book/ws` clients subscribe to
+//! their own tenant's channel. Each channel buffers [`CONTACT_EVENT_CAPACITY`]
+//! events per subscriber; a subscriber that falls further behind is lagged and
+//! gets disconnected rather than slowing the writers down.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before it counts as lagged
+pub const CONTACT_EVENT_CAPACITY: usize = 64;
+
+/// Kind of change made to the address book
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactChange {
+    Created,
+    Updated,
+    Deleted,
+}
+
+/// Message sent to subscribers after a change
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ContactEvent {
+    pub change: ContactChange,
+    /// Id of the changed contact; absent for creates, whose id is not returned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i32>,
+}
+
+impl ContactEvent {
+    pub fn created() -> Self {
+        Self {
+            change: ContactChange::Created,
+            id: None,
+        }
+    }
+
+    pub fn updated(id: i32) -> Self {
+        Self {
+            change: ContactChange::Updated,
+            id: Some(id),
+        }
+    }
+
+    pub fn deleted(id: i32) -> Self {
+        Self {
+            change: ContactChange::Deleted,
+            id: Some(id),
+        }
+    }
+}
+
+/// Per-tenant broadcast channels of contact changes.
+#[derive(Default)]
+pub struct ContactEvents {
+    channels: Mutex<HashMap<String, broadcast::Sender<ContactEvent>>>,
+}
+
+impl ContactEvents {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receives the changes of `tenant_id` published from now on.
+    pub fn subscribe(&self, tenant_id: &str) -> broadcast::Receiver<ContactEvent> {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        channels
+            .entry(tenant_id.to_string())
+            .or_insert_with(|| broadcast::channel(CONTACT_EVENT_CAPACITY).0)
+            .subscribe()
+    }
+
+    /// Sends `event` to the subscribers of `tenant_id` and returns how many there were.
+    ///
+    /// A channel whose subscribers have all gone is dropped.
+    pub fn publish(&self, tenant_id: &str, event: ContactEvent) -> usize {
+        let mut channels = self.channels.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(sender) = channels.get(tenant_id) else {
+            return 0;
+        };
+        match sender.send(event) {
+            Ok(receivers) => receivers,
+            Err(_) => {
+                channels.remove(tenant_id);
+                0
+            }
+        }
+    }
+}
+
+/// Process-wide channels used by the address book controller.
+pub fn contact_events() -> &'static ContactEvents {
+    static EVENTS: OnceLock<ContactEvents> = OnceLock::new();
+    EVENTS.get_or_init(ContactEvents::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::broadcast::error::RecvError;
+
+    use super::*;
+
+    #[actix_web::test]
+    async fn test_events_reach_only_their_tenant() {
+        let events = ContactEvents::new();
+        let mut acme = events.subscribe("acme");
+        let mut globex = events.subscribe("globex");
+
+        assert_eq!(events.publish("acme", ContactEvent::updated(7)), 1);
+        assert_eq!(acme.recv().await.unwrap(), ContactEvent::updated(7));
+        assert!(globex.try_recv().is_err());
+
+        drop(globex);
+        assert_eq!(events.publish("globex", ContactEvent::created()), 0);
+        assert_eq!(events.publish("initech", ContactEvent::created()), 0);
+    }
+
+    #[actix_web::test]
+    async fn test_slow_subscriber_lags() {
+        let events = ContactEvents::new();
+        let mut slow = events.subscribe("acme");
+
+        for id in 0..=CONTACT_EVENT_CAPACITY as i32 {
+            events.publish("acme", ContactEvent::deleted(id));
+        }
+        assert!(matches!(slow.recv().await, Err(RecvError::Lagged(1))));
+    }
+}
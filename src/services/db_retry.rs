@@ -0,0 +1,254 @@
+//! Retry of transient database failures.
+//!
+//! Postgres aborts a transaction with SQLSTATE `40001` (serialization failure)
+//! or `40P01` (deadlock detected) when it loses a race with a concurrent one,
+//! and a connection can drop mid-query; running the operation again usually
+//! succeeds. [`with_retry`] re-runs such operations with jittered exponential
+//! backoff instead of surfacing the error to the user.
+
+use std::thread;
+use std::time::Duration;
+
+use diesel::result::{DatabaseErrorKind, Error as DieselError, QueryResult};
+use rand::Rng;
+
+/// Message Postgres reports for SQLSTATE 40P01, which diesel has no error kind for
+const DEADLOCK_MESSAGE: &str = "deadlock detected";
+
+/// How often and how patiently transient failures are retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt (`DB_RETRY_MAX_RETRIES`); 0 disables retrying
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further one (`DB_RETRY_BASE_DELAY_MS`)
+    pub base_delay: Duration,
+    /// Upper bound of a single delay
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Reads the policy from the environment; unset or unparsable values keep the defaults.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|val| val.trim().parse::<u64>().ok())
+        };
+
+        Self {
+            max_retries: var("DB_RETRY_MAX_RETRIES")
+                .and_then(|val| u32::try_from(val).ok())
+                .unwrap_or(defaults.max_retries),
+            base_delay: var("DB_RETRY_BASE_DELAY_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.base_delay),
+            ..defaults
+        }
+    }
+
+    /// Upper bound of the delay before retry number `retry` (starting at 1).
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay))
+    }
+
+    /// Delay actually slept before retry number `retry`: a random point in the
+    /// upper half of [`RetryPolicy::delay_for`], so that operations which failed
+    /// together do not all retry at the same instant.
+    pub fn jittered_delay_for(&self, retry: u32) -> Duration {
+        let delay = self.delay_for(retry);
+        let half = delay / 2;
+        half + (delay - half).mul_f64(rand::thread_rng().gen::<f64>())
+    }
+}
+
+/// Whether `err` is a serialization failure (40001), a deadlock (40P01) or a
+/// dropped connection.
+///
+/// Constraint violations and missing rows are never retried: running the
+/// operation again cannot change their outcome.
+pub fn is_retryable(err: &DieselError) -> bool {
+    match err {
+        DieselError::DatabaseError(DatabaseErrorKind::SerializationFailure, _)
+        | DieselError::DatabaseError(DatabaseErrorKind::ClosedConnection, _) => true,
+        DieselError::DatabaseError(_, info) => info.message().starts_with(DEADLOCK_MESSAGE),
+        _ => false,
+    }
+}
+
+/// Runs `operation`, re-running it while it fails with a retryable error.
+///
+/// `operation` must be a complete transaction: each attempt starts from scratch
+/// after the previous one was rolled back. Non-retryable errors are returned
+/// immediately; a retryable one is returned once `policy.max_retries` is spent.
+/// Blocks the thread while backing off, so call it from `web::block`.
+///
+/// # Examples
+///
+/// ```no_run
+/// let inserted = with_retry(&RetryPolicy::from_env(), || {
+///     conn.transaction(|conn| insert_rows(conn))
+/// })?;
+/// ```
+pub fn with_retry<T, F>(policy: &RetryPolicy, mut operation: F) -> QueryResult<T>
+where
+    F: FnMut() -> QueryResult<T>,
+{
+    let mut retry = 0;
+    loop {
+        match operation() {
+            Err(err) if retry < policy.max_retries && is_retryable(&err) => {
+                retry += 1;
+                let delay = policy.jittered_delay_for(retry);
+                log::warn!(
+                    "Transient database error, retry {}/{} in {:?}: {}",
+                    retry,
+                    policy.max_retries,
+                    delay,
+                    err
+                );
+                thread::sleep(delay);
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn no_delay(max_retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_retries,
+            base_delay: Duration::ZERO,
+            max_delay: Duration::ZERO,
+        }
+    }
+
+    fn database_error(kind: DatabaseErrorKind, message: &str) -> DieselError {
+        DieselError::DatabaseError(kind, Box::new(message.to_string()))
+    }
+
+    #[test]
+    fn test_serialization_failure_succeeds_on_retry() {
+        let mut attempts = 0;
+        let result = with_retry(&no_delay(3), || {
+            attempts += 1;
+            if attempts < 3 {
+                Err(database_error(
+                    DatabaseErrorKind::SerializationFailure,
+                    "could not serialize access due to concurrent update",
+                ))
+            } else {
+                Ok(attempts)
+            }
+        });
+
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn test_deadlock_is_retried_until_retries_are_spent() {
+        let mut attempts = 0;
+        let result: QueryResult<()> = with_retry(&no_delay(2), || {
+            attempts += 1;
+            Err(database_error(
+                DatabaseErrorKind::Unknown,
+                "deadlock detected",
+            ))
+        });
+
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_non_retryable_error_fails_immediately() {
+        let mut attempts = 0;
+        let result: QueryResult<()> = with_retry(&no_delay(3), || {
+            attempts += 1;
+            Err(database_error(
+                DatabaseErrorKind::UniqueViolation,
+                "duplicate key value violates unique constraint",
+            ))
+        });
+
+        assert!(matches!(
+            result,
+            Err(DieselError::DatabaseError(
+                DatabaseErrorKind::UniqueViolation,
+                _
+            ))
+        ));
+        assert_eq!(attempts, 1);
+        assert!(!is_retryable(&DieselError::NotFound));
+    }
+
+    #[test]
+    fn test_closed_connection_fails_twice_then_succeeds() {
+        let mut attempts = 0;
+        let result = with_retry(&no_delay(3), || {
+            attempts += 1;
+            if attempts <= 2 {
+                Err(database_error(
+                    DatabaseErrorKind::ClosedConnection,
+                    "server closed the connection unexpectedly",
+                ))
+            } else {
+                Ok("inserted")
+            }
+        });
+
+        assert_eq!(result.unwrap(), "inserted");
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_jittered_delay_stays_in_upper_half() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(40),
+            max_delay: Duration::from_secs(1),
+        };
+
+        for retry in 1..=5 {
+            let bound = policy.delay_for(retry);
+            for _ in 0..20 {
+                let delay = policy.jittered_delay_for(retry);
+                assert!(delay >= bound / 2 && delay <= bound, "{:?}", delay);
+            }
+        }
+        assert_eq!(no_delay(1).jittered_delay_for(1), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max_delay() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            base_delay: Duration::from_millis(50),
+            max_delay: Duration::from_millis(300),
+        };
+
+        assert_eq!(policy.delay_for(1), Duration::from_millis(50));
+        assert_eq!(policy.delay_for(2), Duration::from_millis(100));
+        assert_eq!(policy.delay_for(3), Duration::from_millis(200));
+        assert_eq!(policy.delay_for(4), Duration::from_millis(300));
+        assert_eq!(policy.delay_for(40), Duration::from_millis(300));
+    }
+}
@@ -71,9 +71,7 @@ impl<T> QueryReader<T> {
 /// Execute a QueryReader with a database pool
 pub fn run_query<T>(reader: QueryReader<T>, pool: &Pool) -> ServiceResult<T> {
     pool.get()
-        .map_err(|e| {
-            ServiceError::internal_server_error(format!("Failed to get database connection: {}", e))
-        })
+        .map_err(|e| ServiceError::from_pool_error(e, pool))
         .and_then(|mut conn| reader.run(&mut conn))
 }
 
@@ -7,8 +7,8 @@
 use crate::{
     config::db::Pool,
     error::{
-        error_logging, error_pipeline, monadic, ErrorTransformer, ServiceError, ServiceResult,
-        ServiceResultExt,
+        error_logging, error_pipeline, monadic, ErrorTag, ErrorTransformer, ServiceError,
+        ServiceResult, ServiceResultExt,
     },
 };
 use diesel::PgConnection;
@@ -38,11 +38,17 @@ pub struct ServicePipeline<T> {
 struct PipelineErrorAdapter {
     context: &'static str,
     level: Level,
+    /// Tag given to errors that were not categorised by the failing step
+    default_tag: ErrorTag,
 }
 
 impl PipelineErrorAdapter {
-    fn new(context: &'static str, level: Level) -> Self {
-        Self { context, level }
+    fn new(context: &'static str, level: Level, default_tag: ErrorTag) -> Self {
+        Self {
+            context,
+            level,
+            default_tag,
+        }
     }
 }
 
@@ -50,7 +56,13 @@ impl<T> ErrorTransformer<T, ServiceError> for PipelineErrorAdapter {
     fn transform(&self, result: ServiceResult<T>) -> ServiceResult<T> {
         result
             .tap_error(|err| log::debug!("{} error: {}", self.context, err))
-            .map_service_error(|err| err.with_tag(self.context))
+            .map_service_error(|err| {
+                if err.context().tags.is_empty() {
+                    err.with_tag(self.default_tag)
+                } else {
+                    err
+                }
+            })
             .log_on_error(self.level)
     }
 }
@@ -146,14 +158,13 @@ where
 
         let mut connection_logger = error_logging::chain_log_and_transform(
             error_logging::log_errors::<_, ServiceError>(Level::Error),
-            |result: ServiceResult<_>| result.map_service_error(|err| err.with_tag("pool")),
+            |result: ServiceResult<_>| result.map_service_error(|err| err.with_tag(ErrorTag::Db)),
         );
 
-        let mut conn = connection_logger(pool.get().map_err(|e| {
-            ServiceError::internal_server_error("Failed to get database connection")
-                .with_tag("db")
-                .with_detail(e.to_string())
-        }))?;
+        let mut conn = connection_logger(
+            pool.get()
+                .map_err(|e| ServiceError::from_pool_error(e, &pool)),
+        )?;
 
         let mut result_logger = error_logging::compose_transformers(
             error_logging::log_errors::<R, ServiceError>(Level::Error),
@@ -165,7 +176,8 @@ where
 
         let operation_result = result_logger(operation(transformed_data, &mut conn));
 
-        let error_adapter = PipelineErrorAdapter::new("service_pipeline", Level::Error);
+        let error_adapter =
+            PipelineErrorAdapter::new("service_pipeline", Level::Error, ErrorTag::Db);
         error_adapter.transform(operation_result)
     }
 }
@@ -188,14 +200,14 @@ impl FunctionalQueryService {
     {
         let mut connection_logger = error_logging::chain_log_and_transform(
             error_logging::log_errors::<_, ServiceError>(Level::Error),
-            |result: ServiceResult<_>| result.map_service_error(|err| err.with_tag("db")),
+            |result: ServiceResult<_>| result.map_service_error(|err| err.with_tag(ErrorTag::Db)),
         );
 
-        let mut conn = connection_logger(self.pool.get().map_err(|e| {
-            ServiceError::internal_server_error("Failed to get database connection")
-                .with_tag("db")
-                .with_detail(e.to_string())
-        }))?;
+        let mut conn = connection_logger(
+            self.pool
+                .get()
+                .map_err(|e| ServiceError::from_pool_error(e, &self.pool)),
+        )?;
 
         let mut result_logger = error_logging::compose_transformers(
             error_logging::log_errors::<R, ServiceError>(Level::Info),
@@ -207,7 +219,8 @@ impl FunctionalQueryService {
 
         let result = result_logger(query_builder(&mut conn));
 
-        let error_adapter = PipelineErrorAdapter::new("functional_query", Level::Warn);
+        let error_adapter =
+            PipelineErrorAdapter::new("functional_query", Level::Warn, ErrorTag::Db);
         error_adapter.transform(result)
     }
 
@@ -233,7 +246,9 @@ impl FunctionalQueryService {
             .execute(query_fn);
 
         error_pipeline::Pipeline::new()
-            .step(|result: ServiceResult<R>| result.map_service_error(|err| err.with_tag("query")))
+            .step(|result: ServiceResult<R>| {
+                result.map_service_error(|err| err.with_tag(ErrorTag::Db))
+            })
             .step(|result| result.log_on_error(Level::Warn))
             .execute(base_result)
     }
@@ -248,11 +263,11 @@ impl FunctionalQueryService {
             |result: ServiceResult<_>| result,
         );
 
-        let mut conn = connection_logger(self.pool.get().map_err(|e| {
-            ServiceError::internal_server_error("Failed to get database connection")
-                .with_tag("db")
-                .with_detail(e.to_string())
-        }))?;
+        let mut conn = connection_logger(
+            self.pool
+                .get()
+                .map_err(|e| ServiceError::from_pool_error(e, &self.pool)),
+        )?;
 
         let result = monadic::flatten_option(
             query_builder(&mut conn),
@@ -260,7 +275,8 @@ impl FunctionalQueryService {
             || ServiceError::not_found("Record not found"),
         );
 
-        let error_adapter = PipelineErrorAdapter::new("functional_query", Level::Warn);
+        let error_adapter =
+            PipelineErrorAdapter::new("functional_query", Level::Warn, ErrorTag::Db);
         error_adapter.transform(result)
     }
 }
@@ -1,4 +1,10 @@
 pub mod account_service;
 pub mod address_book_service;
+pub mod contact_events;
+pub mod db_retry;
 pub mod functional_patterns;
 pub mod functional_service_base;
+pub mod nfe_service;
+pub mod nfe_xml;
+pub mod tenant_service;
+pub mod transaction;
@@ -0,0 +1,617 @@
+//! NFe XML generation
+//!
+//! Renders an `NfeDocumentPayload` as NFe 4.00 XML. SEFAZ validates the
+//! schema positionally, and the XML-DSig signature is computed over the exact
+//! bytes, so every element is written by hand in the canonical layout order.
+//! No maps or serde-driven serialization are involved: the sequence of each
+//! element's children is fixed in the code below, which makes the output a
+//! pure function of the document.
+//!
+//! Signing is delegated to an [`NfeSigner`]: the generator hands it the
+//! unsigned XML and embeds the returned `<Signature>` element as the last
+//! child of `<NFe>`, right after `</infNFe>`, as the enveloped XML-DSig
+//! layout requires. The server's signer is chosen by [`signer_from_env`].
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use rust_decimal::Decimal;
+
+use crate::models::nfe_document::{
+    NfeDocumentPayload, NfeItemPayload, NfePartyPayload, NfeTaxPayload,
+};
+
+/// Errors raised while producing or signing NFe XML
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum NfeError {
+    #[error("Signing failed: {0}")]
+    SigningFailed(String),
+
+    #[error("Signer returned an invalid Signature element: {0}")]
+    InvalidSignature(String),
+}
+
+/// Produces the XML-DSig `<Signature>` element for an NFe.
+///
+/// Implementations receive the complete unsigned `<NFe>` document and return
+/// only the `<Signature>` element (or an empty string to leave the document
+/// unsigned); placing it is the generator's job.
+pub trait NfeSigner {
+    fn sign(&self, xml: &str) -> Result<String, NfeError>;
+}
+
+/// Signer that leaves documents unsigned, for development and tests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopSigner;
+
+impl NfeSigner for NoopSigner {
+    fn sign(&self, _xml: &str) -> Result<String, NfeError> {
+        Ok(String::new())
+    }
+}
+
+/// Signer that pipes the unsigned XML to an external command.
+///
+/// The command (typically a wrapper around `xmlsec1` or an HSM client that
+/// holds the A1/A3 certificate) reads the `<NFe>` document on stdin and
+/// writes the `<Signature>` element on stdout. A non-zero exit status is a
+/// signing failure.
+#[derive(Debug, Clone)]
+pub struct CommandSigner {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandSigner {
+    /// Parses a whitespace-separated command line; `None` when it is blank.
+    pub fn new(command_line: &str) -> Option<Self> {
+        let mut parts = command_line.split_whitespace().map(str::to_string);
+        let program = parts.next()?;
+        Some(Self {
+            program,
+            args: parts.collect(),
+        })
+    }
+}
+
+impl NfeSigner for CommandSigner {
+    fn sign(&self, xml: &str) -> Result<String, NfeError> {
+        let failed =
+            |e: std::io::Error| NfeError::SigningFailed(format!("{}: {}", self.program, e));
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(failed)?;
+
+        // Written from another thread so a signer that streams its output
+        // before draining stdin cannot deadlock against us.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = xml.to_string();
+        let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+        let output = child.wait_with_output().map_err(failed)?;
+        writer
+            .join()
+            .map_err(|_| {
+                NfeError::SigningFailed(format!("{}: stdin writer panicked", self.program))
+            })?
+            .map_err(failed)?;
+
+        if !output.status.success() {
+            return Err(NfeError::SigningFailed(format!(
+                "{} exited with {}: {}",
+                self.program,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|e| NfeError::InvalidSignature(format!("output is not UTF-8: {}", e)))
+    }
+}
+
+/// Signer shared by every HTTP worker
+pub type SharedNfeSigner = Arc<dyn NfeSigner + Send + Sync>;
+
+/// Builds the signer configured by `NFE_SIGNER_COMMAND`.
+///
+/// When the variable is unset or blank, exported documents are left unsigned
+/// ([`NoopSigner`]).
+pub fn signer_from_env() -> SharedNfeSigner {
+    match std::env::var("NFE_SIGNER_COMMAND")
+        .ok()
+        .as_deref()
+        .and_then(CommandSigner::new)
+    {
+        Some(signer) => {
+            log::info!("Signing exported NFe XML with {}", signer.program);
+            Arc::new(signer)
+        }
+        None => Arc::new(NoopSigner),
+    }
+}
+
+/// XML namespace of the NFe layout
+pub const NFE_NAMESPACE: &str = "http://www.portalfiscal.inf.br/nfe";
+
+/// Layout version written to `infNFe/@versao`
+pub const NFE_VERSION: &str = "4.00";
+
+/// Minimal append-only XML writer.
+///
+/// Children are written in call order, so element order is exactly the order
+/// of the calls that produce them.
+struct XmlWriter {
+    buf: String,
+}
+
+impl XmlWriter {
+    fn new() -> Self {
+        Self { buf: String::new() }
+    }
+
+    fn open(&mut self, tag: &str, attrs: &[(&str, &str)]) {
+        self.buf.push('<');
+        self.buf.push_str(tag);
+        for (name, value) in attrs {
+            self.buf.push(' ');
+            self.buf.push_str(name);
+            self.buf.push_str("=\"");
+            escape_into(&mut self.buf, value);
+            self.buf.push('"');
+        }
+        self.buf.push('>');
+    }
+
+    fn close(&mut self, tag: &str) {
+        self.buf.push_str("</");
+        self.buf.push_str(tag);
+        self.buf.push('>');
+    }
+
+    fn leaf(&mut self, tag: &str, value: &str) {
+        self.open(tag, &[]);
+        escape_into(&mut self.buf, value);
+        self.close(tag);
+    }
+
+    fn opt_leaf(&mut self, tag: &str, value: Option<&str>) {
+        if let Some(value) = value {
+            self.leaf(tag, value);
+        }
+    }
+
+    /// Appends pre-rendered markup verbatim.
+    fn raw(&mut self, markup: &str) {
+        self.buf.push_str(markup);
+    }
+
+    fn money(&mut self, tag: &str, value: Decimal) {
+        self.leaf(tag, &format_decimal(value, 2));
+    }
+
+    fn into_string(self) -> String {
+        self.buf
+    }
+}
+
+fn escape_into(buf: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '&' => buf.push_str("&amp;"),
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '"' => buf.push_str("&quot;"),
+            '\'' => buf.push_str("&apos;"),
+            c => buf.push(c),
+        }
+    }
+}
+
+/// Formats a decimal with a fixed number of places, as required by the layout.
+fn format_decimal(value: Decimal, places: u32) -> String {
+    format!("{:.*}", places as usize, value.round_dp(places))
+}
+
+fn write_ide(w: &mut XmlWriter, doc: &NfeDocumentPayload) {
+    let key = doc.access_key.as_str();
+    let slice = |from: usize, to: usize| key.get(from..to).unwrap_or_default();
+
+    w.open("ide", &[]);
+    w.leaf("cUF", slice(0, 2));
+    w.leaf("cNF", slice(35, 43));
+    w.leaf("mod", doc.modelo.as_deref().unwrap_or("55"));
+    w.leaf("serie", &doc.serie);
+    w.leaf("nNF", &doc.numero);
+    if let Some(emission) = doc.data_emissao {
+        w.leaf("dhEmi", &emission.format("%Y-%m-%dT%H:%M:%S").to_string());
+    }
+    w.leaf("cDV", slice(43, 44));
+    w.close("ide");
+}
+
+fn write_party(w: &mut XmlWriter, tag: &str, party: &NfePartyPayload) {
+    w.open(tag, &[]);
+    w.opt_leaf("CNPJ", party.cnpj.as_deref());
+    w.opt_leaf("CPF", party.cpf.as_deref());
+    w.leaf("xNome", &party.razao_social);
+    w.close(tag);
+}
+
+fn write_tax(w: &mut XmlWriter, tag: &str, rate_tag: &str, value_tag: &str, tax: &NfeTaxPayload) {
+    w.open(tag, &[]);
+    w.leaf("CST", &tax.cst);
+    if let Some(bc) = tax.valor_bc {
+        w.money("vBC", bc);
+    }
+    if let Some(rate) = tax.aliquota {
+        w.leaf(rate_tag, &format_decimal(rate, 4));
+    }
+    if let Some(value) = tax.valor {
+        w.money(value_tag, value);
+    }
+    w.close(tag);
+}
+
+fn write_item(w: &mut XmlWriter, n_item: usize, item: &NfeItemPayload) {
+    w.open("det", &[("nItem", &n_item.to_string())]);
+
+    w.open("prod", &[]);
+    w.leaf("cProd", &item.codigo);
+    w.leaf("cEAN", item.ean.as_deref().unwrap_or("SEM GTIN"));
+    w.leaf("xProd", &item.descricao);
+    w.opt_leaf("NCM", item.ncm.as_deref());
+    w.leaf("CFOP", &item.cfop);
+    w.leaf("uCom", &item.unidade);
+    w.leaf("qCom", &format_decimal(item.quantidade, 4));
+    w.leaf("vUnCom", &format_decimal(item.valor_unitario, 10));
+    w.money("vProd", item.valor_total);
+    if let Some(discount) = item.valor_desconto {
+        w.money("vDesc", discount);
+    }
+    w.close("prod");
+
+    w.open("imposto", &[]);
+    let groups = [
+        (&item.icms, "ICMS", "pICMS", "vICMS"),
+        (&item.ipi, "IPI", "pIPI", "vIPI"),
+        (&item.pis, "PIS", "pPIS", "vPIS"),
+        (&item.cofins, "COFINS", "pCOFINS", "vCOFINS"),
+    ];
+    for (tax, tag, rate_tag, value_tag) in groups {
+        if let Some(tax) = tax {
+            write_tax(w, tag, rate_tag, value_tag, tax);
+        }
+    }
+    w.close("imposto");
+
+    w.close("det");
+}
+
+fn write_total(w: &mut XmlWriter, doc: &NfeDocumentPayload) {
+    let sum = |select: fn(&NfeItemPayload) -> Option<&NfeTaxPayload>,
+               field: fn(&NfeTaxPayload) -> Option<Decimal>| {
+        doc.items
+            .iter()
+            .filter_map(|item| select(item).and_then(field))
+            .sum::<Decimal>()
+    };
+
+    w.open("total", &[]);
+    w.open("ICMSTot", &[]);
+    w.money("vBC", sum(|i| i.icms.as_ref(), |t| t.valor_bc));
+    w.money("vICMS", sum(|i| i.icms.as_ref(), |t| t.valor));
+    w.money("vProd", doc.valor_produtos);
+    w.money("vFrete", doc.valor_frete.unwrap_or_default());
+    w.money("vSeg", doc.valor_seguro.unwrap_or_default());
+    w.money("vDesc", doc.valor_desconto.unwrap_or_default());
+    w.money("vIPI", sum(|i| i.ipi.as_ref(), |t| t.valor));
+    w.money("vPIS", sum(|i| i.pis.as_ref(), |t| t.valor));
+    w.money("vCOFINS", sum(|i| i.cofins.as_ref(), |t| t.valor));
+    w.money("vOutro", doc.valor_outras_despesas.unwrap_or_default());
+    w.money("vNF", doc.valor_total);
+    w.close("ICMSTot");
+    w.close("total");
+}
+
+/// Renders the document as NFe XML in canonical SEFAZ element order.
+///
+/// The output is deterministic: the same document always produces the same
+/// bytes, which keeps signatures computed over it stable.
+///
+/// # Examples
+///
+/// ```
+/// let xml = generate_xml(&payload);
+/// assert!(xml.starts_with("<NFe xmlns=\"http://www.portalfiscal.inf.br/nfe\">"));
+/// ```
+pub fn generate_xml(doc: &NfeDocumentPayload) -> String {
+    let mut w = XmlWriter::new();
+    write_nfe_body(&mut w, doc);
+    w.close("NFe");
+    w.into_string()
+}
+
+/// Renders the document and embeds the signature produced by `signer`.
+///
+/// The signer sees exactly the bytes `generate_xml` returns; its `<Signature>`
+/// element is inserted after `</infNFe>`, before `</NFe>`. An empty signature
+/// yields the unsigned document.
+///
+/// # Returns
+/// The signed XML, or the signer's `NfeError`. A non-empty signer output
+/// that is not a `<Signature>` element is rejected with `InvalidSignature`.
+pub fn generate_signed_xml(
+    doc: &NfeDocumentPayload,
+    signer: &dyn NfeSigner,
+) -> Result<String, NfeError> {
+    let unsigned = generate_xml(doc);
+    let signature = signer.sign(&unsigned)?;
+
+    if signature.is_empty() {
+        return Ok(unsigned);
+    }
+    let trimmed = signature.trim();
+    if !(trimmed.starts_with("<Signature") && trimmed.ends_with("</Signature>")) {
+        return Err(NfeError::InvalidSignature(
+            "expected a single <Signature> element".to_string(),
+        ));
+    }
+
+    let mut w = XmlWriter::new();
+    write_nfe_body(&mut w, doc);
+    w.raw(trimmed);
+    w.close("NFe");
+    Ok(w.into_string())
+}
+
+/// Writes `<NFe>` and its complete `<infNFe>` child, leaving `<NFe>` open.
+fn write_nfe_body(w: &mut XmlWriter, doc: &NfeDocumentPayload) {
+    let id = format!("NFe{}", doc.access_key);
+
+    w.open("NFe", &[("xmlns", NFE_NAMESPACE)]);
+    w.open("infNFe", &[("versao", NFE_VERSION), ("Id", &id)]);
+
+    write_ide(w, doc);
+    write_party(w, "emit", &doc.emitter);
+    if let Some(recipient) = &doc.recipient {
+        write_party(w, "dest", recipient);
+    }
+    for (index, item) in doc.items.iter().enumerate() {
+        write_item(w, index + 1, item);
+    }
+    write_total(w, doc);
+    if let Some(info) = &doc.informacoes_adicionais {
+        w.open("infAdic", &[]);
+        w.leaf("infCpl", info);
+        w.close("infAdic");
+    }
+
+    w.close("infNFe");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// Records the XML it was asked to sign and returns a fixed signature.
+    struct RecordingSigner {
+        seen: RefCell<Vec<String>>,
+    }
+
+    impl NfeSigner for RecordingSigner {
+        fn sign(&self, xml: &str) -> Result<String, NfeError> {
+            self.seen.borrow_mut().push(xml.to_string());
+            Ok("<Signature xmlns=\"http://www.w3.org/2000/09/xmldsig#\"><SignatureValue>abc</SignatureValue></Signature>".to_string())
+        }
+    }
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn tax(cst: &str) -> Option<NfeTaxPayload> {
+        Some(NfeTaxPayload {
+            cst: cst.to_string(),
+            valor_bc: Some(dec("100")),
+            aliquota: Some(dec("1.65")),
+            valor: Some(dec("1.65")),
+        })
+    }
+
+    fn sample_document() -> NfeDocumentPayload {
+        NfeDocumentPayload {
+            access_key: "35240101234567000195550010000000011000000019".to_string(),
+            serie: "1".to_string(),
+            numero: "1".to_string(),
+            data_emissao: chrono::NaiveDate::from_ymd_opt(2024, 1, 15)
+                .and_then(|d| d.and_hms_opt(10, 30, 0)),
+            emitter: NfePartyPayload {
+                cnpj: Some("11222333000181".to_string()),
+                cpf: None,
+                razao_social: "Emitente & Filhos LTDA".to_string(),
+            },
+            recipient: Some(NfePartyPayload {
+                cnpj: None,
+                cpf: Some("52998224725".to_string()),
+                razao_social: "Destinatario".to_string(),
+            }),
+            items: vec![NfeItemPayload {
+                codigo: "P001".to_string(),
+                descricao: "Produto <teste>".to_string(),
+                cfop: "5102".to_string(),
+                unidade: "UN".to_string(),
+                quantidade: dec("2"),
+                valor_unitario: dec("50"),
+                valor_total: dec("100"),
+                icms: tax("00"),
+                ipi: tax("50"),
+                pis: tax("01"),
+                cofins: tax("01"),
+                ..Default::default()
+            }],
+            valor_produtos: dec("100"),
+            valor_total: dec("101.65"),
+            valor_impostos: dec("6.60"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_generation_is_byte_for_byte_stable() {
+        let doc = sample_document();
+        let first = generate_xml(&doc);
+
+        for _ in 0..10 {
+            assert_eq!(generate_xml(&doc).as_bytes(), first.as_bytes());
+            assert_eq!(generate_xml(&doc.clone()).as_bytes(), first.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_elements_follow_canonical_order() {
+        let xml = generate_xml(&sample_document());
+        let position = |tag: &str| {
+            xml.find(tag)
+                .unwrap_or_else(|| panic!("{} missing from {}", tag, xml))
+        };
+
+        let sequence = [
+            "<ide>",
+            "<cUF>",
+            "<cNF>",
+            "<mod>",
+            "<serie>",
+            "<nNF>",
+            "<dhEmi>",
+            "<cDV>",
+            "<emit>",
+            "<dest>",
+            "<det nItem=\"1\">",
+            "<prod>",
+            "<cProd>",
+            "<cEAN>",
+            "<xProd>",
+            "<CFOP>",
+            "<uCom>",
+            "<qCom>",
+            "<vUnCom>",
+            "<imposto>",
+            "<ICMS>",
+            "<IPI>",
+            "<PIS>",
+            "<COFINS>",
+            "<total>",
+            "<ICMSTot>",
+            "<vNF>",
+        ];
+        let positions: Vec<_> = sequence.iter().map(|tag| position(tag)).collect();
+
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_text_is_escaped() {
+        let xml = generate_xml(&sample_document());
+
+        assert!(xml.contains("<xNome>Emitente &amp; Filhos LTDA</xNome>"));
+        assert!(xml.contains("<xProd>Produto &lt;teste&gt;</xProd>"));
+    }
+
+    #[test]
+    fn test_signer_output_is_embedded_after_inf_nfe() {
+        let doc = sample_document();
+        let signer = RecordingSigner {
+            seen: RefCell::new(Vec::new()),
+        };
+
+        let signed = generate_signed_xml(&doc, &signer).unwrap();
+
+        assert_eq!(*signer.seen.borrow(), vec![generate_xml(&doc)]);
+        assert!(signed.ends_with(
+            "</infNFe><Signature xmlns=\"http://www.w3.org/2000/09/xmldsig#\"><SignatureValue>abc</SignatureValue></Signature></NFe>"
+        ));
+        assert_eq!(signed.matches("<Signature ").count(), 1);
+    }
+
+    #[test]
+    fn test_noop_signer_leaves_document_unsigned() {
+        let doc = sample_document();
+
+        assert_eq!(
+            generate_signed_xml(&doc, &NoopSigner).unwrap(),
+            generate_xml(&doc)
+        );
+    }
+
+    #[test]
+    fn test_command_signer_embeds_the_command_output() {
+        // Built by hand: the script contains spaces, which `new` would split.
+        let signer = CommandSigner {
+            program: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "cat >/dev/null; printf '<Signature><SignatureValue>xyz</SignatureValue></Signature>'"
+                    .to_string(),
+            ],
+        };
+        let doc = sample_document();
+
+        let signed = generate_signed_xml(&doc, &signer).unwrap();
+
+        assert!(signed.ends_with(
+            "</infNFe><Signature><SignatureValue>xyz</SignatureValue></Signature></NFe>"
+        ));
+    }
+
+    #[test]
+    fn test_command_signer_failures_are_signing_errors() {
+        let failing = CommandSigner::new("false").unwrap();
+        let missing = CommandSigner::new("/nonexistent/nfe-signer").unwrap();
+        let doc = sample_document();
+
+        assert!(matches!(
+            generate_signed_xml(&doc, &failing),
+            Err(NfeError::SigningFailed(_))
+        ));
+        assert!(matches!(
+            generate_signed_xml(&doc, &missing),
+            Err(NfeError::SigningFailed(_))
+        ));
+        assert!(CommandSigner::new("   ").is_none());
+    }
+
+    #[test]
+    fn test_signer_errors_and_invalid_output_are_reported() {
+        struct Failing;
+        impl NfeSigner for Failing {
+            fn sign(&self, _xml: &str) -> Result<String, NfeError> {
+                Err(NfeError::SigningFailed("certificate expired".to_string()))
+            }
+        }
+        struct Garbage;
+        impl NfeSigner for Garbage {
+            fn sign(&self, _xml: &str) -> Result<String, NfeError> {
+                Ok("<Foo/>".to_string())
+            }
+        }
+
+        let doc = sample_document();
+
+        assert_eq!(
+            generate_signed_xml(&doc, &Failing),
+            Err(NfeError::SigningFailed("certificate expired".to_string()))
+        );
+        assert!(matches!(
+            generate_signed_xml(&doc, &Garbage),
+            Err(NfeError::InvalidSignature(_))
+        ));
+    }
+}
@@ -0,0 +1,189 @@
+//! Multi-statement writes in one transaction.
+//!
+//! [`with_transaction`] takes a connection from the pool, runs a closure in a
+//! transaction and rolls every write back when the closure fails. Transient
+//! failures (see [`db_retry::is_retryable`]) re-run the whole transaction, so
+//! the closure may be called more than once.
+
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::Connection as _;
+
+use crate::{
+    config::db::{Connection, Pool},
+    constants,
+    error::{ErrorTag, ServiceError},
+    services::db_retry::{self, with_retry, RetryPolicy},
+};
+
+/// Failure of a step inside [`with_transaction`]
+///
+/// Both variants roll the transaction back. Only retryable `Query` errors
+/// re-run it; everything else is returned as a `ServiceError`.
+#[derive(Debug)]
+pub enum TransactionError {
+    Query(DieselError),
+    Service(ServiceError),
+}
+
+impl TransactionError {
+    /// Keeps a retryable `err` so the transaction is retried; any other
+    /// error is reported as `fallback`, with `err` as its detail.
+    pub fn query_or(err: DieselError, fallback: impl FnOnce() -> ServiceError) -> Self {
+        if db_retry::is_retryable(&err) {
+            TransactionError::Query(err)
+        } else {
+            TransactionError::Service(fallback().with_detail(err.to_string()))
+        }
+    }
+}
+
+impl From<DieselError> for TransactionError {
+    fn from(err: DieselError) -> Self {
+        TransactionError::Query(err)
+    }
+}
+
+impl From<ServiceError> for TransactionError {
+    fn from(err: ServiceError) -> Self {
+        TransactionError::Service(err)
+    }
+}
+
+/// Maps a failed query to the `ServiceError` reported to the client.
+fn query_error(err: DieselError) -> ServiceError {
+    let error = match &err {
+        DieselError::NotFound => ServiceError::not_found("Record not found"),
+        DieselError::DatabaseError(
+            DatabaseErrorKind::UniqueViolation | DatabaseErrorKind::ForeignKeyViolation,
+            info,
+        ) => ServiceError::conflict(info.message().to_string()),
+        _ => ServiceError::internal_server_error(constants::MESSAGE_INTERNAL_SERVER_ERROR),
+    };
+    error.with_tag(ErrorTag::Db).with_detail(err.to_string())
+}
+
+/// Runs `f` in a transaction on a connection from `pool`.
+///
+/// The transaction commits when `f` returns `Ok` and rolls back otherwise.
+/// Serialization failures, deadlocks and dropped connections re-run it
+/// according to [`RetryPolicy::from_env`]. Sleeps between attempts, so
+/// handlers must reach it through [`request_trace::block_service`], never
+/// directly on a worker thread.
+///
+/// [`request_trace::block_service`]: crate::middleware::request_trace::block_service
+///
+/// # Examples
+///
+/// ```no_run
+/// let inserted = with_transaction(&pool, |conn| {
+///     Person::find_by_id(id, conn)?;
+///     Ok(Person::insert_many(tenant_id, &people, conn)?)
+/// })?;
+/// ```
+pub fn with_transaction<T, F>(pool: &Pool, f: F) -> Result<T, ServiceError>
+where
+    F: FnMut(&mut Connection) -> Result<T, TransactionError>,
+{
+    with_transaction_using(pool, &RetryPolicy::from_env(), f)
+}
+
+/// [`with_transaction`] with an explicit retry policy.
+pub fn with_transaction_using<T, F>(
+    pool: &Pool,
+    policy: &RetryPolicy,
+    mut f: F,
+) -> Result<T, ServiceError>
+where
+    F: FnMut(&mut Connection) -> Result<T, TransactionError>,
+{
+    let mut conn = pool
+        .get()
+        .map_err(|e| ServiceError::from_pool_error(e, pool).with_tag(ErrorTag::Db))?;
+    let mut failure = None;
+
+    let result = with_retry(policy, || {
+        conn.transaction(|conn| {
+            f(conn).map_err(|err| match err {
+                TransactionError::Query(err) => err,
+                TransactionError::Service(err) => {
+                    failure = Some(err);
+                    DieselError::RollbackTransaction
+                }
+            })
+        })
+    });
+
+    match (result, failure) {
+        (Ok(value), _) => Ok(value),
+        (Err(DieselError::RollbackTransaction), Some(err)) => Err(err),
+        (Err(err), _) => Err(query_error(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use diesel::prelude::*;
+    use testcontainers::clients;
+
+    use super::*;
+    use crate::models::person::{Person, PersonDTO};
+    use crate::schema::people;
+    use crate::test_support::start_postgres;
+
+    fn person(email: &str) -> PersonDTO {
+        PersonDTO {
+            name: "Rolled Back".to_string(),
+            gender: false,
+            age: 40,
+            address: "US".to_string(),
+            phone: "0123456789".to_string(),
+            email: email.to_string(),
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_failing_closure_rolls_back_all_writes() {
+        let docker = clients::Cli::default();
+        let Some((_postgres, pool)) =
+            start_postgres(&docker, "test_failing_closure_rolls_back_all_writes")
+        else {
+            return;
+        };
+        let count = |pool: &Pool| -> i64 {
+            people::table
+                .count()
+                .get_result(&mut pool.get().unwrap())
+                .unwrap()
+        };
+
+        let err = with_transaction(&pool, |conn| -> Result<(), TransactionError> {
+            Person::insert_many("tenant1", &[person("one@example.com")], conn)?;
+            Person::insert_many("tenant1", &[person("two@example.com")], conn)?;
+            Err(ServiceError::bad_request("abort").into())
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "abort");
+        assert_eq!(count(&pool), 0);
+
+        // A failed query rolls back the writes before it as well
+        let err = with_transaction(&pool, |conn| {
+            Person::insert_many("tenant1", &[person("three@example.com")], conn)?;
+            Person::find_by_id(-1, conn)?;
+            Ok(())
+        })
+        .unwrap_err();
+        assert_eq!(err.http_status(), actix_web::http::StatusCode::NOT_FOUND);
+        assert_eq!(count(&pool), 0);
+
+        let inserted = with_transaction(&pool, |conn| {
+            Ok(Person::insert_many(
+                "tenant1",
+                &[person("four@example.com")],
+                conn,
+            )?)
+        })
+        .unwrap();
+        assert_eq!(inserted, 1);
+        assert_eq!(count(&pool), 1);
+    }
+}
@@ -0,0 +1,63 @@
+//! Originating address of a request.
+//!
+//! Behind a reverse proxy the peer address is the proxy's, and the client is
+//! the leftmost entry of `X-Forwarded-For`. That header is set by whoever sends
+//! the request, so it is only read when `TRUST_PROXY=true` says a proxy in
+//! front of the service overwrites it.
+
+use actix_web::HttpRequest;
+use once_cell::sync::Lazy;
+
+/// Whether `X-Forwarded-For` is trusted, read from `TRUST_PROXY` (`true` to enable)
+pub static TRUST_PROXY: Lazy<bool> =
+    Lazy::new(|| std::env::var("TRUST_PROXY").as_deref() == Ok("true"));
+
+/// IP address of the client that sent `req`.
+///
+/// With `trust_proxy` the leftmost `X-Forwarded-For` entry wins; otherwise,
+/// or when the header is missing, the peer address is used.
+pub fn client_ip(req: &HttpRequest, trust_proxy: bool) -> Option<String> {
+    let forwarded = req
+        .headers()
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(',').next())
+        .map(str::trim)
+        .filter(|client| !client.is_empty());
+
+    match forwarded {
+        Some(client) if trust_proxy => Some(client.to_string()),
+        _ => req.peer_addr().map(|addr| addr.ip().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    fn request(forwarded_for: Option<&str>) -> HttpRequest {
+        let req = TestRequest::default().peer_addr("10.0.0.2:41000".parse().unwrap());
+        match forwarded_for {
+            Some(value) => req.insert_header(("X-Forwarded-For", value)),
+            None => req,
+        }
+        .to_http_request()
+    }
+
+    #[test]
+    fn test_trusted_proxy_uses_leftmost_forwarded_entry() {
+        let req = request(Some(" 203.0.113.7, 198.51.100.1"));
+        assert_eq!(client_ip(&req, true).as_deref(), Some("203.0.113.7"));
+
+        let req = request(None);
+        assert_eq!(client_ip(&req, true).as_deref(), Some("10.0.0.2"));
+    }
+
+    #[test]
+    fn test_untrusted_forwarded_header_is_ignored() {
+        let req = request(Some("203.0.113.7"));
+        assert_eq!(client_ip(&req, false).as_deref(), Some("10.0.0.2"));
+    }
+}
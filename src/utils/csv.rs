@@ -0,0 +1,49 @@
+//! CSV encoding for exports (RFC 4180).
+//!
+//! Records end with `\r\n`. Fields containing a comma, a double quote or a
+//! line break are quoted, with embedded quotes doubled.
+
+use std::borrow::Cow;
+
+/// `field` as it appears in a CSV record, quoted only when needed.
+pub fn escape_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Appends one record made of `fields` to `out`.
+pub fn write_record<'a>(out: &mut String, fields: impl IntoIterator<Item = &'a str>) {
+    for (i, field) in fields.into_iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str(&escape_field(field));
+    }
+    out.push_str("\r\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fields_are_quoted_only_when_needed() {
+        assert_eq!(escape_field("plain"), "plain");
+        assert_eq!(escape_field(""), "");
+        assert_eq!(escape_field("a,b"), "\"a,b\"");
+        assert_eq!(escape_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(escape_field("line\nbreak"), "\"line\nbreak\"");
+        assert_eq!(escape_field("cr\r"), "\"cr\r\"");
+    }
+
+    #[test]
+    fn test_write_record_joins_escaped_fields() {
+        let mut out = String::new();
+        write_record(&mut out, ["1", "Doe, Jane", "x"]);
+        write_record(&mut out, ["2"]);
+        assert_eq!(out, "1,\"Doe, Jane\",x\r\n2\r\n");
+    }
+}
@@ -0,0 +1,295 @@
+//! JSON Patch (RFC 6902) over `serde_json` values.
+//!
+//! Supports the six operations of the RFC: `add`, `remove`, `replace`,
+//! `move`, `copy` and `test`, with paths written as JSON Pointers (RFC 6901).
+//! A patch is applied atomically: when any operation fails the document is
+//! left unchanged.
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use thiserror::Error;
+
+/// One operation of a patch document, as sent by the client.
+///
+/// `op` stays a string so an unknown operation is reported by [`apply`]
+/// rather than failing to deserialize.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PatchOperation {
+    pub op: String,
+    pub path: String,
+    /// Present for `add`, `replace` and `test`; `null` is a value
+    #[serde(default, deserialize_with = "present")]
+    pub value: Option<Value>,
+    /// Source path of `move` and `copy`
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+/// Deserializes a key that is present in the body, so `null` becomes `Some(Value::Null)`.
+fn present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Why a patch could not be applied
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JsonPatchError {
+    #[error("unsupported operation '{0}'")]
+    UnsupportedOperation(String),
+    #[error("'{0}' is not a valid JSON pointer")]
+    InvalidPointer(String),
+    #[error("path '{0}' does not exist")]
+    PathNotFound(String),
+    #[error("'{op}' operation requires '{field}'")]
+    MissingField { op: String, field: &'static str },
+    #[error("cannot move '{0}' into one of its children")]
+    MoveIntoChild(String),
+    #[error("test failed at '{0}'")]
+    TestFailed(String),
+}
+
+/// Applies `operations` to `document` in order.
+///
+/// On error `document` is left as it was.
+pub fn apply(document: &mut Value, operations: &[PatchOperation]) -> Result<(), JsonPatchError> {
+    let mut patched = document.clone();
+    for operation in operations {
+        apply_operation(&mut patched, operation)?;
+    }
+    *document = patched;
+    Ok(())
+}
+
+fn apply_operation(document: &mut Value, operation: &PatchOperation) -> Result<(), JsonPatchError> {
+    let required = |value: Option<&Value>, field| {
+        value.cloned().ok_or_else(|| JsonPatchError::MissingField {
+            op: operation.op.clone(),
+            field,
+        })
+    };
+    let from = || {
+        operation
+            .from
+            .as_deref()
+            .ok_or_else(|| JsonPatchError::MissingField {
+                op: operation.op.clone(),
+                field: "from",
+            })
+    };
+    let path = operation.path.as_str();
+
+    match operation.op.as_str() {
+        "add" => add(document, path, required(operation.value.as_ref(), "value")?),
+        "remove" => remove(document, path).map(|_| ()),
+        "replace" => {
+            let value = required(operation.value.as_ref(), "value")?;
+            *lookup_mut(document, path)? = value;
+            Ok(())
+        }
+        "move" => {
+            let from = from()?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err(JsonPatchError::MoveIntoChild(from.to_string()));
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        "copy" => {
+            let value = lookup_mut(document, from()?)?.clone();
+            add(document, path, value)
+        }
+        "test" => {
+            let expected = required(operation.value.as_ref(), "value")?;
+            if *lookup_mut(document, path)? == expected {
+                Ok(())
+            } else {
+                Err(JsonPatchError::TestFailed(path.to_string()))
+            }
+        }
+        other => Err(JsonPatchError::UnsupportedOperation(other.to_string())),
+    }
+}
+
+/// Splits `path` into its parent pointer and its unescaped last token.
+fn split_last(path: &str) -> Result<(&str, String), JsonPatchError> {
+    validate_pointer(path)?;
+    let Some(slash) = path.rfind('/') else {
+        // The root has no parent
+        return Err(JsonPatchError::PathNotFound(path.to_string()));
+    };
+    Ok((&path[..slash], unescape(&path[slash + 1..])))
+}
+
+fn validate_pointer(path: &str) -> Result<(), JsonPatchError> {
+    let escapes_valid = path
+        .split('~')
+        .skip(1)
+        .all(|rest| rest.starts_with('0') || rest.starts_with('1'));
+    if (path.is_empty() || path.starts_with('/')) && escapes_valid {
+        Ok(())
+    } else {
+        Err(JsonPatchError::InvalidPointer(path.to_string()))
+    }
+}
+
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Array index named by `token`: digits without leading zeros.
+fn array_index(token: &str) -> Option<usize> {
+    let canonical = token == "0" || !token.starts_with('0');
+    if canonical && !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit()) {
+        token.parse().ok()
+    } else {
+        None
+    }
+}
+
+fn lookup_mut<'a>(document: &'a mut Value, path: &str) -> Result<&'a mut Value, JsonPatchError> {
+    validate_pointer(path)?;
+    let not_found = || JsonPatchError::PathNotFound(path.to_string());
+    let mut current = document;
+    for token in path.split('/').skip(1).map(unescape) {
+        current = match current {
+            Value::Object(map) => map.get_mut(&token).ok_or_else(not_found)?,
+            Value::Array(items) => array_index(&token)
+                .and_then(|index| items.get_mut(index))
+                .ok_or_else(not_found)?,
+            _ => return Err(not_found()),
+        };
+    }
+    Ok(current)
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), JsonPatchError> {
+    if path.is_empty() {
+        *document = value;
+        return Ok(());
+    }
+    let (parent, token) = split_last(path)?;
+    let not_found = || JsonPatchError::PathNotFound(path.to_string());
+    match lookup_mut(document, parent)? {
+        Value::Object(map) => {
+            map.insert(token, value);
+        }
+        Value::Array(items) if token == "-" => items.push(value),
+        Value::Array(items) => {
+            let index = array_index(&token)
+                .filter(|index| *index <= items.len())
+                .ok_or_else(not_found)?;
+            items.insert(index, value);
+        }
+        _ => return Err(not_found()),
+    }
+    Ok(())
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, JsonPatchError> {
+    let (parent, token) = split_last(path)?;
+    let not_found = || JsonPatchError::PathNotFound(path.to_string());
+    match lookup_mut(document, parent)? {
+        Value::Object(map) => map.remove(&token).ok_or_else(not_found),
+        Value::Array(items) => {
+            let index = array_index(&token)
+                .filter(|index| *index < items.len())
+                .ok_or_else(not_found)?;
+            Ok(items.remove(index))
+        }
+        _ => Err(not_found()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn patch(operations: Value) -> Vec<PatchOperation> {
+        serde_json::from_value(operations).unwrap()
+    }
+
+    #[test]
+    fn test_operations_apply_in_order() {
+        let mut doc = json!({ "name": "Ann", "tags": ["a", "b"], "a~b": { "c/d": 1 } });
+        apply(
+            &mut doc,
+            &patch(json!([
+                { "op": "replace", "path": "/name", "value": "Bea" },
+                { "op": "add", "path": "/tags/1", "value": "x" },
+                { "op": "add", "path": "/tags/-", "value": "z" },
+                { "op": "remove", "path": "/tags/0" },
+                { "op": "copy", "from": "/name", "path": "/nickname" },
+                { "op": "move", "from": "/a~0b/c~1d", "path": "/count" },
+                { "op": "test", "path": "/count", "value": 1 },
+                { "op": "add", "path": "/note", "value": null },
+            ])),
+        )
+        .unwrap();
+        assert_eq!(
+            doc,
+            json!({
+                "name": "Bea",
+                "nickname": "Bea",
+                "tags": ["x", "b", "z"],
+                "a~b": {},
+                "count": 1,
+                "note": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_failed_patch_leaves_document_unchanged() {
+        let original = json!({ "name": "Ann", "tags": ["a"] });
+        let cases = [
+            (
+                json!([{ "op": "append", "path": "/name", "value": "x" }]),
+                JsonPatchError::UnsupportedOperation("append".to_string()),
+            ),
+            (
+                json!([{ "op": "replace", "path": "/nickname", "value": "x" }]),
+                JsonPatchError::PathNotFound("/nickname".to_string()),
+            ),
+            (
+                json!([{ "op": "add", "path": "/tags/2", "value": "x" }]),
+                JsonPatchError::PathNotFound("/tags/2".to_string()),
+            ),
+            (
+                json!([{ "op": "remove", "path": "/tags/01" }]),
+                JsonPatchError::PathNotFound("/tags/01".to_string()),
+            ),
+            (
+                json!([{ "op": "replace", "path": "name", "value": "x" }]),
+                JsonPatchError::InvalidPointer("name".to_string()),
+            ),
+            (
+                json!([{ "op": "replace", "path": "/name" }]),
+                JsonPatchError::MissingField {
+                    op: "replace".to_string(),
+                    field: "value",
+                },
+            ),
+            (
+                json!([{ "op": "move", "from": "/tags", "path": "/tags/0" }]),
+                JsonPatchError::MoveIntoChild("/tags".to_string()),
+            ),
+            (
+                json!([
+                    { "op": "replace", "path": "/name", "value": "Bea" },
+                    { "op": "test", "path": "/name", "value": "Ann" },
+                ]),
+                JsonPatchError::TestFailed("/name".to_string()),
+            ),
+        ];
+
+        for (operations, expected) in cases {
+            let mut doc = original.clone();
+            assert_eq!(apply(&mut doc, &patch(operations)), Err(expected));
+            assert_eq!(doc, original);
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Per-request logging context.
+//!
+//! [`RequestLog`](crate::middleware::request_log::RequestLog) opens a
+//! [`LogContext`] for every request and the authentication middleware fills in
+//! the tenant and user once the token is verified. [`format`], installed as the
+//! `env_logger` format in `main`, appends the fields of the current context to
+//! every line logged while the request is handled:
+//!
+//! ```text
+//! [2024-01-01T00:00:00Z INFO  app] Valid token tenant_id=acme user_id=alice request_id=6f1c...
+//! ```
+//!
+//! The context follows the request future, not the thread, so lines from
+//! concurrent requests on the same worker are tagged correctly. Work moved to
+//! another thread (`web::block`, `tokio::spawn`) is logged without it.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::io::{self, Write};
+use std::rc::Rc;
+
+use log::Record;
+
+tokio::task_local! {
+    static LOG_CONTEXT: SharedLogContext;
+}
+
+/// Fields attached to the log lines of one request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogContext {
+    /// Id echoed in `X-Request-Id`; correlates every line of the request
+    pub request_id: String,
+    /// Tenant from the verified token, or from `X-Tenant-Id` before authentication
+    pub tenant_id: Option<String>,
+    /// User from the verified token
+    pub user_id: Option<String>,
+}
+
+impl LogContext {
+    pub fn new(request_id: impl Into<String>) -> Self {
+        LogContext {
+            request_id: request_id.into(),
+            ..LogContext::default()
+        }
+    }
+}
+
+impl fmt::Display for LogContext {
+    /// `key=value` pairs, omitting fields that are not known yet.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(tenant_id) = &self.tenant_id {
+            write!(f, "tenant_id={} ", tenant_id)?;
+        }
+        if let Some(user_id) = &self.user_id {
+            write!(f, "user_id={} ", user_id)?;
+        }
+        write!(f, "request_id={}", self.request_id)
+    }
+}
+
+/// Context shared by the middleware layers of one request.
+pub type SharedLogContext = Rc<RefCell<LogContext>>;
+
+/// Runs `f` with `context` as the current context.
+pub fn sync_scope<R>(context: SharedLogContext, f: impl FnOnce() -> R) -> R {
+    LOG_CONTEXT.sync_scope(context, f)
+}
+
+/// Makes `context` the current context whenever `future` is polled.
+pub fn scope<F: Future>(context: SharedLogContext, future: F) -> impl Future<Output = F::Output> {
+    LOG_CONTEXT.scope(context, future)
+}
+
+/// A copy of the current context, if any.
+pub fn current() -> Option<LogContext> {
+    LOG_CONTEXT
+        .try_with(|context| context.borrow().clone())
+        .ok()
+}
+
+/// Records the authenticated tenant and user in the current context.
+///
+/// Does nothing outside a request, e.g. in tests without `RequestLog`.
+pub fn set_identity(tenant_id: &str, user_id: &str) {
+    let _ = LOG_CONTEXT.try_with(|context| {
+        let mut context = context.borrow_mut();
+        context.tenant_id = Some(tenant_id.to_string());
+        context.user_id = Some(user_id.to_string());
+    });
+}
+
+/// `env_logger` format: the default layout followed by the current context.
+pub fn format(buf: &mut env_logger::fmt::Formatter, record: &Record) -> io::Result<()> {
+    match current() {
+        Some(context) => writeln!(
+            buf,
+            "[{} {:<5} {}] {} {}",
+            buf.timestamp(),
+            record.level(),
+            record.target(),
+            record.args(),
+            context
+        ),
+        None => writeln!(
+            buf,
+            "[{} {:<5} {}] {}",
+            buf.timestamp(),
+            record.level(),
+            record.target(),
+            record.args()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{log_line, CapturedLog};
+
+    #[actix_web::test]
+    async fn test_context_is_appended_inside_scope() {
+        let captured = CapturedLog::default();
+        let logger = captured.logger();
+
+        log_line(&logger, "outside");
+        let context = Rc::new(RefCell::new(LogContext::new("req-1")));
+        scope(context, async {
+            set_identity("tenant1", "alice");
+            log_line(&logger, "inside");
+        })
+        .await;
+
+        let output = captured.contents();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("] outside"), "{}", lines[0]);
+        assert!(
+            lines[1].ends_with("] inside tenant_id=tenant1 user_id=alice request_id=req-1"),
+            "{}",
+            lines[1]
+        );
+        assert!(current().is_none());
+    }
+}
@@ -0,0 +1,520 @@
+//! Shared log tailing for the `/logs` Server-Sent Events endpoint.
+//!
+//! A single tail task per log file reads new lines and broadcasts them as SSE
+//! frames to every connected client, so N clients cost one file reader rather
+//! than N. The number of concurrent clients is capped by
+//! `MAX_LOG_STREAM_CLIENTS`; connections beyond the cap are refused with 503.
+//! Activity is counted in [`LogStreamStats`], reported by `/health/performance`.
+
+use std::io::Error as IoError;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use actix_web::web::Bytes;
+use futures::stream::{self, Stream, StreamExt};
+use log::{debug, error, info};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, SeekFrom};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinHandle;
+
+use crate::error::{ErrorTag, ServiceError};
+
+/// Default cap on concurrent `/logs` clients
+pub const DEFAULT_MAX_LOG_STREAM_CLIENTS: usize = 16;
+
+/// Seconds a refused client is asked to wait before reconnecting
+const LOG_STREAM_RETRY_AFTER_SECS: u64 = 5;
+
+/// How often the tail task checks the log file for new data
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Polls between keep-alive frames (30 seconds with the default interval)
+const KEEP_ALIVE_POLLS: u32 = 3;
+
+/// Frames buffered per client before a slow client starts skipping lines
+const BROADCAST_CAPACITY: usize = 100;
+
+/// Reads `MAX_LOG_STREAM_CLIENTS`, falling back to the default when unset or invalid.
+fn max_clients_from_env() -> usize {
+    std::env::var("MAX_LOG_STREAM_CLIENTS")
+        .ok()
+        .and_then(|value| value.trim().parse::<usize>().ok())
+        .unwrap_or(DEFAULT_MAX_LOG_STREAM_CLIENTS)
+}
+
+/// Snapshot of the log streaming counters
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct LogStreamStats {
+    /// SSE clients currently connected
+    pub active_clients: usize,
+    /// Configured cap on concurrent clients
+    pub max_clients: usize,
+    /// Log frames delivered, counted once per client
+    pub frames_sent: u64,
+    /// Frames skipped by clients that fell behind the broadcast buffer
+    pub frames_dropped: u64,
+    /// Payload bytes delivered, counted once per client
+    pub bytes_streamed: u64,
+    /// Times the tail reopened the log file after rotation or truncation
+    pub reopens: u64,
+}
+
+#[derive(Default)]
+struct LogStreamCounters {
+    frames_sent: AtomicU64,
+    frames_dropped: AtomicU64,
+    bytes_streamed: AtomicU64,
+    reopens: AtomicU64,
+}
+
+/// The running tail task and the channel it broadcasts on.
+struct SharedTail {
+    path: String,
+    sender: broadcast::Sender<Bytes>,
+    task: JoinHandle<()>,
+}
+
+/// Hands out log stream subscriptions backed by one shared tail task.
+pub struct LogStreamHub {
+    max_clients: usize,
+    poll_interval: Duration,
+    clients: Arc<AtomicUsize>,
+    counters: Arc<LogStreamCounters>,
+    tail: Arc<Mutex<Option<SharedTail>>>,
+}
+
+/// A slot counted against the client cap, released when dropped.
+struct ClientPermit {
+    clients: Arc<AtomicUsize>,
+}
+
+impl Drop for ClientPermit {
+    fn drop(&mut self) {
+        self.clients.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// A connected client: its cap slot and its receiver on the shared tail.
+pub struct LogSubscription {
+    permit: ClientPermit,
+    receiver: broadcast::Receiver<Bytes>,
+    counters: Arc<LogStreamCounters>,
+}
+
+impl LogStreamHub {
+    pub fn new(max_clients: usize) -> Self {
+        Self::with_poll_interval(max_clients, DEFAULT_POLL_INTERVAL)
+    }
+
+    pub fn with_poll_interval(max_clients: usize, poll_interval: Duration) -> Self {
+        Self {
+            max_clients,
+            poll_interval,
+            clients: Arc::new(AtomicUsize::new(0)),
+            counters: Arc::new(LogStreamCounters::default()),
+            tail: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Number of clients currently holding a subscription
+    pub fn client_count(&self) -> usize {
+        self.clients.load(Ordering::Acquire)
+    }
+
+    /// Current values of the streaming counters
+    pub fn stats(&self) -> LogStreamStats {
+        LogStreamStats {
+            active_clients: self.client_count(),
+            max_clients: self.max_clients,
+            frames_sent: self.counters.frames_sent.load(Ordering::Relaxed),
+            frames_dropped: self.counters.frames_dropped.load(Ordering::Relaxed),
+            bytes_streamed: self.counters.bytes_streamed.load(Ordering::Relaxed),
+            reopens: self.counters.reopens.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Registers a client for the log file at `path`.
+    ///
+    /// Starts the tail task if none is running for `path`; otherwise the client
+    /// joins the existing broadcast. Must be called from within a Tokio runtime.
+    ///
+    /// # Returns
+    ///
+    /// The subscription, or `ServiceUnavailable` with `Retry-After` when
+    /// `max_clients` clients are already connected.
+    pub fn subscribe(&self, path: &str) -> Result<LogSubscription, ServiceError> {
+        let permit = self.acquire_permit()?;
+
+        let mut slot = self.tail.lock().unwrap_or_else(|e| e.into_inner());
+        let reusable = slot
+            .as_ref()
+            .is_some_and(|tail| tail.path == path && !tail.task.is_finished());
+
+        let receiver = if reusable {
+            slot.as_ref().map(|tail| tail.sender.subscribe())
+        } else {
+            None
+        };
+        let receiver = match receiver {
+            Some(receiver) => receiver,
+            None => {
+                let (sender, receiver) = broadcast::channel(BROADCAST_CAPACITY);
+                let task = tokio::spawn(tail_log_file(
+                    path.to_string(),
+                    sender.clone(),
+                    Arc::clone(&self.tail),
+                    Arc::clone(&self.counters),
+                    self.poll_interval,
+                ));
+                *slot = Some(SharedTail {
+                    path: path.to_string(),
+                    sender,
+                    task,
+                });
+                receiver
+            }
+        };
+
+        Ok(LogSubscription {
+            permit,
+            receiver,
+            counters: Arc::clone(&self.counters),
+        })
+    }
+
+    fn acquire_permit(&self) -> Result<ClientPermit, ServiceError> {
+        self.clients
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                (count < self.max_clients).then_some(count + 1)
+            })
+            .map(|_| ClientPermit {
+                clients: Arc::clone(&self.clients),
+            })
+            .map_err(|_| {
+                ServiceError::service_unavailable(
+                    "Too many log stream clients, please retry later",
+                    LOG_STREAM_RETRY_AFTER_SECS,
+                )
+                .with_tag(ErrorTag::Internal)
+                .with_metadata("max_clients", self.max_clients.to_string())
+            })
+    }
+}
+
+impl LogSubscription {
+    /// Converts the subscription into an SSE body: `greeting`, then every broadcast frame.
+    ///
+    /// A client that falls more than the channel capacity behind skips the
+    /// missed frames instead of stalling the shared reader. The cap slot is
+    /// released when the stream is dropped.
+    pub fn into_stream(self, greeting: Bytes) -> impl Stream<Item = Result<Bytes, IoError>> {
+        let LogSubscription {
+            permit,
+            receiver,
+            counters,
+        } = self;
+
+        let frames = stream::unfold((receiver, permit), move |(mut receiver, permit)| {
+            let counters = Arc::clone(&counters);
+            async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(frame) => {
+                            counters.frames_sent.fetch_add(1, Ordering::Relaxed);
+                            counters
+                                .bytes_streamed
+                                .fetch_add(frame.len() as u64, Ordering::Relaxed);
+                            return Some((Ok(frame), (receiver, permit)));
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            counters
+                                .frames_dropped
+                                .fetch_add(skipped, Ordering::Relaxed);
+                            debug!("log stream client lagged, skipped {} frames", skipped);
+                        }
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            }
+        });
+
+        stream::once(async move { Ok(greeting) }).chain(frames)
+    }
+}
+
+/// Tails `path` from its current end, broadcasting each new line as an SSE frame.
+///
+/// When the file at `path` is replaced (rename rotation) or truncated below the
+/// read position (copytruncate), the new file is reopened and read from its start.
+/// Exits once no client is subscribed, clearing `slot` if it still points at
+/// this task's channel, so the next client starts a fresh tail.
+async fn tail_log_file(
+    path: String,
+    sender: broadcast::Sender<Bytes>,
+    slot: Arc<Mutex<Option<SharedTail>>>,
+    counters: Arc<LogStreamCounters>,
+    poll_interval: Duration,
+) {
+    let mut file = match tokio::fs::File::open(&path).await {
+        Ok(f) => f,
+        Err(e) => {
+            error!("Failed to open log file: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = file.seek(SeekFrom::End(0)).await {
+        error!("Failed to seek to end of log file: {}", e);
+        return;
+    }
+
+    let mut buffer = [0u8; 8192];
+    let mut pending_data = Vec::new();
+    let mut keep_alive_count = 0;
+
+    loop {
+        tokio::time::sleep(poll_interval).await;
+
+        {
+            let mut slot = slot.lock().unwrap_or_else(|e| e.into_inner());
+            if sender.receiver_count() == 0 {
+                if slot
+                    .as_ref()
+                    .is_some_and(|tail| tail.sender.same_channel(&sender))
+                {
+                    *slot = None;
+                }
+                return;
+            }
+        }
+
+        // Check if file has grown
+        let metadata = match file.metadata().await {
+            Ok(m) => m,
+            Err(e) => {
+                error!("Error getting file metadata: {}", e);
+                continue;
+            }
+        };
+
+        let current_pos = match file.stream_position().await {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Error getting current position: {}", e);
+                continue;
+            }
+        };
+
+        if is_rotated(&metadata, &path, current_pos).await {
+            match tokio::fs::File::open(&path).await {
+                Ok(reopened) => {
+                    info!("Log file {} rotated, reopening", path);
+                    file = reopened;
+                    pending_data.clear();
+                    counters.reopens.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) => error!("Failed to reopen rotated log file: {}", e),
+            }
+            continue;
+        }
+
+        if metadata.len() > current_pos {
+            let to_read = (metadata.len() - current_pos) as usize;
+            if to_read <= buffer.len() {
+                match file.read(&mut buffer[..to_read]).await {
+                    Ok(n) if n == to_read => {
+                        pending_data.extend_from_slice(&buffer[..n]);
+                    }
+                    _ => {
+                        error!("Failed to read expected data");
+                        continue;
+                    }
+                }
+            } else {
+                // File grew too much, skip to the end
+                if file.seek(SeekFrom::End(0)).await.is_ok() {
+                    pending_data.clear();
+                }
+                continue;
+            }
+
+            // Process complete lines
+            while let Some(pos) = pending_data.iter().position(|&b| b == b'\n') {
+                let line_bytes = pending_data.drain(..=pos).collect::<Vec<_>>();
+                if let Ok(line) = String::from_utf8(line_bytes) {
+                    let trimmed = line.trim_end_matches('\n').trim_end_matches('\r');
+                    if !trimmed.is_empty() {
+                        // Fails only when every client has gone; the next tick exits
+                        let _ = sender.send(Bytes::from(format!("data: {}\n\n", trimmed)));
+                    }
+                }
+            }
+        }
+
+        keep_alive_count += 1;
+        if keep_alive_count >= KEEP_ALIVE_POLLS {
+            keep_alive_count = 0;
+            let _ = sender.send(Bytes::from("data: \n\n"));
+        }
+    }
+}
+
+/// Whether the open file no longer is the one at `path`, or was truncated below `position`.
+async fn is_rotated(open: &std::fs::Metadata, path: &str, position: u64) -> bool {
+    let Ok(current) = tokio::fs::metadata(path).await else {
+        // Mid-rotation: keep the old handle until the new file appears
+        return false;
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if (open.dev(), open.ino()) != (current.dev(), current.ino()) {
+            return true;
+        }
+    }
+
+    open.len() < position
+}
+
+/// Process-wide hub used by the `/logs` endpoint, sized from `MAX_LOG_STREAM_CLIENTS`.
+pub fn log_stream_hub() -> &'static LogStreamHub {
+    static HUB: OnceLock<LogStreamHub> = OnceLock::new();
+    HUB.get_or_init(|| LogStreamHub::new(max_clients_from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use actix_web::ResponseError;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    /// Next non-keep-alive frame of `stream`, as text.
+    async fn next_line<S>(stream: &mut S) -> String
+    where
+        S: Stream<Item = Result<Bytes, IoError>> + Unpin,
+    {
+        loop {
+            let frame = tokio::time::timeout(Duration::from_secs(5), stream.next())
+                .await
+                .expect("no frame within timeout")
+                .expect("stream ended")
+                .unwrap();
+            if frame.as_ref() != b"data: \n\n" {
+                return String::from_utf8(frame.to_vec()).unwrap();
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_clients_beyond_cap_are_rejected() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        let hub = LogStreamHub::with_poll_interval(2, Duration::from_millis(20));
+
+        let first = hub.subscribe(path).unwrap();
+        let _second = hub.subscribe(path).unwrap();
+        let refused = hub.subscribe(path).err().expect("third client accepted");
+
+        assert!(matches!(refused, ServiceError::ServiceUnavailable { .. }));
+        assert_eq!(
+            refused.status_code(),
+            actix_web::http::StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(hub.client_count(), 2);
+
+        // A disconnect frees its slot
+        drop(first);
+        assert_eq!(hub.client_count(), 1);
+        assert!(hub.subscribe(path).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_active_client_gauge_tracks_connections() {
+        let file = NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap();
+        let hub = LogStreamHub::with_poll_interval(3, Duration::from_millis(20));
+        assert_eq!(hub.stats().active_clients, 0);
+        assert_eq!(hub.stats().max_clients, 3);
+
+        let first = hub.subscribe(path).unwrap();
+        let second = hub
+            .subscribe(path)
+            .unwrap()
+            .into_stream(Bytes::from("data: hello\n\n"));
+        assert_eq!(hub.stats().active_clients, 2);
+
+        // The slot is held by the stream until the response body is dropped
+        drop(first);
+        assert_eq!(hub.stats().active_clients, 1);
+        drop(second);
+        assert_eq!(hub.stats().active_clients, 0);
+    }
+
+    #[cfg(unix)]
+    #[actix_web::test]
+    async fn test_tail_reopens_rotated_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        std::fs::write(&path, "").unwrap();
+        let path_str = path.to_str().unwrap().to_string();
+        let hub = LogStreamHub::with_poll_interval(1, Duration::from_millis(20));
+
+        let mut client = Box::pin(
+            hub.subscribe(&path_str)
+                .unwrap()
+                .into_stream(Bytes::from("data: hello\n\n")),
+        );
+        assert_eq!(next_line(&mut client).await, "data: hello\n\n");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        std::fs::write(&path, "after rotation\n").unwrap();
+
+        assert_eq!(next_line(&mut client).await, "data: after rotation\n\n");
+        assert_eq!(hub.stats().reopens, 1);
+    }
+
+    #[actix_web::test]
+    async fn test_clients_share_one_broadcast() {
+        let mut file = NamedTempFile::new().unwrap();
+        let path = file.path().to_str().unwrap().to_string();
+        let hub = LogStreamHub::with_poll_interval(4, Duration::from_millis(20));
+
+        let mut first = Box::pin(
+            hub.subscribe(&path)
+                .unwrap()
+                .into_stream(Bytes::from("data: hello\n\n")),
+        );
+        let mut second = Box::pin(
+            hub.subscribe(&path)
+                .unwrap()
+                .into_stream(Bytes::from("data: hello\n\n")),
+        );
+        {
+            let slot = hub.tail.lock().unwrap();
+            assert_eq!(slot.as_ref().unwrap().sender.receiver_count(), 2);
+        }
+
+        assert_eq!(next_line(&mut first).await, "data: hello\n\n");
+        assert_eq!(next_line(&mut second).await, "data: hello\n\n");
+
+        // Let the tail task open the file and seek to its end before appending
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        writeln!(file, "shared line").unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(next_line(&mut first).await, "data: shared line\n\n");
+        assert_eq!(next_line(&mut second).await, "data: shared line\n\n");
+
+        let stats = hub.stats();
+        assert!(stats.frames_sent >= 2);
+        assert!(stats.bytes_streamed >= 2 * "data: shared line\n\n".len() as u64);
+        assert_eq!(stats.frames_dropped, 0);
+    }
+}
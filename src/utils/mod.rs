@@ -1,3 +1,9 @@
+pub mod client_ip;
+pub mod csv;
+pub mod json_patch;
+pub mod log_context;
+pub mod log_stream;
+pub mod state_cleanup;
 pub mod token_utils;
 
 use uuid::Uuid;
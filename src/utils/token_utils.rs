@@ -3,15 +3,17 @@ use jsonwebtoken::{DecodingKey, TokenData, Validation};
 
 use crate::{
     config::db::Pool,
+    constants,
+    error::{ErrorTag, ServiceError},
     models::{
         user::operations as user_ops,
-        user_token::{UserToken, SECRET_KEY},
+        user_token::{jwt_keys, JwtAlgorithm, UserToken},
     },
 };
 
 /// Decode a JWT string into `TokenData<UserToken>`.
 ///
-/// The token is validated using the crate-level secret `KEY` and `jsonwebtoken`'s default validation settings.
+/// The token is validated with the configured [`jwt_keys`]; see [`decode_token_with`].
 /// Any decoding or validation error from `jsonwebtoken` is propagated to the caller.
 ///
 /// # Examples
@@ -21,17 +23,29 @@ use crate::{
 /// assert!(res.is_err());
 /// ```
 pub fn decode_token(token: String) -> jsonwebtoken::errors::Result<TokenData<UserToken>> {
-    jsonwebtoken::decode::<UserToken>(
-        &token,
-        &DecodingKey::from_secret(SECRET_KEY.as_slice()),
-        &Validation::default(),
-    )
+    let keys = jwt_keys();
+    decode_token_with(&token, keys.algorithm, &keys.decoding)
+}
+
+/// Decode a JWT signed with `algorithm`, verifying it with `key`.
+///
+/// Only `algorithm` is accepted: a token whose `alg` header names anything else
+/// fails with `ErrorKind::InvalidAlgorithm`, so an HS256 token forged with the RS256
+/// public key as HMAC secret is rejected (algorithm confusion).
+pub fn decode_token_with(
+    token: &str,
+    algorithm: JwtAlgorithm,
+    key: &DecodingKey,
+) -> jsonwebtoken::errors::Result<TokenData<UserToken>> {
+    jsonwebtoken::decode::<UserToken>(token, key, &Validation::new(algorithm.into()))
 }
 
 /// Verify that the JWT claims represent a valid login session and return the associated user identifier.
 ///
 /// # Returns
-/// `Ok(String)` containing the user identifier when the session is valid, `Err(String)` with `"Invalid token"` otherwise.
+/// `Ok(String)` containing the user identifier when the session is valid, an unauthorized
+/// `ServiceError` when it is not, or the [`ServiceError::from_pool_error`] mapping when no
+/// connection could be taken from `pool`.
 ///
 /// # Examples
 ///
@@ -39,15 +53,18 @@ pub fn decode_token(token: String) -> jsonwebtoken::errors::Result<TokenData<Use
 /// // Given a decoded `token_data: jsonwebtoken::TokenData<UserToken>` and a `pool: Pool`
 /// // let user_id = verify_token(&token_data, &pool)?;
 /// ```
-pub fn verify_token(token_data: &TokenData<UserToken>, pool: &Pool) -> Result<String, String> {
+pub fn verify_token(
+    token_data: &TokenData<UserToken>,
+    pool: &Pool,
+) -> Result<String, ServiceError> {
     let mut conn = pool
         .get()
-        .map_err(|e| format!("Failed to get db connection: {}", e))?;
+        .map_err(|e| ServiceError::from_pool_error(e, pool))?;
 
     if user_ops::is_valid_login_session(&token_data.claims, &mut conn) {
         Ok(token_data.claims.user.to_string())
     } else {
-        Err("Invalid token".to_string())
+        Err(ServiceError::unauthorized(constants::MESSAGE_INVALID_TOKEN).with_tag(ErrorTag::Auth))
     }
 }
 
@@ -72,3 +89,118 @@ pub fn is_auth_header_valid(authen_header: &HeaderValue) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::errors::ErrorKind;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    use super::*;
+    use crate::models::{user::LoginInfoDTO, user_token::JwtKeys};
+
+    const PRIVATE_PEM: &[u8] = include_bytes!("../../tests/fixtures/jwt_rs256_private.pem");
+    const PUBLIC_PEM: &[u8] = include_bytes!("../../tests/fixtures/jwt_rs256_public.pem");
+    const LIFETIME_SECS: i64 = 900;
+
+    fn login() -> LoginInfoDTO {
+        LoginInfoDTO {
+            username: "alice".to_string(),
+            login_session: "session-123".to_string(),
+            tenant_id: "tenant1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_rs256_token_verifies_with_public_key_only() {
+        let keys = JwtKeys::rs256(PRIVATE_PEM, PUBLIC_PEM).unwrap();
+        let token = UserToken::generate_token_with(&login(), &keys, LIFETIME_SECS);
+
+        let public_only = DecodingKey::from_rsa_pem(PUBLIC_PEM).unwrap();
+        let token_data = decode_token_with(&token, JwtAlgorithm::RS256, &public_only).unwrap();
+
+        assert_eq!(token_data.header.alg, jsonwebtoken::Algorithm::RS256);
+        assert_eq!(token_data.claims.user, "alice");
+        assert_eq!(token_data.claims.tenant_id, "tenant1");
+    }
+
+    #[test]
+    fn test_hs256_token_round_trips() {
+        let keys = JwtKeys::hs256(b"test-secret");
+        let token = UserToken::generate_token_with(&login(), &keys, LIFETIME_SECS);
+
+        let token_data = decode_token_with(&token, JwtAlgorithm::HS256, &keys.decoding).unwrap();
+        assert_eq!(token_data.claims.login_session, "session-123");
+    }
+
+    #[test]
+    fn test_token_exp_reflects_the_configured_lifetime() {
+        let keys = JwtKeys::hs256(b"test-secret");
+        let token = UserToken::generate_token_with(&login(), &keys, 120);
+
+        let claims = decode_token_with(&token, JwtAlgorithm::HS256, &keys.decoding)
+            .unwrap()
+            .claims;
+        assert_eq!(claims.exp - claims.iat, 120);
+    }
+
+    #[test]
+    fn test_expired_token_is_rejected() {
+        let keys = JwtKeys::hs256(b"test-secret");
+        // Expired beyond the default 60 second leeway
+        let token = UserToken::generate_token_with(&login(), &keys, -120);
+
+        let err = decode_token_with(&token, JwtAlgorithm::HS256, &keys.decoding)
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::ExpiredSignature));
+    }
+
+    #[test]
+    fn test_token_with_unexpected_alg_is_rejected() {
+        let rs256 = JwtKeys::rs256(PRIVATE_PEM, PUBLIC_PEM).unwrap();
+        let hs256 = JwtKeys::hs256(b"test-secret");
+
+        let token = UserToken::generate_token_with(&login(), &hs256, LIFETIME_SECS);
+        let err = decode_token_with(&token, JwtAlgorithm::RS256, &rs256.decoding)
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidAlgorithm));
+
+        // HS256 token forged with the public key as HMAC secret
+        let payload = decode_token_with(&token, JwtAlgorithm::HS256, &hs256.decoding)
+            .unwrap()
+            .claims;
+        let forged = jsonwebtoken::encode(
+            &Header::new(jsonwebtoken::Algorithm::HS256),
+            &payload,
+            &EncodingKey::from_secret(PUBLIC_PEM),
+        )
+        .unwrap();
+        let err = decode_token_with(&forged, JwtAlgorithm::RS256, &rs256.decoding)
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidAlgorithm));
+    }
+
+    #[test]
+    fn test_unreachable_pool_is_not_an_invalid_token() {
+        let keys = JwtKeys::hs256(b"test-secret");
+        let token = UserToken::generate_token_with(&login(), &keys, LIFETIME_SECS);
+        let token_data = decode_token_with(&token, JwtAlgorithm::HS256, &keys.decoding).unwrap();
+        let manager = diesel::r2d2::ConnectionManager::new("postgres://127.0.0.1:1/unused");
+        let pool = diesel::r2d2::Pool::builder()
+            .connection_timeout(std::time::Duration::from_millis(100))
+            .build_unchecked(manager);
+
+        let err = verify_token(&token_data, &pool).unwrap_err();
+        assert_eq!(
+            err.http_status(),
+            actix_web::http::StatusCode::INTERNAL_SERVER_ERROR
+        );
+        assert!(err
+            .context()
+            .tags
+            .iter()
+            .any(|tag| tag == ErrorTag::Db.as_str()));
+    }
+}